@@ -4,6 +4,9 @@ mod index_info;
 mod settings;
 
 pub use constants::*;
-pub use enums::{DocLang, EmbeddingType, PrebuiltMode, RerankType};
-pub use index_info::{log_startup_info, IndexInfo};
+pub use enums::{
+    CategoryWeight, DocLang, EmbeddingType, IndexPolicy, OutputFormat, PrebuiltMode, RerankStage,
+    RerankType, VectorDistance,
+};
+pub use index_info::{log_startup_info, recorded_version, startup_info_lines, IndexInfo};
 pub use settings::Settings;
@@ -2,14 +2,29 @@ use std::path::PathBuf;
 
 pub const DEFAULT_DOCS_VERSION: &str = "dev";
 pub const DOCS_REPO_URL: &str = "https://gitcode.com/Cangjie/cangjie_docs.git";
+pub const DEFAULT_DOCS_WEB_BASE: &str = "https://gitcode.com/Cangjie/cangjie_docs/blob";
 pub const RUNTIME_REPO_URL: &str = "https://gitcode.com/Cangjie/cangjie_runtime.git";
 pub const STDX_REPO_URL: &str = "https://gitcode.com/Cangjie/cangjie_stdx.git";
 pub const DEFAULT_LOCAL_MODEL: &str = "paraphrase-multilingual-MiniLM-L12-v2";
 pub const DEFAULT_RRF_K: u32 = 60;
+pub const DEFAULT_FUSION_CANDIDATE_K: usize = 50;
+pub const MAX_FUSION_CANDIDATE_K: usize = 200;
+pub const DEFAULT_MAX_FETCH_CANDIDATES: usize = 500;
+pub const DEFAULT_TITLE_BOOST: f32 = 2.0;
+pub const DEFAULT_LENGTH_PENALTY: f64 = 0.0;
 pub const DEFAULT_RERANK_MODEL: &str = "BAAI/bge-reranker-v2-m3";
 pub const DEFAULT_RERANK_TOP_K: usize = 5;
 pub const DEFAULT_RERANK_INITIAL_K: usize = 20;
+pub const DEFAULT_RERANK_MAX_DOC_CHARS: usize = 2000;
+pub const DEFAULT_RERANK_WINDOW_TOKENS: usize = 8000;
+pub const DEFAULT_RERANK_WARMUP: bool = true;
+pub const DEFAULT_INDEX_CODE: bool = false;
+pub const DEFAULT_DECOMPRESS_DOCS: bool = false;
+pub const DEFAULT_STRIP_HTML: bool = false;
+pub const DEFAULT_EXTRA_DOCS_PREFIX: &str = "internal";
 pub const DEFAULT_CHUNK_OVERLAP_CHARS: usize = 100;
+pub const DEFAULT_CHUNK_BREADCRUMBS: bool = true;
+pub const DEFAULT_SKIP_DISK_CHECK: bool = false;
 pub const CODE_DENSE_THRESHOLD: f64 = 0.6;
 pub const CODE_MIXED_THRESHOLD: f64 = 0.2;
 pub const DEFAULT_CODE_DENSE_CHARS: usize = 800;
@@ -23,22 +38,39 @@ pub const DEFAULT_SERVER_PORT: u16 = 8765;
 pub const DEFAULT_HTTP_POOL_IDLE_TIMEOUT_SECS: u64 = 90;
 pub const DEFAULT_HTTP_POOL_MAX_IDLE_PER_HOST: usize = 16;
 pub const DEFAULT_HTTP_TCP_KEEPALIVE_SECS: u64 = 60;
-pub const DEFAULT_HTTP_CONNECT_TIMEOUT_SECS: u64 = 10;
+pub const DEFAULT_HTTP_CONNECT_TIMEOUT_SECS: u64 = 5;
 pub const DEFAULT_HTTP_ENABLE_HTTP2: bool = true;
+pub const DEFAULT_REMOTE_CACHE_TTL_SECS: u64 = 300;
+pub const DEFAULT_REMOTE_MSGPACK: bool = false;
+pub const DEFAULT_GIT_RETRIES: usize = 2;
+pub const DEFAULT_MAX_CONCURRENT_GIT_OPS: usize = 4;
+pub const DEFAULT_INDEX_BUILD_TIMEOUT_SECS: u64 = 1800;
+pub const DEFAULT_WATCH: bool = false;
+pub const DEFAULT_REFRESH_INTERVAL_MINS: u64 = 60;
+pub const DEFAULT_HTTP_RETRY_BASE_MS: u64 = 2000;
+pub const DEFAULT_HTTP_RETRY_CAP_MS: u64 = 30000;
+pub const DEFAULT_HTTP_USER_AGENT: &str = concat!("cangjie-mcp/", env!("CARGO_PKG_VERSION"));
 pub const DEFAULT_SERVER_ENABLE_HTTP2: bool = true;
 pub const DEFAULT_MAX_PER_FILE: usize = 2;
-pub const DEFAULT_MIN_VECTOR_SCORE: f64 = 0.3;
+pub const DEFAULT_MAX_CONCURRENT_TOOLS: usize = 8;
+pub const VECTOR_SCORE_THRESHOLD: f64 = 0.3;
 
 pub const MIN_TOP_K: usize = 1;
 pub const MAX_TOP_K: usize = 20;
+// Remote clients over-fetch candidates for reranking and pagination, so the
+// HTTP search endpoint accepts far more than MAX_TOP_K.
+pub const MAX_HTTP_SEARCH_TOP_K: usize = 1000;
 pub const DEFAULT_TOP_K: usize = 5;
-pub const DEFAULT_EMBEDDING_DIM: usize = 384;
-pub const SIMILARITY_THRESHOLD: f64 = 0.6;
+pub const TOPIC_SUGGESTION_THRESHOLD: f64 = 0.6;
 pub const MAX_SUGGESTIONS: usize = 5;
 pub const PACKAGE_FETCH_MULTIPLIER: usize = 3;
+/// Score multiplier for results in the category inferred from a query.
+pub const INFERRED_CATEGORY_BOOST: f64 = 1.25;
 pub const DEFAULT_TOPIC_MAX_LENGTH: usize = 10000;
+pub const MAX_TOPICS_PER_REQUEST: usize = 10;
 pub const CATEGORY_FILTER_MULTIPLIER: usize = 4;
 pub const VECTOR_BATCH_SIZE: usize = 64;
+pub const DEFAULT_VECTOR_INSERT_BATCH_SIZE: usize = VECTOR_BATCH_SIZE;
 pub const INDEX_WRITER_HEAP_BYTES: usize = 50_000_000;
 
 pub fn get_default_data_dir() -> PathBuf {
@@ -121,6 +121,165 @@ impl FromStr for DocLang {
     }
 }
 
+/// Whether an existing index may be rebuilt when its recorded chunking
+/// parameters differ from the current settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexPolicy {
+    #[default]
+    Auto,
+    Never,
+}
+
+impl fmt::Display for IndexPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexPolicy::Auto => write!(f, "auto"),
+            IndexPolicy::Never => write!(f, "never"),
+        }
+    }
+}
+
+impl FromStr for IndexPolicy {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "never" => Ok(Self::Never),
+            _ => Err(format!("unknown index policy: {s}")),
+        }
+    }
+}
+
+/// Distance the vector store ranks embeddings by. `l2` is what indexes built
+/// before the option existed use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorDistance {
+    Cosine,
+    #[default]
+    L2,
+    Dot,
+}
+
+impl fmt::Display for VectorDistance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VectorDistance::Cosine => write!(f, "cosine"),
+            VectorDistance::L2 => write!(f, "l2"),
+            VectorDistance::Dot => write!(f, "dot"),
+        }
+    }
+}
+
+impl FromStr for VectorDistance {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cosine" => Ok(Self::Cosine),
+            "l2" => Ok(Self::L2),
+            "dot" => Ok(Self::Dot),
+            _ => Err(format!("unknown vector distance: {s}")),
+        }
+    }
+}
+
+/// Where the reranker runs in a hybrid query. `post-fusion` reranks the
+/// RRF-fused list once. `pre-fusion` reranks the BM25 and vector lists
+/// separately before fusing them: this costs a reranker call per retriever,
+/// but lets each retriever's best match survive fusion on its reranked rank
+/// instead of its raw one. Experimental.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RerankStage {
+    #[default]
+    PostFusion,
+    PreFusion,
+}
+
+impl fmt::Display for RerankStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RerankStage::PostFusion => write!(f, "post-fusion"),
+            RerankStage::PreFusion => write!(f, "pre-fusion"),
+        }
+    }
+}
+
+impl FromStr for RerankStage {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "post-fusion" => Ok(Self::PostFusion),
+            "pre-fusion" => Ok(Self::PreFusion),
+            _ => Err(format!("unknown rerank stage: {s}")),
+        }
+    }
+}
+
+/// Rendering of tool results: structured JSON (the default) or readable
+/// markdown.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Markdown,
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "markdown" => Ok(Self::Markdown),
+            _ => Err(format!("unknown output format: {s}")),
+        }
+    }
+}
+
+/// A `category=factor` score multiplier from `--category-weight`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryWeight {
+    pub category: String,
+    pub factor: f64,
+}
+
+impl FromStr for CategoryWeight {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (category, factor) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected category=factor, got: {s}"))?;
+        let category = category.trim();
+        if category.is_empty() {
+            return Err(format!("missing category in: {s}"));
+        }
+        let factor: f64 = factor
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid weight factor in: {s}"))?;
+        if !factor.is_finite() || factor < 0.0 {
+            return Err(format!("weight factor must be a non-negative number: {s}"));
+        }
+        Ok(Self {
+            category: category.to_string(),
+            factor,
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PrebuiltMode {
     Off,
@@ -169,4 +328,19 @@ mod tests {
         assert_eq!("en".parse::<DocLang>().unwrap(), DocLang::En);
         assert!("invalid".parse::<DocLang>().is_err());
     }
+
+    #[test]
+    fn test_category_weight_from_str() {
+        assert_eq!(
+            "stdlib=1.5".parse::<CategoryWeight>().unwrap(),
+            CategoryWeight {
+                category: "stdlib".to_string(),
+                factor: 1.5
+            }
+        );
+        assert!("stdlib".parse::<CategoryWeight>().is_err());
+        assert!("=2".parse::<CategoryWeight>().is_err());
+        assert!("stdlib=-1".parse::<CategoryWeight>().is_err());
+        assert!("stdlib=high".parse::<CategoryWeight>().is_err());
+    }
 }
@@ -1,39 +1,105 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use super::enums::{DocLang, EmbeddingType, RerankType};
+use super::enums::{DocLang, EmbeddingType, RerankType, VectorDistance};
 use super::settings::Settings;
+use crate::types::{ChunkingParams, ExtraDocs, IndexMetadata, SearchMode};
 
 fn sanitize_for_path(name: &str) -> String {
     name.replace([':', '/'], "--")
 }
 
+/// The index metadata recorded in `dir`, if it has any.
+fn recorded_metadata(dir: &Path) -> Option<IndexMetadata> {
+    let content = std::fs::read_to_string(dir.join("index_metadata.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// The version recorded in `dir`'s index metadata, if it has any.
+pub fn recorded_version(dir: &Path) -> Option<String> {
+    recorded_metadata(dir).map(|meta| meta.version)
+}
+
+/// Warn when the index in `--index-dir` was recorded for another version,
+/// language or model than the one about to be served from it.
+fn warn_on_index_dir_mismatch(dir: &Path, settings: &Settings, resolved_version: &str) {
+    let Some(meta) = recorded_metadata(dir) else {
+        return;
+    };
+    let expected = [
+        ("version", resolved_version.to_string()),
+        ("language", settings.docs_lang.to_string()),
+        ("embedding model", settings.embedding_model_name()),
+    ];
+    let recorded = [meta.version, meta.lang, meta.embedding_model];
+    for ((what, expected), recorded) in expected.into_iter().zip(recorded) {
+        if expected != recorded {
+            tracing::warn!(
+                "Index at {} records {what} {recorded}, but {expected} was requested; \
+                 serving it anyway",
+                dir.display()
+            );
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct IndexInfo {
     pub version: String,
     pub lang: DocLang,
     pub embedding_model_name: String,
     pub data_dir: PathBuf,
+    pub docs_repo_dir_override: Option<PathBuf>,
+    pub index_code: bool,
+    /// Serve the index from this directory instead of the one derived from
+    /// `data_dir`, version, lang and model. Kept even when the directory's
+    /// metadata records another version, language or model.
+    pub index_dir_override: Option<PathBuf>,
+    pub extra_docs_dirs: Vec<PathBuf>,
+    pub extra_docs_prefix: String,
+    pub max_chunk_chars: Option<usize>,
+    pub chunk_overlap_chars: usize,
+    pub chunk_breadcrumbs: bool,
+    pub strip_html: bool,
+    /// Distance a hybrid index is built and searched with.
+    pub vector_distance: VectorDistance,
 }
 
 impl IndexInfo {
     pub fn from_settings(settings: &Settings, resolved_version: &str) -> Self {
+        if let Some(dir) = &settings.index_dir_override {
+            warn_on_index_dir_mismatch(dir, settings, resolved_version);
+        }
         Self {
             version: resolved_version.to_string(),
             lang: settings.docs_lang,
             embedding_model_name: settings.embedding_model_name(),
             data_dir: settings.data_dir.clone(),
+            docs_repo_dir_override: settings.docs_repo_dir_override.clone(),
+            index_code: settings.index_code,
+            index_dir_override: settings.index_dir_override.clone(),
+            extra_docs_dirs: settings.extra_docs_dirs.clone(),
+            extra_docs_prefix: settings.extra_docs_prefix.clone(),
+            max_chunk_chars: settings.max_chunk_chars,
+            chunk_overlap_chars: settings.chunk_overlap_chars,
+            chunk_breadcrumbs: settings.chunk_breadcrumbs,
+            strip_html: settings.strip_html,
+            vector_distance: settings.vector_distance,
         }
     }
 
     pub fn index_dir(&self) -> PathBuf {
+        if let Some(dir) = &self.index_dir_override {
+            return dir.clone();
+        }
         let model_dir = if self.embedding_model_name == "none" {
             "bm25-only".to_string()
         } else {
             sanitize_for_path(&self.embedding_model_name)
         };
+        // Resolved branch keys like `release/1.0(abc1234)` must stay one path component.
         self.data_dir
             .join("indexes")
-            .join(&self.version)
+            .join(sanitize_for_path(&self.version))
             .join(self.lang.to_string())
             .join(model_dir)
     }
@@ -46,8 +112,14 @@ impl IndexInfo {
         self.index_dir().join("vector_db")
     }
 
+    pub fn code_index_dir(&self) -> PathBuf {
+        self.index_dir().join("code_index")
+    }
+
     pub fn docs_repo_dir(&self) -> PathBuf {
-        self.data_dir.join("docs_repo")
+        self.docs_repo_dir_override
+            .clone()
+            .unwrap_or_else(|| self.data_dir.join("docs_repo"))
     }
 
     pub fn runtime_repo_dir(&self) -> PathBuf {
@@ -58,6 +130,31 @@ impl IndexInfo {
         self.data_dir.join("stdx_repo")
     }
 
+    /// The extra-docs configuration to record in index metadata; `None` when
+    /// no extra directories are configured.
+    pub fn extra_docs(&self) -> Option<ExtraDocs> {
+        if self.extra_docs_dirs.is_empty() {
+            return None;
+        }
+        Some(ExtraDocs {
+            prefix: self.extra_docs_prefix.trim_matches('/').to_string(),
+            dirs: self
+                .extra_docs_dirs
+                .iter()
+                .map(|d| d.display().to_string())
+                .collect(),
+        })
+    }
+
+    pub fn chunking(&self) -> ChunkingParams {
+        ChunkingParams {
+            max_chunk_chars: self.max_chunk_chars,
+            chunk_overlap_chars: self.chunk_overlap_chars,
+            strip_html: self.strip_html,
+            chunk_breadcrumbs: self.chunk_breadcrumbs,
+        }
+    }
+
     pub fn docs_source_dir(&self) -> PathBuf {
         self.docs_repo_dir()
             .join("docs")
@@ -66,63 +163,92 @@ impl IndexInfo {
     }
 }
 
-pub fn log_startup_info(settings: &Settings, index_info: &IndexInfo) {
-    use tracing::info;
+/// Log the effective configuration at startup. `loaded` is the search mode
+/// the opened stores actually serve; `None` reports the configured mode (e.g.
+/// in remote mode or before the stores are opened). A hybrid configuration
+/// that only loaded BM25 is reported as BM25 and warned about.
+pub fn log_startup_info(settings: &Settings, index_info: &IndexInfo, loaded: Option<SearchMode>) {
+    use tracing::{info, warn};
+
+    for line in startup_info_lines(settings, index_info, loaded) {
+        info!("{line}");
+    }
+    if settings.server_url.is_none() && settings.has_embedding() && loaded == Some(SearchMode::Bm25)
+    {
+        warn!(
+            "Hybrid search is configured but the vector store at {} is not loaded; \
+             serving BM25-only results until the index is rebuilt",
+            index_info.vector_db_dir().display()
+        );
+    }
+}
 
-    info!("Cangjie MCP v{}", crate::VERSION);
+/// Lines logged by [`log_startup_info`].
+pub fn startup_info_lines(
+    settings: &Settings,
+    index_info: &IndexInfo,
+    loaded: Option<SearchMode>,
+) -> Vec<String> {
+    let mut lines = vec![format!("Cangjie MCP v{}", crate::VERSION)];
 
     if let Some(ref url) = settings.server_url {
-        info!("Mode: remote -> {url}");
+        lines.push(format!("Mode: remote -> {url}"));
     } else {
-        let search_mode = if settings.has_embedding() {
-            "hybrid (BM25 + vector)"
+        let configured = if settings.has_embedding() {
+            SearchMode::Hybrid
         } else {
-            "BM25"
+            SearchMode::Bm25
+        };
+        let search_mode = match (configured, loaded.unwrap_or(configured)) {
+            (_, SearchMode::Hybrid) => "hybrid (BM25 + vector)",
+            (SearchMode::Hybrid, SearchMode::Bm25) => "BM25 (vector store unavailable)",
+            (SearchMode::Bm25, SearchMode::Bm25) => "BM25",
         };
-        info!("Search: {search_mode}");
-        info!(
+        lines.push(format!("Search: {search_mode}"));
+        lines.push(format!(
             "Chunk: overlap_chars={}, max_chunk_chars={:?}",
             settings.chunk_overlap_chars, settings.max_chunk_chars,
-        );
+        ));
         if settings.has_embedding() {
             let model = match settings.embedding_type {
                 EmbeddingType::Local => &settings.local_model,
                 _ => &settings.openai_model,
             };
-            info!("Embedding: {} / {model}", settings.embedding_type);
+            lines.push(format!("Embedding: {} / {model}", settings.embedding_type));
         }
 
         if matches!(settings.embedding_type, EmbeddingType::Local)
             || matches!(settings.rerank_type, RerankType::Local)
         {
-            info!(
+            lines.push(format!(
                 "Fastembed cache: {}",
                 settings.fastembed_cache_dir().display()
-            );
+            ));
         }
     }
 
     match settings.rerank_type {
         RerankType::None => {}
         _ => {
-            info!(
+            lines.push(format!(
                 "Rerank: {} / {} (top_k={}, initial_k={})",
                 settings.rerank_type,
                 settings.rerank_model,
                 settings.rerank_top_k,
                 settings.rerank_initial_k,
-            );
+            ));
         }
     }
 
-    info!("Version: {}", index_info.version);
-    info!("Language: {}", index_info.lang);
+    lines.push(format!("Version: {}", index_info.version));
+    lines.push(format!("Language: {}", index_info.lang));
     if settings.has_embedding() {
-        info!("Model: {}", index_info.embedding_model_name);
+        lines.push(format!("Model: {}", index_info.embedding_model_name));
     }
     if settings.server_url.is_none() {
-        info!("Index dir: {}", index_info.index_dir().display());
+        lines.push(format!("Index dir: {}", index_info.index_dir().display()));
     }
+    lines
 }
 
 #[cfg(test)]
@@ -136,6 +262,16 @@ mod tests {
             lang: DocLang::Zh,
             embedding_model_name: "none".to_string(),
             data_dir: PathBuf::from("/data"),
+            docs_repo_dir_override: None,
+            index_code: false,
+            index_dir_override: None,
+            extra_docs_dirs: Vec::new(),
+            extra_docs_prefix: String::new(),
+            max_chunk_chars: None,
+            chunk_overlap_chars: 0,
+            chunk_breadcrumbs: true,
+            strip_html: false,
+            vector_distance: VectorDistance::L2,
         };
 
         assert_eq!(
@@ -159,6 +295,16 @@ mod tests {
             lang: DocLang::En,
             embedding_model_name: "openai:BAAI/bge-m3".to_string(),
             data_dir: PathBuf::from("/data"),
+            docs_repo_dir_override: None,
+            index_code: false,
+            index_dir_override: None,
+            extra_docs_dirs: Vec::new(),
+            extra_docs_prefix: String::new(),
+            max_chunk_chars: None,
+            chunk_overlap_chars: 0,
+            chunk_breadcrumbs: true,
+            strip_html: false,
+            vector_distance: VectorDistance::L2,
         };
 
         assert_eq!(
@@ -167,6 +313,79 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_index_info_branch_version_is_one_path_component() {
+        let settings = Settings {
+            data_dir: PathBuf::from("/data"),
+            ..Settings::default()
+        };
+        let info = IndexInfo::from_settings(&settings, "release/1.0(abc1234)+rt-main(def5678)");
+
+        assert_eq!(
+            info.index_dir(),
+            PathBuf::from("/data/indexes/release--1.0(abc1234)+rt-main(def5678)/zh/bm25-only")
+        );
+    }
+
+    #[test]
+    fn test_index_info_docs_repo_override() {
+        let settings = Settings {
+            data_dir: PathBuf::from("/slow/data"),
+            docs_repo_dir_override: Some(PathBuf::from("/fast/docs")),
+            ..Settings::default()
+        };
+        let info = IndexInfo::from_settings(&settings, "dev");
+
+        assert_eq!(settings.docs_repo_dir(), PathBuf::from("/fast/docs"));
+        assert_eq!(info.docs_repo_dir(), PathBuf::from("/fast/docs"));
+        assert_eq!(
+            info.docs_source_dir(),
+            PathBuf::from("/fast/docs/docs/dev-guide").join(DocLang::Zh.source_dir_name())
+        );
+        assert_eq!(
+            info.index_dir(),
+            PathBuf::from("/slow/data/indexes/dev/zh/bm25-only")
+        );
+        assert_eq!(
+            info.runtime_repo_dir(),
+            PathBuf::from("/slow/data/runtime_repo")
+        );
+    }
+
+    #[test]
+    fn test_index_dir_override_kept_for_a_mismatched_version() {
+        let tmp = tempfile::tempdir().unwrap();
+        let metadata = IndexMetadata {
+            version: "v1.0.0".to_string(),
+            lang: "zh".to_string(),
+            embedding_model: "none".to_string(),
+            document_count: 1,
+            search_mode: SearchMode::Bm25,
+            index_code: false,
+            extra_docs: None,
+            chunking: None,
+            vector_distance: None,
+            source_commit: None,
+            tokenizer_version: 0,
+        };
+        std::fs::write(
+            tmp.path().join("index_metadata.json"),
+            serde_json::to_string(&metadata).unwrap(),
+        )
+        .unwrap();
+        let settings = Settings {
+            data_dir: PathBuf::from("/data"),
+            index_dir_override: Some(tmp.path().to_path_buf()),
+            ..Settings::default()
+        };
+
+        let served = IndexInfo::from_settings(&settings, "v1.0.0");
+        assert_eq!(served.index_dir(), tmp.path());
+        let other = IndexInfo::from_settings(&settings, "v2.0.0");
+        assert_eq!(other.index_dir(), tmp.path());
+        assert_eq!(recorded_version(tmp.path()).as_deref(), Some("v1.0.0"));
+    }
+
     #[test]
     fn test_sanitize_for_path() {
         assert_eq!(
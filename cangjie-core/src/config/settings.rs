@@ -1,7 +1,10 @@
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
 use super::constants::*;
-use super::enums::{DocLang, EmbeddingType, PrebuiltMode, RerankType};
+use super::enums::{
+    CategoryWeight, DocLang, EmbeddingType, IndexPolicy, OutputFormat, PrebuiltMode, RerankStage,
+    RerankType, VectorDistance,
+};
 
 #[derive(Debug, Clone)]
 pub struct Settings {
@@ -13,23 +16,67 @@ pub struct Settings {
     pub rerank_model: String,
     pub rerank_top_k: usize,
     pub rerank_initial_k: usize,
+    pub rerank_max_doc_chars: usize,
+    pub rerank_window_tokens: usize,
+    pub rerank_warmup: bool,
+    pub index_code: bool,
+    pub decompress_docs: bool,
+    pub strip_html: bool,
+    pub extra_docs_dirs: Vec<PathBuf>,
+    pub extra_docs_prefix: String,
+    pub docs_web_base: String,
     pub rrf_k: u32,
+    pub fusion_candidate_k: usize,
+    pub rerank_stage: RerankStage,
+    pub max_fetch_candidates: usize,
+    pub title_boost: f32,
+    pub length_penalty: f64,
+    pub vector_insert_batch_size: usize,
+    pub vector_score_threshold: f64,
+    pub vector_distance: VectorDistance,
+    pub topic_suggestion_threshold: f64,
     pub chunk_overlap_chars: usize,
+    pub chunk_breadcrumbs: bool,
     pub max_chunk_chars: Option<usize>,
+    pub index_policy: IndexPolicy,
+    pub skip_disk_check: bool,
     pub data_dir: PathBuf,
+    pub docs_repo_dir_override: Option<PathBuf>,
+    pub index_dir_override: Option<PathBuf>,
     pub runtime_version: String,
     pub stdx_version: String,
     pub server_url: Option<String>,
+    pub remote_cache_ttl_secs: u64,
+    pub remote_msgpack: bool,
     pub openai_api_key: Option<String>,
     pub openai_base_url: String,
     pub openai_model: String,
     pub http_pool_idle_timeout_secs: u64,
     pub http_pool_max_idle_per_host: usize,
     pub http_tcp_keepalive_secs: u64,
+    pub http_connect_timeout_secs: u64,
     pub http_enable_http2: bool,
+    pub http_user_agent: String,
+    pub git_retries: usize,
+    pub max_concurrent_git_ops: usize,
+    pub index_build_timeout_secs: u64,
+    pub watch: bool,
+    pub refresh_interval_mins: u64,
+    pub http_retry_base_ms: u64,
+    pub http_retry_cap_ms: u64,
     pub server_enable_http2: bool,
+    pub enabled_tools: Vec<String>,
+    pub disabled_tools: Vec<String>,
+    pub pinned_topics: Vec<String>,
+    /// Score multipliers per category; unlisted categories weigh 1.0.
+    pub category_weights: Vec<CategoryWeight>,
+    /// Format for `search_docs`/`get_topics` results when a request does not
+    /// pick one; `None` means JSON.
+    pub output_format: Option<OutputFormat>,
     pub max_per_file: usize,
+    pub max_concurrent_tools: usize,
     pub summary_model: Option<String>,
+    pub stopwords_file: Option<PathBuf>,
     pub prebuilt: PrebuiltMode,
 }
 
@@ -44,23 +91,64 @@ impl Default for Settings {
             rerank_model: DEFAULT_RERANK_MODEL.to_string(),
             rerank_top_k: DEFAULT_RERANK_TOP_K,
             rerank_initial_k: DEFAULT_RERANK_INITIAL_K,
+            rerank_max_doc_chars: DEFAULT_RERANK_MAX_DOC_CHARS,
+            rerank_window_tokens: DEFAULT_RERANK_WINDOW_TOKENS,
+            rerank_warmup: DEFAULT_RERANK_WARMUP,
+            index_code: DEFAULT_INDEX_CODE,
+            decompress_docs: DEFAULT_DECOMPRESS_DOCS,
+            strip_html: DEFAULT_STRIP_HTML,
+            extra_docs_dirs: Vec::new(),
+            extra_docs_prefix: DEFAULT_EXTRA_DOCS_PREFIX.to_string(),
+            docs_web_base: DEFAULT_DOCS_WEB_BASE.to_string(),
             rrf_k: DEFAULT_RRF_K,
+            fusion_candidate_k: DEFAULT_FUSION_CANDIDATE_K,
+            rerank_stage: RerankStage::default(),
+            max_fetch_candidates: DEFAULT_MAX_FETCH_CANDIDATES,
+            title_boost: DEFAULT_TITLE_BOOST,
+            length_penalty: DEFAULT_LENGTH_PENALTY,
+            vector_insert_batch_size: DEFAULT_VECTOR_INSERT_BATCH_SIZE,
+            vector_score_threshold: VECTOR_SCORE_THRESHOLD,
+            vector_distance: VectorDistance::default(),
+            topic_suggestion_threshold: TOPIC_SUGGESTION_THRESHOLD,
             chunk_overlap_chars: DEFAULT_CHUNK_OVERLAP_CHARS,
+            chunk_breadcrumbs: DEFAULT_CHUNK_BREADCRUMBS,
             max_chunk_chars: None,
+            index_policy: IndexPolicy::Auto,
+            skip_disk_check: DEFAULT_SKIP_DISK_CHECK,
             data_dir: get_default_data_dir(),
+            docs_repo_dir_override: None,
+            index_dir_override: None,
             runtime_version: DEFAULT_DOCS_VERSION.to_string(),
             stdx_version: DEFAULT_DOCS_VERSION.to_string(),
             server_url: None,
+            remote_cache_ttl_secs: DEFAULT_REMOTE_CACHE_TTL_SECS,
+            remote_msgpack: DEFAULT_REMOTE_MSGPACK,
             openai_api_key: None,
             openai_base_url: DEFAULT_OPENAI_BASE_URL.to_string(),
             openai_model: DEFAULT_OPENAI_MODEL.to_string(),
             http_pool_idle_timeout_secs: DEFAULT_HTTP_POOL_IDLE_TIMEOUT_SECS,
             http_pool_max_idle_per_host: DEFAULT_HTTP_POOL_MAX_IDLE_PER_HOST,
             http_tcp_keepalive_secs: DEFAULT_HTTP_TCP_KEEPALIVE_SECS,
+            http_connect_timeout_secs: DEFAULT_HTTP_CONNECT_TIMEOUT_SECS,
             http_enable_http2: DEFAULT_HTTP_ENABLE_HTTP2,
+            http_user_agent: DEFAULT_HTTP_USER_AGENT.to_string(),
+            git_retries: DEFAULT_GIT_RETRIES,
+            max_concurrent_git_ops: DEFAULT_MAX_CONCURRENT_GIT_OPS,
+            index_build_timeout_secs: DEFAULT_INDEX_BUILD_TIMEOUT_SECS,
+            watch: DEFAULT_WATCH,
+            refresh_interval_mins: DEFAULT_REFRESH_INTERVAL_MINS,
+            http_retry_base_ms: DEFAULT_HTTP_RETRY_BASE_MS,
+            http_retry_cap_ms: DEFAULT_HTTP_RETRY_CAP_MS,
             server_enable_http2: DEFAULT_SERVER_ENABLE_HTTP2,
+            enabled_tools: Vec::new(),
+            disabled_tools: Vec::new(),
+            pinned_topics: Vec::new(),
+            category_weights: Vec::new(),
+            output_format: None,
             max_per_file: DEFAULT_MAX_PER_FILE,
+            max_concurrent_tools: DEFAULT_MAX_CONCURRENT_TOOLS,
             summary_model: None,
+            stopwords_file: None,
             prebuilt: PrebuiltMode::Off,
         }
     }
@@ -79,12 +167,35 @@ impl Settings {
         }
     }
 
+    /// Number of results a search should return. A per-request `top_k`
+    /// always bounds the final count; without one, `rerank_top_k` applies when
+    /// the search is reranked and [`DEFAULT_TOP_K`] otherwise.
+    pub fn effective_top_k(&self, requested: Option<usize>, rerank: bool) -> usize {
+        match requested {
+            Some(top_k) => top_k,
+            None if rerank && self.rerank_type != RerankType::None => self.rerank_top_k,
+            None => DEFAULT_TOP_K,
+        }
+    }
+
+    /// Score multiplier for results in `category`: the last matching
+    /// `--category-weight`, else 1.0.
+    pub fn category_weight(&self, category: &str) -> f64 {
+        self.category_weights
+            .iter()
+            .rev()
+            .find(|w| w.category == category)
+            .map_or(1.0, |w| w.factor)
+    }
+
     pub fn fastembed_cache_dir(&self) -> PathBuf {
         self.data_dir.join("cache").join("fastembed")
     }
 
     pub fn docs_repo_dir(&self) -> PathBuf {
-        self.data_dir.join("docs_repo")
+        self.docs_repo_dir_override
+            .clone()
+            .unwrap_or_else(|| self.data_dir.join("docs_repo"))
     }
 
     pub fn runtime_repo_dir(&self) -> PathBuf {
@@ -94,6 +205,73 @@ impl Settings {
     pub fn stdx_repo_dir(&self) -> PathBuf {
         self.data_dir.join("stdx_repo")
     }
+
+    /// Reject a docs repo override that overlaps the data directory's own
+    /// layout, where rebuilding indexes or re-cloning the other repositories
+    /// would clobber the checkout (or the checkout would swallow the indexes).
+    pub fn validate_docs_repo_dir(&self) -> anyhow::Result<()> {
+        let Some(repo_dir) = &self.docs_repo_dir_override else {
+            return Ok(());
+        };
+        // Compare resolved paths so `..` or a symlink cannot hide nesting.
+        let repo = resolve_path(repo_dir);
+        let data_dir = resolve_path(&self.data_dir);
+        if data_dir.starts_with(&repo) {
+            anyhow::bail!(
+                "docs repo dir {} must not contain the data dir {}",
+                repo_dir.display(),
+                self.data_dir.display()
+            );
+        }
+        let managed = [
+            self.data_dir.join("indexes"),
+            self.runtime_repo_dir(),
+            self.stdx_repo_dir(),
+            self.fastembed_cache_dir(),
+        ];
+        if managed
+            .iter()
+            .any(|dir| repo.starts_with(resolve_path(dir)))
+        {
+            anyhow::bail!(
+                "docs repo dir {} must not be inside a directory managed under {}",
+                repo_dir.display(),
+                self.data_dir.display()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// `path` made absolute with `.` and `..` removed and symlinks resolved as
+/// far as it exists; the part that does not exist yet is kept as written.
+fn resolve_path(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            other => normalized.push(other),
+        }
+    }
+    let mut missing = Vec::new();
+    let mut existing = normalized.as_path();
+    loop {
+        if let Ok(mut resolved) = existing.canonicalize() {
+            resolved.extend(missing.iter().rev());
+            return resolved;
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return normalized,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -141,6 +319,19 @@ mod tests {
         assert!(s.max_chunk_chars.is_none());
     }
 
+    #[test]
+    fn test_effective_top_k_precedence() {
+        let mut s = test_settings();
+        s.rerank_top_k = 8;
+        assert_eq!(s.effective_top_k(Some(3), true), 3);
+        assert_eq!(s.effective_top_k(None, true), DEFAULT_TOP_K);
+
+        s.rerank_type = RerankType::Local;
+        assert_eq!(s.effective_top_k(Some(3), true), 3);
+        assert_eq!(s.effective_top_k(None, true), 8);
+        assert_eq!(s.effective_top_k(None, false), DEFAULT_TOP_K);
+    }
+
     #[test]
     fn test_fastembed_cache_dir_under_data_dir() {
         let s = Settings {
@@ -152,4 +343,48 @@ mod tests {
             PathBuf::from("/data/cache/fastembed")
         );
     }
+
+    #[test]
+    fn test_validate_docs_repo_dir_rejects_nesting() {
+        let with_repo = |repo: &str| Settings {
+            data_dir: PathBuf::from("/data"),
+            docs_repo_dir_override: Some(PathBuf::from(repo)),
+            ..Settings::default()
+        };
+
+        assert!(Settings::default().validate_docs_repo_dir().is_ok());
+        assert!(with_repo("/ssd/docs").validate_docs_repo_dir().is_ok());
+        assert!(with_repo("/data/docs_repo")
+            .validate_docs_repo_dir()
+            .is_ok());
+        assert!(with_repo("/data").validate_docs_repo_dir().is_err());
+        assert!(with_repo("/").validate_docs_repo_dir().is_err());
+        assert!(with_repo("/data/indexes/docs")
+            .validate_docs_repo_dir()
+            .is_err());
+        assert!(with_repo("/data/runtime_repo")
+            .validate_docs_repo_dir()
+            .is_err());
+        assert!(with_repo("/data/docs_repo/../indexes/docs")
+            .validate_docs_repo_dir()
+            .is_err());
+        assert!(with_repo("/ssd/../data").validate_docs_repo_dir().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_validate_docs_repo_dir_resolves_symlinks() {
+        let tmp = tempfile::tempdir().unwrap();
+        let data_dir = tmp.path().join("data");
+        std::fs::create_dir_all(data_dir.join("indexes")).unwrap();
+        let link = tmp.path().join("link");
+        std::os::unix::fs::symlink(data_dir.join("indexes"), &link).unwrap();
+
+        let settings = Settings {
+            data_dir,
+            docs_repo_dir_override: Some(link.join("docs")),
+            ..Settings::default()
+        };
+        assert!(settings.validate_docs_repo_dir().is_err());
+    }
 }
@@ -1,5 +1,9 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::config::VectorDistance;
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SearchResultMetadata {
     pub file_path: String,
@@ -9,6 +13,18 @@ pub struct SearchResultMetadata {
     pub has_code: bool,
     #[serde(default)]
     pub chunk_id: String,
+    /// Zero-based position of the chunk among its document's chunks
+    #[serde(default)]
+    pub chunk_index: usize,
+    /// Character offset of the chunk's source text within its document
+    #[serde(default)]
+    pub char_offset: usize,
+    /// Headings enclosing the chunk, outermost first, joined by " > "
+    #[serde(default)]
+    pub breadcrumb: String,
+    /// Front-matter key/values of the source document
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +59,81 @@ pub struct IndexMetadata {
     pub document_count: usize,
     #[serde(default)]
     pub search_mode: SearchMode,
+    /// Whether the build also wrote the code index; `false` for indexes that
+    /// predate recording it.
+    #[serde(default)]
+    pub index_code: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_docs: Option<ExtraDocs>,
+    /// Chunking parameters the index was built with; `None` for indexes that
+    /// predate recording them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunking: Option<ChunkingParams>,
+    /// Distance the vector store was built with; `None` for BM25-only
+    /// indexes and for hybrid indexes that predate recording it (`l2`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector_distance: Option<VectorDistance>,
+    /// Docs repository commit the index was built from; `None` for indexes
+    /// that predate recording it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_commit: Option<String>,
+    /// Version of the BM25 tokenizer the index was built with; `0` for
+    /// indexes that predate recording it (plain Jieba segmentation).
+    #[serde(default)]
+    pub tokenizer_version: u32,
+}
+
+/// Chunk sizing used to build an index, recorded so that changing it
+/// triggers a rebuild.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkingParams {
+    pub max_chunk_chars: Option<usize>,
+    pub chunk_overlap_chars: usize,
+    /// Whether HTML tags were stripped from documents before chunking;
+    /// indexes from before this was recorded kept them.
+    #[serde(default)]
+    pub strip_html: bool,
+    /// Indexes from before this was recorded always prefixed breadcrumbs.
+    #[serde(default = "default_chunk_breadcrumbs")]
+    pub chunk_breadcrumbs: bool,
+}
+
+fn default_chunk_breadcrumbs() -> bool {
+    true
+}
+
+/// Extra markdown directories indexed alongside the official docs, recorded
+/// so that changing them triggers a rebuild.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtraDocs {
+    pub prefix: String,
+    pub dirs: Vec<String>,
+}
+
+/// The commit a requested documentation version (tag, branch, or "latest") resolved to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedVersion {
+    pub requested: String,
+    pub resolved_ref: String,
+    pub commit_oid: String,
+    pub short_hash: String,
+}
+
+/// Topics (document paths relative to the docs source directory, without
+/// `.md`) that differ between two documentation versions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionTopicDiff {
+    pub from: String,
+    pub to: String,
+    /// Topics only in `to`
+    pub added: Vec<String>,
+    /// Topics only in `from`
+    pub removed: Vec<String>,
+    pub common: Vec<String>,
+    /// Versions (`from` and/or `to`) that have no docs source directory;
+    /// their topic lists are treated as empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing_source: Vec<String>,
 }
 
 /// Lightweight document container (no framework dependency).
@@ -62,6 +153,14 @@ pub struct DocMetadata {
     pub code_block_count: usize,
     pub has_code: bool,
     pub chunk_id: String,
+    /// Zero-based position of the chunk among its document's chunks.
+    pub chunk_index: usize,
+    /// Character offset of the chunk's source text within its document.
+    pub char_offset: usize,
+    /// Headings enclosing the chunk, outermost first, joined by " > ".
+    pub breadcrumb: String,
+    /// Key/values from the document's YAML or TOML front matter.
+    pub custom: HashMap<String, String>,
 }
 
 /// A text chunk produced by the chunker with its metadata.
@@ -3,25 +3,32 @@
 use std::time::Duration;
 
 use anyhow::{Context, Result};
-use backon::{ExponentialBuilder, Retryable};
+use backon::Retryable;
 use reqwest::StatusCode;
 use serde::{de::DeserializeOwned, Serialize};
 use tracing::warn;
 
 use cangjie_core::config::Settings;
 
+use crate::backoff::JitteredBackoff;
+
 const DEFAULT_POST_JSON_MAX_ATTEMPTS: usize = 6;
-const DEFAULT_RETRY_INITIAL_BACKOFF_SECS: u64 = 2;
-const DEFAULT_RETRY_MAX_BACKOFF_SECS: u64 = 30;
-const DEFAULT_RETRY_FACTOR: f32 = 2.0;
+
+/// Media type of MessagePack request and response bodies.
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// How long to wait for a TCP connection, kept short so a dead host fails
+/// fast even when the overall request timeout allows a slow response.
+fn connect_timeout(settings: &Settings, timeout: Duration) -> Duration {
+    Duration::from_secs(settings.http_connect_timeout_secs).min(timeout)
+}
 
 /// Build a shared HTTP client optimized for external API calls.
 fn build_http_client(settings: &Settings, timeout: Duration) -> Result<reqwest::Client> {
     let mut builder = reqwest::Client::builder()
+        .user_agent(settings.http_user_agent.as_str())
         .timeout(timeout)
-        .connect_timeout(Duration::from_secs(
-            cangjie_core::config::DEFAULT_HTTP_CONNECT_TIMEOUT_SECS,
-        ))
+        .connect_timeout(connect_timeout(settings, timeout))
         .pool_idle_timeout(Duration::from_secs(settings.http_pool_idle_timeout_secs))
         .pool_max_idle_per_host(settings.http_pool_max_idle_per_host)
         .tcp_keepalive(Duration::from_secs(settings.http_tcp_keepalive_secs));
@@ -122,6 +129,38 @@ async fn decode_json_response<T: DeserializeOwned>(
     })
 }
 
+/// Whether an API error came from the server rejecting our credentials
+/// (HTTP 401/403), as opposed to a network or server failure.
+pub(crate) fn is_auth_rejection(err: &anyhow::Error) -> bool {
+    let msg = format!("{err:#}");
+    msg.contains("HTTP 401") || msg.contains("HTTP 403")
+}
+
+/// Whether a request failed because the server could not be reached or
+/// answered with a 5xx status, so another server may be worth trying.
+pub(crate) fn is_server_unavailable(err: &anyhow::Error) -> bool {
+    let unreachable = err.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_connect() || e.is_timeout())
+    });
+    unreachable || format!("{err:#}").contains("HTTP 5")
+}
+
+/// Probable cause to report for a base URL with no path, such as
+/// `https://api.openai.com` where OpenAI-compatible APIs live under `/v1`.
+/// `None` when the URL has a path or does not parse.
+pub(crate) fn missing_version_path_hint(base_url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(base_url).ok()?;
+    if !url.path().trim_matches('/').is_empty() {
+        return None;
+    }
+    let base = base_url.trim_end_matches('/');
+    Some(format!(
+        "the base URL {base} has no version path; OpenAI-compatible APIs usually expect one, e.g. {base}/v1"
+    ))
+}
+
 fn is_retryable_status(status: StatusCode) -> bool {
     matches!(
         status,
@@ -131,19 +170,13 @@ fn is_retryable_status(status: StatusCode) -> bool {
     )
 }
 
-fn retry_backoff(max_attempts: usize) -> ExponentialBuilder {
-    ExponentialBuilder::default()
-        .with_factor(DEFAULT_RETRY_FACTOR)
-        .with_min_delay(Duration::from_secs(DEFAULT_RETRY_INITIAL_BACKOFF_SECS))
-        .with_max_delay(Duration::from_secs(DEFAULT_RETRY_MAX_BACKOFF_SECS))
-        .with_max_times(max_attempts)
-}
-
 /// HTTP client (no auth) with base-URL handling and retry support.
 #[derive(Clone)]
 pub(crate) struct HttpClient {
     base_url: String,
     client: reqwest::Client,
+    post_max_attempts: usize,
+    retry_backoff: JitteredBackoff,
 }
 
 impl HttpClient {
@@ -151,9 +184,21 @@ impl HttpClient {
         Ok(Self {
             base_url: base_url.trim_end_matches('/').to_string(),
             client: build_http_client(settings, timeout)?,
+            post_max_attempts: DEFAULT_POST_JSON_MAX_ATTEMPTS,
+            retry_backoff: JitteredBackoff::new(
+                Duration::from_millis(settings.http_retry_base_ms),
+                Duration::from_millis(settings.http_retry_cap_ms),
+            ),
         })
     }
 
+    /// Override how many times `post_json` sends a request before giving up;
+    /// `1` (the minimum) disables retries.
+    pub fn with_post_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.post_max_attempts = max_attempts.max(1);
+        self
+    }
+
     fn url_for(&self, endpoint: &str) -> String {
         format!("{}/{}", self.base_url, endpoint)
     }
@@ -193,8 +238,9 @@ impl HttpClient {
             }
         };
 
+        let max_attempts = max_attempts.max(1);
         send_request
-            .retry(retry_backoff(max_attempts))
+            .retry(self.retry_backoff.with_max_times(max_attempts - 1))
             .notify(|err: &anyhow::Error, wait: Duration| {
                 warn!("{request_label} transient failure: {err}; retrying in {wait:?}");
             })
@@ -224,12 +270,44 @@ impl HttpClient {
         payload: &P,
     ) -> Result<T> {
         let request_label = format!("POST /{endpoint}");
-        self.send_json_with_retry(&request_label, DEFAULT_POST_JSON_MAX_ATTEMPTS, || {
+        self.send_json_with_retry(&request_label, self.post_max_attempts, || {
             self.post(endpoint).json(payload)
         })
         .await
     }
 
+    /// Like [`Self::post_json`], but asks for a MessagePack response. A server
+    /// that answers with JSON anyway is decoded as JSON.
+    pub async fn post_json_accept_msgpack<P: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        payload: &P,
+    ) -> Result<T> {
+        let request_label = format!("POST /{endpoint}");
+        let response = self
+            .send_with_retry(&request_label, self.post_max_attempts, || {
+                self.post(endpoint)
+                    .header(reqwest::header::ACCEPT, MSGPACK_CONTENT_TYPE)
+                    .json(payload)
+            })
+            .await?;
+        let is_msgpack = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with(MSGPACK_CONTENT_TYPE));
+        if !is_msgpack || !response.status().is_success() {
+            return decode_json_response(response, &request_label).await;
+        }
+        let url = response.url().clone();
+        let body = response
+            .bytes()
+            .await
+            .with_context(|| format!("Failed to read {request_label} response body from {url}"))?;
+        rmp_serde::from_slice(&body)
+            .with_context(|| format!("Invalid MessagePack {request_label} response from {url}"))
+    }
+
     /// GET with retry + exponential backoff, for init/startup scenarios.
     pub async fn get_with_retry<T: DeserializeOwned>(
         &self,
@@ -238,7 +316,7 @@ impl HttpClient {
     ) -> Result<T> {
         let url = self.url_for(endpoint);
         let request_label = format!("GET /{endpoint}");
-        self.send_json_with_retry(&request_label, max_retries as usize, || {
+        self.send_json_with_retry(&request_label, max_retries as usize + 1, || {
             self.client.get(&url)
         })
         .await
@@ -251,6 +329,8 @@ pub(crate) struct ApiClient {
     http: HttpClient,
     model: String,
     auth_header: String,
+    /// Set when the base URL has no version path, to explain a 404.
+    path_hint: Option<String>,
 }
 
 impl ApiClient {
@@ -261,10 +341,15 @@ impl ApiClient {
         base_url: &str,
         timeout: Duration,
     ) -> Result<Self> {
+        let path_hint = missing_version_path_hint(base_url);
+        if let Some(ref hint) = path_hint {
+            warn!("OpenAI-compatible API: {hint}");
+        }
         Ok(Self {
             http: HttpClient::new(settings, base_url, timeout)?,
             auth_header: format!("Bearer {}", api_key),
             model: model.to_string(),
+            path_hint,
         })
     }
 
@@ -286,6 +371,12 @@ impl ApiClient {
                 self.post(endpoint).json(payload)
             })
             .await
+            .map_err(|e| match self.path_hint {
+                Some(ref hint) if format!("{e:#}").contains("HTTP 404") => {
+                    e.context(format!("Probable cause: {hint}"))
+                }
+                _ => e,
+            })
     }
 
     pub fn model(&self) -> &str {
@@ -371,6 +462,199 @@ mod tests {
         assert!(msg.contains("x-siliconcloud-trace-id: trace-123"), "{msg}");
     }
 
+    #[test]
+    fn missing_version_path_hint_flags_bare_host_only() {
+        let hint = missing_version_path_hint("https://api.openai.com/").unwrap();
+        assert!(hint.contains("https://api.openai.com/v1"), "{hint}");
+        assert_eq!(missing_version_path_hint("https://api.openai.com/v1"), None);
+        assert_eq!(
+            missing_version_path_hint("https://api.siliconflow.cn/v1/"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn post_json_not_found_on_bare_host_names_probable_cause() {
+        let not_found = || MockResponse {
+            status_line: "404 Not Found",
+            headers: &[],
+            body: r#"{"error":"not found"}"#,
+        };
+        let base_url = spawn_json_server(vec![not_found(), not_found()]).await;
+        let payload = serde_json::json!({"model":"test-model","input":["hello"]});
+
+        let bare = ApiClient::new(
+            &Settings::default(),
+            "test-key",
+            "test-model",
+            &base_url,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        let err = bare
+            .post_json::<_, serde_json::Value>("embeddings", &payload)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains(&format!("{base_url}/v1")),
+            "{err:#}"
+        );
+
+        let suffixed = ApiClient::new(
+            &Settings::default(),
+            "test-key",
+            "test-model",
+            &format!("{base_url}/v1"),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        assert_eq!(suffixed.base_url(), format!("{base_url}/v1"));
+        let err = suffixed
+            .post_json::<_, serde_json::Value>("embeddings", &payload)
+            .await
+            .unwrap_err();
+        assert!(!format!("{err:#}").contains("Probable cause"), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn post_json_with_zero_max_attempts_sends_once() {
+        let base_url = spawn_json_server(vec![MockResponse {
+            status_line: "503 Service Unavailable",
+            headers: &[],
+            body: r#"{"error":"busy"}"#,
+        }])
+        .await;
+        let client = HttpClient::new(&Settings::default(), &base_url, Duration::from_secs(5))
+            .unwrap()
+            .with_post_max_attempts(0);
+
+        let err = client
+            .post_json::<_, serde_json::Value>("search", &serde_json::json!({}))
+            .await
+            .unwrap_err();
+        let msg = format!("{err:#}");
+        assert!(msg.contains("after 1 attempts"), "{msg}");
+        assert!(msg.contains("503"), "{msg}");
+    }
+
+    #[tokio::test]
+    async fn post_json_unauthorized_is_auth_rejection() {
+        let base_url = spawn_json_server(vec![
+            MockResponse {
+                status_line: "401 Unauthorized",
+                headers: &[],
+                body: r#"{"error":{"message":"Incorrect API key provided"}}"#,
+            },
+            MockResponse {
+                status_line: "500 Internal Server Error",
+                headers: &[],
+                body: r#"{"error":"boom"}"#,
+            },
+        ])
+        .await;
+        let client = ApiClient::new(
+            &Settings::default(),
+            "wrong-key",
+            "test-model",
+            &base_url,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        let payload = serde_json::json!({"model":"test-model","input":["hello"]});
+
+        let err = client
+            .post_json::<_, serde_json::Value>("embeddings", &payload)
+            .await
+            .unwrap_err();
+        assert!(is_auth_rejection(&err), "{err:#}");
+
+        let err = client
+            .post_json::<_, serde_json::Value>("embeddings", &payload)
+            .await
+            .unwrap_err();
+        assert!(!is_auth_rejection(&err), "{err:#}");
+    }
+
+    #[tokio::test]
+    async fn http_client_sends_configured_user_agent() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = tokio::sync::oneshot::channel::<String>();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0_u8; 4096];
+            let n = stream.read(&mut buf).await.unwrap();
+            let _ = tx.send(String::from_utf8_lossy(&buf[..n]).to_string());
+
+            let body = r#"{"ok":true}"#;
+            let payload = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len(),
+            );
+            stream.write_all(payload.as_bytes()).await.unwrap();
+        });
+
+        let settings = Settings {
+            http_user_agent: "cangjie-mcp-test/1.2.3".to_string(),
+            ..Settings::default()
+        };
+        let client =
+            HttpClient::new(&settings, &format!("http://{addr}"), Duration::from_secs(5)).unwrap();
+
+        let body: serde_json::Value = client.get_with_retry("info", 1).await.unwrap();
+        assert_eq!(body, serde_json::json!({"ok": true}));
+
+        let request = rx.await.unwrap().to_ascii_lowercase();
+        assert!(
+            request.contains("user-agent: cangjie-mcp-test/1.2.3"),
+            "{request}"
+        );
+    }
+
+    #[test]
+    fn connect_timeout_uses_setting_within_request_timeout() {
+        let settings = Settings {
+            http_connect_timeout_secs: 3,
+            ..Settings::default()
+        };
+        assert_eq!(
+            connect_timeout(&settings, Duration::from_secs(60)),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            connect_timeout(&settings, Duration::from_secs(1)),
+            Duration::from_secs(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn unroutable_host_fails_within_connect_timeout() {
+        let settings = Settings {
+            http_connect_timeout_secs: 1,
+            ..Settings::default()
+        };
+        // TEST-NET-1 (RFC 5737) is never routed, so the connect either hangs
+        // until the connect timeout or is rejected outright.
+        let client =
+            HttpClient::new(&settings, "http://192.0.2.1:9", Duration::from_secs(60)).unwrap();
+
+        let started = std::time::Instant::now();
+        let err = client
+            .get_with_retry::<serde_json::Value>("info", 0)
+            .await
+            .unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(10), "{err:#}");
+        assert!(is_server_unavailable(&err), "{err:#}");
+    }
+
+    #[test]
+    fn default_user_agent_includes_crate_version() {
+        let settings = Settings::default();
+        assert!(settings.http_user_agent.starts_with("cangjie-mcp/"));
+        assert!(!settings.http_user_agent.ends_with('/'));
+    }
+
     #[tokio::test]
     async fn post_json_retries_rate_limited_response_without_retry_after() {
         let base_url = spawn_json_server(vec![
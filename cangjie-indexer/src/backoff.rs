@@ -0,0 +1,104 @@
+//! Exponential backoff with full jitter for retried network operations.
+
+use std::time::Duration;
+
+use backon::BackoffBuilder;
+
+/// Exponential backoff where the delay before retry `n` is drawn uniformly
+/// from `[0, min(cap, base * 2^n)]`. Randomizing the whole interval keeps
+/// clients that failed together from retrying in lockstep.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct JitteredBackoff {
+    base: Duration,
+    cap: Duration,
+    max_times: usize,
+}
+
+impl JitteredBackoff {
+    pub(crate) fn new(base: Duration, cap: Duration) -> Self {
+        Self {
+            base,
+            cap: cap.max(base),
+            max_times: 3,
+        }
+    }
+
+    pub(crate) fn with_max_times(mut self, max_times: usize) -> Self {
+        self.max_times = max_times;
+        self
+    }
+
+    /// Largest delay that may precede retry `attempt` (0-based).
+    pub(crate) fn ceiling(&self, attempt: usize) -> Duration {
+        let factor = 1_u32.checked_shl(attempt as u32).unwrap_or(u32::MAX);
+        self.base.saturating_mul(factor).min(self.cap)
+    }
+
+    pub(crate) fn delay(&self, attempt: usize) -> Duration {
+        self.ceiling(attempt).mul_f64(fastrand::f64())
+    }
+}
+
+impl BackoffBuilder for JitteredBackoff {
+    type Backoff = JitteredDelays;
+
+    fn build(self) -> Self::Backoff {
+        JitteredDelays {
+            backoff: self,
+            attempt: 0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct JitteredDelays {
+    backoff: JitteredBackoff,
+    attempt: usize,
+}
+
+impl Iterator for JitteredDelays {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        if self.attempt >= self.backoff.max_times {
+            return None;
+        }
+        let delay = self.backoff.delay(self.attempt);
+        self.attempt += 1;
+        Some(delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delays_stay_within_cap_and_ceiling_grows() {
+        let cap = Duration::from_millis(800);
+        let backoff = JitteredBackoff::new(Duration::from_millis(100), cap).with_max_times(8);
+
+        let ceilings: Vec<Duration> = (0..8).map(|n| backoff.ceiling(n)).collect();
+        assert_eq!(ceilings[0], Duration::from_millis(100));
+        assert!(ceilings.windows(2).all(|w| w[0] <= w[1]));
+        assert!(ceilings[..4].windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(ceilings[7], cap);
+
+        for _ in 0..50 {
+            let delays: Vec<Duration> = backoff.build().collect();
+            assert_eq!(delays.len(), 8);
+            for (attempt, delay) in delays.iter().enumerate() {
+                assert!(*delay <= backoff.ceiling(attempt), "{delay:?}");
+                assert!(*delay <= cap, "{delay:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_ceiling_saturates_for_large_attempts() {
+        let cap = Duration::from_secs(30);
+        let backoff = JitteredBackoff::new(Duration::from_secs(2), cap);
+        assert_eq!(backoff.ceiling(64), cap);
+        assert_eq!(backoff.ceiling(usize::MAX), cap);
+    }
+}
@@ -142,7 +142,7 @@ fn parse_headings(text: &str) -> Vec<(usize, usize, String)> {
         .collect()
 }
 
-/// Build a breadcrumb string like `[H1 > H2 > H3]` for the heading stack
+/// Build a breadcrumb string like `H1 > H2 > H3` for the heading stack
 /// that is active at `byte_offset` in the original document.
 fn heading_breadcrumb(headings: &[(usize, usize, String)], byte_offset: usize) -> Option<String> {
     let mut stack: Vec<(usize, &str)> = Vec::new(); // (level, title)
@@ -162,12 +162,13 @@ fn heading_breadcrumb(headings: &[(usize, usize, String)], byte_offset: usize) -
         return None;
     }
 
-    let crumb = stack
-        .iter()
-        .map(|(_, t)| *t)
-        .collect::<Vec<_>>()
-        .join(" > ");
-    Some(format!("[{crumb}]"))
+    Some(
+        stack
+            .iter()
+            .map(|(_, t)| *t)
+            .collect::<Vec<_>>()
+            .join(" > "),
+    )
 }
 
 /// Find the byte offset of `chunk_text` inside `full_text`.
@@ -191,13 +192,14 @@ fn count_code_blocks(text: &str) -> usize {
 /// Split a document into chunks in two stages: markdown structure via
 /// `MarkdownSplitter`, then oversized Cangjie code blocks via `CodeSplitter`.
 ///
-/// Each chunk is prefixed with its heading breadcrumb (e.g. `[H1 > H2]\n\n`),
-/// and adjacent chunks share a small overlap to preserve context across
-/// boundaries.
+/// Each chunk records its heading breadcrumb in its metadata and, with
+/// `breadcrumbs`, is prefixed with it (e.g. `[H1 > H2]\n\n`). Adjacent chunks
+/// share a small overlap to preserve context across boundaries.
 pub fn chunk_document(
     doc: &DocData,
     max_chunk_chars: Option<usize>,
     overlap_chars: usize,
+    breadcrumbs: bool,
 ) -> Vec<TextChunk> {
     let text = &doc.text;
     if text.is_empty() {
@@ -220,13 +222,15 @@ pub fn chunk_document(
     for raw_chunk in &raw_chunks {
         let byte_off = chunk_byte_offset(text, raw_chunk);
         let sub_chunks = split_chunk_code_blocks(raw_chunk, budget);
-        let prefix = heading_breadcrumb(&headings, byte_off);
+        let breadcrumb = heading_breadcrumb(&headings, byte_off);
 
         for sub_chunk in &sub_chunks {
+            let sub_off = byte_off + raw_chunk.find(sub_chunk.as_str()).unwrap_or(0);
             let mut assembled = String::new();
-            if let Some(pfx) = &prefix {
-                assembled.push_str(pfx);
-                assembled.push_str("\n\n");
+            if let Some(crumb) = breadcrumb.as_ref().filter(|_| breadcrumbs) {
+                assembled.push('[');
+                assembled.push_str(crumb);
+                assembled.push_str("]\n\n");
             }
             if chunk_idx > 0 && overlap_chars > 0 {
                 if let Some(ref tail) = prev_chunk_tail {
@@ -243,6 +247,9 @@ pub fn chunk_document(
             meta.has_code = code_block_count > 0;
             meta.code_block_count = code_block_count;
             meta.chunk_id = format!("{}#{}", doc.metadata.file_path, chunk_idx);
+            meta.chunk_index = chunk_idx;
+            meta.char_offset = text[..sub_off].chars().count();
+            meta.breadcrumb = breadcrumb.clone().unwrap_or_default();
 
             results.push(TextChunk {
                 text: assembled,
@@ -294,11 +301,17 @@ pub async fn chunk_documents(
     docs: Vec<DocData>,
     max_chunk_chars: Option<usize>,
     overlap_chars: usize,
+    breadcrumbs: bool,
 ) -> Vec<TextChunk> {
     tokio::task::spawn_blocking(move || {
         let mut all_chunks = Vec::new();
         for doc in &docs {
-            all_chunks.extend(chunk_document(doc, max_chunk_chars, overlap_chars));
+            all_chunks.extend(chunk_document(
+                doc,
+                max_chunk_chars,
+                overlap_chars,
+                breadcrumbs,
+            ));
         }
         info!(
             "Created {} chunks from {} documents.",
@@ -399,7 +412,7 @@ mod tests {
         let doc = make_doc(
             "# Title\n\nSome text.\n\n```cangjie\nfunc main() {\n    println(\"hello\")\n}\n```\n\nMore text.",
         );
-        let chunks = chunk_document(&doc, None, DEFAULT_CHUNK_OVERLAP_CHARS);
+        let chunks = chunk_document(&doc, None, DEFAULT_CHUNK_OVERLAP_CHARS, true);
         assert!(!chunks.is_empty());
         for chunk in &chunks {
             assert!(!chunk.text.is_empty());
@@ -409,14 +422,14 @@ mod tests {
     #[test]
     fn test_chunk_empty_document() {
         let doc = make_doc("");
-        let chunks = chunk_document(&doc, Some(500), 200);
+        let chunks = chunk_document(&doc, Some(500), 200, true);
         assert!(chunks.is_empty());
     }
 
     #[test]
     fn test_chunk_small_document() {
         let doc = make_doc("Short text.");
-        let chunks = chunk_document(&doc, Some(500), 200);
+        let chunks = chunk_document(&doc, Some(500), 200, true);
         assert_eq!(chunks.len(), 1);
         assert!(chunks[0].text.contains("Short text."));
     }
@@ -424,7 +437,7 @@ mod tests {
     #[test]
     fn test_chunk_preserves_metadata() {
         let doc = make_doc("Some text content.");
-        let chunks = chunk_document(&doc, Some(500), 200);
+        let chunks = chunk_document(&doc, Some(500), 200, true);
         assert_eq!(chunks[0].metadata.category, "test");
         assert_eq!(chunks[0].metadata.topic, "doc");
         assert_eq!(chunks[0].metadata.file_path, "test/doc.md");
@@ -433,7 +446,7 @@ mod tests {
     #[test]
     fn test_chunk_detects_code() {
         let doc = make_doc("Some text\n\n```cangjie\nfunc main() {}\n```\n");
-        let chunks = chunk_document(&doc, Some(5000), 200);
+        let chunks = chunk_document(&doc, Some(5000), 200, true);
         assert!(chunks.iter().any(|c| c.metadata.has_code));
         assert!(chunks.iter().any(|c| c.metadata.code_block_count > 0));
     }
@@ -443,7 +456,7 @@ mod tests {
         let paragraph = "This is a paragraph of text. ".repeat(50);
         let text = format!("# Title\n\n{paragraph}\n\n## Section 2\n\n{paragraph}");
         let doc = make_doc(&text);
-        let chunks = chunk_document(&doc, Some(500), 200);
+        let chunks = chunk_document(&doc, Some(500), 200, true);
         assert!(
             chunks.len() > 1,
             "Large document should be split into multiple chunks"
@@ -453,10 +466,28 @@ mod tests {
     #[test]
     fn test_chunk_id_generation() {
         let doc = make_doc("# Hello\n\nSome content here.");
-        let chunks = chunk_document(&doc, Some(5000), 200);
+        let chunks = chunk_document(&doc, Some(5000), 200, true);
         assert_eq!(chunks[0].metadata.chunk_id, "test/doc.md#0");
     }
 
+    #[test]
+    fn test_chunk_positions_increase_within_document() {
+        let paragraph = "这是一段文字。This is a paragraph of text. ".repeat(30);
+        let text = format!("# Title\n\n{paragraph}\n\n## Section 2\n\n{paragraph}");
+        let doc = make_doc(&text);
+        let chunks = chunk_document(&doc, Some(500), 200, true);
+        assert!(chunks.len() > 2);
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.metadata.chunk_index, i);
+        }
+        assert_eq!(chunks[0].metadata.char_offset, 0);
+        assert!(chunks
+            .windows(2)
+            .all(|w| w[0].metadata.char_offset < w[1].metadata.char_offset));
+        let last = chunks.last().unwrap();
+        assert!(last.metadata.char_offset < text.chars().count());
+    }
+
     #[test]
     fn test_heading_breadcrumb_basic() {
         let headings = parse_headings("# Title\n\nText\n\n## Sub\n\nMore");
@@ -469,7 +500,7 @@ mod tests {
     fn test_heading_prefix_injected() {
         let text = "# Main\n\n## Sub Section\n\nContent here.";
         let doc = make_doc(text);
-        let chunks = chunk_document(&doc, Some(5000), 200);
+        let chunks = chunk_document(&doc, Some(5000), 200, true);
         assert!(
             chunks[0].text.contains("[Main]") || chunks[0].text.contains("[Main > Sub Section]"),
             "Chunk should have heading breadcrumb, got: {}",
@@ -477,6 +508,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_nested_chunk_carries_full_breadcrumb() {
+        let body = "集合类型提供常用的数据结构。".repeat(20);
+        let doc = make_doc(&format!(
+            "# 标准库\n\n{body}\n\n## 集合类型\n\n{body}\n\n### HashMap\n\n{body}"
+        ));
+        let chunks = chunk_document(&doc, Some(500), 200, true);
+        assert!(chunks.len() > 1);
+        let last = chunks.last().unwrap();
+        assert_eq!(last.metadata.breadcrumb, "标准库 > 集合类型 > HashMap");
+        assert!(last.text.starts_with("[标准库 > 集合类型 > HashMap]"));
+
+        let plain = chunk_document(&doc, Some(500), 200, false);
+        let last = plain.last().unwrap();
+        assert_eq!(last.metadata.breadcrumb, "标准库 > 集合类型 > HashMap");
+        assert!(!last.text.starts_with('['));
+    }
+
     #[test]
     fn test_heading_breadcrumb_hierarchy() {
         let headings = vec![
@@ -485,7 +534,7 @@ mod tests {
             (20, 3, "C".to_string()),
         ];
         let bc = heading_breadcrumb(&headings, 25);
-        assert_eq!(bc.unwrap(), "[A > B > C]");
+        assert_eq!(bc.unwrap(), "A > B > C");
     }
 
     #[test]
@@ -497,7 +546,7 @@ mod tests {
         ];
         // At offset 25, H2="C" should replace H2="B"
         let bc = heading_breadcrumb(&headings, 25);
-        assert_eq!(bc.unwrap(), "[A > C]");
+        assert_eq!(bc.unwrap(), "A > C");
     }
 
     #[test]
@@ -514,7 +563,7 @@ mod tests {
     #[tokio::test]
     async fn test_chunk_documents_multiple() {
         let docs = vec![make_doc("Doc 1"), make_doc("Doc 2"), make_doc("Doc 3")];
-        let chunks = chunk_documents(docs, Some(500), 200).await;
+        let chunks = chunk_documents(docs, Some(500), 200, true).await;
         assert_eq!(chunks.len(), 3);
     }
 
@@ -1,4 +1,5 @@
 use regex::Regex;
+use std::collections::HashMap;
 use std::sync::LazyLock;
 
 use super::CODE_BLOCK_RE;
@@ -14,12 +15,201 @@ pub fn extract_title_from_content(content: &str) -> String {
         .unwrap_or_default()
 }
 
+/// Split a leading YAML (`---`) or TOML (`+++`) front-matter block off
+/// `content`, returning its key/values and the remaining body.
+///
+/// Only top-level keys are kept. Scalars are stored as text and lists are
+/// joined with `", "`. Content without front matter, or whose block does
+/// not parse as a mapping, is returned unchanged with no fields.
+pub fn split_front_matter(content: &str) -> (HashMap<String, String>, &str) {
+    let trimmed = content.trim_start_matches('\u{feff}');
+    for fence in ["---", "+++"] {
+        let Some(rest) = trimmed
+            .strip_prefix(fence)
+            .and_then(|r| r.strip_prefix("\r\n").or_else(|| r.strip_prefix('\n')))
+        else {
+            continue;
+        };
+        let Some((block, body)) = split_at_fence(rest, fence) else {
+            return (HashMap::new(), content);
+        };
+        let fields = if fence == "---" {
+            parse_yaml_fields(block)
+        } else {
+            parse_toml_fields(block)
+        };
+        return match fields {
+            Some(fields) => (fields, body),
+            None => (HashMap::new(), content),
+        };
+    }
+    (HashMap::new(), content)
+}
+
+/// Find the closing `fence` line, returning the block before it and the
+/// text after it.
+fn split_at_fence<'a>(rest: &'a str, fence: &str) -> Option<(&'a str, &'a str)> {
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == fence {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Flat `key: value` YAML, including `[a, b]` and `- item` lists. `None`
+/// unless the block is a non-empty mapping, so a page opening with a `---`
+/// horizontal rule keeps its text.
+fn parse_yaml_fields(block: &str) -> Option<HashMap<String, String>> {
+    let unquote = |v: &str| v.trim().trim_matches(|c| c == '"' || c == '\'').to_string();
+    let mut fields = HashMap::new();
+    let mut list_key: Option<String> = None;
+    let mut list_items: Vec<String> = Vec::new();
+    for line in block.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let (Some(_), Some(item)) = (&list_key, trimmed.strip_prefix("- ")) {
+            list_items.push(unquote(item));
+            continue;
+        }
+        if let Some(key) = list_key.take() {
+            fields.insert(key, std::mem::take(&mut list_items).join(", "));
+        }
+        if line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        let (key, value) = line.split_once(':')?;
+        let key = unquote(key);
+        let is_key = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.');
+        if key.is_empty() || !key.chars().all(is_key) {
+            return None;
+        }
+        if !(value.is_empty() || value.starts_with(char::is_whitespace)) {
+            return None;
+        }
+        let value = value.trim();
+        if value.is_empty() {
+            list_key = Some(key);
+        } else if let Some(items) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            let items: Vec<String> = items.split(',').map(unquote).collect();
+            fields.insert(key, items.join(", "));
+        } else {
+            fields.insert(key, unquote(value));
+        }
+    }
+    if let Some(key) = list_key {
+        fields.insert(key, list_items.join(", "));
+    }
+    (!fields.is_empty()).then_some(fields)
+}
+
+fn parse_toml_fields(block: &str) -> Option<HashMap<String, String>> {
+    let table: toml::Table = block.parse().ok()?;
+    let text = |value: &toml::Value| match value {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    Some(
+        table
+            .iter()
+            .filter(|(_, v)| !v.is_table())
+            .map(|(k, v)| {
+                let value = match v {
+                    toml::Value::Array(items) => {
+                        items.iter().map(text).collect::<Vec<_>>().join(", ")
+                    }
+                    other => text(other),
+                };
+                (k.clone(), value)
+            })
+            .collect(),
+    )
+}
+
+/// Whether `custom` has every key/value in `filter`. A list field matches
+/// when any of its items equals the filter value; comparison ignores case.
+pub fn custom_fields_match(
+    custom: &HashMap<String, String>,
+    filter: &HashMap<String, String>,
+) -> bool {
+    filter.iter().all(|(key, wanted)| {
+        custom.get(key).is_some_and(|value| {
+            value.eq_ignore_ascii_case(wanted)
+                || value
+                    .split(',')
+                    .any(|item| item.trim().eq_ignore_ascii_case(wanted.trim()))
+        })
+    })
+}
+
+/// HTML comments and tags of common HTML elements. Limited to known element
+/// names so generic types in prose (`Array<Int>`) are left alone.
+static HTML_TAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?is)<!--.*?-->|</?(a|abbr|b|br|center|details|div|em|font|h[1-6]|hr|i|img|kbd|li|ol|p|pre|span|strong|sub|summary|sup|table|tbody|td|tfoot|th|thead|tr|u|ul)(\s[^<>]*)?/?>",
+    )
+    .unwrap()
+});
+
+/// Remove HTML tags from markdown, keeping the text between them. Line and
+/// row breaks become newlines and table cells are separated by spaces. Fenced
+/// code blocks and inline code are left verbatim.
+pub fn strip_html(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut in_fence = false;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            out.push_str(line);
+            continue;
+        }
+        if in_fence {
+            out.push_str(line);
+            continue;
+        }
+        // Odd segments between backticks are inline code.
+        for (i, segment) in line.split('`').enumerate() {
+            if i > 0 {
+                out.push('`');
+            }
+            if i % 2 == 1 {
+                out.push_str(segment);
+                continue;
+            }
+            let stripped = HTML_TAG_RE.replace_all(segment, |caps: &regex::Captures| {
+                match caps.get(1).map(|m| m.as_str().to_ascii_lowercase()) {
+                    Some(tag) if matches!(tag.as_str(), "br" | "p" | "div" | "tr" | "li") => "\n",
+                    Some(tag) if matches!(tag.as_str(), "td" | "th") => " ",
+                    _ => "",
+                }
+            });
+            out.push_str(&stripped);
+        }
+    }
+    out
+}
+
+/// Load a markdown document, splitting off its front matter and, with
+/// `strip_html`, removing HTML tags from the body.
 pub fn load_document_from_content(
-    content: String,
+    mut content: String,
     relative_path: &str,
     category: &str,
     topic: &str,
+    strip_html: bool,
 ) -> Option<DocData> {
+    let (custom, body) = split_front_matter(&content);
+    if body.len() != content.len() {
+        content = body.to_string();
+    }
+    if strip_html {
+        content = self::strip_html(&content);
+    }
     if content.trim().is_empty() {
         return None;
     }
@@ -37,6 +227,10 @@ pub fn load_document_from_content(
             code_block_count: 0,
             has_code,
             chunk_id: String::new(),
+            chunk_index: 0,
+            char_offset: 0,
+            custom,
+            breadcrumb: String::new(),
         },
         doc_id: relative_path.to_string(),
     })
@@ -89,6 +283,7 @@ mod tests {
             "syntax/functions.md",
             "syntax",
             "functions",
+            false,
         );
         assert!(doc.is_some());
         let doc = doc.unwrap();
@@ -98,23 +293,115 @@ mod tests {
         assert!(doc.metadata.has_code);
     }
 
+    #[test]
+    fn test_yaml_front_matter_becomes_custom_fields() {
+        let content = "---\ntitle: Guide\naudience: \"internal\"\ntags: [beta, ops]\nowners:\n  - alice\n  - bob\n---\n# Deploying\n\nSteps.\n".to_string();
+        let doc =
+            load_document_from_content(content, "team/deploy.md", "team", "deploy", false).unwrap();
+        let custom = &doc.metadata.custom;
+        assert_eq!(custom["audience"], "internal");
+        assert_eq!(custom["tags"], "beta, ops");
+        assert_eq!(custom["owners"], "alice, bob");
+        assert_eq!(doc.metadata.title, "Deploying");
+        assert!(doc.text.starts_with("# Deploying"));
+
+        let filter = HashMap::from([("tags".to_string(), "OPS".to_string())]);
+        assert!(custom_fields_match(custom, &filter));
+        let filter = HashMap::from([("audience".to_string(), "public".to_string())]);
+        assert!(!custom_fields_match(custom, &filter));
+    }
+
+    #[test]
+    fn test_toml_front_matter_and_unterminated_block() {
+        let (fields, body) = split_front_matter(
+            "+++\nstatus = \"draft\"\nversion = 2\ntags = [\"a\", \"b\"]\n+++\nBody",
+        );
+        assert_eq!(fields["status"], "draft");
+        assert_eq!(fields["version"], "2");
+        assert_eq!(fields["tags"], "a, b");
+        assert_eq!(body, "Body");
+
+        let content = "---\nnot closed\n# Title";
+        let (fields, body) = split_front_matter(content);
+        assert!(fields.is_empty());
+        assert_eq!(body, content);
+    }
+
+    #[test]
+    fn test_leading_horizontal_rule_is_not_front_matter() {
+        let content = "---\nIntro paragraph before the title.\n\n---\n# Title\n";
+        let (fields, body) = split_front_matter(content);
+        assert!(fields.is_empty());
+        assert_eq!(body, content);
+
+        let content = "---\nSee https://example.com first.\n---\nBody\n";
+        assert_eq!(split_front_matter(content).1, content);
+        let content = "---\n---\nBody\n";
+        assert_eq!(split_front_matter(content).1, content);
+
+        let doc = load_document_from_content(
+            "---\nIntro text.\n---\n# Guide\n\nSteps.\n".to_string(),
+            "team/guide.md",
+            "team",
+            "guide",
+            false,
+        )
+        .unwrap();
+        assert!(doc.text.contains("Intro text."));
+        assert!(doc.metadata.custom.is_empty());
+    }
+
     #[test]
     fn test_load_document_from_content_empty() {
-        let doc = load_document_from_content("".to_string(), "a/b.md", "a", "b");
+        let doc = load_document_from_content("".to_string(), "a/b.md", "a", "b", false);
         assert!(doc.is_none());
 
-        let doc = load_document_from_content("   ".to_string(), "a/b.md", "a", "b");
+        let doc = load_document_from_content("   ".to_string(), "a/b.md", "a", "b", false);
         assert!(doc.is_none());
     }
 
     #[test]
     fn test_load_document_from_content_no_code() {
-        let doc = load_document_from_content("# Title\nJust text.".to_string(), "a/b.md", "a", "b");
+        let doc = load_document_from_content(
+            "# Title\nJust text.".to_string(),
+            "a/b.md",
+            "a",
+            "b",
+            false,
+        );
         let doc = doc.unwrap();
         assert!(!doc.metadata.has_code);
         assert_eq!(doc.metadata.code_block_count, 0);
     }
 
+    #[test]
+    fn test_strip_html_keeps_cell_text_and_code() {
+        let content = "# Table\n\n<table>\n<tr><th>Name</th><th>Type</th></tr>\n<tr><td>count</td><td>Int64</td></tr>\n</table>\n\nUse `<br>` for breaks, Array<Int> stays.<br/>Next line.\n\n```html\n<div>kept</div>\n```\n";
+        let stripped = strip_html(content);
+
+        assert!(!stripped.contains("<table>"), "{stripped}");
+        assert!(!stripped.contains("<td>"), "{stripped}");
+        assert!(!stripped.contains("<br/>"), "{stripped}");
+        assert!(stripped.contains("Name"));
+        assert!(stripped.contains("count"));
+        assert!(stripped.contains("Int64"));
+        assert!(stripped.contains("`<br>`"));
+        assert!(stripped.contains("Array<Int> stays."));
+        assert!(stripped.contains("```html\n<div>kept</div>\n```"));
+    }
+
+    #[test]
+    fn test_load_document_from_content_strips_html() {
+        let content = "# Table\n\n<table><tr><td>count</td></tr></table>\n".to_string();
+        let kept = load_document_from_content(content.clone(), "a/b.md", "a", "b", false).unwrap();
+        assert!(kept.text.contains("<td>"));
+
+        let doc = load_document_from_content(content, "a/b.md", "a", "b", true).unwrap();
+        assert!(!doc.text.contains("<td>"), "{}", doc.text);
+        assert!(doc.text.contains("count"));
+        assert_eq!(doc.metadata.title, "Table");
+    }
+
     #[test]
     fn test_extract_metadata_from_relative_path() {
         let (cat, topic) = extract_metadata_from_relative_path("syntax/functions.md");
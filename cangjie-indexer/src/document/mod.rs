@@ -4,8 +4,12 @@ pub mod source;
 pub mod summarizer;
 
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::LazyLock;
 
+use crate::{DocMetadata, TextChunk};
+
 /// Matches markdown headings H1-H6, capturing level (group 1) and title (group 2).
 pub static HEADING_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?m)^(#{1,6})\s+(.+)$").unwrap());
@@ -14,3 +18,364 @@ pub static HEADING_RE: LazyLock<Regex> =
 /// Group 1: language, Group 2: code body.
 pub static CODE_BLOCK_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?s)```(\w*)\n(.*?)```").unwrap());
+
+/// Topic name → categories containing a document with that name.
+pub type TopicCategoryMap = BTreeMap<String, BTreeSet<String>>;
+
+/// Group `(topic, category)` pairs into a [`TopicCategoryMap`].
+pub fn build_topic_category_map<'a>(
+    pairs: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> TopicCategoryMap {
+    let mut map = TopicCategoryMap::new();
+    for (topic, category) in pairs {
+        if topic.is_empty() {
+            continue;
+        }
+        map.entry(topic.to_string())
+            .or_default()
+            .insert(category.to_string());
+    }
+    map
+}
+
+/// A topic and the title of its document.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CatalogTopic {
+    pub topic: String,
+    pub title: String,
+}
+
+/// Category → its topics with titles, each list sorted by topic.
+pub type TopicCatalog = BTreeMap<String, Vec<CatalogTopic>>;
+
+/// Group `(category, topic, title)` triples into a [`TopicCatalog`]; the
+/// first title seen for a topic in a category wins.
+pub fn build_topic_catalog<'a>(
+    entries: impl IntoIterator<Item = (&'a str, &'a str, &'a str)>,
+) -> TopicCatalog {
+    let mut grouped: BTreeMap<&str, BTreeMap<&str, &str>> = BTreeMap::new();
+    for (category, topic, title) in entries {
+        if topic.is_empty() {
+            continue;
+        }
+        grouped
+            .entry(category)
+            .or_default()
+            .entry(topic)
+            .or_insert(title);
+    }
+    grouped
+        .into_iter()
+        .map(|(category, topics)| {
+            let topics = topics
+                .into_iter()
+                .map(|(topic, title)| CatalogTopic {
+                    topic: topic.to_string(),
+                    title: title.to_string(),
+                })
+                .collect();
+            (category.to_string(), topics)
+        })
+        .collect()
+}
+
+/// The [`TopicCategoryMap`] a catalog describes.
+pub fn catalog_topic_categories(catalog: &TopicCatalog) -> TopicCategoryMap {
+    build_topic_category_map(catalog.iter().flat_map(|(category, topics)| {
+        topics
+            .iter()
+            .map(move |t| (t.topic.as_str(), category.as_str()))
+    }))
+}
+
+/// A catalog with every topic of `map` and empty titles, for servers that
+/// only list topic names.
+pub fn catalog_from_topic_categories(map: &TopicCategoryMap) -> TopicCatalog {
+    build_topic_catalog(map.iter().flat_map(|(topic, categories)| {
+        categories
+            .iter()
+            .map(move |category| (category.as_str(), topic.as_str(), ""))
+    }))
+}
+
+/// Keep only topics that appear in more than one category.
+pub fn ambiguous_topics(map: TopicCategoryMap) -> TopicCategoryMap {
+    map.into_iter()
+        .filter(|(_, categories)| categories.len() > 1)
+        .collect()
+}
+
+/// One `category/topic` entry of `--pinned-topics`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PinnedTopic {
+    pub category: String,
+    pub topic: String,
+}
+
+/// Split `--pinned-topics` entries into the ones `map` has, in configured
+/// order, and the ones that name no indexed topic. The topic is the part
+/// after the last `/`, so categories may contain `/` themselves.
+pub fn resolve_pinned_topics(
+    pinned: &[String],
+    map: &TopicCategoryMap,
+) -> (Vec<PinnedTopic>, Vec<String>) {
+    let mut found = Vec::new();
+    let mut missing = Vec::new();
+    for entry in pinned {
+        let resolved = entry
+            .trim_matches('/')
+            .rsplit_once('/')
+            .filter(|(category, topic)| {
+                map.get(*topic)
+                    .is_some_and(|categories| categories.contains(*category))
+            });
+        match resolved {
+            Some((category, topic)) => found.push(PinnedTopic {
+                category: category.to_string(),
+                topic: topic.to_string(),
+            }),
+            None => missing.push(entry.clone()),
+        }
+    }
+    (found, missing)
+}
+
+/// Topic names from `candidates` similar to `topic` (case-insensitive
+/// Jaro-Winkler similarity of at least `threshold`), best match first.
+pub fn suggest_topics<'a>(
+    candidates: impl IntoIterator<Item = &'a str>,
+    topic: &str,
+    threshold: f64,
+    limit: usize,
+) -> Vec<String> {
+    let wanted = topic.to_lowercase();
+    let mut scored: Vec<(f64, &str)> = candidates
+        .into_iter()
+        .map(|candidate| {
+            (
+                strsim::jaro_winkler(&wanted, &candidate.to_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(score, _)| *score >= threshold)
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    scored.dedup_by(|a, b| a.1 == b.1);
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// A fenced code block; `language` is empty for untagged fences.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlock {
+    pub language: String,
+    pub code: String,
+}
+
+impl CodeBlock {
+    /// Render the block back as a fenced markdown code block.
+    pub fn to_markdown(&self) -> String {
+        let newline = if self.code.ends_with('\n') { "" } else { "\n" };
+        format!("```{}\n{}{newline}```", self.language, self.code)
+    }
+}
+
+/// All fenced code blocks in `text`, in document order.
+pub fn extract_code_blocks(text: &str) -> Vec<CodeBlock> {
+    CODE_BLOCK_RE
+        .captures_iter(text)
+        .map(|cap| CodeBlock {
+            language: cap[1].to_string(),
+            code: cap[2].to_string(),
+        })
+        .collect()
+}
+
+/// One searchable chunk per code block in `chunks`: the block preceded by the
+/// nearest heading and the prose paragraph right before it.
+pub fn code_block_chunks(chunks: &[TextChunk]) -> Vec<TextChunk> {
+    let mut out = Vec::new();
+    for chunk in chunks {
+        let text = &chunk.text;
+        let mut prose_start = 0;
+        for (n, cap) in CODE_BLOCK_RE.captures_iter(text).enumerate() {
+            let fence = cap.get(0).expect("whole match");
+            let prose_before = CODE_BLOCK_RE.replace_all(&text[..fence.start()], "");
+            let heading = HEADING_RE
+                .find_iter(&prose_before)
+                .last()
+                .map(|m| m.as_str().trim());
+            let paragraph = text[prose_start..fence.start()]
+                .split("\n\n")
+                .map(str::trim)
+                .filter(|p| !p.is_empty() && !HEADING_RE.is_match(p))
+                .last();
+            prose_start = fence.end();
+
+            let block = CodeBlock {
+                language: cap[1].to_string(),
+                code: cap[2].to_string(),
+            };
+            let mut parts: Vec<String> = heading
+                .into_iter()
+                .chain(paragraph)
+                .map(str::to_string)
+                .collect();
+            parts.push(block.to_markdown());
+            out.push(TextChunk {
+                text: parts.join("\n\n"),
+                metadata: DocMetadata {
+                    code_block_count: 1,
+                    has_code: true,
+                    chunk_id: format!("{}~code{n}", chunk.metadata.chunk_id),
+                    ..chunk.metadata.clone()
+                },
+            });
+        }
+    }
+    out
+}
+
+/// Lowercase a fence language tag, folding `cj` into `cangjie`.
+fn canonical_language(lang: &str) -> String {
+    let lang = lang.trim().to_lowercase();
+    if lang == "cj" {
+        "cangjie".to_string()
+    } else {
+        lang
+    }
+}
+
+/// Keep only blocks whose fence language matches `language` (case-insensitive,
+/// `cangjie` and `cj` are equivalent). Untagged blocks are kept only when
+/// `include_untagged` is set.
+pub fn filter_code_blocks(
+    blocks: Vec<CodeBlock>,
+    language: &str,
+    include_untagged: bool,
+) -> Vec<CodeBlock> {
+    let wanted = canonical_language(language);
+    blocks
+        .into_iter()
+        .filter(|block| {
+            if block.language.is_empty() {
+                include_untagged
+            } else {
+                canonical_language(&block.language) == wanted
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_topic_category_map_groups_categories() {
+        let map = build_topic_category_map([
+            ("overview", "syntax"),
+            ("overview", "stdlib"),
+            ("functions", "syntax"),
+            ("overview", "syntax"),
+            ("", "syntax"),
+        ]);
+        assert_eq!(map.len(), 2);
+        assert_eq!(
+            map["overview"].iter().collect::<Vec<_>>(),
+            vec!["stdlib", "syntax"]
+        );
+
+        let ambiguous = ambiguous_topics(map);
+        assert_eq!(ambiguous.keys().collect::<Vec<_>>(), vec!["overview"]);
+    }
+
+    #[test]
+    fn test_resolve_pinned_topics_keeps_order_and_reports_missing() {
+        let map = build_topic_category_map([
+            ("functions", "syntax"),
+            ("hashmap", "stdlib"),
+            ("review", "internal/guides"),
+        ]);
+        let pinned: Vec<String> = [
+            "stdlib/hashmap",
+            "internal/guides/review",
+            "syntax/hashmap",
+            "nope",
+            "syntax/functions",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let (found, missing) = resolve_pinned_topics(&pinned, &map);
+        let found: Vec<(&str, &str)> = found
+            .iter()
+            .map(|p| (p.category.as_str(), p.topic.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("stdlib", "hashmap"),
+                ("internal/guides", "review"),
+                ("syntax", "functions")
+            ]
+        );
+        assert_eq!(missing, vec!["syntax/hashmap", "nope"]);
+    }
+
+    #[test]
+    fn test_suggest_topics_ranks_near_misses() {
+        let topics = ["functions", "function_overloading", "variables", "lambda"];
+        let suggestions = suggest_topics(topics, "Functon", 0.6, 2);
+        assert_eq!(suggestions[0], "functions");
+        assert_eq!(suggestions.len(), 2);
+        assert!(suggest_topics(topics, "xyz", 0.9, 5).is_empty());
+    }
+
+    #[test]
+    fn test_code_block_chunks_carry_heading_and_paragraph() {
+        let chunk = TextChunk {
+            text: "# HashMap\n\nIntro.\n\n## Insert\n\nUse put to add entries.\n\n```cangjie\nmap.put(1, 2)\n```\n\n```bash\ncjpm run\n```\n".to_string(),
+            metadata: DocMetadata {
+                file_path: "stdlib/hashmap.md".to_string(),
+                chunk_id: "stdlib/hashmap.md#0".to_string(),
+                code_block_count: 2,
+                ..Default::default()
+            },
+        };
+        let code = code_block_chunks(&[chunk]);
+        assert_eq!(code.len(), 2);
+        assert_eq!(
+            code[0].text,
+            "## Insert\n\nUse put to add entries.\n\n```cangjie\nmap.put(1, 2)\n```"
+        );
+        assert_eq!(code[0].metadata.chunk_id, "stdlib/hashmap.md#0~code0");
+        assert_eq!(code[0].metadata.code_block_count, 1);
+        // No prose between the two fences: only the heading is carried over.
+        assert_eq!(code[1].text, "## Insert\n\n```bash\ncjpm run\n```");
+        assert_eq!(code[1].metadata.file_path, "stdlib/hashmap.md");
+    }
+
+    #[test]
+    fn test_filter_code_blocks_keeps_only_cangjie() {
+        let text = "Install:\n\n```bash\ncjpm build\n```\n\nThen:\n\n```cangjie\nmain() {\n    println(\"hi\")\n}\n```\n\n```\nuntagged\n```\n";
+        let blocks = extract_code_blocks(text);
+        assert_eq!(blocks.len(), 3);
+
+        let cangjie = filter_code_blocks(blocks.clone(), "CJ", false);
+        assert_eq!(cangjie.len(), 1);
+        assert_eq!(cangjie[0].language, "cangjie");
+        assert!(cangjie[0].code.contains("println"));
+        assert_eq!(
+            cangjie[0].to_markdown(),
+            "```cangjie\nmain() {\n    println(\"hi\")\n}\n```"
+        );
+
+        let with_untagged = filter_code_blocks(blocks, "cangjie", true);
+        assert_eq!(with_untagged.len(), 2);
+        assert_eq!(with_untagged[1].code, "untagged\n");
+    }
+}
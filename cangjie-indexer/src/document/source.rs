@@ -1,3 +1,4 @@
+use std::io::Read;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
@@ -5,6 +6,7 @@ use async_trait::async_trait;
 use tracing::{info, warn};
 
 use crate::document::loader::load_document_from_content;
+use crate::repo::normalize_tree_path;
 use crate::DocData;
 use cangjie_core::config::DocLang;
 
@@ -20,20 +22,53 @@ fn open_repo(repo_dir: &Path) -> Result<gix::Repository> {
     gix::open(repo_dir).context("Failed to open git repository")
 }
 
-fn read_file(repo_dir: &Path, path: &str) -> Result<String> {
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// Largest markdown text a compressed blob may expand to, so a gzip bomb in
+/// the docs tree cannot exhaust memory.
+const MAX_DECOMPRESSED_BYTES: u64 = 16 * 1024 * 1024;
+
+fn read_blob(repo_dir: &Path, path: &str) -> Result<Vec<u8>> {
     let repo = open_repo(repo_dir)?;
     let tree = repo.head_commit()?.tree()?;
     let entry = tree
-        .lookup_entry_by_path(path)?
+        .lookup_entry_by_path(normalize_tree_path(path)?)?
         .with_context(|| format!("Path not found: {path}"))?;
-    let object = repo.find_object(entry.oid())?;
-    Ok(std::str::from_utf8(&object.data)?.to_string())
+    let mut object = repo.find_object(entry.oid())?;
+    Ok(std::mem::take(&mut object.data))
+}
+
+#[cfg(test)]
+fn read_file(repo_dir: &Path, path: &str) -> Result<String> {
+    read_markdown(repo_dir, path, false)
+}
+
+/// Read a markdown blob as text. With `decompress`, a blob starting with the
+/// gzip magic bytes is gunzipped first.
+fn read_markdown(repo_dir: &Path, path: &str, decompress: bool) -> Result<String> {
+    let data = read_blob(repo_dir, path)?;
+    if decompress && data.starts_with(&GZIP_MAGIC) {
+        return gunzip(&data, MAX_DECOMPRESSED_BYTES, path);
+    }
+    Ok(String::from_utf8(data)?)
+}
+
+/// Gunzip `data` as UTF-8 text, failing once it expands past `limit` bytes.
+fn gunzip(data: &[u8], limit: u64, path: &str) -> Result<String> {
+    let mut content = String::new();
+    flate2::read::GzDecoder::new(data)
+        .take(limit + 1)
+        .read_to_string(&mut content)
+        .with_context(|| format!("Failed to decompress {path}"))?;
+    if content.len() as u64 > limit {
+        anyhow::bail!("{path} decompresses to more than {limit} bytes");
+    }
+    Ok(content)
 }
 
 fn list_dirs(repo_dir: &Path, path: &str) -> Result<Vec<String>> {
     let repo = open_repo(repo_dir)?;
     let tree = repo.head_commit()?.tree()?;
-    let entry = match tree.lookup_entry_by_path(path)? {
+    let entry = match tree.lookup_entry_by_path(normalize_tree_path(path)?)? {
         Some(e) => e,
         None => return Ok(Vec::new()),
     };
@@ -56,7 +91,7 @@ fn list_dirs(repo_dir: &Path, path: &str) -> Result<Vec<String>> {
 fn list_md_files(repo_dir: &Path, base_path: &str) -> Result<Vec<String>> {
     let repo = open_repo(repo_dir)?;
     let tree = repo.head_commit()?.tree()?;
-    let entry = match tree.lookup_entry_by_path(base_path)? {
+    let entry = match tree.lookup_entry_by_path(normalize_tree_path(base_path)?)? {
         Some(e) => e,
         None => return Ok(Vec::new()),
     };
@@ -72,7 +107,7 @@ fn list_md_files(repo_dir: &Path, base_path: &str) -> Result<Vec<String>> {
 fn list_md_files_shallow(repo_dir: &Path, base_path: &str) -> Result<Vec<String>> {
     let repo = open_repo(repo_dir)?;
     let tree = repo.head_commit()?.tree()?;
-    let entry = match tree.lookup_entry_by_path(base_path)? {
+    let entry = match tree.lookup_entry_by_path(normalize_tree_path(base_path)?)? {
         Some(e) => e,
         None => return Ok(Vec::new()),
     };
@@ -138,6 +173,8 @@ pub struct GitDocumentSource {
     docs_base_path: String,
     category_prefix: Option<String>,
     root_category: Option<String>,
+    decompress: bool,
+    strip_html: bool,
 }
 
 impl GitDocumentSource {
@@ -152,9 +189,23 @@ impl GitDocumentSource {
             docs_base_path,
             category_prefix,
             root_category,
+            decompress: false,
+            strip_html: false,
         }
     }
 
+    /// Gunzip markdown blobs stored gzip-compressed in the tree.
+    pub fn with_decompress(mut self, decompress: bool) -> Self {
+        self.decompress = decompress;
+        self
+    }
+
+    /// Remove HTML tags from loaded documents.
+    pub fn with_strip_html(mut self, strip_html: bool) -> Self {
+        self.strip_html = strip_html;
+        self
+    }
+
     pub fn for_docs(repo_dir: PathBuf, lang: DocLang) -> Result<Self> {
         Ok(Self::new(
             repo_dir,
@@ -203,6 +254,11 @@ impl GitDocumentSource {
             Some("release-notes".to_string()),
         ))
     }
+
+    /// Tree path the documents are read from, relative to the repository root.
+    pub fn base_path(&self) -> &str {
+        &self.docs_base_path
+    }
 }
 
 #[async_trait]
@@ -216,6 +272,8 @@ impl DocumentSource for GitDocumentSource {
         let base = self.docs_base_path.clone();
         let prefix = self.category_prefix.clone();
         let root_category = self.root_category.clone();
+        let decompress = self.decompress;
+        let strip_html = self.strip_html;
 
         tokio::task::spawn_blocking(move || {
             let mut documents = Vec::new();
@@ -224,13 +282,29 @@ impl DocumentSource for GitDocumentSource {
                 let path = format!("{base}/{category}");
                 let display_cat = apply_prefix(&prefix, category);
                 for file in &list_md_files(&repo_dir, &path)? {
-                    load_md_into(&repo_dir, &path, file, &display_cat, &mut documents);
+                    load_md_into(
+                        &repo_dir,
+                        &path,
+                        file,
+                        &display_cat,
+                        decompress,
+                        strip_html,
+                        &mut documents,
+                    );
                 }
             }
 
             if let Some(cat) = &root_category {
                 for file in &list_md_files_shallow(&repo_dir, &base)? {
-                    load_md_into(&repo_dir, &base, file, cat, &mut documents);
+                    load_md_into(
+                        &repo_dir,
+                        &base,
+                        file,
+                        cat,
+                        decompress,
+                        strip_html,
+                        &mut documents,
+                    );
                 }
             }
 
@@ -242,6 +316,142 @@ impl DocumentSource for GitDocumentSource {
     }
 }
 
+/// Markdown files in a plain directory, e.g. team guides indexed alongside the
+/// official docs. Subdirectories become `<prefix>/<dir>` categories and files
+/// at the top level land in `<prefix>`.
+pub struct FsDocumentSource {
+    root: PathBuf,
+    prefix: String,
+    strip_html: bool,
+}
+
+impl FsDocumentSource {
+    pub fn new(root: PathBuf, prefix: &str) -> Self {
+        Self {
+            root,
+            prefix: prefix.trim_matches('/').to_string(),
+            strip_html: false,
+        }
+    }
+
+    /// Remove HTML tags from loaded documents.
+    pub fn with_strip_html(mut self, strip_html: bool) -> Self {
+        self.strip_html = strip_html;
+        self
+    }
+}
+
+fn is_hidden_name(name: &str) -> bool {
+    name.starts_with('.') || name.starts_with('_')
+}
+
+/// Sorted `.md` paths under `dir`, relative to it and `/`-separated.
+fn collect_fs_md_files(dir: &Path, prefix: &str, recursive: bool) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    for entry in
+        std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if is_hidden_name(&name) {
+            continue;
+        }
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{prefix}/{name}")
+        };
+        let file_type = entry.file_type()?;
+        if file_type.is_file() && name.ends_with(".md") {
+            files.push(path);
+        } else if file_type.is_dir() && recursive {
+            files.extend(collect_fs_md_files(&entry.path(), &path, true)?);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn load_fs_md_into(
+    dir: &Path,
+    file: &str,
+    category: &str,
+    strip_html: bool,
+    documents: &mut Vec<DocData>,
+) {
+    let full_path = dir.join(file);
+    match std::fs::read_to_string(&full_path) {
+        Ok(content) => {
+            let topic = topic_name_from_md_path(file).unwrap_or_default();
+            let relative_path = format!("{category}/{file}");
+            if let Some(doc) =
+                load_document_from_content(content, &relative_path, category, &topic, strip_html)
+            {
+                documents.push(doc);
+            }
+        }
+        Err(e) => warn!("Failed to load {}: {}", full_path.display(), e),
+    }
+}
+
+#[async_trait]
+impl DocumentSource for FsDocumentSource {
+    async fn is_available(&self) -> bool {
+        self.root.is_dir()
+    }
+
+    async fn load_all_documents(&self) -> Result<Vec<DocData>> {
+        let root = self.root.clone();
+        let prefix = self.prefix.clone();
+        let strip_html = self.strip_html;
+
+        tokio::task::spawn_blocking(move || {
+            let mut documents = Vec::new();
+
+            let mut subdirs = Vec::new();
+            for entry in std::fs::read_dir(&root)
+                .with_context(|| format!("Failed to read {}", root.display()))?
+            {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().to_string();
+                if entry.file_type()?.is_dir() && !is_hidden_name(&name) {
+                    subdirs.push(name);
+                }
+            }
+            subdirs.sort();
+
+            for subdir in &subdirs {
+                let dir = root.join(subdir);
+                let category = if prefix.is_empty() {
+                    subdir.clone()
+                } else {
+                    format!("{prefix}/{subdir}")
+                };
+                for file in &collect_fs_md_files(&dir, "", true)? {
+                    load_fs_md_into(&dir, file, &category, strip_html, &mut documents);
+                }
+            }
+            let root_category = if prefix.is_empty() {
+                "general"
+            } else {
+                prefix.as_str()
+            };
+            for file in &collect_fs_md_files(&root, "", false)? {
+                load_fs_md_into(&root, file, root_category, strip_html, &mut documents);
+            }
+
+            info!(
+                "Loaded {} documents from {}.",
+                documents.len(),
+                root.display()
+            );
+            Ok(documents)
+        })
+        .await
+        .context("load_all_documents task panicked")?
+    }
+}
+
 /// Read `dir/file` from the git tree and append the loaded document to
 /// `documents`, using `category` as both the category metadata and the
 /// `<category>/<file>` file_path. Errors are logged and skipped.
@@ -250,14 +460,17 @@ fn load_md_into(
     dir: &str,
     file: &str,
     category: &str,
+    decompress: bool,
+    strip_html: bool,
     documents: &mut Vec<DocData>,
 ) {
     let full_path = format!("{dir}/{file}");
-    match read_file(repo_dir, &full_path) {
+    match read_markdown(repo_dir, &full_path, decompress) {
         Ok(content) => {
             let topic = topic_name_from_md_path(file).unwrap_or_default();
             let relative_path = format!("{category}/{file}");
-            if let Some(doc) = load_document_from_content(content, &relative_path, category, &topic)
+            if let Some(doc) =
+                load_document_from_content(content, &relative_path, category, &topic, strip_html)
             {
                 documents.push(doc);
             }
@@ -325,6 +538,47 @@ mod tests {
         assert!(content.contains("Content about functions."));
     }
 
+    #[tokio::test]
+    async fn test_decompress_reads_gzip_markdown() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let tmp = TempDir::new().unwrap();
+        let syntax = tmp.path().join("docs/dev-guide/source_zh_cn/syntax");
+        std::fs::create_dir_all(&syntax).unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all("# Generated\n\nContent from a compressed blob.".as_bytes())
+            .unwrap();
+        std::fs::write(syntax.join("generated.md"), encoder.finish().unwrap()).unwrap();
+        crate::testutil::git_init_and_commit(tmp.path());
+
+        let plain = GitDocumentSource::for_docs(tmp.path().to_path_buf(), DocLang::Zh).unwrap();
+        assert!(plain.load_all_documents().await.unwrap().is_empty());
+
+        let source = GitDocumentSource::for_docs(tmp.path().to_path_buf(), DocLang::Zh)
+            .unwrap()
+            .with_decompress(true);
+        let docs = source.load_all_documents().await.unwrap();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].metadata.file_path, "syntax/generated.md");
+        assert!(docs[0].text.contains("Content from a compressed blob."));
+    }
+
+    #[test]
+    fn test_gunzip_rejects_oversized_output() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&[b'a'; 4096]).unwrap();
+        let data = encoder.finish().unwrap();
+
+        assert_eq!(gunzip(&data, 4096, "big.md").unwrap().len(), 4096);
+        let err = gunzip(&data, 4095, "big.md").unwrap_err().to_string();
+        assert!(err.contains("more than 4095 bytes"), "{err}");
+    }
+
     #[test]
     fn test_read_file_not_found() {
         let tmp = create_test_repo_tmp();
@@ -462,6 +716,31 @@ mod tests {
         assert_eq!(overview.metadata.file_path, "stdx/libs_overview.md");
     }
 
+    #[tokio::test]
+    async fn test_fs_source_prefixes_categories() {
+        let tmp = TempDir::new().unwrap();
+        let guides = tmp.path().join("guides");
+        std::fs::create_dir_all(guides.join("nested")).unwrap();
+        std::fs::create_dir_all(tmp.path().join(".git")).unwrap();
+        std::fs::write(guides.join("style.md"), "# Style\nUse four spaces.").unwrap();
+        std::fs::write(guides.join("nested/review.md"), "# Review\nTwo approvals.").unwrap();
+        std::fs::write(tmp.path().join("onboarding.md"), "# Onboarding\nWelcome.").unwrap();
+        std::fs::write(tmp.path().join("notes.txt"), "not markdown").unwrap();
+
+        let source = FsDocumentSource::new(tmp.path().to_path_buf(), "internal/");
+        assert!(source.is_available().await);
+        let docs = source.load_all_documents().await.unwrap();
+        assert_eq!(docs.len(), 3);
+
+        let paths: Vec<(&str, &str)> = docs
+            .iter()
+            .map(|d| (d.metadata.category.as_str(), d.metadata.file_path.as_str()))
+            .collect();
+        assert!(paths.contains(&("internal/guides", "internal/guides/style.md")));
+        assert!(paths.contains(&("internal/guides", "internal/guides/nested/review.md")));
+        assert!(paths.contains(&("internal", "internal/onboarding.md")));
+    }
+
     #[tokio::test]
     async fn test_for_docs_still_skips_root_files() {
         // Regression: dev-guide loader must not pick up the top-level
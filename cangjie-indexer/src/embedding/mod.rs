@@ -1,7 +1,8 @@
 pub mod openai;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
+use tracing::warn;
 
 use cangjie_core::config::{EmbeddingType, Settings};
 
@@ -44,6 +45,55 @@ pub fn model_max_input_chars(model_name: &str) -> Option<usize> {
     }
 }
 
+/// Output dimension of known embedding models, matched on the model name.
+/// Returns `None` for unknown models.
+pub fn model_embedding_dim(model_name: &str) -> Option<usize> {
+    match model_name {
+        s if s.contains("MiniLM") => Some(384),
+        s if s.contains("bge-m3") => Some(1024),
+        s if s.contains("bge-large") => Some(1024),
+        s if s.contains("bge-base") => Some(768),
+        s if s.contains("bge-small-zh") => Some(512),
+        s if s.contains("bge-small") => Some(384),
+        s if s.contains("e5-large") => Some(1024),
+        s if s.contains("e5-base") => Some(768),
+        s if s.contains("e5-small") => Some(384),
+        s if s.contains("nomic-embed-text") => Some(768),
+        s if s.contains("text-embedding-3-large") => Some(3072),
+        s if s.contains("text-embedding-3-small") => Some(1536),
+        s if s.contains("text-embedding-ada-002") => Some(1536),
+        _ => None,
+    }
+}
+
+/// Output dimension of `embedder`, detected by embedding a probe text.
+///
+/// When the probe fails (e.g. offline), a known model falls back to its
+/// dimension from [`model_embedding_dim`]. Unknown models have no fallback:
+/// a wrong dimension would create a vector table the real embeddings do not
+/// fit, so their failed probe is an error.
+pub async fn embedding_dim(embedder: &dyn Embedder) -> Result<usize> {
+    let probe = match embedder.embed(&["test"], EmbedKind::Document).await {
+        Ok(probe) => probe,
+        Err(e) => {
+            let model = embedder.model_name();
+            if let Some(dim) = model_embedding_dim(model) {
+                warn!("Test embedding failed ({e:#}), using the known dimension {dim} of {model}");
+                return Ok(dim);
+            }
+            return Err(e)
+                .with_context(|| format!("Failed to detect the embedding dimension of {model}"));
+        }
+    };
+    match probe.first().map(Vec::len) {
+        Some(dim) if dim > 0 => Ok(dim),
+        _ => anyhow::bail!(
+            "Failed to detect the embedding dimension of {}: the test embedding was empty",
+            embedder.model_name()
+        ),
+    }
+}
+
 /// Create an embedder from settings; `None` when embedding is disabled.
 pub async fn create_embedder(settings: &Settings) -> Result<Option<Box<dyn Embedder>>> {
     match settings.embedding_type {
@@ -110,6 +160,71 @@ mod tests {
         assert!(result.is_err());
     }
 
+    struct SizedEmbedder(usize);
+
+    #[async_trait]
+    impl Embedder for SizedEmbedder {
+        async fn embed(&self, texts: &[&str], _kind: EmbedKind) -> Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|_| vec![0.5; self.0]).collect())
+        }
+
+        fn model_name(&self) -> &str {
+            "sized"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embedding_dim_uses_probe_length() {
+        assert_eq!(embedding_dim(&SizedEmbedder(1024)).await.unwrap(), 1024);
+    }
+
+    #[tokio::test]
+    async fn test_embedding_dim_rejects_empty_probe() {
+        let err = embedding_dim(&SizedEmbedder(0)).await.unwrap_err();
+        assert!(err.to_string().contains("sized"), "{err}");
+    }
+
+    struct OfflineEmbedder(&'static str);
+
+    #[async_trait]
+    impl Embedder for OfflineEmbedder {
+        async fn embed(&self, _texts: &[&str], _kind: EmbedKind) -> Result<Vec<Vec<f32>>> {
+            anyhow::bail!("network unreachable")
+        }
+
+        fn model_name(&self) -> &str {
+            self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn test_embedding_dim_falls_back_to_known_model() {
+        let dim = embedding_dim(&OfflineEmbedder("BAAI/bge-m3"))
+            .await
+            .unwrap();
+        assert_eq!(dim, 1024);
+        let dim = embedding_dim(&OfflineEmbedder(
+            "sentence-transformers/paraphrase-multilingual-MiniLM-L12-v2",
+        ))
+        .await
+        .unwrap();
+        assert_eq!(dim, 384);
+
+        let err = embedding_dim(&OfflineEmbedder("unknown-model-xyz"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown-model-xyz"), "{err}");
+    }
+
+    #[test]
+    fn test_model_embedding_dim_distinguishes_variants() {
+        assert_eq!(model_embedding_dim("text-embedding-3-large"), Some(3072));
+        assert_eq!(model_embedding_dim("text-embedding-3-small"), Some(1536));
+        assert_eq!(model_embedding_dim("BAAI/bge-small-zh-v1.5"), Some(512));
+        assert_eq!(model_embedding_dim("BAAI/bge-small-en-v1.5"), Some(384));
+        assert_eq!(model_embedding_dim("unknown-model-xyz"), None);
+    }
+
     #[test]
     fn test_model_max_input_chars_bge_m3() {
         assert_eq!(model_max_input_chars("BAAI/bge-m3"), Some(12000));
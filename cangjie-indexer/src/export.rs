@@ -0,0 +1,144 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::search::bm25::BM25Store;
+use crate::{IndexMetadata, SearchResultMetadata};
+use cangjie_core::config::IndexInfo;
+
+#[derive(Serialize)]
+struct HeaderLine<'a> {
+    index_metadata: &'a IndexMetadata,
+}
+
+#[derive(Serialize)]
+struct ChunkLine<'a> {
+    doc_id: &'a str,
+    text: &'a str,
+    metadata: &'a SearchResultMetadata,
+}
+
+/// Write the index described by `index_info` to `out` as NDJSON: a header
+/// line holding `index_metadata.json`, then one line per indexed chunk in
+/// file and chunk order. Returns the number of chunks written.
+pub async fn export_index<W: Write>(index_info: &IndexInfo, out: &mut W) -> Result<usize> {
+    let metadata_path = index_info.index_dir().join("index_metadata.json");
+    let content = tokio::fs::read_to_string(&metadata_path)
+        .await
+        .with_context(|| {
+            format!(
+                "No index found at {}; run `cangjie-mcp index` first",
+                index_info.index_dir().display()
+            )
+        })?;
+    let metadata: IndexMetadata = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid index metadata in {}", metadata_path.display()))?;
+
+    let mut bm25 = BM25Store::new(index_info.bm25_index_dir());
+    if !bm25.load().await? {
+        anyhow::bail!(
+            "BM25 index not found at {}",
+            index_info.bm25_index_dir().display()
+        );
+    }
+    let chunks = bm25.all_chunks().await?;
+
+    serde_json::to_writer(
+        &mut *out,
+        &HeaderLine {
+            index_metadata: &metadata,
+        },
+    )?;
+    writeln!(out)?;
+    for chunk in &chunks {
+        serde_json::to_writer(
+            &mut *out,
+            &ChunkLine {
+                doc_id: &chunk.metadata.chunk_id,
+                text: &chunk.text,
+                metadata: &chunk.metadata,
+            },
+        )?;
+        writeln!(out)?;
+    }
+    out.flush()?;
+    Ok(chunks.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DocMetadata, SearchMode, TextChunk};
+    use cangjie_core::config::Settings;
+
+    fn chunk(file_path: &str, index: usize, text: &str) -> TextChunk {
+        TextChunk {
+            text: text.to_string(),
+            metadata: DocMetadata {
+                file_path: file_path.to_string(),
+                category: "syntax".to_string(),
+                topic: "functions".to_string(),
+                title: "Functions".to_string(),
+                code_block_count: 0,
+                has_code: false,
+                chunk_id: format!("{file_path}#{index}"),
+                chunk_index: index,
+                char_offset: 0,
+                custom: Default::default(),
+                breadcrumb: String::new(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_writes_header_and_one_line_per_chunk() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let settings = Settings {
+            data_dir: tmp.path().to_path_buf(),
+            ..Settings::default()
+        };
+        let index_info = IndexInfo::from_settings(&settings, "v1.0.0");
+
+        let chunks = vec![
+            chunk("syntax/functions.md", 0, "函数定义"),
+            chunk("syntax/functions.md", 1, "函数调用"),
+            chunk("syntax/lambda.md", 0, "Lambda 表达式"),
+        ];
+        let mut bm25 = BM25Store::new(index_info.bm25_index_dir());
+        bm25.build_from_chunks(&chunks).await.unwrap();
+        let metadata = IndexMetadata {
+            version: index_info.version.clone(),
+            lang: index_info.lang.to_string(),
+            embedding_model: "none".to_string(),
+            document_count: chunks.len(),
+            search_mode: SearchMode::Bm25,
+            index_code: false,
+            extra_docs: None,
+            chunking: None,
+            source_commit: None,
+            vector_distance: None,
+            tokenizer_version: crate::search::TOKENIZER_VERSION,
+        };
+        std::fs::write(
+            index_info.index_dir().join("index_metadata.json"),
+            serde_json::to_string(&metadata).unwrap(),
+        )
+        .unwrap();
+
+        let mut out = Vec::new();
+        let written = export_index(&index_info, &mut out).await.unwrap();
+        assert_eq!(written, chunks.len());
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), chunks.len() + 1);
+        assert_eq!(lines[0]["index_metadata"]["version"], "v1.0.0");
+        assert_eq!(lines[1]["doc_id"], "syntax/functions.md#0");
+        assert_eq!(lines[1]["text"], "函数定义");
+        assert_eq!(lines[1]["metadata"]["topic"], "functions");
+    }
+}
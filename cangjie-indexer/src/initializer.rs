@@ -1,41 +1,263 @@
 mod build;
 mod prebuilt;
+mod refresh;
+
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
+use tokio_util::sync::CancellationToken;
 use tracing::info;
 
-use cangjie_core::config::{IndexInfo, Settings};
+use crate::{ResolvedVersion, VersionTopicDiff};
+use cangjie_core::config::{IndexInfo, IndexPolicy, Settings};
 
 use build::build_index;
-use prebuilt::{index_is_ready, load_prebuilt_index};
+use prebuilt::{
+    chunking_changed, discover_prebuilt_versions, index_is_ready, load_index_dir,
+    load_prebuilt_index, reconcile_source_commit, tokenizer_changed, vector_distance_changed,
+};
 
-/// Initialize repository and build index if needed.
-pub async fn initialize_and_index(settings: &Settings) -> Result<IndexInfo> {
+pub use refresh::{IndexRefresher, RebuildCoordinator, RefreshOutcome};
+
+/// Report the commit the local documentation checkout resolved to.
+///
+/// Returns `None` when the docs repository has not been cloned (e.g. prebuilt mode).
+pub async fn resolved_docs_version(settings: &Settings) -> Result<Option<ResolvedVersion>> {
+    crate::repo::GitManager::new(
+        settings.docs_repo_dir(),
+        cangjie_core::config::DOCS_REPO_URL.to_string(),
+    )
+    .resolved_version(&settings.docs_version)
+    .await
+}
+
+/// Compare the topics of two documentation versions in the local docs
+/// checkout, reading each version's tree without changing the checkout.
+pub async fn diff_docs_versions(
+    settings: &Settings,
+    from: &str,
+    to: &str,
+) -> Result<VersionTopicDiff> {
+    let source_dir = format!("docs/dev-guide/{}", settings.docs_lang.source_dir_name());
+    let git_mgr = crate::repo::GitManager::new(
+        settings.docs_repo_dir(),
+        cangjie_core::config::DOCS_REPO_URL.to_string(),
+    )
+    .with_tree_root(&source_dir)?;
+    let mut diff = VersionTopicDiff {
+        from: from.to_string(),
+        to: to.to_string(),
+        ..Default::default()
+    };
+    let mut topics = Vec::with_capacity(2);
+    for version in [from, to] {
+        let files = git_mgr
+            .list_md_files_at(version, &source_dir)
+            .await
+            .with_context(|| format!("Failed to list topics of version '{version}'"))?;
+        if files.is_none() {
+            diff.missing_source.push(version.to_string());
+        }
+        let set: BTreeSet<String> = files
+            .unwrap_or_default()
+            .iter()
+            .filter(|path| !path.split('/').any(|s| s.starts_with(['_', '.'])))
+            .filter_map(|path| path.strip_suffix(".md"))
+            .map(str::to_string)
+            .collect();
+        topics.push(set);
+    }
+    let (old, new) = (&topics[0], &topics[1]);
+    diff.added = new.difference(old).cloned().collect();
+    diff.removed = old.difference(new).cloned().collect();
+    diff.common = old.intersection(new).cloned().collect();
+    Ok(diff)
+}
+
+/// Versions with a ready index under `data_dir/indexes/` for the configured
+/// language and embedding model; with `--index-dir`, the version it holds.
+pub async fn indexed_versions(settings: &Settings) -> Result<Vec<String>> {
+    if let Some(index_dir) = &settings.index_dir_override {
+        return Ok(vec![load_index_dir(settings, index_dir).await?.version]);
+    }
+    discover_prebuilt_versions(settings).await
+}
+
+/// Whether `index_info` names a complete index on disk.
+pub(crate) async fn index_ready(index_info: &IndexInfo) -> bool {
+    index_is_ready(index_info).await
+}
+
+/// The ready index for the current checkouts (or `--index-dir` /
+/// `--prebuilt`), located without fetching, checking out, or building.
+pub async fn open_existing_index(settings: &Settings) -> Result<IndexInfo> {
+    use crate::repo::GitManager;
+    use cangjie_core::config::{DOCS_REPO_URL, RUNTIME_REPO_URL, STDX_REPO_URL};
+
+    if let Some(index_dir) = &settings.index_dir_override {
+        return load_index_dir(settings, index_dir).await;
+    }
     if settings.prebuilt.is_prebuilt() {
         return load_prebuilt_index(settings).await;
     }
 
+    let docs_mgr = GitManager::new(settings.docs_repo_dir(), DOCS_REPO_URL.to_string());
+    let runtime_mgr = GitManager::new(settings.runtime_repo_dir(), RUNTIME_REPO_URL.to_string());
+    let stdx_mgr = GitManager::new(settings.stdx_repo_dir(), STDX_REPO_URL.to_string());
+    let (docs, runtime, stdx) = tokio::try_join!(
+        docs_mgr.checked_out_version(),
+        runtime_mgr.checked_out_version(),
+        stdx_mgr.checked_out_version(),
+    )?;
+    let (Some(docs), Some(runtime), Some(stdx)) = (docs, runtime, stdx) else {
+        anyhow::bail!(
+            "No index found: the documentation repositories are not cloned under {}. \
+             Run `cangjie-mcp index` first",
+            settings.data_dir.display()
+        );
+    };
+
+    let combined_version = format!("{docs}+rt-{runtime}+stdx-{stdx}");
+    let index_info = IndexInfo::from_settings(settings, &combined_version);
+    if !index_is_ready(&index_info).await {
+        anyhow::bail!(
+            "No ready index for version {combined_version} (lang: {}). \
+             Run `cangjie-mcp index` first",
+            settings.docs_lang
+        );
+    }
+    Ok(index_info)
+}
+
+/// Initialize repository and build index if needed, giving up after
+/// `index_build_timeout_secs` (0 = no limit). A build cut short leaves no
+/// ready index behind.
+pub async fn initialize_and_index(settings: &Settings) -> Result<IndexInfo> {
+    initialize_and_index_cancellable(settings, &CancellationToken::new()).await
+}
+
+/// [`initialize_and_index`] that stops once `cancel` fires, e.g. because a
+/// newer rebuild superseded it. A cancelled build leaves no ready index
+/// behind.
+pub async fn initialize_and_index_cancellable(
+    settings: &Settings,
+    cancel: &CancellationToken,
+) -> Result<IndexInfo> {
+    match settings.index_build_timeout_secs {
+        0 => initialize_and_index_untimed(settings, cancel).await,
+        secs => {
+            let timed = cancel.child_token();
+            with_build_timeout(
+                Duration::from_secs(secs),
+                &timed,
+                initialize_and_index_untimed(settings, &timed),
+            )
+            .await
+        }
+    }
+}
+
+const BUILD_CANCELLED: &str = "Index build cancelled: superseded by a newer rebuild";
+
+/// Run one read-only build phase, abandoning it as soon as `cancel` fires.
+/// Phases that write to disk check [`ensure_not_cancelled`] around them
+/// instead, so no blocking writer outlives the build.
+async fn cancellable<T>(
+    cancel: &CancellationToken,
+    phase: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::select! {
+        biased;
+        _ = cancel.cancelled() => anyhow::bail!(BUILD_CANCELLED),
+        result = phase => result,
+    }
+}
+
+fn ensure_not_cancelled(cancel: &CancellationToken) -> Result<()> {
+    if cancel.is_cancelled() {
+        anyhow::bail!(BUILD_CANCELLED);
+    }
+    Ok(())
+}
+
+/// Run `build`, which observes `cancel`, failing with a clear timeout error
+/// once `limit` has passed. On timeout `cancel` fires and the build is awaited
+/// until it stops at its next safe point, so its cleanup runs and no blocking
+/// writer outlives it.
+async fn with_build_timeout<T>(
+    limit: Duration,
+    cancel: &CancellationToken,
+    build: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::pin!(build);
+    tokio::select! {
+        result = &mut build => return result,
+        _ = tokio::time::sleep(limit) => {}
+    }
+    cancel.cancel();
+    match build.await {
+        // Finished before it noticed the cancellation.
+        Ok(value) => Ok(value),
+        Err(_) => anyhow::bail!(
+            "Index build timed out after {}s; the incomplete index was discarded. \
+             Check network access to the docs repositories and embedding endpoint, \
+             or raise --index-build-timeout-secs",
+            limit.as_secs()
+        ),
+    }
+}
+
+async fn initialize_and_index_untimed(
+    settings: &Settings,
+    cancel: &CancellationToken,
+) -> Result<IndexInfo> {
+    if let Some(index_dir) = &settings.index_dir_override {
+        return load_index_dir(settings, index_dir).await;
+    }
+    if settings.prebuilt.is_prebuilt() {
+        let index_info = load_prebuilt_index(settings).await?;
+        let mut git_mgr = crate::repo::GitManager::new(
+            settings.docs_repo_dir(),
+            cangjie_core::config::DOCS_REPO_URL.to_string(),
+        );
+        reconcile_source_commit(&mut git_mgr, &index_info, false).await;
+        return Ok(index_info);
+    }
+
     use crate::repo::GitManager;
 
+    settings.validate_docs_repo_dir()?;
+
     // Resolve versions concurrently (ensures repos are cloned, fetched, and checked out)
     let mut git_mgr = GitManager::new(
         settings.docs_repo_dir(),
         cangjie_core::config::DOCS_REPO_URL.to_string(),
-    );
+    )
+    .with_retries(settings.git_retries)
+    .with_max_concurrent_ops(settings.max_concurrent_git_ops);
     let mut runtime_mgr = GitManager::new(
         settings.runtime_repo_dir(),
         cangjie_core::config::RUNTIME_REPO_URL.to_string(),
-    );
+    )
+    .with_retries(settings.git_retries)
+    .with_max_concurrent_ops(settings.max_concurrent_git_ops);
     let mut stdx_mgr = GitManager::new(
         settings.stdx_repo_dir(),
         cangjie_core::config::STDX_REPO_URL.to_string(),
-    );
+    )
+    .with_retries(settings.git_retries)
+    .with_max_concurrent_ops(settings.max_concurrent_git_ops);
 
+    // Checkouts run to completion: dropping one would release the repo gate
+    // while its blocking task still writes the working tree.
     let (docs_result, runtime_result, stdx_result) = tokio::join!(
         git_mgr.resolve_version(&settings.docs_version),
         runtime_mgr.resolve_version(&settings.runtime_version),
         stdx_mgr.resolve_version(&settings.stdx_version),
     );
+    ensure_not_cancelled(cancel)?;
     let resolved_version = docs_result.context("Failed to resolve documentation version")?;
     let runtime_resolved =
         runtime_result.context("Failed to resolve runtime documentation version")?;
@@ -56,15 +278,107 @@ pub async fn initialize_and_index(settings: &Settings) -> Result<IndexInfo> {
     let combined_version = format!("{resolved_version}+rt-{runtime_resolved}+stdx-{stdx_resolved}");
     let index_info = IndexInfo::from_settings(settings, &combined_version);
 
-    if index_is_ready(&index_info).await {
+    if index_is_ready(&index_info).await
+        && (settings.index_policy == IndexPolicy::Never
+            || !(chunking_changed(&index_info).await
+                || vector_distance_changed(&index_info).await
+                || tokenizer_changed(&index_info).await))
+    {
         info!(
             "Index already exists (version: {}, lang: {})",
             resolved_version, settings.docs_lang
         );
+        reconcile_source_commit(&mut git_mgr, &index_info, true).await;
         return Ok(index_info);
     }
 
-    build_index(settings, &index_info).await?;
+    build_index(settings, &index_info, cancel).await?;
 
     Ok(index_info)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_open_existing_index_does_not_clone_or_build() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let settings = Settings {
+            data_dir: tmp.path().to_path_buf(),
+            ..Settings::default()
+        };
+        let err = open_existing_index(&settings)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Run `cangjie-mcp index` first"), "{err}");
+        assert!(!settings.docs_repo_dir().exists());
+        assert!(!tmp.path().join("indexes").exists());
+    }
+
+    #[tokio::test]
+    async fn test_build_timeout_cuts_off_slow_step() {
+        let cancel = CancellationToken::new();
+        let slow = cancellable(&cancel, async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(())
+        });
+        let err = with_build_timeout(Duration::from_millis(20), &cancel, slow)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Index build timed out"), "{err}");
+        assert!(cancel.is_cancelled());
+
+        let cancel = CancellationToken::new();
+        let fast = async { Ok(7) };
+        assert_eq!(
+            with_build_timeout(Duration::from_secs(5), &cancel, fast)
+                .await
+                .unwrap(),
+            7
+        );
+        assert!(!cancel.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_build_timeout_cancels_a_running_build_phase() {
+        // A summary endpoint that accepts connections but never answers keeps
+        // the build in its summary phase until the timeout fires.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                held.push(stream);
+            }
+        });
+        let (repo, _) = crate::testutil::create_test_repo();
+        let data = tempfile::TempDir::new().unwrap();
+        let settings = Settings {
+            data_dir: data.path().to_path_buf(),
+            docs_repo_dir_override: Some(repo.path().to_path_buf()),
+            summary_model: Some("summary".to_string()),
+            openai_api_key: Some("test-key".to_string()),
+            openai_base_url: format!("http://{addr}"),
+            skip_disk_check: true,
+            ..Settings::default()
+        };
+        let index_info = IndexInfo::from_settings(&settings, "v1");
+        let cancel = CancellationToken::new();
+
+        let started = std::time::Instant::now();
+        let err = with_build_timeout(
+            Duration::from_millis(300),
+            &cancel,
+            build_index(&settings, &index_info, &cancel),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Index build timed out"), "{err}");
+        assert!(cancel.is_cancelled());
+        assert!(started.elapsed() < Duration::from_secs(30));
+        assert!(!index_is_ready(&index_info).await);
+        assert!(!index_info.bm25_index_dir().exists());
+    }
+}
@@ -1,13 +1,20 @@
-use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context, Result};
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn};
 
+use super::{cancellable, ensure_not_cancelled};
+
 use crate::document::chunker::chunk_documents;
-use crate::document::source::{DocumentSource, GitDocumentSource};
+use crate::document::source::{DocumentSource, FsDocumentSource, GitDocumentSource};
+use crate::document::{ambiguous_topics, build_topic_category_map, code_block_chunks};
 use crate::embedding;
 use crate::search::bm25::BM25Store;
 use crate::search::vector::VectorStore;
-use crate::{DocData, IndexMetadata, SearchMode};
-use cangjie_core::config::{IndexInfo, Settings, DEFAULT_EMBEDDING_DIM, VECTOR_BATCH_SIZE};
+use crate::search::TOKENIZER_VERSION;
+use crate::{DocData, IndexMetadata, SearchMode, TextChunk};
+use cangjie_core::config::{IndexInfo, Settings, VECTOR_BATCH_SIZE};
 
 fn extend_or_warn(documents: &mut Vec<DocData>, label: &str, result: Result<Vec<DocData>>) {
     match result {
@@ -19,37 +26,212 @@ fn extend_or_warn(documents: &mut Vec<DocData>, label: &str, result: Result<Vec<
     }
 }
 
-/// Build the BM25 (and optionally vector) index from documentation.
-pub(super) async fn build_index(settings: &Settings, index_info: &IndexInfo) -> Result<()> {
+/// Log topics whose name exists in several categories; looking them up without
+/// a category picks only one.
+fn warn_ambiguous_topics(documents: &[DocData]) {
+    let map = build_topic_category_map(
+        documents
+            .iter()
+            .map(|d| (d.metadata.topic.as_str(), d.metadata.category.as_str())),
+    );
+    for (topic, categories) in ambiguous_topics(map) {
+        let categories: Vec<_> = categories.into_iter().collect();
+        warn!(
+            "Topic '{topic}' exists in multiple categories: {}",
+            categories.join(", ")
+        );
+    }
+}
+
+/// Error for a version/language whose checkout has nothing to index, usually
+/// because the tree predates the expected docs layout.
+fn no_documents_error(index_info: &IndexInfo, path: &str) -> anyhow::Error {
+    anyhow!(
+        "No documents found for version {} lang {} under path {path}; \
+         try a different version or language",
+        index_info.version,
+        index_info.lang
+    )
+}
+
+/// Headroom over the estimate for segment merges and SQLite journals.
+const DISK_ESTIMATE_MARGIN: f64 = 1.5;
+
+/// Rough disk space an index over `chunks` needs: the BM25 index stores the
+/// text plus its postings (about twice the text), the code index about as
+/// much again, and with an embedding dimension the vector store keeps one
+/// more copy of the text plus one `f32` vector per chunk.
+fn estimate_index_bytes(
+    chunks: &[TextChunk],
+    index_code: bool,
+    embedding_dim: Option<usize>,
+) -> u64 {
+    let text_bytes: u64 = chunks.iter().map(|c| c.text.len() as u64).sum();
+    let mut bytes = text_bytes * 2;
+    if index_code {
+        bytes += text_bytes;
+    }
+    if let Some(dim) = embedding_dim {
+        let vector_bytes = (dim * std::mem::size_of::<f32>()) as u64;
+        bytes += text_bytes + chunks.len() as u64 * vector_bytes;
+    }
+    (bytes as f64 * DISK_ESTIMATE_MARGIN) as u64
+}
+
+fn format_size(bytes: u64) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= 1024.0 * MIB {
+        format!("{:.1} GiB", bytes as f64 / (1024.0 * MIB))
+    } else {
+        format!("{:.1} MiB", bytes as f64 / MIB)
+    }
+}
+
+/// Fail before writing anything when the filesystem holding `index_info`
+/// has less free space than the build needs.
+fn check_disk_space(index_info: &IndexInfo, needed: u64) -> Result<()> {
+    // The index dir may not exist yet; measure its closest existing ancestor.
+    let index_dir = index_info.index_dir();
+    let Some(existing) = index_dir.ancestors().find(|p| p.exists()) else {
+        return Ok(());
+    };
+    let available = match fs2::available_space(existing) {
+        Ok(available) => available,
+        Err(e) => {
+            warn!("Could not check free space at {}: {e}", existing.display());
+            return Ok(());
+        }
+    };
+    if available < needed {
+        bail!(
+            "Insufficient disk space at {} (need ~{}, have {}); free some space, \
+             choose another --data-dir, or pass --skip-disk-check",
+            existing.display(),
+            format_size(needed),
+            format_size(available)
+        );
+    }
+    Ok(())
+}
+
+/// Remove stores left by an earlier build in the same index dir (e.g. before
+/// a rebuild for changed chunking); the summary cache is kept. The metadata
+/// goes first, so a rebuild that fails part-way never leaves an index that
+/// reads as ready over missing stores.
+async fn clear_index_stores(index_info: &IndexInfo) -> Result<()> {
+    let metadata_path = index_info.index_dir().join("index_metadata.json");
+    if tokio::fs::try_exists(&metadata_path).await? {
+        tokio::fs::remove_file(&metadata_path)
+            .await
+            .with_context(|| format!("Failed to remove {}", metadata_path.display()))?;
+    }
+    for dir in [
+        index_info.bm25_index_dir(),
+        index_info.code_index_dir(),
+        index_info.vector_db_dir(),
+    ] {
+        if tokio::fs::try_exists(&dir).await? {
+            info!("Removing stale index data at {}", dir.display());
+            tokio::fs::remove_dir_all(&dir)
+                .await
+                .with_context(|| format!("Failed to remove {}", dir.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes the stores of an index whose build did not finish, including one
+/// dropped mid-way by the build timeout or a superseding rebuild, so none of
+/// it is mistaken for usable.
+pub(super) struct PartialBuildGuard {
+    dirs: Vec<PathBuf>,
+    pub(super) finished: bool,
+}
+
+impl PartialBuildGuard {
+    pub(super) fn new(index_info: &IndexInfo) -> Self {
+        Self {
+            dirs: vec![
+                index_info.bm25_index_dir(),
+                index_info.code_index_dir(),
+                index_info.vector_db_dir(),
+            ],
+            finished: false,
+        }
+    }
+}
+
+impl Drop for PartialBuildGuard {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        for dir in &self.dirs {
+            if dir.exists() {
+                warn!("Discarding incomplete index data at {}", dir.display());
+                if let Err(e) = std::fs::remove_dir_all(dir) {
+                    warn!("Failed to remove {}: {e}", dir.display());
+                }
+            }
+        }
+    }
+}
+
+/// Build the BM25 (and optionally vector) index from documentation, giving
+/// up at the next safe point once `cancel` fires.
+pub(super) async fn build_index(
+    settings: &Settings,
+    index_info: &IndexInfo,
+    cancel: &CancellationToken,
+) -> Result<()> {
     info!("Loading documents...");
-    let docs_source = GitDocumentSource::for_docs(index_info.docs_repo_dir(), index_info.lang)?;
-    let tools_source = GitDocumentSource::for_tools(index_info.docs_repo_dir(), index_info.lang)?;
-    let release_notes_source = GitDocumentSource::for_release_notes(index_info.docs_repo_dir())?;
+    let decompress = settings.decompress_docs;
+    let strip_html = index_info.strip_html;
+    let docs_source = GitDocumentSource::for_docs(index_info.docs_repo_dir(), index_info.lang)?
+        .with_decompress(decompress)
+        .with_strip_html(strip_html);
+    let tools_source = GitDocumentSource::for_tools(index_info.docs_repo_dir(), index_info.lang)?
+        .with_decompress(decompress)
+        .with_strip_html(strip_html);
+    let release_notes_source = GitDocumentSource::for_release_notes(index_info.docs_repo_dir())?
+        .with_decompress(decompress)
+        .with_strip_html(strip_html);
     let runtime_source =
-        GitDocumentSource::for_runtime(index_info.runtime_repo_dir(), index_info.lang)?;
-    let stdx_source = GitDocumentSource::for_stdx(index_info.stdx_repo_dir(), index_info.lang)?;
+        GitDocumentSource::for_runtime(index_info.runtime_repo_dir(), index_info.lang)?
+            .with_decompress(decompress)
+            .with_strip_html(strip_html);
+    let stdx_source = GitDocumentSource::for_stdx(index_info.stdx_repo_dir(), index_info.lang)?
+        .with_decompress(decompress)
+        .with_strip_html(strip_html);
 
     // Auxiliary sources are best-effort: docs is required, the rest log and skip on failure.
-    let (docs_result, tools_result, release_notes_result, runtime_result, stdx_result) = tokio::join!(
-        docs_source.load_all_documents(),
-        tools_source.load_all_documents(),
-        release_notes_source.load_all_documents(),
-        runtime_source.load_all_documents(),
-        stdx_source.load_all_documents(),
-    );
+    let (docs_result, tools_result, release_notes_result, runtime_result, stdx_result) =
+        cancellable(cancel, async {
+            Ok(tokio::join!(
+                docs_source.load_all_documents(),
+                tools_source.load_all_documents(),
+                release_notes_source.load_all_documents(),
+                runtime_source.load_all_documents(),
+                stdx_source.load_all_documents(),
+            ))
+        })
+        .await?;
     let mut documents = docs_result?;
+    if documents.is_empty() {
+        return Err(no_documents_error(index_info, docs_source.base_path()));
+    }
     extend_or_warn(&mut documents, "tools", tools_result);
     extend_or_warn(&mut documents, "release-notes", release_notes_result);
     extend_or_warn(&mut documents, "runtime stdlib", runtime_result);
     extend_or_warn(&mut documents, "stdx", stdx_result);
-    if documents.is_empty() {
-        bail!(
-            "No documents found for version={}, lang={}",
-            index_info.version,
-            index_info.lang
-        );
+    for dir in &index_info.extra_docs_dirs {
+        let source = FsDocumentSource::new(dir.clone(), &index_info.extra_docs_prefix)
+            .with_strip_html(strip_html);
+        let label = format!("extra ({})", dir.display());
+        extend_or_warn(&mut documents, &label, source.load_all_documents().await);
     }
     info!("Loaded {} documents", documents.len());
+    warn_ambiguous_topics(&documents);
 
     // Capture doc texts before consuming documents (avoids a second load_all_documents call).
     let needs_summaries = settings.summary_model.is_some() && settings.openai_api_key.is_some();
@@ -70,13 +252,31 @@ pub(super) async fn build_index(settings: &Settings, index_info: &IndexInfo) ->
         "Chunking documents (max_chunk_chars={:?}, overlap={})...",
         settings.max_chunk_chars, settings.chunk_overlap_chars
     );
-    let mut chunks = chunk_documents(
-        documents,
-        settings.max_chunk_chars,
-        settings.chunk_overlap_chars,
-    )
-    .await;
+    let mut chunks = cancellable(cancel, async {
+        Ok(chunk_documents(
+            documents,
+            settings.max_chunk_chars,
+            settings.chunk_overlap_chars,
+            settings.chunk_breadcrumbs,
+        )
+        .await)
+    })
+    .await?;
     info!("Created {} chunks", chunks.len());
+    if chunks.is_empty() {
+        return Err(no_documents_error(index_info, docs_source.base_path()));
+    }
+
+    let embedding_dim = match embedder {
+        Some(ref emb) => Some(crate::embedding::embedding_dim(emb.as_ref()).await?),
+        None => None,
+    };
+    if settings.skip_disk_check {
+        info!("Skipping the disk space check");
+    } else {
+        let needed = estimate_index_bytes(&chunks, index_info.index_code, embedding_dim);
+        check_disk_space(index_info, needed)?;
+    }
 
     // Contextual retrieval: generate LLM summaries if summary_model is configured.
     if let Some(ref summary_model) = settings.summary_model {
@@ -92,11 +292,14 @@ pub(super) async fn build_index(settings: &Settings, index_info: &IndexInfo) ->
             let summarizer = crate::document::summarizer::ChunkSummarizer::new(api);
             let cache_path = index_info.index_dir().join("context_cache.json");
 
-            crate::document::summarizer::apply_context_summaries(
-                &mut chunks,
-                &doc_texts,
-                &summarizer,
-                &cache_path,
+            cancellable(
+                cancel,
+                crate::document::summarizer::apply_context_summaries(
+                    &mut chunks,
+                    &doc_texts,
+                    &summarizer,
+                    &cache_path,
+                ),
             )
             .await?;
         } else {
@@ -104,24 +307,36 @@ pub(super) async fn build_index(settings: &Settings, index_info: &IndexInfo) ->
         }
     }
 
+    ensure_not_cancelled(cancel)?;
+    clear_index_stores(index_info).await?;
+    let mut guard = PartialBuildGuard::new(index_info);
+
     info!("Building BM25 index...");
     let mut bm25 = BM25Store::new(index_info.bm25_index_dir());
     bm25.build_from_chunks(&chunks).await?;
 
-    if let Some(ref emb) = embedder {
+    if index_info.index_code {
+        let code_chunks = code_block_chunks(&chunks);
+        info!(
+            "Building code index from {} code blocks...",
+            code_chunks.len()
+        );
+        ensure_not_cancelled(cancel)?;
+        BM25Store::new(index_info.code_index_dir())
+            .build_from_chunks(&code_chunks)
+            .await?;
+    }
+
+    if let (Some(emb), Some(dim)) = (embedder.as_ref(), embedding_dim) {
         info!(
             "Building vector index with embedder: {}...",
             emb.model_name()
         );
-        let dim = {
-            let test = emb
-                .embed(&["test"], crate::embedding::EmbedKind::Document)
-                .await?;
-            test.first()
-                .map(|v| v.len())
-                .unwrap_or(DEFAULT_EMBEDDING_DIM)
-        };
-        let mut vs = VectorStore::open(&index_info.vector_db_dir(), dim).await?;
+        let mut vs = VectorStore::open(&index_info.vector_db_dir(), dim)
+            .await?
+            .with_distance(settings.vector_distance)
+            .with_insert_batch_size(settings.vector_insert_batch_size)
+            .with_cancellation(cancel.clone());
         vs.build_from_chunks(&chunks, emb.as_ref(), VECTOR_BATCH_SIZE)
             .await?;
     }
@@ -131,18 +346,187 @@ pub(super) async fn build_index(settings: &Settings, index_info: &IndexInfo) ->
     } else {
         SearchMode::Bm25
     };
+    let source_commit = crate::repo::GitManager::new(
+        index_info.docs_repo_dir(),
+        cangjie_core::config::DOCS_REPO_URL.to_string(),
+    )
+    .resolved_version(&index_info.version)
+    .await
+    .ok()
+    .flatten()
+    .map(|resolved| resolved.commit_oid);
     let metadata = IndexMetadata {
         version: index_info.version.clone(),
         lang: index_info.lang.to_string(),
         embedding_model: settings.embedding_model_name(),
         document_count: chunks.len(),
         search_mode,
+        index_code: index_info.index_code,
+        extra_docs: index_info.extra_docs(),
+        chunking: Some(index_info.chunking()),
+        source_commit,
+        vector_distance: embedder.is_some().then_some(settings.vector_distance),
+        tokenizer_version: TOKENIZER_VERSION,
     };
+    ensure_not_cancelled(cancel)?;
     let metadata_path = index_info.index_dir().join("index_metadata.json");
     tokio::fs::create_dir_all(metadata_path.parent().context("Invalid metadata path")?).await?;
     let json = serde_json::to_string_pretty(&metadata)?;
     tokio::fs::write(&metadata_path, json).await?;
+    guard.finished = true;
 
     info!("Index built successfully!");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::git_init_and_commit;
+    use cangjie_core::config::{DocLang, EmbeddingType, RerankType};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_build_index_without_docs_source_dir_fails_descriptively() {
+        let tmp = TempDir::new().unwrap();
+        let repo = tmp.path().join("docs_repo");
+        // Only release notes: an old tag without docs/dev-guide/source_zh_cn.
+        std::fs::create_dir_all(repo.join("release-notes")).unwrap();
+        std::fs::write(
+            repo.join("release-notes").join("notes.md"),
+            "# Notes\n\nOld notes.",
+        )
+        .unwrap();
+        git_init_and_commit(&repo);
+
+        let settings = Settings {
+            docs_lang: DocLang::Zh,
+            embedding_type: EmbeddingType::None,
+            rerank_type: RerankType::None,
+            data_dir: tmp.path().join("data"),
+            docs_repo_dir_override: Some(repo),
+            ..Settings::default()
+        };
+        let index_info = IndexInfo::from_settings(&settings, "v0.1");
+
+        let err = build_index(&settings, &index_info, &CancellationToken::new())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("version v0.1 lang zh"), "{err}");
+        assert!(err.contains("docs/dev-guide/source_zh_cn"), "{err}");
+        assert!(err.contains("try a different version or language"), "{err}");
+        assert!(!index_info.index_dir().join("index_metadata.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_failed_rebuild_leaves_no_ready_index() {
+        let (repo_tmp, _repo) = crate::testutil::create_test_repo();
+        let tmp = TempDir::new().unwrap();
+        let settings = Settings {
+            docs_lang: DocLang::Zh,
+            embedding_type: EmbeddingType::None,
+            rerank_type: RerankType::None,
+            data_dir: tmp.path().join("data"),
+            docs_repo_dir_override: Some(repo_tmp.path().to_path_buf()),
+            ..Settings::default()
+        };
+        let index_info = IndexInfo::from_settings(&settings, "v0.1");
+
+        build_index(&settings, &index_info, &CancellationToken::new())
+            .await
+            .unwrap();
+        assert!(crate::initializer::index_ready(&index_info).await);
+
+        // A file where a store directory belongs makes the second build fail
+        // while clearing the first one's stores.
+        std::fs::write(index_info.vector_db_dir(), "not a directory").unwrap();
+        assert!(
+            build_index(&settings, &index_info, &CancellationToken::new())
+                .await
+                .is_err()
+        );
+        assert!(!index_info.index_dir().join("index_metadata.json").exists());
+        assert!(!crate::initializer::index_ready(&index_info).await);
+    }
+
+    #[tokio::test]
+    async fn test_build_follows_index_info_index_code() {
+        let (repo_tmp, _repo) = crate::testutil::create_test_repo();
+        let tmp = TempDir::new().unwrap();
+        let settings = Settings {
+            docs_lang: DocLang::Zh,
+            embedding_type: EmbeddingType::None,
+            rerank_type: RerankType::None,
+            data_dir: tmp.path().join("data"),
+            docs_repo_dir_override: Some(repo_tmp.path().to_path_buf()),
+            ..Settings::default()
+        };
+        let mut index_info = IndexInfo::from_settings(&settings, "v0.1");
+        index_info.index_code = !settings.index_code;
+
+        build_index(&settings, &index_info, &CancellationToken::new())
+            .await
+            .unwrap();
+        assert!(crate::initializer::index_ready(&index_info).await);
+        assert_eq!(index_info.code_index_dir().exists(), index_info.index_code);
+    }
+
+    #[test]
+    fn test_partial_build_guard_discards_unfinished_stores() {
+        let tmp = TempDir::new().unwrap();
+        let settings = Settings {
+            data_dir: tmp.path().to_path_buf(),
+            ..Settings::default()
+        };
+        let index_info = IndexInfo::from_settings(&settings, "v0.1");
+        std::fs::create_dir_all(index_info.bm25_index_dir()).unwrap();
+
+        let mut guard = PartialBuildGuard::new(&index_info);
+        guard.finished = true;
+        drop(guard);
+        assert!(index_info.bm25_index_dir().exists());
+
+        drop(PartialBuildGuard::new(&index_info));
+        assert!(!index_info.bm25_index_dir().exists());
+    }
+
+    #[test]
+    fn test_estimate_index_bytes_for_known_corpus() {
+        // 1000 chunks of 1 KiB: 1,024,000 bytes of text.
+        let chunk = TextChunk {
+            text: "x".repeat(1024),
+            metadata: Default::default(),
+        };
+        let chunks = vec![chunk; 1000];
+        let text = 1_024_000u64;
+
+        let bm25_only = estimate_index_bytes(&chunks, false, None);
+        assert_eq!(bm25_only, (text * 2) * 3 / 2);
+
+        let with_code = estimate_index_bytes(&chunks, true, None);
+        assert_eq!(with_code, (text * 3) * 3 / 2);
+
+        // 1024-dim f32 vectors add 4 KiB per chunk on top of a text copy.
+        let hybrid = estimate_index_bytes(&chunks, false, Some(1024));
+        assert_eq!(hybrid, (text * 3 + 1000 * 4096) * 3 / 2);
+    }
+
+    #[test]
+    fn test_check_disk_space_reports_need_and_availability() {
+        let tmp = TempDir::new().unwrap();
+        let settings = Settings {
+            data_dir: tmp.path().join("data"),
+            ..Settings::default()
+        };
+        let index_info = IndexInfo::from_settings(&settings, "v0.1");
+
+        assert!(check_disk_space(&index_info, 1).is_ok());
+        let err = check_disk_space(&index_info, u64::MAX)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Insufficient disk space"), "{err}");
+        assert!(err.contains("need ~"), "{err}");
+        assert!(err.contains("--skip-disk-check"), "{err}");
+    }
+}
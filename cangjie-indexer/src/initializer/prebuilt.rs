@@ -1,8 +1,12 @@
-use anyhow::{bail, Result};
-use tracing::info;
+use std::path::Path;
 
-use crate::IndexMetadata;
-use cangjie_core::config::{IndexInfo, PrebuiltMode, Settings};
+use anyhow::{bail, Context, Result};
+use tracing::{info, warn};
+
+use crate::repo::GitManager;
+use crate::search::TOKENIZER_VERSION;
+use crate::{IndexMetadata, SearchMode};
+use cangjie_core::config::{IndexInfo, PrebuiltMode, Settings, VectorDistance};
 
 /// Check if a valid index exists by reading the metadata file.
 pub(super) async fn index_is_ready(index_info: &IndexInfo) -> bool {
@@ -13,6 +17,8 @@ pub(super) async fn index_is_ready(index_info: &IndexInfo) -> bool {
                 meta.version == index_info.version
                     && meta.lang == index_info.lang.to_string()
                     && meta.document_count > 0
+                    && meta.index_code == index_info.index_code
+                    && meta.extra_docs == index_info.extra_docs()
             }
             Err(_) => false,
         },
@@ -20,9 +26,143 @@ pub(super) async fn index_is_ready(index_info: &IndexInfo) -> bool {
     }
 }
 
+/// Whether a ready index was built with different chunking parameters than
+/// `index_info` asks for, logging each one that changed. Indexes that predate
+/// recording chunking are kept.
+pub(super) async fn chunking_changed(index_info: &IndexInfo) -> bool {
+    let metadata_path = index_info.index_dir().join("index_metadata.json");
+    let Ok(content) = tokio::fs::read_to_string(&metadata_path).await else {
+        return false;
+    };
+    let Some(recorded) = serde_json::from_str::<IndexMetadata>(&content)
+        .ok()
+        .and_then(|meta| meta.chunking)
+    else {
+        return false;
+    };
+    let current = index_info.chunking();
+    if recorded.max_chunk_chars != current.max_chunk_chars {
+        info!(
+            "Chunking changed: max_chunk_chars {:?} -> {:?}",
+            recorded.max_chunk_chars, current.max_chunk_chars
+        );
+    }
+    if recorded.chunk_overlap_chars != current.chunk_overlap_chars {
+        info!(
+            "Chunking changed: chunk_overlap_chars {} -> {}",
+            recorded.chunk_overlap_chars, current.chunk_overlap_chars
+        );
+    }
+    if recorded.strip_html != current.strip_html {
+        info!(
+            "Chunking changed: strip_html {} -> {}",
+            recorded.strip_html, current.strip_html
+        );
+    }
+    if recorded.chunk_breadcrumbs != current.chunk_breadcrumbs {
+        info!(
+            "Chunking changed: chunk_breadcrumbs {} -> {}",
+            recorded.chunk_breadcrumbs, current.chunk_breadcrumbs
+        );
+    }
+    recorded != current
+}
+
+/// Whether a ready hybrid index was built with a different vector distance
+/// than `index_info` asks for. Hybrid indexes that predate recording it were
+/// built with L2; BM25-only indexes have no vector store to rebuild.
+pub(super) async fn vector_distance_changed(index_info: &IndexInfo) -> bool {
+    let metadata_path = index_info.index_dir().join("index_metadata.json");
+    let Ok(content) = tokio::fs::read_to_string(&metadata_path).await else {
+        return false;
+    };
+    let Ok(meta) = serde_json::from_str::<IndexMetadata>(&content) else {
+        return false;
+    };
+    if meta.search_mode != SearchMode::Hybrid {
+        return false;
+    }
+    let recorded = meta.vector_distance.unwrap_or(VectorDistance::L2);
+    if recorded != index_info.vector_distance {
+        info!(
+            "Vector distance changed: {} -> {}",
+            recorded, index_info.vector_distance
+        );
+        return true;
+    }
+    false
+}
+
+/// Whether a ready index was built with a different BM25 tokenizer than
+/// [`TOKENIZER_VERSION`], so its tokens no longer match how queries are split.
+pub(super) async fn tokenizer_changed(index_info: &IndexInfo) -> bool {
+    let metadata_path = index_info.index_dir().join("index_metadata.json");
+    let Ok(content) = tokio::fs::read_to_string(&metadata_path).await else {
+        return false;
+    };
+    let Ok(meta) = serde_json::from_str::<IndexMetadata>(&content) else {
+        return false;
+    };
+    if meta.tokenizer_version != TOKENIZER_VERSION {
+        info!(
+            "Tokenizer changed: version {} -> {}",
+            meta.tokenizer_version, TOKENIZER_VERSION
+        );
+        return true;
+    }
+    false
+}
+
+/// Compare the docs checkout's HEAD with the commit a ready index was built
+/// from. On a mismatch, warn and, when `restore` is set, check the indexed
+/// commit back out so documents read from the tree match search results.
+/// Indexes that predate recording the commit, and uncloned repos, are skipped.
+pub(super) async fn reconcile_source_commit(
+    git_mgr: &mut GitManager,
+    index_info: &IndexInfo,
+    restore: bool,
+) {
+    let metadata_path = index_info.index_dir().join("index_metadata.json");
+    let Ok(content) = tokio::fs::read_to_string(&metadata_path).await else {
+        return;
+    };
+    let Some(recorded) = serde_json::from_str::<IndexMetadata>(&content)
+        .ok()
+        .and_then(|meta| meta.source_commit)
+    else {
+        return;
+    };
+    let head = match git_mgr.resolved_version(&index_info.version).await {
+        Ok(Some(head)) => head,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("Failed to read the docs checkout's HEAD: {e:#}");
+            return;
+        }
+    };
+    if head.commit_oid == recorded {
+        return;
+    }
+    warn!(
+        "Docs checkout is on {} ({}), but the index for version {} was built from {}",
+        head.resolved_ref, head.short_hash, index_info.version, recorded
+    );
+    if !restore {
+        warn!("Search results may not match documents read from the checkout");
+        return;
+    }
+    match git_mgr.checkout_local(&recorded).await {
+        Ok(()) => info!("Checked out indexed commit {recorded}"),
+        Err(e) => warn!(
+            "Failed to check out indexed commit {recorded}: {e:#}; \
+             search results may not match documents read from the checkout"
+        ),
+    }
+}
+
 /// Discover all version directories under `data_dir/indexes/` that contain a
 /// valid index matching the current settings (lang + embedding model).
-async fn discover_prebuilt_versions(settings: &Settings) -> Result<Vec<String>> {
+pub(super) async fn discover_prebuilt_versions(settings: &Settings) -> Result<Vec<String>> {
     let indexes_dir = settings.data_dir.join("indexes");
     if !indexes_dir.exists() {
         return Ok(Vec::new());
@@ -44,6 +184,62 @@ async fn discover_prebuilt_versions(settings: &Settings) -> Result<Vec<String>>
     Ok(versions)
 }
 
+/// Serve the index in `index_dir` (from `--index-dir`) as-is: no git work and
+/// no build. A lang, embedding model, or `--prebuilt` version that differs
+/// from the recorded metadata is only warned about.
+pub(super) async fn load_index_dir(settings: &Settings, index_dir: &Path) -> Result<IndexInfo> {
+    let metadata_path = index_dir.join("index_metadata.json");
+    let content = tokio::fs::read_to_string(&metadata_path)
+        .await
+        .with_context(|| format!("No index metadata at {}", metadata_path.display()))?;
+    let meta: IndexMetadata = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid index metadata at {}", metadata_path.display()))?;
+    if meta.document_count == 0 {
+        bail!("Index at {} has no documents", index_dir.display());
+    }
+    if meta.lang != settings.docs_lang.to_string() {
+        warn!(
+            "Index at {} was built for lang={}, but lang={} was requested",
+            index_dir.display(),
+            meta.lang,
+            settings.docs_lang
+        );
+    }
+    if meta.embedding_model != settings.embedding_model_name() {
+        warn!(
+            "Index at {} was built with model={}, but model={} was requested",
+            index_dir.display(),
+            meta.embedding_model,
+            settings.embedding_model_name()
+        );
+    }
+    if meta.tokenizer_version != TOKENIZER_VERSION {
+        warn!(
+            "Index at {} was built with tokenizer version {}, but this build uses {}; \
+             rebuild it for full keyword search accuracy",
+            index_dir.display(),
+            meta.tokenizer_version,
+            TOKENIZER_VERSION
+        );
+    }
+    if let PrebuiltMode::Version(version) = &settings.prebuilt {
+        if *version != meta.version {
+            warn!(
+                "Index at {} holds version {}, but version {} was requested",
+                index_dir.display(),
+                meta.version,
+                version
+            );
+        }
+    }
+    info!(
+        "Using index at {} (version: {})",
+        index_dir.display(),
+        meta.version
+    );
+    Ok(IndexInfo::from_settings(settings, &meta.version))
+}
+
 /// Load a pre-built index without any git operations.
 ///
 /// `PrebuiltMode::Auto`: scan and auto-select (exactly one must exist).
@@ -92,7 +288,7 @@ pub(super) async fn load_prebuilt_index(settings: &Settings) -> Result<IndexInfo
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::SearchMode;
+    use crate::ChunkingParams;
     use cangjie_core::config::{DocLang, EmbeddingType, RerankType};
     use std::path::PathBuf;
     use tempfile::TempDir;
@@ -127,6 +323,12 @@ mod tests {
             embedding_model: "none".to_string(),
             document_count: doc_count,
             search_mode: SearchMode::Bm25,
+            index_code: false,
+            extra_docs: None,
+            chunking: None,
+            vector_distance: None,
+            source_commit: None,
+            tokenizer_version: TOKENIZER_VERSION,
         };
         let json = serde_json::to_string_pretty(&metadata).unwrap();
         let metadata_path = index_dir.join("index_metadata.json");
@@ -179,6 +381,165 @@ mod tests {
         assert!(index_is_ready(&index_info).await);
     }
 
+    #[tokio::test]
+    async fn test_index_is_ready_rebuilds_when_index_code_changes() {
+        let tmp = TempDir::new().unwrap();
+        write_valid_metadata(tmp.path(), "v0.55.4", "zh", 100).await;
+
+        let mut settings = test_settings(tmp.path().to_path_buf());
+        settings.index_code = true;
+        let index_info = IndexInfo::from_settings(&settings, "v0.55.4");
+
+        assert!(!index_is_ready(&index_info).await);
+    }
+
+    #[tokio::test]
+    async fn test_index_is_ready_rebuilds_when_extra_docs_change() {
+        let tmp = TempDir::new().unwrap();
+        write_valid_metadata(tmp.path(), "v0.55.4", "zh", 100).await;
+
+        let mut settings = test_settings(tmp.path().to_path_buf());
+        settings.extra_docs_dirs = vec![PathBuf::from("/team/guides")];
+        let index_info = IndexInfo::from_settings(&settings, "v0.55.4");
+
+        assert!(!index_is_ready(&index_info).await);
+    }
+
+    #[tokio::test]
+    async fn test_tokenizer_changed_rebuilds_older_indexes() {
+        let tmp = TempDir::new().unwrap();
+        write_valid_metadata(tmp.path(), "v0.55.4", "zh", 100).await;
+        let settings = test_settings(tmp.path().to_path_buf());
+        let index_info = IndexInfo::from_settings(&settings, "v0.55.4");
+        assert!(!tokenizer_changed(&index_info).await);
+
+        // Indexes from before the version was recorded were built by plain Jieba.
+        let metadata_path = index_info.index_dir().join("index_metadata.json");
+        let mut meta: serde_json::Value =
+            serde_json::from_str(&tokio::fs::read_to_string(&metadata_path).await.unwrap())
+                .unwrap();
+        meta.as_object_mut().unwrap().remove("tokenizer_version");
+        tokio::fs::write(&metadata_path, meta.to_string())
+            .await
+            .unwrap();
+        assert!(index_is_ready(&index_info).await);
+        assert!(tokenizer_changed(&index_info).await);
+    }
+
+    async fn write_metadata_with_chunking(index_info: &IndexInfo, max_chunk_chars: Option<usize>) {
+        tokio::fs::create_dir_all(index_info.index_dir())
+            .await
+            .unwrap();
+        let metadata = IndexMetadata {
+            version: index_info.version.clone(),
+            lang: "zh".to_string(),
+            embedding_model: "none".to_string(),
+            document_count: 100,
+            search_mode: SearchMode::Bm25,
+            index_code: false,
+            extra_docs: None,
+            chunking: Some(ChunkingParams {
+                max_chunk_chars,
+                chunk_overlap_chars: index_info.chunk_overlap_chars,
+                strip_html: index_info.strip_html,
+                chunk_breadcrumbs: index_info.chunk_breadcrumbs,
+            }),
+            vector_distance: None,
+            source_commit: None,
+            tokenizer_version: TOKENIZER_VERSION,
+        };
+        tokio::fs::write(
+            index_info.index_dir().join("index_metadata.json"),
+            serde_json::to_string_pretty(&metadata).unwrap(),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_chunking_changed_detects_new_chunk_size() {
+        let tmp = TempDir::new().unwrap();
+        let mut settings = test_settings(tmp.path().to_path_buf());
+        settings.max_chunk_chars = Some(1200);
+        write_metadata_with_chunking(&IndexInfo::from_settings(&settings, "v1"), Some(1200)).await;
+
+        let unchanged = IndexInfo::from_settings(&settings, "v1");
+        assert!(index_is_ready(&unchanged).await);
+        assert!(!chunking_changed(&unchanged).await);
+
+        settings.max_chunk_chars = Some(800);
+        let resized = IndexInfo::from_settings(&settings, "v1");
+        assert!(index_is_ready(&resized).await);
+        assert!(chunking_changed(&resized).await);
+    }
+
+    #[tokio::test]
+    async fn test_chunking_changed_detects_strip_html_toggle() {
+        let tmp = TempDir::new().unwrap();
+        let mut settings = test_settings(tmp.path().to_path_buf());
+        write_metadata_with_chunking(&IndexInfo::from_settings(&settings, "v1"), None).await;
+        assert!(!chunking_changed(&IndexInfo::from_settings(&settings, "v1")).await);
+
+        settings.strip_html = true;
+        assert!(chunking_changed(&IndexInfo::from_settings(&settings, "v1")).await);
+    }
+
+    #[tokio::test]
+    async fn test_vector_distance_changed_rebuilds_hybrid_index() {
+        let tmp = TempDir::new().unwrap();
+        let settings = test_settings(tmp.path().to_path_buf());
+        let index_info = IndexInfo::from_settings(&settings, "v1");
+        tokio::fs::create_dir_all(index_info.index_dir())
+            .await
+            .unwrap();
+        let write = |search_mode, vector_distance| {
+            let metadata = IndexMetadata {
+                version: "v1".to_string(),
+                lang: "zh".to_string(),
+                embedding_model: "none".to_string(),
+                document_count: 100,
+                search_mode,
+                extra_docs: None,
+                chunking: None,
+                vector_distance,
+                source_commit: None,
+                index_code: false,
+                tokenizer_version: TOKENIZER_VERSION,
+            };
+            std::fs::write(
+                index_info.index_dir().join("index_metadata.json"),
+                serde_json::to_string(&metadata).unwrap(),
+            )
+            .unwrap();
+        };
+
+        write(SearchMode::Hybrid, Some(VectorDistance::Dot));
+        assert!(vector_distance_changed(&index_info).await);
+        write(SearchMode::Hybrid, Some(VectorDistance::L2));
+        assert!(!vector_distance_changed(&index_info).await);
+        // Hybrid indexes from before the distance was recorded used L2.
+        write(SearchMode::Hybrid, None);
+        assert!(!vector_distance_changed(&index_info).await);
+
+        let mut settings = settings;
+        settings.vector_distance = VectorDistance::Cosine;
+        let cosine = IndexInfo::from_settings(&settings, "v1");
+        assert!(vector_distance_changed(&cosine).await);
+        write(SearchMode::Bm25, None);
+        assert!(!vector_distance_changed(&cosine).await);
+    }
+
+    #[tokio::test]
+    async fn test_chunking_changed_keeps_index_without_recorded_chunking() {
+        let tmp = TempDir::new().unwrap();
+        write_valid_metadata(tmp.path(), "v1", "zh", 100).await;
+
+        let mut settings = test_settings(tmp.path().to_path_buf());
+        settings.max_chunk_chars = Some(800);
+        let index_info = IndexInfo::from_settings(&settings, "v1");
+        assert!(!chunking_changed(&index_info).await);
+    }
+
     #[tokio::test]
     async fn test_index_is_ready_zero_document_count() {
         let tmp = TempDir::new().unwrap();
@@ -208,6 +569,12 @@ mod tests {
             embedding_model: "none".to_string(),
             document_count: 100,
             search_mode: SearchMode::Bm25,
+            index_code: false,
+            extra_docs: None,
+            chunking: None,
+            vector_distance: None,
+            source_commit: None,
+            tokenizer_version: TOKENIZER_VERSION,
         };
         let json = serde_json::to_string_pretty(&metadata).unwrap();
         tokio::fs::write(index_dir.join("index_metadata.json"), json)
@@ -359,6 +726,76 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_load_index_dir_uses_recorded_version() {
+        let tmp = TempDir::new().unwrap();
+        write_valid_metadata(tmp.path(), "v0.55.4", "en", 100).await;
+        let index_dir =
+            IndexInfo::from_settings(&test_settings(tmp.path().to_path_buf()), "v0.55.4")
+                .index_dir();
+
+        let mut settings = test_settings(tmp.path().join("elsewhere"));
+        settings.index_dir_override = Some(index_dir.clone());
+        settings.prebuilt = PrebuiltMode::Version("v0.60.0".to_string());
+
+        // Mismatched lang and version are warned about, not rejected.
+        let index_info = load_index_dir(&settings, &index_dir).await.unwrap();
+        assert_eq!(index_info.version, "v0.55.4");
+        assert_eq!(index_info.index_dir(), index_dir);
+        assert!(load_index_dir(&settings, tmp.path()).await.is_err());
+    }
+
+    fn head_oid(dir: &std::path::Path) -> String {
+        gix::open(dir).unwrap().head_id().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_source_commit_restores_indexed_commit() {
+        let (repo_tmp, _repo) = crate::testutil::create_test_repo();
+        let indexed = head_oid(repo_tmp.path());
+        // Someone moves the checkout after the index was built.
+        crate::testutil::git(
+            repo_tmp.path(),
+            &["commit", "--allow-empty", "-m", "later docs"],
+        );
+        let moved = head_oid(repo_tmp.path());
+        assert_ne!(moved, indexed);
+
+        let tmp = TempDir::new().unwrap();
+        let index_info = IndexInfo::from_settings(&test_settings(tmp.path().to_path_buf()), "v1");
+        tokio::fs::create_dir_all(index_info.index_dir())
+            .await
+            .unwrap();
+        let metadata = IndexMetadata {
+            version: "v1".to_string(),
+            lang: "zh".to_string(),
+            embedding_model: "none".to_string(),
+            document_count: 1,
+            search_mode: SearchMode::Bm25,
+            index_code: false,
+            extra_docs: None,
+            chunking: None,
+            vector_distance: None,
+            source_commit: Some(indexed.clone()),
+            tokenizer_version: TOKENIZER_VERSION,
+        };
+        tokio::fs::write(
+            index_info.index_dir().join("index_metadata.json"),
+            serde_json::to_string(&metadata).unwrap(),
+        )
+        .await
+        .unwrap();
+
+        let mut git_mgr =
+            GitManager::new(repo_tmp.path().to_path_buf(), "https://example.com".into());
+        // Prebuilt mode only warns.
+        reconcile_source_commit(&mut git_mgr, &index_info, false).await;
+        assert_eq!(head_oid(repo_tmp.path()), moved);
+
+        reconcile_source_commit(&mut git_mgr, &index_info, true).await;
+        assert_eq!(head_oid(repo_tmp.path()), indexed);
+    }
+
     #[tokio::test]
     async fn test_load_prebuilt_auto_multiple_indexes() {
         let tmp = TempDir::new().unwrap();
@@ -0,0 +1,409 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex as StdMutex};
+
+use anyhow::{Context, Result};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::repo::GitManager;
+use cangjie_core::config::{IndexInfo, Settings, DOCS_REPO_URL};
+
+/// Result of one [`IndexRefresher::refresh`] cycle.
+#[derive(Debug)]
+pub enum RefreshOutcome {
+    /// The docs version still resolves to the commit last seen.
+    Unchanged,
+    /// The docs version moved to a new commit and its index is ready.
+    Rebuilt(IndexInfo),
+    /// A newer rebuild cancelled this one and publishes its own index.
+    Superseded,
+}
+
+/// Lets a newer index rebuild supersede the one in flight: the older build is
+/// cancelled, and its partial output discarded, before the newer one starts
+/// writing. Clones share the same state.
+#[derive(Clone, Default)]
+pub struct RebuildCoordinator {
+    /// Cancels the most recently requested rebuild.
+    latest: Arc<StdMutex<CancellationToken>>,
+    /// Held by the rebuild that is running, so two never write at once.
+    running: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl RebuildCoordinator {
+    /// Build (or reuse) the index for `settings`, cancelling any rebuild
+    /// still running. `None` when a newer rebuild superseded this one.
+    pub async fn rebuild(&self, settings: &Settings) -> Result<Option<IndexInfo>> {
+        self.run(|cancel| async move {
+            super::initialize_and_index_cancellable(settings, &cancel).await
+        })
+        .await
+    }
+
+    async fn run<T, F, Fut>(&self, build: F) -> Result<Option<T>>
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let cancel = CancellationToken::new();
+        std::mem::replace(
+            &mut *self.latest.lock().expect("mutex poisoned"),
+            cancel.clone(),
+        )
+        .cancel();
+        // Waits for a cancelled build to unwind; its guard has removed the
+        // partial stores by the time the lock is released.
+        let _running = self.running.lock().await;
+        if cancel.is_cancelled() {
+            return Ok(None);
+        }
+        match build(cancel.clone()).await {
+            // A build that finished before noticing is still a valid index.
+            Err(_) if cancel.is_cancelled() => Ok(None),
+            result => result.map(Some),
+        }
+    }
+}
+
+/// Follows the configured docs version for `--watch` mode, rebuilding the
+/// index whenever the version resolves to a different commit.
+pub struct IndexRefresher {
+    settings: Settings,
+    git_mgr: GitManager,
+    last_resolved: Option<String>,
+    rebuilds: RebuildCoordinator,
+    /// Index built for the commit last seen.
+    served: Option<IndexInfo>,
+    /// Indexes of earlier commits, removed on the next cycle so requests
+    /// still running against them can finish first.
+    superseded: Vec<IndexInfo>,
+}
+
+impl IndexRefresher {
+    /// Start from the commit the docs checkout is on now, i.e. the one the
+    /// running index was built from.
+    pub async fn new(settings: Settings) -> Result<Self> {
+        let git_mgr = GitManager::new(settings.docs_repo_dir(), DOCS_REPO_URL.to_string())
+            .with_retries(settings.git_retries)
+            .with_max_concurrent_ops(settings.max_concurrent_git_ops);
+        let last_resolved = git_mgr
+            .resolved_version(&settings.docs_version)
+            .await?
+            .map(|v| v.resolved_ref);
+        let served = super::open_existing_index(&settings).await.ok();
+        Ok(Self {
+            settings,
+            git_mgr,
+            last_resolved,
+            rebuilds: RebuildCoordinator::default(),
+            served,
+            superseded: Vec::new(),
+        })
+    }
+
+    /// Run rebuilds through `rebuilds`, so they supersede (and are superseded
+    /// by) other rebuilds sharing it.
+    pub fn with_coordinator(mut self, rebuilds: RebuildCoordinator) -> Self {
+        self.rebuilds = rebuilds;
+        self
+    }
+
+    /// Fetch the docs version and report whether it resolved to a different
+    /// commit than on the previous cycle.
+    async fn docs_changed(&mut self) -> Result<bool> {
+        let resolved = self
+            .git_mgr
+            .resolve_version(&self.settings.docs_version)
+            .await
+            .context("Failed to refresh documentation version")?;
+        if self.last_resolved.as_deref() == Some(resolved.as_str()) {
+            return Ok(false);
+        }
+        info!(
+            "Docs version {} moved: {} -> {}",
+            self.settings.docs_version,
+            self.last_resolved.as_deref().unwrap_or("<none>"),
+            resolved
+        );
+        self.last_resolved = Some(resolved);
+        Ok(true)
+    }
+
+    /// Run one refresh cycle: fetch, and when the commit changed, build (or
+    /// reuse) the index for the new commit. A failed or superseded build is
+    /// retried on the next cycle.
+    pub async fn refresh(&mut self) -> Result<RefreshOutcome> {
+        self.prune_superseded().await;
+        let previous = self.last_resolved.clone();
+        if !self.docs_changed().await? {
+            return Ok(RefreshOutcome::Unchanged);
+        }
+        match self.rebuilds.rebuild(&self.settings).await {
+            Ok(Some(index_info)) => {
+                if let Some(old) = self.served.replace(index_info.clone()) {
+                    if old.version != index_info.version {
+                        self.superseded.push(old);
+                    }
+                }
+                Ok(RefreshOutcome::Rebuilt(index_info))
+            }
+            Ok(None) => {
+                self.last_resolved = previous;
+                Ok(RefreshOutcome::Superseded)
+            }
+            Err(e) => {
+                self.last_resolved = previous;
+                Err(e)
+            }
+        }
+    }
+
+    /// Remove the indexes of commits an earlier cycle moved past, unless the
+    /// docs have since moved back to one of them.
+    async fn prune_superseded(&mut self) {
+        for stale in std::mem::take(&mut self.superseded) {
+            if self
+                .served
+                .as_ref()
+                .is_some_and(|served| served.version == stale.version)
+            {
+                continue;
+            }
+            let dir = stale.index_dir();
+            match tokio::fs::remove_dir_all(&dir).await {
+                Ok(()) => info!("Removed superseded index at {}", dir.display()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    warn!(
+                        "Failed to remove superseded index at {}: {e}",
+                        dir.display()
+                    );
+                    continue;
+                }
+            }
+            // Drop the lang and version directories once nothing else is in them.
+            for parent in dir.ancestors().skip(1).take(2) {
+                if tokio::fs::remove_dir(parent).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::initializer::build::PartialBuildGuard;
+    use crate::initializer::{cancellable, index_is_ready};
+    use crate::testutil::{create_test_repo_with_remote, git, git_init_and_commit};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn commit_and_push(dir: &std::path::Path) {
+        git(dir, &["commit", "--allow-empty", "-m", "update docs"]);
+        git(dir, &["update-ref", "refs/remotes/origin/main", "HEAD"]);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_detects_only_new_commits() {
+        let (tmp, _repo) = create_test_repo_with_remote("main");
+        let settings = Settings {
+            docs_version: "latest".to_string(),
+            docs_repo_dir_override: Some(tmp.path().to_path_buf()),
+            git_retries: 0,
+            ..Settings::default()
+        };
+        let mut refresher = IndexRefresher::new(settings).await.unwrap();
+        // Settle on the branch the index would have been built from.
+        refresher.docs_changed().await.unwrap();
+
+        assert!(!refresher.docs_changed().await.unwrap());
+
+        commit_and_push(tmp.path());
+        assert!(refresher.docs_changed().await.unwrap());
+        assert!(!refresher.docs_changed().await.unwrap());
+    }
+
+    /// A cloned repository at `dir` whose `origin/main` is its only commit.
+    fn init_repo_with_remote(dir: &std::path::Path) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(dir.join("README.md"), "# Repo").unwrap();
+        git_init_and_commit(dir);
+        git(
+            dir,
+            &["remote", "add", "origin", "https://example.com/fake.git"],
+        );
+        git(dir, &["update-ref", "refs/remotes/origin/main", "HEAD"]);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rebuilds_only_when_the_commit_changes() {
+        let (docs, _repo) = create_test_repo_with_remote("main");
+        let data = TempDir::new().unwrap();
+        let settings = Settings {
+            docs_version: "latest".to_string(),
+            runtime_version: "latest".to_string(),
+            stdx_version: "latest".to_string(),
+            data_dir: data.path().to_path_buf(),
+            docs_repo_dir_override: Some(docs.path().to_path_buf()),
+            git_retries: 0,
+            ..Settings::default()
+        };
+        init_repo_with_remote(&settings.runtime_repo_dir());
+        init_repo_with_remote(&settings.stdx_repo_dir());
+
+        let mut refresher = IndexRefresher::new(settings).await.unwrap();
+        // The first cycle settles on the branch and builds its index.
+        let RefreshOutcome::Rebuilt(first) = refresher.refresh().await.unwrap() else {
+            panic!("expected the first cycle to build an index");
+        };
+        assert!(matches!(
+            refresher.refresh().await.unwrap(),
+            RefreshOutcome::Unchanged
+        ));
+
+        commit_and_push(docs.path());
+        let RefreshOutcome::Rebuilt(second) = refresher.refresh().await.unwrap() else {
+            panic!("expected a rebuild for the new commit");
+        };
+        assert_ne!(first.version, second.version);
+        assert!(first.index_dir().exists());
+
+        // The next cycle drops the index of the commit moved past.
+        assert!(matches!(
+            refresher.refresh().await.unwrap(),
+            RefreshOutcome::Unchanged
+        ));
+        assert!(!first.index_dir().exists());
+        assert!(second.index_dir().join("index_metadata.json").exists());
+    }
+
+    /// Stand-in for a build: writes `marker` into the BM25 store, then, when
+    /// `slow`, runs a phase only cancellation ends. `started` fires once the
+    /// marker is on disk.
+    async fn marker_build(
+        index_info: &IndexInfo,
+        marker: &str,
+        slow: bool,
+        started: Option<tokio::sync::oneshot::Sender<()>>,
+        cancel: CancellationToken,
+    ) -> Result<()> {
+        let mut guard = PartialBuildGuard::new(index_info);
+        let dir = index_info.bm25_index_dir();
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join(marker), marker)?;
+        if let Some(started) = started {
+            let _ = started.send(());
+        }
+        if slow {
+            cancellable(&cancel, async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(())
+            })
+            .await?;
+        }
+        guard.finished = true;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_newer_rebuild_supersedes_running_one() {
+        let tmp = TempDir::new().unwrap();
+        let settings = Settings {
+            data_dir: tmp.path().to_path_buf(),
+            ..Settings::default()
+        };
+        let index_info = IndexInfo::from_settings(&settings, "v0.1");
+        let rebuilds = RebuildCoordinator::default();
+
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let first = {
+            let rebuilds = rebuilds.clone();
+            let index_info = index_info.clone();
+            tokio::spawn(async move {
+                rebuilds
+                    .run(|cancel| {
+                        marker_build(&index_info, "first", true, Some(started_tx), cancel)
+                    })
+                    .await
+            })
+        };
+        started_rx.await.unwrap();
+
+        let second = rebuilds
+            .run(|cancel| marker_build(&index_info, "second", false, None, cancel))
+            .await
+            .unwrap();
+        assert!(second.is_some());
+        assert!(first.await.unwrap().unwrap().is_none());
+
+        // Only the latest build's output is left; the first one's partial
+        // store was discarded before the second started writing.
+        let mut files: Vec<String> = std::fs::read_dir(index_info.bm25_index_dir())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["second"]);
+    }
+
+    #[tokio::test]
+    async fn test_newer_rebuild_cancels_a_real_build() {
+        // A summary endpoint that accepts connections but never answers keeps
+        // the first build in its summary phase until it is cancelled.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let mut started = Some(started_tx);
+            let mut held = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                if let Some(started) = started.take() {
+                    let _ = started.send(());
+                }
+                held.push(stream);
+            }
+        });
+
+        let (docs, _repo) = create_test_repo_with_remote("main");
+        let data = TempDir::new().unwrap();
+        let settings = Settings {
+            docs_version: "latest".to_string(),
+            runtime_version: "latest".to_string(),
+            stdx_version: "latest".to_string(),
+            data_dir: data.path().to_path_buf(),
+            docs_repo_dir_override: Some(docs.path().to_path_buf()),
+            git_retries: 0,
+            skip_disk_check: true,
+            ..Settings::default()
+        };
+        init_repo_with_remote(&settings.runtime_repo_dir());
+        init_repo_with_remote(&settings.stdx_repo_dir());
+        let hanging = Settings {
+            summary_model: Some("summary".to_string()),
+            openai_api_key: Some("test-key".to_string()),
+            openai_base_url: format!("http://{addr}"),
+            ..settings.clone()
+        };
+
+        let rebuilds = RebuildCoordinator::default();
+        let first = {
+            let rebuilds = rebuilds.clone();
+            tokio::spawn(async move { rebuilds.rebuild(&hanging).await })
+        };
+        started_rx.await.unwrap();
+
+        let second = tokio::time::timeout(Duration::from_secs(60), rebuilds.rebuild(&settings))
+            .await
+            .expect("the first build should stop once cancelled")
+            .unwrap()
+            .expect("the latest rebuild should not be superseded");
+        assert!(first.await.unwrap().unwrap().is_none());
+
+        // The latest build's index is complete and loads.
+        assert!(index_is_ready(&second).await);
+        let mut bm25 = crate::search::bm25::BM25Store::new(second.bm25_index_dir());
+        assert!(bm25.load().await.unwrap());
+    }
+}
@@ -1,6 +1,8 @@
 pub(crate) mod api_client;
+pub(crate) mod backoff;
 pub mod document;
 pub mod embedding;
+pub mod export;
 pub mod initializer;
 pub mod repo;
 pub mod rerank;
@@ -9,6 +11,8 @@ pub mod search;
 #[cfg(test)]
 pub(crate) mod testutil;
 
+pub use api_client::MSGPACK_CONTENT_TYPE;
 pub use cangjie_core::types::{
-    DocData, DocMetadata, IndexMetadata, SearchMode, SearchResult, SearchResultMetadata, TextChunk,
+    ChunkingParams, DocData, DocMetadata, ExtraDocs, IndexMetadata, ResolvedVersion, SearchMode,
+    SearchResult, SearchResultMetadata, TextChunk, VersionTopicDiff,
 };
@@ -1,14 +1,145 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, LazyLock, Mutex as StdMutex};
+use std::time::Duration;
 
 use anyhow::{bail, Context, Result};
+use backon::BlockingRetryable;
+use gix::progress::tree;
 use gix::refs::transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog};
 use gix::refs::Target;
+use tokio::sync::{RwLock, Semaphore};
 use tracing::{info, warn};
 
+use crate::backoff::JitteredBackoff;
+use crate::ResolvedVersion;
+use cangjie_core::config::{DEFAULT_GIT_RETRIES, DEFAULT_MAX_CONCURRENT_GIT_OPS};
+
+const GIT_RETRY_MIN_BACKOFF_SECS: u64 = 1;
+const GIT_RETRY_MAX_BACKOFF_SECS: u64 = 10;
+/// Minimum interval between clone/fetch progress log lines.
+const GIT_PROGRESS_LOG_INTERVAL: Duration = Duration::from_secs(5);
+
 pub struct GitManager {
     repo_dir: PathBuf,
     url: String,
     repo: Option<gix::Repository>,
+    retries: usize,
+    tree_root: Option<String>,
+    max_concurrent_ops: usize,
+}
+
+/// Coordinates git work on one repository: clones and checkouts hold
+/// `checkout` exclusively, tree reads share it and each take a `reads` permit.
+struct RepoGate {
+    checkout: RwLock<()>,
+    reads: Semaphore,
+}
+
+/// Gates of the repositories used by this process, keyed by directory, so
+/// separate managers for the same checkout coordinate with each other.
+static REPO_GATES: LazyLock<StdMutex<HashMap<PathBuf, Arc<RepoGate>>>> =
+    LazyLock::new(Default::default);
+
+/// Gate for `repo_dir`; the first manager to use a repository sets its read limit.
+fn repo_gate(repo_dir: &Path, max_reads: usize) -> Arc<RepoGate> {
+    let mut gates = REPO_GATES.lock().expect("mutex poisoned");
+    Arc::clone(gates.entry(repo_dir.to_path_buf()).or_insert_with(|| {
+        Arc::new(RepoGate {
+            checkout: RwLock::new(()),
+            reads: Semaphore::new(max_reads.max(1)),
+        })
+    }))
+}
+
+/// Error fragments that mean retrying cannot help (bad credentials, missing repo or ref).
+const FATAL_GIT_ERROR_MARKERS: &[&str] = &[
+    "authentication",
+    "unauthorized",
+    "forbidden",
+    "permission denied",
+    "not found",
+    "does not exist",
+    "could not read username",
+    "401",
+    "403",
+    "404",
+];
+
+/// Error fragments that indicate a transient network problem.
+const TRANSIENT_GIT_ERROR_MARKERS: &[&str] = &[
+    "timed out",
+    "timeout",
+    "connection",
+    "connect",
+    "network",
+    "reset by peer",
+    "broken pipe",
+    "unexpected eof",
+    "early eof",
+    "temporarily",
+    "dns",
+    "resolve",
+    "502",
+    "503",
+    "504",
+];
+
+/// Decide whether a failed clone/fetch is worth retrying.
+///
+/// Auth and not-found failures are permanent; connection, timeout, and
+/// gateway errors are assumed transient. Anything unrecognised is not retried.
+pub(crate) fn is_retryable_git_error(err: &anyhow::Error) -> bool {
+    for cause in err.chain() {
+        if let Some(io_err) = cause.downcast_ref::<std::io::Error>() {
+            use std::io::ErrorKind;
+            match io_err.kind() {
+                ErrorKind::PermissionDenied | ErrorKind::NotFound => return false,
+                ErrorKind::TimedOut
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionRefused
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::NotConnected
+                | ErrorKind::BrokenPipe
+                | ErrorKind::UnexpectedEof
+                | ErrorKind::Interrupted => return true,
+                _ => {}
+            }
+        }
+    }
+
+    let message = format!("{err:#}").to_lowercase();
+    if FATAL_GIT_ERROR_MARKERS.iter().any(|m| message.contains(m)) {
+        return false;
+    }
+    TRANSIENT_GIT_ERROR_MARKERS
+        .iter()
+        .any(|m| message.contains(m))
+}
+
+fn git_retry_backoff(retries: usize) -> JitteredBackoff {
+    JitteredBackoff::new(
+        Duration::from_secs(GIT_RETRY_MIN_BACKOFF_SECS),
+        Duration::from_secs(GIT_RETRY_MAX_BACKOFF_SECS),
+    )
+    .with_max_times(retries)
+}
+
+/// Run a blocking git network operation, retrying transient failures with backoff.
+fn retry_git_op<T>(
+    label: &str,
+    backoff: JitteredBackoff,
+    op: impl FnMut() -> Result<T>,
+) -> Result<T> {
+    op.retry(backoff)
+        .sleep(std::thread::sleep)
+        .when(is_retryable_git_error)
+        .notify(|err: &anyhow::Error, wait: Duration| {
+            warn!("git {label} failed: {err:#}; retrying in {wait:?}");
+        })
+        .call()
 }
 
 /// Create a RefEdit that points a ref at an object (detached).
@@ -87,7 +218,73 @@ impl GitManager {
             repo_dir,
             url,
             repo: None,
+            retries: DEFAULT_GIT_RETRIES,
+            tree_root: None,
+            max_concurrent_ops: DEFAULT_MAX_CONCURRENT_GIT_OPS,
+        }
+    }
+
+    /// Set how many times a transient clone/fetch failure is retried.
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Set how many tree reads may run at once on this repository.
+    pub fn with_max_concurrent_ops(mut self, max_concurrent_ops: usize) -> Self {
+        self.max_concurrent_ops = max_concurrent_ops;
+        self
+    }
+
+    /// Run a read-only git operation on the blocking pool, waiting for any
+    /// checkout of the repository and for a free read slot.
+    async fn read_op<T: Send + 'static>(
+        &self,
+        label: &str,
+        op: impl FnOnce() -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let gate = repo_gate(&self.repo_dir, self.max_concurrent_ops);
+        let _shared = gate.checkout.read().await;
+        let _permit = gate.reads.acquire().await.context("git read gate closed")?;
+        tokio::task::spawn_blocking(op)
+            .await
+            .with_context(|| format!("{label} task panicked"))?
+    }
+
+    /// Run a git operation that moves the working tree or HEAD, with no other
+    /// operation on the repository in flight.
+    async fn write_op<T: Send + 'static>(
+        &self,
+        label: &str,
+        op: impl FnOnce() -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let gate = repo_gate(&self.repo_dir, self.max_concurrent_ops);
+        let _exclusive = gate.checkout.write().await;
+        tokio::task::spawn_blocking(op)
+            .await
+            .with_context(|| format!("{label} task panicked"))?
+    }
+
+    /// Confine tree reads to paths under `root` (e.g. the docs source prefix).
+    pub fn with_tree_root(mut self, root: &str) -> Result<Self> {
+        self.tree_root = Some(normalize_tree_path(root)?);
+        Ok(self)
+    }
+
+    /// Normalize `path` and check it stays under the configured tree root.
+    fn tree_path(&self, path: &str) -> Result<String> {
+        let normalized = normalize_tree_path(path)?;
+        if let Some(ref root) = self.tree_root {
+            let inside = root.is_empty()
+                || normalized == *root
+                || normalized
+                    .strip_prefix(root.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'));
+            if !inside {
+                bail!("Path '{path}' is outside the allowed tree root '{root}'");
+            }
         }
+        Ok(normalized)
     }
 
     pub fn is_cloned(&self) -> bool {
@@ -103,6 +300,7 @@ impl GitManager {
         repo: Option<gix::Repository>,
         fetch: bool,
         url: &str,
+        retries: usize,
     ) -> Result<gix::Repository> {
         if repo_dir.exists() && repo_dir.join(".git").exists() {
             let mut repo = match repo {
@@ -111,7 +309,7 @@ impl GitManager {
             };
             ensure_committer_for_ref_edits(&mut repo)?;
             if fetch {
-                fetch_all(&repo)?;
+                fetch_all(&repo, retries)?;
             }
             Ok(repo)
         } else {
@@ -119,13 +317,11 @@ impl GitManager {
             if let Some(parent) = repo_dir.parent() {
                 std::fs::create_dir_all(parent)?;
             }
-            let (mut checkout, _) = gix::prepare_clone(url, repo_dir)
-                .context("Failed to prepare clone")?
-                .fetch_then_checkout(gix::progress::Discard, &gix::interrupt::IS_INTERRUPTED)
-                .context("Failed to fetch during clone")?;
-            let (mut repo, _) = checkout
-                .main_worktree(gix::progress::Discard, &gix::interrupt::IS_INTERRUPTED)
-                .context("Failed to checkout worktree during clone")?;
+            // gix removes the partially cloned directory when a clone attempt
+            // fails, so each retry starts from an empty target.
+            let mut repo = retry_git_op("clone", git_retry_backoff(retries), || {
+                clone_repo(repo_dir, url)
+            })?;
             ensure_committer_for_ref_edits(&mut repo)?;
             info!("Repository cloned successfully.");
             Ok(repo)
@@ -169,53 +365,115 @@ impl GitManager {
         }
     }
 
+    fn describe_head(repo: &gix::Repository, requested: &str) -> Result<ResolvedVersion> {
+        let resolved_ref = Self::resolve_after_checkout(repo)?;
+        let commit_oid = repo
+            .head_commit()
+            .context("Failed to read HEAD commit")?
+            .id()
+            .to_string();
+        Ok(ResolvedVersion {
+            requested: requested.to_string(),
+            resolved_ref,
+            short_hash: commit_oid[..7].to_string(),
+            commit_oid,
+        })
+    }
+
+    /// Report which commit the local checkout is on, without fetching.
+    ///
+    /// Returns `None` when the repository has not been cloned yet.
+    pub async fn resolved_version(&self, requested: &str) -> Result<Option<ResolvedVersion>> {
+        if !self.is_cloned() {
+            return Ok(None);
+        }
+        let repo_dir = self.repo_dir.clone();
+        let requested = requested.to_string();
+        self.read_op("resolved_version", move || {
+            let repo = gix::open(&repo_dir).context("Failed to open repository")?;
+            Self::describe_head(&repo, &requested).map(Some)
+        })
+        .await
+    }
+
+    /// The version key [`resolve_version`](Self::resolve_version) would
+    /// report for the current checkout, read without fetching or checking
+    /// anything out. `None` when the repository is not cloned.
+    pub async fn checked_out_version(&self) -> Result<Option<String>> {
+        if !self.is_cloned() {
+            return Ok(None);
+        }
+        let repo_dir = self.repo_dir.clone();
+        self.read_op("checked_out_version", move || {
+            let repo = gix::open(&repo_dir).context("Failed to open repository")?;
+            Self::resolve_after_checkout(&repo).map(Some)
+        })
+        .await
+    }
+
     pub async fn ensure_cloned(&mut self, fetch: bool) -> Result<()> {
         let repo_dir = self.repo_dir.clone();
         let repo = self.repo.take();
         let url = self.url.clone();
+        let retries = self.retries;
 
-        let repo =
-            tokio::task::spawn_blocking(move || Self::open_or_clone(&repo_dir, repo, fetch, &url))
-                .await
-                .context("ensure_cloned task panicked")??;
+        let repo = self
+            .write_op("ensure_cloned", move || {
+                Self::open_or_clone(&repo_dir, repo, fetch, &url, retries)
+            })
+            .await?;
 
         self.repo = Some(repo);
         Ok(())
     }
 
     pub async fn checkout(&mut self, version: &str) -> Result<()> {
+        self.checkout_with_fetch(version, true).await
+    }
+
+    /// Check out `version` (e.g. a commit already in the local object store)
+    /// without fetching from the remote first.
+    pub async fn checkout_local(&mut self, version: &str) -> Result<()> {
+        self.checkout_with_fetch(version, false).await
+    }
+
+    async fn checkout_with_fetch(&mut self, version: &str, fetch: bool) -> Result<()> {
         let repo_dir = self.repo_dir.clone();
         let repo = self.repo.take();
         let version = version.to_string();
         let url = self.url.clone();
+        let retries = self.retries;
 
-        let repo = tokio::task::spawn_blocking(move || -> Result<gix::Repository> {
-            let mut repo = Self::open_or_clone(&repo_dir, repo, true, &url)?;
-            checkout(&mut repo, &version)?;
-            Ok(repo)
-        })
-        .await
-        .context("checkout task panicked")??;
+        let repo = self
+            .write_op("checkout", move || {
+                let mut repo = Self::open_or_clone(&repo_dir, repo, fetch, &url, retries)?;
+                checkout(&mut repo, &version)?;
+                Ok(repo)
+            })
+            .await?;
 
         self.repo = Some(repo);
         Ok(())
     }
 
+    /// Check out `version` and return a key for the checked-out content: the
+    /// tag at HEAD, or `<branch>(<short hash>)` for moving refs such as
+    /// `latest` and `dev`. Index paths are keyed by this, never by the request.
     pub async fn resolve_version(&mut self, version: &str) -> Result<String> {
         let repo_dir = self.repo_dir.clone();
         let repo = self.repo.take();
         let version = version.to_string();
         let url = self.url.clone();
+        let retries = self.retries;
 
-        let (repo, resolved) =
-            tokio::task::spawn_blocking(move || -> Result<(gix::Repository, String)> {
-                let mut repo = Self::open_or_clone(&repo_dir, repo, true, &url)?;
+        let (repo, resolved) = self
+            .write_op("resolve_version", move || {
+                let mut repo = Self::open_or_clone(&repo_dir, repo, true, &url, retries)?;
                 checkout(&mut repo, &version)?;
                 let resolved = Self::resolve_after_checkout(&repo)?;
                 Ok((repo, resolved))
             })
-            .await
-            .context("resolve_version task panicked")??;
+            .await?;
 
         self.repo = Some(repo);
         Ok(resolved)
@@ -223,32 +481,138 @@ impl GitManager {
 
     pub async fn read_file_from_tree(&self, path: &str) -> Result<String> {
         let repo_dir = self.repo_dir.clone();
-        let path = path.to_string();
-        tokio::task::spawn_blocking(move || read_file(&repo_dir, &path))
+        let path = self.tree_path(path)?;
+        self.read_op("read_file_from_tree", move || read_file(&repo_dir, &path))
             .await
-            .context("read_file_from_tree task panicked")?
     }
 
     pub async fn list_tree_dirs(&self, path: &str) -> Result<Vec<String>> {
         let repo_dir = self.repo_dir.clone();
-        let path = path.to_string();
-        tokio::task::spawn_blocking(move || list_tree_dirs(&repo_dir, &path))
+        let path = self.tree_path(path)?;
+        self.read_op("list_tree_dirs", move || list_tree_dirs(&repo_dir, &path))
             .await
-            .context("list_tree_dirs task panicked")?
     }
 
     pub async fn list_md_files(&self, base_path: &str) -> Result<Vec<String>> {
         let repo_dir = self.repo_dir.clone();
-        let base_path = base_path.to_string();
-        tokio::task::spawn_blocking(move || list_md_files(&repo_dir, &base_path))
-            .await
-            .context("list_md_files task panicked")?
+        let base_path = self.tree_path(base_path)?;
+        self.read_op("list_md_files", move || {
+            list_md_files(&repo_dir, &base_path)
+        })
+        .await
+    }
+
+    /// Markdown files under `base_path` at `version`, read from that
+    /// commit's tree without checking it out. `None` when the version has no
+    /// `base_path`.
+    pub async fn list_md_files_at(
+        &self,
+        version: &str,
+        base_path: &str,
+    ) -> Result<Option<Vec<String>>> {
+        if !self.is_cloned() {
+            bail!("Repository is not cloned at {}", self.repo_dir.display());
+        }
+        let repo_dir = self.repo_dir.clone();
+        let version = version.to_string();
+        let base_path = self.tree_path(base_path)?;
+        self.read_op("list_md_files_at", move || {
+            list_md_files_at(&repo_dir, &version, &base_path)
+        })
+        .await
     }
 }
 
-fn fetch_all(repo: &gix::Repository) -> Result<()> {
+/// Normalize a repository-relative tree path: drop `.` and empty segments and
+/// resolve `..`, rejecting absolute paths and any `..` that climbs above the
+/// repository root.
+pub(crate) fn normalize_tree_path(path: &str) -> Result<String> {
+    let has_drive = path.as_bytes().get(1) == Some(&b':');
+    if path.starts_with(['/', '\\']) || has_drive {
+        bail!("Absolute paths are not allowed in the repository tree: '{path}'");
+    }
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                if segments.pop().is_none() {
+                    bail!("Path '{path}' escapes the repository root");
+                }
+            }
+            name => segments.push(name),
+        }
+    }
+    Ok(segments.join("/"))
+}
+
+/// Format one progress line, e.g. `receiving pack: 512/2048 (25%)`.
+fn format_progress(name: &str, step: usize, done_at: Option<usize>) -> String {
+    match done_at {
+        Some(max) if max > 0 => {
+            let pct = (step.min(max) as f64 / max as f64 * 100.0).round() as u32;
+            format!("{name}: {step}/{max} ({pct}%)")
+        }
+        _ => format!("{name}: {step}"),
+    }
+}
+
+/// Run `op` with a gix progress tree whose active tasks are logged at info
+/// level every [`GIT_PROGRESS_LOG_INTERVAL`] while it runs.
+///
+/// Logs go through `tracing`, which writes to stderr (or the daemon log), so
+/// the stdio MCP transport is unaffected.
+fn with_progress_logging<T>(label: &str, op: impl FnOnce(tree::Item) -> T) -> T {
+    let root = tree::Root::new();
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let mut snapshot = Vec::new();
+            let mut last = String::new();
+            while let Err(RecvTimeoutError::Timeout) =
+                stop_rx.recv_timeout(GIT_PROGRESS_LOG_INTERVAL)
+            {
+                root.sorted_snapshot(&mut snapshot);
+                let line = snapshot
+                    .iter()
+                    .filter_map(|(_, task)| {
+                        let value = task.progress.as_ref()?;
+                        let step = value.step.load(Ordering::Relaxed);
+                        (step > 0).then(|| format_progress(&task.name, step, value.done_at))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if !line.is_empty() && line != last {
+                    info!("git {label} progress: {line}");
+                    last = line;
+                }
+            }
+        });
+        let result = op(root.add_child(label));
+        drop(stop_tx);
+        result
+    })
+}
+
+fn clone_repo(repo_dir: &Path, url: &str) -> Result<gix::Repository> {
+    with_progress_logging("clone", |mut progress| {
+        let (mut checkout, _) = gix::prepare_clone(url, repo_dir)
+            .context("Failed to prepare clone")?
+            .fetch_then_checkout(progress.add_child("fetch"), &gix::interrupt::IS_INTERRUPTED)
+            .context("Failed to fetch during clone")?;
+        let (repo, _) = checkout
+            .main_worktree(
+                progress.add_child("checkout"),
+                &gix::interrupt::IS_INTERRUPTED,
+            )
+            .context("Failed to checkout worktree during clone")?;
+        Ok(repo)
+    })
+}
+
+fn fetch_all(repo: &gix::Repository, retries: usize) -> Result<()> {
     info!("Fetching latest tags and commits...");
-    match do_fetch(repo) {
+    match retry_git_op("fetch", git_retry_backoff(retries), || do_fetch(repo)) {
         Ok(()) => info!("Fetch complete."),
         Err(e) => warn!("Failed to fetch from remote: {e}"),
     }
@@ -259,9 +623,11 @@ fn do_fetch(repo: &gix::Repository) -> Result<()> {
     let remote = repo.find_remote("origin")?;
     let tagged = remote.with_fetch_tags(gix::remote::fetch::Tags::All);
     let conn = tagged.connect(gix::remote::Direction::Fetch)?;
-    let prep = conn.prepare_fetch(gix::progress::Discard, Default::default())?;
-    prep.receive(gix::progress::Discard, &gix::interrupt::IS_INTERRUPTED)?;
-    Ok(())
+    with_progress_logging("fetch", |mut progress| {
+        let prep = conn.prepare_fetch(progress.add_child("negotiate"), Default::default())?;
+        prep.receive(progress, &gix::interrupt::IS_INTERRUPTED)?;
+        Ok(())
+    })
 }
 
 fn sync_branch(repo: &mut gix::Repository) -> Result<()> {
@@ -416,14 +782,75 @@ fn list_tree_dirs(repo_dir: &Path, path: &str) -> Result<Vec<String>> {
 fn list_md_files(repo_dir: &Path, base_path: &str) -> Result<Vec<String>> {
     let repo = gix::open(repo_dir).context("Failed to open repository")?;
     let tree = repo.head_commit()?.tree()?;
-    let entry = tree
-        .lookup_entry_by_path(base_path)?
-        .with_context(|| format!("Path not found in tree: {base_path}"))?;
+    md_files_in_tree(&repo, &tree, base_path)?
+        .with_context(|| format!("Path not found in tree: {base_path}"))
+}
+
+fn list_md_files_at(
+    repo_dir: &Path,
+    version: &str,
+    base_path: &str,
+) -> Result<Option<Vec<String>>> {
+    let repo = gix::open(repo_dir).context("Failed to open repository")?;
+    let oid = version_commit(&repo, version)?;
+    let tree = repo
+        .find_commit(oid)
+        .with_context(|| format!("Version '{version}' does not point at a commit"))?
+        .tree()?;
+    md_files_in_tree(&repo, &tree, base_path)
+}
+
+/// Sorted markdown files under `base_path` in `tree`, or `None` when the
+/// path does not exist there.
+fn md_files_in_tree(
+    repo: &gix::Repository,
+    tree: &gix::Tree,
+    base_path: &str,
+) -> Result<Option<Vec<String>>> {
+    let Some(entry) = tree.lookup_entry_by_path(base_path)? else {
+        return Ok(None);
+    };
     let subtree = repo.find_object(entry.oid())?.into_tree();
     let mut files = Vec::new();
-    collect_md_files_recursive(&repo, &subtree, "", &mut files)?;
+    collect_md_files_recursive(repo, &subtree, "", &mut files)?;
     files.sort();
-    Ok(files)
+    Ok(Some(files))
+}
+
+/// The commit `version` names, resolved the way [`checkout`] does (tag,
+/// remote branch, local branch, or commit hash) but without moving HEAD.
+fn version_commit(repo: &gix::Repository, version: &str) -> Result<gix::ObjectId> {
+    let refs: Vec<String> = if version == "latest" {
+        ["main", "master"]
+            .iter()
+            .flat_map(|b| {
+                [
+                    format!("refs/remotes/origin/{b}"),
+                    format!("refs/heads/{b}"),
+                ]
+            })
+            .collect()
+    } else {
+        vec![
+            format!("refs/tags/{version}"),
+            format!("refs/remotes/origin/{version}"),
+            format!("refs/heads/{version}"),
+        ]
+    };
+    for name in &refs {
+        if let Ok(mut reference) = repo.find_reference(name.as_str()) {
+            return Ok(reference
+                .peel_to_id()
+                .with_context(|| format!("Failed to peel {name}"))?
+                .detach());
+        }
+    }
+    if let Ok(oid) = gix::ObjectId::from_hex(version.as_bytes()) {
+        if repo.find_commit(oid).is_ok() {
+            return Ok(oid);
+        }
+    }
+    bail!("Version '{version}' not found as tag, branch, or commit")
 }
 
 fn collect_md_files_recursive(
@@ -476,6 +903,60 @@ mod tests {
         assert!(mgr2.is_cloned());
     }
 
+    fn fast_backoff(retries: usize) -> JitteredBackoff {
+        JitteredBackoff::new(Duration::from_millis(1), Duration::from_millis(5))
+            .with_max_times(retries)
+    }
+
+    #[test]
+    fn test_is_retryable_git_error_classification() {
+        let transient = [
+            anyhow::anyhow!("Connection reset by peer"),
+            anyhow::anyhow!("operation timed out"),
+            anyhow::anyhow!("HTTP 503 Service Unavailable"),
+            anyhow::Error::new(std::io::Error::from(std::io::ErrorKind::ConnectionRefused))
+                .context("Failed to fetch during clone"),
+        ];
+        for err in &transient {
+            assert!(is_retryable_git_error(err), "{err:#}");
+        }
+
+        let fatal = [
+            anyhow::anyhow!("Authentication failed for 'https://example.com/repo.git'"),
+            anyhow::anyhow!("HTTP 404: repository not found"),
+            anyhow::anyhow!("Could not read username"),
+            anyhow::anyhow!("Failed to checkout version 'x'"),
+        ];
+        for err in &fatal {
+            assert!(!is_retryable_git_error(err), "{err:#}");
+        }
+    }
+
+    #[test]
+    fn test_retry_git_op_recovers_after_transient_failures() {
+        let mut calls = 0;
+        let result = retry_git_op("fetch", fast_backoff(3), || {
+            calls += 1;
+            if calls < 3 {
+                bail!("connection reset by peer");
+            }
+            Ok(calls)
+        });
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_retry_git_op_does_not_retry_fatal_errors() {
+        let mut calls = 0;
+        let result: Result<()> = retry_git_op("clone", fast_backoff(3), || {
+            calls += 1;
+            bail!("authentication failed")
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
     #[test]
     fn test_repo_returns_none_initially() {
         let tmp = TempDir::new().unwrap();
@@ -507,6 +988,54 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_normalize_tree_path() {
+        assert_eq!(
+            normalize_tree_path("docs/./dev-guide//source_zh_cn/syntax/../stdlib").unwrap(),
+            "docs/dev-guide/source_zh_cn/stdlib"
+        );
+        for bad in [
+            "../../secret",
+            "docs/../../secret",
+            "/etc/passwd",
+            "\\server\\x",
+            "C:\\x",
+        ] {
+            assert!(
+                normalize_tree_path(bad).is_err(),
+                "{bad} should be rejected"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tree_reads_reject_traversal() {
+        let (tmp, _repo) = create_test_repo();
+        let mgr = test_mgr(tmp.path().to_path_buf())
+            .with_tree_root("docs/dev-guide/source_zh_cn")
+            .unwrap();
+
+        let err = mgr.read_file_from_tree("../../secret").await.unwrap_err();
+        assert!(err.to_string().contains("escapes"), "{err:#}");
+        assert!(mgr.read_file_from_tree("/etc/passwd").await.is_err());
+        assert!(mgr
+            .list_md_files("docs/dev-guide/source_zh_cn/../../tools")
+            .await
+            .is_err());
+        assert!(mgr.list_tree_dirs("docs").await.is_err());
+
+        let content = mgr
+            .read_file_from_tree("docs/dev-guide/source_zh_cn/stdlib/../syntax/functions.md")
+            .await
+            .unwrap();
+        assert!(content.contains("# Functions"));
+        let files = mgr
+            .list_md_files("docs/dev-guide/source_zh_cn/syntax")
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 2);
+    }
+
     #[tokio::test]
     async fn test_list_tree_dirs() {
         let (tmp, _repo) = create_test_repo();
@@ -908,6 +1437,39 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_concurrent_reads_during_checkout_stay_consistent() {
+        const FILE: &str = "docs/dev-guide/source_zh_cn/syntax/functions.md";
+        let (tmp, _repo) = create_test_repo_with_remote("main");
+        let reader = Arc::new(test_mgr(tmp.path().to_path_buf()).with_max_concurrent_ops(2));
+        let mut writer = test_mgr(tmp.path().to_path_buf());
+
+        let reads: Vec<_> = (0..8)
+            .map(|i| {
+                let reader = Arc::clone(&reader);
+                tokio::spawn(async move {
+                    if i % 2 == 0 {
+                        reader.read_file_from_tree(FILE).await.map(|c| vec![c])
+                    } else {
+                        reader.list_md_files("docs/dev-guide/source_zh_cn").await
+                    }
+                })
+            })
+            .collect();
+        writer.checkout_local("latest").await.unwrap();
+
+        for (i, read) in reads.into_iter().enumerate() {
+            let read = read.await.unwrap().unwrap();
+            if i % 2 == 0 {
+                assert!(read[0].contains("# Functions"));
+            } else {
+                assert!(read.iter().any(|p| p.ends_with("functions.md")), "{read:?}");
+            }
+        }
+        let after = reader.read_file_from_tree(FILE).await.unwrap();
+        assert!(after.contains("Content about functions."));
+    }
+
     #[tokio::test]
     async fn test_resolve_version_async() {
         let (tmp, _repo) = create_test_repo_with_remote("main");
@@ -929,6 +1491,27 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_resolve_version_latest_is_stable_path_key() {
+        let (tmp, repo) = create_test_repo_with_remote("main");
+        let short_hash = repo.head_commit().unwrap().id().to_string()[..7].to_string();
+        let mut mgr = test_mgr(tmp.path().to_path_buf());
+
+        let first = mgr.resolve_version("latest").await.unwrap();
+        let second = mgr.resolve_version("latest").await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first, format!("main({short_hash})"));
+
+        let settings = cangjie_core::config::Settings {
+            data_dir: PathBuf::from("/data"),
+            ..Default::default()
+        };
+        let index_dir =
+            cangjie_core::config::IndexInfo::from_settings(&settings, &first).index_dir();
+        assert!(index_dir.starts_with(format!("/data/indexes/main({short_hash})")));
+        assert!(!index_dir.components().any(|c| c.as_os_str() == "latest"));
+    }
+
     #[tokio::test]
     async fn test_resolve_version_tag() {
         let (tmp, _repo) = create_test_repo();
@@ -951,4 +1534,72 @@ mod tests {
             "resolved version should be the tag name"
         );
     }
+
+    #[tokio::test]
+    async fn test_resolved_version_reports_commit() {
+        let (tmp, _repo) = create_test_repo();
+        Command::new("git")
+            .args(["tag", "v4.0.0"])
+            .current_dir(tmp.path())
+            .status()
+            .unwrap();
+
+        let mut mgr = test_mgr(tmp.path().to_path_buf());
+        mgr.resolve_version("v4.0.0").await.unwrap();
+
+        let resolved = mgr.resolved_version("v4.0.0").await.unwrap().unwrap();
+        assert_eq!(resolved.requested, "v4.0.0");
+        assert_eq!(resolved.resolved_ref, "v4.0.0");
+        assert_eq!(resolved.commit_oid.len(), 40);
+        assert!(resolved.commit_oid.starts_with(&resolved.short_hash));
+    }
+
+    #[tokio::test]
+    async fn test_list_md_files_at_reads_versions_without_checkout() {
+        let (tmp, _repo) = create_test_repo();
+        let git = |args: &[&str]| crate::testutil::git(tmp.path(), args);
+        git(&["tag", "v1.0.0"]);
+        let base = tmp.path().join("docs/dev-guide/source_zh_cn");
+        std::fs::write(base.join("syntax/lambda.md"), "# Lambda").unwrap();
+        std::fs::remove_file(base.join("syntax/variables.md")).unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-m", "v2 docs"]);
+        git(&["tag", "v2.0.0"]);
+        git(&["checkout", "--detach", "v1.0.0"]);
+
+        let mgr = test_mgr(tmp.path().to_path_buf());
+        let src = "docs/dev-guide/source_zh_cn/syntax";
+        let v1 = mgr.list_md_files_at("v1.0.0", src).await.unwrap().unwrap();
+        let v2 = mgr.list_md_files_at("v2.0.0", src).await.unwrap().unwrap();
+        assert_eq!(v1, vec!["functions.md", "variables.md"]);
+        assert_eq!(v2, vec!["functions.md", "lambda.md"]);
+
+        let missing = mgr
+            .list_md_files_at("v1.0.0", "docs/dev-guide/source_en")
+            .await
+            .unwrap();
+        assert!(missing.is_none());
+        assert!(mgr.list_md_files_at("v9.9.9", src).await.is_err());
+
+        // HEAD stays where it was.
+        let repo = gix::open(tmp.path()).unwrap();
+        assert_eq!(GitManager::resolve_after_checkout(&repo).unwrap(), "v1.0.0");
+    }
+
+    #[tokio::test]
+    async fn test_resolved_version_not_cloned() {
+        let tmp = TempDir::new().unwrap();
+        let mgr = test_mgr(tmp.path().join("missing"));
+        assert!(mgr.resolved_version("dev").await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_format_progress_with_and_without_total() {
+        assert_eq!(
+            format_progress("receiving pack", 512, Some(2048)),
+            "receiving pack: 512/2048 (25%)"
+        );
+        assert_eq!(format_progress("read pack", 4096, None), "read pack: 4096");
+        assert_eq!(format_progress("checkout", 3, Some(0)), "checkout: 3");
+    }
 }
@@ -2,8 +2,9 @@ pub mod noop;
 pub mod openai;
 
 use anyhow::Result;
+use tracing::warn;
 
-use crate::SearchResult;
+use crate::{SearchResult, SearchResultMetadata};
 use cangjie_core::config::{RerankType, Settings};
 
 /// Enum dispatch for reranking -- the variant set is known and fixed,
@@ -22,17 +23,75 @@ impl RerankerKind {
         results: Vec<SearchResult>,
         top_k: usize,
     ) -> Result<Vec<SearchResult>> {
-        match self {
-            RerankerKind::NoOp => noop::NoOpReranker.rerank(results, top_k),
-            RerankerKind::OpenAI(r) => r.rerank(query, results, top_k).await,
+        let reranked = match self {
+            RerankerKind::NoOp => return noop::NoOpReranker.rerank(results, top_k),
+            RerankerKind::OpenAI(r) => r.rerank(query, results.clone(), top_k).await?,
             #[cfg(feature = "local")]
-            RerankerKind::Local(r) => r.rerank(query, results, top_k).await,
-        }
+            RerankerKind::Local(r) => r.rerank(query, results.clone(), top_k).await?,
+        };
+        Ok(backfill(reranked, results, top_k))
     }
 
     pub fn is_enabled(&self) -> bool {
         !matches!(self, RerankerKind::NoOp)
     }
+
+    /// Rerank a trivial two-candidate list so the local model's first
+    /// inference, or the API connection, is paid before the first real query.
+    pub async fn warmup(&self) -> Result<()> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        let candidates = ["仓颉函数定义", "仓颉变量声明"]
+            .into_iter()
+            .map(|text| SearchResult {
+                text: text.to_string(),
+                score: 0.0,
+                metadata: SearchResultMetadata::default(),
+            })
+            .collect();
+        self.rerank("仓颉", candidates, 2).await.map(|_| ())
+    }
+}
+
+/// Top up `reranked` to `top_k` (or all candidates) with the candidates the
+/// reranker dropped, in their pre-rerank order. Backfilled results take the
+/// lowest reranked score so they never outrank a reranked result.
+fn backfill(
+    mut reranked: Vec<SearchResult>,
+    candidates: Vec<SearchResult>,
+    top_k: usize,
+) -> Vec<SearchResult> {
+    let wanted = top_k.min(candidates.len());
+    if reranked.len() >= wanted {
+        return reranked;
+    }
+    warn!(
+        "Reranker returned {} of {} requested results; backfilling from the original ranking",
+        reranked.len(),
+        wanted
+    );
+    let floor = reranked
+        .iter()
+        .map(|r| r.score)
+        .reduce(f64::min)
+        .unwrap_or(0.0);
+    let mut dropped: Vec<SearchResult> = candidates
+        .into_iter()
+        .filter(|c| {
+            !reranked.iter().any(|r| {
+                r.text == c.text
+                    && r.metadata.file_path == c.metadata.file_path
+                    && r.metadata.chunk_id == c.metadata.chunk_id
+            })
+        })
+        .take(wanted - reranked.len())
+        .collect();
+    for result in &mut dropped {
+        result.score = floor;
+    }
+    reranked.extend(dropped);
+    reranked
 }
 
 pub async fn create_reranker(settings: &Settings) -> Result<RerankerKind> {
@@ -58,7 +117,7 @@ pub async fn create_reranker(settings: &Settings) -> Result<RerankerKind> {
             }
             #[cfg(not(feature = "local"))]
             {
-                tracing::warn!("Local reranking requires 'local' feature, using NoOp");
+                warn!("Local reranking requires 'local' feature, using NoOp");
                 Ok(RerankerKind::NoOp)
             }
         }
@@ -98,6 +157,10 @@ mod tests {
                 title: "Test".to_string(),
                 has_code: false,
                 chunk_id: String::new(),
+                custom: Default::default(),
+                chunk_index: 0,
+                char_offset: 0,
+                breadcrumb: String::new(),
             },
         }
     }
@@ -116,6 +179,11 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_noop_reranker_warmup_is_noop() {
+        assert!(RerankerKind::NoOp.warmup().await.is_ok());
+    }
+
     #[tokio::test]
     async fn test_noop_reranker_passthrough() {
         let reranker = RerankerKind::NoOp;
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
 use anyhow::Result;
 use tracing::info;
 
@@ -6,8 +9,49 @@ use crate::SearchResult;
 use cangjie_core::api_types::RerankResponse;
 use cangjie_core::config::Settings;
 
+/// Conservative characters-per-token ratio used to estimate request size.
+const CHARS_PER_TOKEN: usize = 2;
+/// Candidates shared between consecutive rerank windows.
+const WINDOW_OVERLAP: usize = 2;
+
 pub struct OpenAIReranker {
     api: ApiClient,
+    max_doc_chars: usize,
+    window_tokens: usize,
+}
+
+/// Truncate `text` to at most `max_chars` characters.
+fn truncate_chars(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((idx, _)) => &text[..idx],
+        None => text,
+    }
+}
+
+fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Split candidates into windows whose estimated token total stays within
+/// `budget` (every window holds at least one candidate). Consecutive windows
+/// share `overlap` candidates.
+fn plan_windows(token_counts: &[usize], budget: usize, overlap: usize) -> Vec<Range<usize>> {
+    let mut windows = Vec::new();
+    let mut start = 0;
+    while start < token_counts.len() {
+        let mut end = start;
+        let mut used = 0;
+        while end < token_counts.len() && (end == start || used + token_counts[end] <= budget) {
+            used += token_counts[end];
+            end += 1;
+        }
+        windows.push(start..end);
+        if end == token_counts.len() {
+            break;
+        }
+        start = end.saturating_sub(overlap).max(start + 1);
+    }
+    windows
 }
 
 impl OpenAIReranker {
@@ -20,9 +64,39 @@ impl OpenAIReranker {
                 base_url,
                 std::time::Duration::from_secs(30),
             )?,
+            max_doc_chars: settings.rerank_max_doc_chars.max(1),
+            window_tokens: settings.rerank_window_tokens.max(1),
         })
     }
 
+    /// Score `documents` in one API call; returns `(index, score)` pairs.
+    async fn score_window(
+        &self,
+        query: &str,
+        documents: &[&str],
+        top_n: usize,
+    ) -> Result<Vec<(usize, f64)>> {
+        let body: RerankResponse = self
+            .api
+            .post_json(
+                "rerank",
+                &serde_json::json!({
+                    "model": self.api.model(),
+                    "query": query,
+                    "documents": documents,
+                    "top_n": top_n,
+                    "return_documents": false,
+                }),
+            )
+            .await?;
+        Ok(body
+            .results
+            .into_iter()
+            .filter(|item| item.index < documents.len())
+            .map(|item| (item.index, item.relevance_score))
+            .collect())
+    }
+
     pub async fn rerank(
         &self,
         query: &str,
@@ -41,33 +115,202 @@ impl OpenAIReranker {
 
         let documents: Vec<&str> = results
             .iter()
-            .map(|r| crate::document::chunker::strip_chunk_artifacts(&r.text))
+            .map(|r| {
+                truncate_chars(
+                    crate::document::chunker::strip_chunk_artifacts(&r.text),
+                    self.max_doc_chars,
+                )
+            })
             .collect();
+        let token_counts: Vec<usize> = documents.iter().map(|d| estimate_tokens(d)).collect();
+        let windows = plan_windows(&token_counts, self.window_tokens, WINDOW_OVERLAP);
 
-        let body: RerankResponse = self
-            .api
-            .post_json(
-                "rerank",
-                &serde_json::json!({
-                    "model": self.api.model(),
-                    "query": query,
-                    "documents": documents,
-                    "top_n": top_k,
-                    "return_documents": false,
-                }),
-            )
-            .await?;
-
-        let mut reranked = Vec::new();
-        for item in body.results {
-            if item.index < results.len() {
-                let mut result = results[item.index].clone();
-                result.score = item.relevance_score;
-                reranked.push(result);
+        let scored = if windows.len() == 1 {
+            self.score_window(query, &documents, top_k).await?
+        } else {
+            info!("Reranking in {} windows", windows.len());
+            // Candidates in overlapping windows keep their best score.
+            let mut best: HashMap<usize, f64> = HashMap::new();
+            for window in windows {
+                let offset = window.start;
+                let window_docs = &documents[window];
+                for (index, score) in self
+                    .score_window(query, window_docs, window_docs.len())
+                    .await?
+                {
+                    let entry = best.entry(offset + index).or_insert(score);
+                    *entry = entry.max(score);
+                }
             }
+            let mut merged: Vec<(usize, f64)> = best.into_iter().collect();
+            merged.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+            merged.truncate(top_k);
+            merged
+        };
+
+        let mut reranked = Vec::with_capacity(scored.len());
+        for (index, score) in scored {
+            let mut result = results[index].clone();
+            result.score = score;
+            reranked.push(result);
         }
 
         info!("Reranking complete.");
         Ok(reranked)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SearchResultMetadata;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_plan_windows_respects_budget_and_overlap() {
+        assert_eq!(plan_windows(&[10, 10, 10], 100, 2), vec![0..3]);
+        assert_eq!(
+            plan_windows(&[10, 10, 10, 10], 20, 1),
+            vec![0..2, 1..3, 2..4]
+        );
+        // A single oversized candidate still gets its own window.
+        assert_eq!(plan_windows(&[50, 5], 20, 0), vec![0..1, 1..2]);
+    }
+
+    #[test]
+    fn test_truncate_chars_respects_char_boundaries() {
+        assert_eq!(truncate_chars("仓颉语言", 2), "仓颉");
+        assert_eq!(truncate_chars("abc", 10), "abc");
+    }
+
+    /// Mock rerank API scoring each document by the number after its `doc`
+    /// prefix and answering with at most `max_results` of them.
+    async fn spawn_rerank_server(
+        received: Arc<Mutex<Vec<Vec<String>>>>,
+        max_results: usize,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut raw = Vec::new();
+                let mut buf = [0_u8; 8192];
+                let body = loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    raw.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&raw).to_string();
+                    if let Some(split) = text.find("\r\n\r\n") {
+                        let length = text[..split]
+                            .lines()
+                            .find_map(|l| {
+                                let (name, value) = l.split_once(':')?;
+                                name.eq_ignore_ascii_case("content-length")
+                                    .then(|| value.trim().parse::<usize>().unwrap())
+                            })
+                            .unwrap_or(0);
+                        if raw.len() >= split + 4 + length {
+                            break raw[split + 4..split + 4 + length].to_vec();
+                        }
+                    }
+                };
+                let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                let documents: Vec<String> = request["documents"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|d| d.as_str().unwrap().to_string())
+                    .collect();
+                let results: Vec<serde_json::Value> = documents
+                    .iter()
+                    .enumerate()
+                    .map(|(index, doc)| {
+                        let n: f64 = doc[3..4].parse().unwrap();
+                        serde_json::json!({"index": index, "relevance_score": n / 10.0})
+                    })
+                    .take(max_results)
+                    .collect();
+                received.lock().unwrap().push(documents);
+                let payload = serde_json::json!({ "results": results }).to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{payload}",
+                    payload.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_rerank_truncates_and_windows_oversized_candidates() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let base_url = spawn_rerank_server(received.clone(), usize::MAX).await;
+        let settings = Settings {
+            rerank_max_doc_chars: 50,
+            rerank_window_tokens: 60,
+            ..Settings::default()
+        };
+        let reranker = OpenAIReranker::new(&settings, "key", "model", &base_url).unwrap();
+
+        let candidates: Vec<SearchResult> = (0..6)
+            .map(|n| SearchResult {
+                text: format!("doc{n} {}", "x".repeat(500)),
+                score: 0.0,
+                metadata: SearchResultMetadata {
+                    chunk_id: format!("c{n}"),
+                    ..Default::default()
+                },
+            })
+            .collect();
+
+        let reranked = reranker.rerank("query", candidates, 3).await.unwrap();
+        let ids: Vec<&str> = reranked
+            .iter()
+            .map(|r| r.metadata.chunk_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["c5", "c4", "c3"]);
+        assert!(reranked.windows(2).all(|w| w[0].score >= w[1].score));
+
+        let requests = received.lock().unwrap();
+        assert!(
+            requests.len() > 1,
+            "oversized candidates should be windowed"
+        );
+        for documents in requests.iter() {
+            assert!(documents.len() <= 2);
+            assert!(documents.iter().all(|d| d.chars().count() <= 50));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_truncated_rerank_response_is_backfilled() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let base_url = spawn_rerank_server(received, 1).await;
+        let reranker = crate::rerank::RerankerKind::OpenAI(
+            OpenAIReranker::new(&Settings::default(), "key", "model", &base_url).unwrap(),
+        );
+
+        let candidates: Vec<SearchResult> = (0..4)
+            .map(|n| SearchResult {
+                text: format!("doc{n}"),
+                score: 0.0,
+                metadata: SearchResultMetadata {
+                    chunk_id: format!("c{n}"),
+                    ..Default::default()
+                },
+            })
+            .collect();
+
+        let reranked = reranker.rerank("query", candidates, 3).await.unwrap();
+        let ids: Vec<&str> = reranked
+            .iter()
+            .map(|r| r.metadata.chunk_id.as_str())
+            .collect();
+        // The mock keeps only the first document; the rest follow in their
+        // original order.
+        assert_eq!(ids, vec!["c0", "c1", "c2"]);
+        assert!(reranked.windows(2).all(|w| w[0].score >= w[1].score));
+    }
+}
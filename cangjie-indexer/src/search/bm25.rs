@@ -1,56 +1,44 @@
 use std::path::PathBuf;
-use std::sync::Arc;
 
 use anyhow::{Context, Result};
-use jieba_rs::Jieba;
-use tantivy::collector::TopDocs;
+use tantivy::collector::{DocSetCollector, TopDocs};
 use tantivy::query::{BooleanQuery, Occur, QueryParser, TermQuery};
 use tantivy::schema::*;
 use tantivy::tokenizer::*;
 use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term};
 use tracing::{info, warn};
 
-use super::{synonyms, GLOBAL_JIEBA};
+use super::{stopwords, synonyms, tokenize};
+use crate::document::{
+    build_topic_catalog, catalog_topic_categories, TopicCatalog, TopicCategoryMap,
+};
 use crate::{SearchResult, SearchResultMetadata, TextChunk};
-use cangjie_core::config::INDEX_WRITER_HEAP_BYTES;
+use cangjie_core::config::{DEFAULT_TITLE_BOOST, INDEX_WRITER_HEAP_BYTES};
 
 const TOKENIZER_NAME: &str = "jieba";
+const TITLE_TEXT_FIELD: &str = "title_text";
 
+/// Indexes with [`tokenize`], the same segmentation queries use.
 #[derive(Clone)]
-struct JiebaTokenizer {
-    jieba: Arc<Jieba>,
-}
-
-impl JiebaTokenizer {
-    fn new() -> Self {
-        Self {
-            jieba: Arc::clone(&GLOBAL_JIEBA),
-        }
-    }
-}
+struct JiebaTokenizer;
 
 impl Tokenizer for JiebaTokenizer {
     type TokenStream<'a> = JiebaTokenStream;
 
     fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
-        let lower = text.to_lowercase();
-        let words = self.jieba.cut_for_search(&lower, true);
         let mut tokens = Vec::new();
         let mut offset = 0;
 
-        for token in words {
-            let word = token.word.trim();
-            if word.is_empty() {
-                continue;
-            }
+        for word in tokenize(text) {
+            let len = word.len();
             tokens.push(Token {
                 offset_from: offset,
-                offset_to: offset + word.len(),
+                offset_to: offset + len,
                 position: tokens.len(),
-                text: word.to_string(),
+                text: word,
                 position_length: 1,
             });
-            offset += word.len();
+            offset += len;
         }
 
         JiebaTokenStream {
@@ -99,6 +87,12 @@ pub struct BM25Store {
     field_title: Field,
     field_has_code: Field,
     field_chunk_id: Field,
+    field_title_text: Field,
+    field_custom: Field,
+    field_chunk_index: Field,
+    field_char_offset: Field,
+    field_breadcrumb: Field,
+    title_boost: f32,
 }
 
 impl BM25Store {
@@ -120,6 +114,21 @@ impl BM25Store {
         let field_title = schema_builder.add_text_field("title", STRING | STORED);
         let field_has_code = schema_builder.add_text_field("has_code", STRING | STORED);
         let field_chunk_id = schema_builder.add_text_field("chunk_id", STRING | STORED);
+        // Tokenized title + topic, searched alongside `text` with a configurable boost.
+        // Appended last so field ids of indexes built before it existed stay valid.
+        let title_text_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(TOKENIZER_NAME)
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+        );
+        let field_title_text = schema_builder.add_text_field(TITLE_TEXT_FIELD, title_text_options);
+        // Front-matter fields as a JSON object; absent from older indexes.
+        let field_custom = schema_builder.add_text_field("custom", STORED);
+        // Chunk position within its document; absent from older indexes.
+        let field_chunk_index = schema_builder.add_u64_field("chunk_index", STORED);
+        let field_char_offset = schema_builder.add_u64_field("char_offset", STORED);
+        // Heading breadcrumb of the chunk; absent from older indexes.
+        let field_breadcrumb = schema_builder.add_text_field("breadcrumb", STORED);
 
         let schema = schema_builder.build();
 
@@ -135,11 +144,24 @@ impl BM25Store {
             field_title,
             field_has_code,
             field_chunk_id,
+            field_title_text,
+            field_custom,
+            field_chunk_index,
+            field_char_offset,
+            field_breadcrumb,
+            title_boost: DEFAULT_TITLE_BOOST,
         }
     }
 
+    /// Set the query-time weight of title/topic matches relative to body text.
+    /// A boost of `0.0` searches the body only.
+    pub fn with_title_boost(mut self, title_boost: f32) -> Self {
+        self.title_boost = title_boost.max(0.0);
+        self
+    }
+
     fn register_tokenizer(index: &Index) {
-        let tokenizer = JiebaTokenizer::new();
+        let tokenizer = JiebaTokenizer;
         index
             .tokenizers()
             .register(TOKENIZER_NAME, TextAnalyzer::builder(tokenizer).build());
@@ -165,6 +187,11 @@ impl BM25Store {
         let fti = self.field_title;
         let fhc = self.field_has_code;
         let fci = self.field_chunk_id;
+        let ftt = self.field_title_text;
+        let fcu = self.field_custom;
+        let fcx = self.field_chunk_index;
+        let fco = self.field_char_offset;
+        let fbc = self.field_breadcrumb;
 
         let (index, reader) =
             tokio::task::spawn_blocking(move || -> Result<(Index, IndexReader)> {
@@ -195,6 +222,18 @@ impl BM25Store {
                         },
                     );
                     doc.add_text(fci, &chunk.metadata.chunk_id);
+                    doc.add_text(
+                        ftt,
+                        format!("{} {}", chunk.metadata.title, chunk.metadata.topic),
+                    );
+                    if !chunk.metadata.custom.is_empty() {
+                        doc.add_text(fcu, serde_json::to_string(&chunk.metadata.custom)?);
+                    }
+                    doc.add_u64(fcx, chunk.metadata.chunk_index as u64);
+                    doc.add_u64(fco, chunk.metadata.char_offset as u64);
+                    if !chunk.metadata.breadcrumb.is_empty() {
+                        doc.add_text(fbc, &chunk.metadata.breadcrumb);
+                    }
                     writer.add_document(doc)?;
                 }
 
@@ -259,24 +298,15 @@ impl BM25Store {
 
         let query = query.to_string();
         let category = category.map(|s| s.to_string());
+        let fields = self.stored_fields();
         let field_text = self.field_text;
-        let field_file_path = self.field_file_path;
         let field_category = self.field_category;
-        let field_topic = self.field_topic;
-        let field_title = self.field_title;
-        let field_has_code = self.field_has_code;
-        let field_chunk_id = self.field_chunk_id;
-        let jieba = Arc::clone(&GLOBAL_JIEBA);
+        let title_boost = self.title_boost;
 
         tokio::task::spawn_blocking(move || {
-            // Tokenize with jieba for better CJK search.
-            let query_lower = query.to_lowercase();
-            let tokens: Vec<&str> = jieba
-                .cut_for_search(&query_lower, true)
-                .into_iter()
-                .map(|t| t.word)
-                .filter(|w| !w.trim().is_empty())
-                .collect();
+            // Tokenize exactly as the index was, for better CJK search.
+            let tokens = tokenize(&query);
+            let tokens = stopwords::stopwords().filter(tokens.iter().map(String::as_str).collect());
 
             if tokens.is_empty() {
                 return Ok(Vec::new());
@@ -285,7 +315,18 @@ impl BM25Store {
             let query_str = synonyms::expand_query(&tokens);
 
             let searcher = reader.searcher();
-            let query_parser = QueryParser::for_index(&index, vec![field_text]);
+            // Indexes built before the title field existed simply lack it.
+            let title_field = index
+                .schema()
+                .get_field(TITLE_TEXT_FIELD)
+                .ok()
+                .filter(|_| title_boost > 0.0);
+            let mut default_fields = vec![field_text];
+            default_fields.extend(title_field);
+            let mut query_parser = QueryParser::for_index(&index, default_fields);
+            if let Some(field) = title_field {
+                query_parser.set_field_boost(field, title_boost);
+            }
 
             let text_query = query_parser
                 .parse_query(&query_str)
@@ -310,55 +351,7 @@ impl BM25Store {
             let mut results = Vec::new();
             for (score, doc_addr) in top_docs {
                 let doc: TantivyDocument = searcher.doc(doc_addr)?;
-
-                let file_path = doc
-                    .get_first(field_file_path)
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string();
-                let cat = doc
-                    .get_first(field_category)
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string();
-                let topic = doc
-                    .get_first(field_topic)
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string();
-                let title = doc
-                    .get_first(field_title)
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string();
-                let has_code = doc
-                    .get_first(field_has_code)
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("false")
-                    == "true";
-                let text = doc
-                    .get_first(field_text)
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string();
-                let chunk_id = doc
-                    .get_first(field_chunk_id)
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("")
-                    .to_string();
-
-                results.push(SearchResult {
-                    text,
-                    score: score as f64,
-                    metadata: SearchResultMetadata {
-                        file_path,
-                        category: cat,
-                        topic,
-                        title,
-                        has_code,
-                        chunk_id,
-                    },
-                });
+                results.push(fields.to_result(&doc, score as f64));
             }
 
             Ok(results)
@@ -366,4 +359,194 @@ impl BM25Store {
         .await
         .context("BM25 search task panicked")?
     }
+
+    /// All chunks of one document, in their original order.
+    pub async fn file_chunks(&self, file_path: &str) -> Result<Vec<SearchResult>> {
+        let term = Term::from_field_text(self.field_file_path, file_path);
+        self.lookup_chunks(vec![term]).await
+    }
+
+    /// All chunks of documents with the given topic name, optionally limited
+    /// to one category, ordered by file path and then chunk order.
+    pub async fn topic_chunks(
+        &self,
+        topic: &str,
+        category: Option<&str>,
+    ) -> Result<Vec<SearchResult>> {
+        let mut terms = vec![Term::from_field_text(self.field_topic, topic)];
+        if let Some(cat) = category {
+            terms.push(Term::from_field_text(self.field_category, cat));
+        }
+        self.lookup_chunks(terms).await
+    }
+
+    /// Every indexed chunk, grouped by file in chunk order.
+    pub async fn all_chunks(&self) -> Result<Vec<SearchResult>> {
+        self.lookup_chunks(Vec::new()).await
+    }
+
+    /// Every chunk matching all `terms` exactly; no terms matches every chunk.
+    async fn lookup_chunks(&self, terms: Vec<Term>) -> Result<Vec<SearchResult>> {
+        let reader = match &self.reader {
+            Some(r) => r.clone(),
+            None => return Ok(Vec::new()),
+        };
+        let fields = self.stored_fields();
+
+        tokio::task::spawn_blocking(move || {
+            let searcher = reader.searcher();
+            let query: Box<dyn tantivy::query::Query> = if terms.is_empty() {
+                Box::new(tantivy::query::AllQuery)
+            } else {
+                Box::new(BooleanQuery::new(
+                    terms
+                        .into_iter()
+                        .map(|term| {
+                            let q: Box<dyn tantivy::query::Query> =
+                                Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+                            (Occur::Must, q)
+                        })
+                        .collect(),
+                ))
+            };
+            let doc_addrs = searcher
+                .search(&query, &DocSetCollector)
+                .context("Document lookup failed")?;
+
+            let mut results = Vec::with_capacity(doc_addrs.len());
+            for doc_addr in doc_addrs {
+                let doc: TantivyDocument = searcher.doc(doc_addr)?;
+                results.push((doc_addr, fields.to_result(&doc, 0.0)));
+            }
+            // Chunk ids end in `#<index>`; fall back to index order for legacy data.
+            results.sort_by(|(a_addr, a), (b_addr, b)| {
+                (
+                    &a.metadata.file_path,
+                    chunk_index(&a.metadata.chunk_id),
+                    a_addr,
+                )
+                    .cmp(&(
+                        &b.metadata.file_path,
+                        chunk_index(&b.metadata.chunk_id),
+                        b_addr,
+                    ))
+            });
+            Ok(results.into_iter().map(|(_, r)| r).collect())
+        })
+        .await
+        .context("BM25 lookup task panicked")?
+    }
+
+    /// Topic name → categories, over every indexed chunk.
+    pub async fn topic_categories(&self) -> Result<TopicCategoryMap> {
+        Ok(catalog_topic_categories(&self.topic_catalog().await?))
+    }
+
+    /// Category → topics with their titles, over every indexed chunk.
+    pub async fn topic_catalog(&self) -> Result<TopicCatalog> {
+        let reader = match &self.reader {
+            Some(r) => r.clone(),
+            None => return Ok(TopicCatalog::new()),
+        };
+        let field_topic = self.field_topic;
+        let field_category = self.field_category;
+        let field_title = self.field_title;
+
+        tokio::task::spawn_blocking(move || {
+            let searcher = reader.searcher();
+            let doc_addrs = searcher
+                .search(&tantivy::query::AllQuery, &DocSetCollector)
+                .context("Topic scan failed")?;
+
+            let mut entries = Vec::with_capacity(doc_addrs.len());
+            for doc_addr in doc_addrs {
+                let doc: TantivyDocument = searcher.doc(doc_addr)?;
+                let get = |field| {
+                    doc.get_first(field)
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string()
+                };
+                entries.push((get(field_category), get(field_topic), get(field_title)));
+            }
+            // Chunks come back unordered; sort so the title kept per topic
+            // does not depend on scan order.
+            entries.sort();
+            Ok(build_topic_catalog(entries.iter().map(|(c, t, title)| {
+                (c.as_str(), t.as_str(), title.as_str())
+            })))
+        })
+        .await
+        .context("BM25 topic scan task panicked")?
+    }
+
+    fn stored_fields(&self) -> StoredFields {
+        StoredFields {
+            text: self.field_text,
+            file_path: self.field_file_path,
+            category: self.field_category,
+            topic: self.field_topic,
+            title: self.field_title,
+            has_code: self.field_has_code,
+            chunk_id: self.field_chunk_id,
+            custom: self.field_custom,
+            chunk_index: self.field_chunk_index,
+            char_offset: self.field_char_offset,
+            breadcrumb: self.field_breadcrumb,
+        }
+    }
+}
+
+/// Stored fields read back into a `SearchResult`.
+#[derive(Clone, Copy)]
+struct StoredFields {
+    text: Field,
+    file_path: Field,
+    category: Field,
+    topic: Field,
+    title: Field,
+    has_code: Field,
+    chunk_id: Field,
+    custom: Field,
+    chunk_index: Field,
+    char_offset: Field,
+    breadcrumb: Field,
+}
+
+impl StoredFields {
+    fn to_result(self, doc: &TantivyDocument, score: f64) -> SearchResult {
+        let get = |field: Field| {
+            doc.get_first(field)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string()
+        };
+        let get_u64 = |field: Field| doc.get_first(field).and_then(|v| v.as_u64());
+        let chunk_id = get(self.chunk_id);
+        SearchResult {
+            text: get(self.text),
+            score,
+            metadata: SearchResultMetadata {
+                file_path: get(self.file_path),
+                category: get(self.category),
+                topic: get(self.topic),
+                title: get(self.title),
+                has_code: get(self.has_code) == "true",
+                // Indexes built before positions were stored fall back to the id suffix.
+                chunk_index: get_u64(self.chunk_index)
+                    .map(|i| i as usize)
+                    .or_else(|| chunk_index(&chunk_id))
+                    .unwrap_or(0),
+                char_offset: get_u64(self.char_offset).unwrap_or(0) as usize,
+                chunk_id,
+                custom: serde_json::from_str(&get(self.custom)).unwrap_or_default(),
+                breadcrumb: get(self.breadcrumb),
+            },
+        }
+    }
+}
+
+/// Position of a chunk within its file, from the `#idx` suffix of its chunk id.
+pub fn chunk_index(chunk_id: &str) -> Option<usize> {
+    chunk_id.rsplit_once('#')?.1.parse().ok()
 }
@@ -86,6 +86,10 @@ mod tests {
                 title: "Test".to_string(),
                 has_code: false,
                 chunk_id: chunk_id.to_string(),
+                custom: Default::default(),
+                chunk_index: 0,
+                char_offset: 0,
+                breadcrumb: String::new(),
             },
         }
     }
@@ -1,27 +1,158 @@
+use std::collections::BTreeSet;
 use std::num::NonZeroUsize;
-use std::sync::Mutex as StdMutex;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Instant;
 
 use anyhow::{Context, Result};
 use lru::LruCache;
-use tracing::{info, warn};
+use serde::Serialize;
+use tokio::sync::Mutex as AsyncMutex;
+use tracing::{error, info, warn};
 
+use crate::api_client::is_auth_rejection;
+use crate::document::{code_block_chunks, resolve_pinned_topics, TopicCatalog, TopicCategoryMap};
 use crate::embedding::{self, EmbedKind, Embedder};
+use crate::initializer::RebuildCoordinator;
 use crate::rerank::{self, RerankerKind};
 use crate::search::bm25::BM25Store;
 use crate::search::fusion::reciprocal_rank_fusion;
 use crate::search::vector::{self, VectorStore};
-use crate::search::GLOBAL_JIEBA;
-use crate::SearchResult;
-use cangjie_core::config::{IndexInfo, Settings, DEFAULT_EMBEDDING_DIM};
+use crate::search::{is_blank_query, EMPTY_QUERY_MESSAGE, GLOBAL_JIEBA};
+use crate::{DocMetadata, IndexMetadata, SearchMode, SearchResult, TextChunk};
+use cangjie_core::config::{
+    recorded_version, EmbeddingType, IndexInfo, RerankStage, RerankType, Settings, VectorDistance,
+    MAX_FUSION_CANDIDATE_K,
+};
+use cangjie_core::error::CangjieError;
 
 const EMBEDDING_CACHE_SIZE: NonZeroUsize = NonZeroUsize::new(64).unwrap();
+/// Indexes of other versions kept open at once; the least recently queried
+/// one is closed when another is opened.
+const VERSION_INDEX_CACHE_SIZE: NonZeroUsize = NonZeroUsize::new(4).unwrap();
+const WARMUP_QUERY: &str = "仓颉";
+
+/// Milliseconds spent on each component during warmup; `None` when the
+/// component is not configured or its probe failed.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WarmupReport {
+    pub bm25_ms: Option<u64>,
+    pub embedder_ms: Option<u64>,
+    pub vector_ms: Option<u64>,
+    pub reranker_ms: Option<u64>,
+}
+
+/// How [`LocalSearchIndex::related_topics`] found a topic, which also says
+/// what its score means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelatedBy {
+    /// Vector similarity to the source topic's content.
+    Vector,
+    /// Fused BM25 rank of a keyword search on the source topic's title; not a
+    /// similarity.
+    Keyword,
+}
+
+/// A topic near another one, from [`LocalSearchIndex::related_topics`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedTopic {
+    pub topic: String,
+    pub category: String,
+    pub title: String,
+    /// Vector similarity to the source topic; `None` for keyword matches,
+    /// whose score is not one.
+    pub similarity: Option<f64>,
+    pub score: f64,
+    pub matched_by: RelatedBy,
+}
+
+/// Candidate chunks fetched per related topic requested, since neighbouring
+/// chunks often belong to the same few topics.
+const RELATED_TOPIC_CANDIDATES: usize = 8;
+
+/// Best-scoring chunk of each topic in `candidates` (ordered best first),
+/// skipping the source topic and categories outside `allowed`, up to `k`
+/// topics.
+fn group_related_topics(
+    candidates: Vec<SearchResult>,
+    source_topic: &str,
+    source_category: &str,
+    allowed: Option<&BTreeSet<String>>,
+    k: usize,
+    matched_by: RelatedBy,
+) -> Vec<RelatedTopic> {
+    let mut related: Vec<RelatedTopic> = Vec::with_capacity(k);
+    for c in candidates {
+        let meta = c.metadata;
+        if meta.topic == source_topic && meta.category == source_category {
+            continue;
+        }
+        if allowed.is_some_and(|a| !a.contains(&meta.category)) {
+            continue;
+        }
+        if related
+            .iter()
+            .any(|r| r.topic == meta.topic && r.category == meta.category)
+        {
+            continue;
+        }
+        related.push(RelatedTopic {
+            topic: meta.topic,
+            category: meta.category,
+            title: meta.title,
+            similarity: (matched_by == RelatedBy::Vector).then_some(c.score),
+            score: c.score,
+            matched_by,
+        });
+        if related.len() >= k {
+            break;
+        }
+    }
+    related
+}
+
+fn elapsed_ms(started: Instant) -> u64 {
+    started.elapsed().as_millis() as u64
+}
+
+/// User-facing message for a failed OpenAI preflight call.
+fn preflight_failure_message(component: &str, base_url: &str, err: &anyhow::Error) -> String {
+    if is_auth_rejection(err) {
+        format!(
+            "OpenAI credentials were rejected by {base_url} during the {component} preflight check. \
+             Check OPENAI_API_KEY / --openai-api-key."
+        )
+    } else {
+        format!("OpenAI {component} preflight check against {base_url} failed: {err:#}")
+    }
+}
 
 fn new_embedding_cache() -> StdMutex<LruCache<String, Vec<f32>>> {
     StdMutex::new(LruCache::new(EMBEDDING_CACHE_SIZE))
 }
 
+/// The `index_metadata.json` of `index_info`, if present and readable.
+async fn read_index_metadata(index_info: &IndexInfo) -> Option<IndexMetadata> {
+    let metadata_path = index_info.index_dir().join("index_metadata.json");
+    let content = tokio::fs::read_to_string(&metadata_path).await.ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn new_version_cache() -> AsyncMutex<LruCache<String, Arc<LocalSearchIndex>>> {
+    AsyncMutex::new(LruCache::new(VERSION_INDEX_CACHE_SIZE))
+}
+
+/// Whether `version` names a single directory under `indexes/`, so a
+/// requested version cannot point the lookup elsewhere in the data dir.
+fn is_valid_version_key(version: &str) -> bool {
+    !version.is_empty() && version != "." && version != ".." && !version.contains(['\\', '\0'])
+}
+
+/// Query variants searched per BM25 lookup, including the original query.
+pub(super) const MAX_QUERY_VARIANTS: usize = 3;
+
 /// Generate query variants via synonym expansion, up to `max_variants` (including the original).
-fn generate_query_variants(query: &str, max_variants: usize) -> Vec<String> {
+pub(super) fn generate_query_variants(query: &str, max_variants: usize) -> Vec<String> {
     use crate::search::synonyms::SYNONYM_MAP;
 
     let lower = query.to_lowercase();
@@ -64,7 +195,7 @@ async fn bm25_multi_query_search(
     category: Option<&str>,
     rrf_k: u32,
 ) -> Result<Vec<SearchResult>> {
-    let variants = generate_query_variants(query, 3);
+    let variants = generate_query_variants(query, MAX_QUERY_VARIANTS);
     let mut bm25_lists = Vec::with_capacity(variants.len());
     for variant in &variants {
         let results = bm25.search(variant, fetch_k, category).await?;
@@ -76,10 +207,16 @@ async fn bm25_multi_query_search(
 pub struct LocalSearchIndex {
     settings: Settings,
     bm25_store: Option<BM25Store>,
+    code_store: Option<BM25Store>,
     vector_store: Option<VectorStore>,
-    embedder: Option<Box<dyn Embedder>>,
-    reranker: RerankerKind,
+    embedder: Option<Arc<dyn Embedder>>,
+    reranker: Arc<RerankerKind>,
     embedding_cache: StdMutex<LruCache<String, Vec<f32>>>,
+    /// Indexes of other versions in the same data dir, opened on first query
+    /// and sharing this index's embedder and reranker.
+    version_indexes: AsyncMutex<LruCache<String, Arc<LocalSearchIndex>>>,
+    /// Docs commit recorded in the opened index's metadata.
+    source_commit: Option<String>,
 }
 
 impl LocalSearchIndex {
@@ -93,12 +230,27 @@ impl LocalSearchIndex {
                 RerankerKind::NoOp
             });
         Self {
-            settings,
             bm25_store: Some(bm25_store),
+            ..Self::from_parts(settings, None, Arc::new(reranker))
+        }
+    }
+
+    /// An index with no stores open yet, using the given models.
+    fn from_parts(
+        settings: Settings,
+        embedder: Option<Arc<dyn Embedder>>,
+        reranker: Arc<RerankerKind>,
+    ) -> Self {
+        Self {
+            settings,
+            bm25_store: None,
+            code_store: None,
             vector_store: None,
-            embedder: None,
+            embedder,
             reranker,
             embedding_cache: new_embedding_cache(),
+            version_indexes: new_version_cache(),
+            source_commit: None,
         }
     }
 
@@ -115,20 +267,60 @@ impl LocalSearchIndex {
                 warn!("Failed to create embedder: {}", e);
                 None
             });
-        Self {
-            settings,
-            bm25_store: None,
-            vector_store: None,
-            embedder,
-            reranker,
-            embedding_cache: new_embedding_cache(),
+        Self::from_parts(settings, embedder.map(Arc::from), Arc::new(reranker))
+    }
+
+    /// Attach a code-block BM25 store for `query_code` (for testing).
+    #[doc(hidden)]
+    pub fn with_code_store(mut self, code_store: BM25Store) -> Self {
+        self.code_store = Some(code_store);
+        self
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// The search mode queries are actually served with: hybrid only once
+    /// the vector store is open, whatever the embedding configuration says.
+    pub fn search_mode(&self) -> SearchMode {
+        if self.vector_store.is_some() && self.embedder.is_some() {
+            SearchMode::Hybrid
+        } else {
+            SearchMode::Bm25
         }
     }
 
     pub async fn init(&mut self) -> Result<IndexInfo> {
         let index_info = crate::initializer::initialize_and_index(&self.settings).await?;
+        self.load_stores(&index_info).await;
+        Ok(index_info)
+    }
 
-        let mut bm25 = BM25Store::new(index_info.bm25_index_dir());
+    /// [`Self::init`] with the build run through `rebuilds`, so a later
+    /// rebuild sharing it cancels this one instead of writing alongside it.
+    /// `None` when a newer rebuild superseded it.
+    pub async fn init_with(&mut self, rebuilds: &RebuildCoordinator) -> Result<Option<IndexInfo>> {
+        let Some(index_info) = rebuilds.rebuild(&self.settings).await? else {
+            return Ok(None);
+        };
+        self.load_stores(&index_info).await;
+        Ok(Some(index_info))
+    }
+
+    /// Docs repository commit the opened index was built from; `None` before
+    /// an index is opened or when its metadata predates recording it.
+    pub fn source_commit(&self) -> Option<&str> {
+        self.source_commit.as_deref()
+    }
+
+    /// Open the BM25 (and code) stores of an index already on disk.
+    async fn load_stores(&mut self, index_info: &IndexInfo) {
+        self.source_commit = read_index_metadata(index_info)
+            .await
+            .and_then(|meta| meta.source_commit);
+        let mut bm25 =
+            BM25Store::new(index_info.bm25_index_dir()).with_title_boost(self.settings.title_boost);
         match bm25.load().await {
             Ok(true) => {
                 self.bm25_store = Some(bm25);
@@ -141,30 +333,148 @@ impl LocalSearchIndex {
             }
         }
 
-        Ok(index_info)
+        if index_info.index_code {
+            if let Err(e) = self.init_code_store(index_info).await {
+                warn!("Code search unavailable: {:#}", e);
+            }
+        }
+    }
+
+    /// Open an index already on disk, without any git work.
+    pub async fn open(settings: Settings, index_info: &IndexInfo) -> Result<Self> {
+        LocalSearchIndex::new(settings)
+            .await
+            .open_stores(index_info)
+            .await
+    }
+
+    async fn open_stores(mut self, index_info: &IndexInfo) -> Result<Self> {
+        self.load_stores(index_info).await;
+        if self.bm25_store.is_none() {
+            anyhow::bail!(
+                "BM25 index for version {} could not be loaded",
+                index_info.version
+            );
+        }
+        if let Err(e) = self.init_vector_store(index_info).await {
+            warn!(
+                "Vector store for version {} unavailable: {:#}",
+                index_info.version, e
+            );
+        }
+        Ok(self)
+    }
+
+    /// Versions indexed in this data dir for the configured language and model.
+    pub async fn indexed_versions(&self) -> Result<Vec<String>> {
+        crate::initializer::indexed_versions(&self.settings).await
+    }
+
+    /// The index for `version` in the same data dir, opened without any git
+    /// work on first use and cached. `None` when that version is not indexed.
+    pub async fn version_index(&self, version: &str) -> Result<Option<Arc<LocalSearchIndex>>> {
+        if !is_valid_version_key(version) {
+            anyhow::bail!("Invalid version '{version}'");
+        }
+        let mut indexes = self.version_indexes.lock().await;
+        if let Some(index) = indexes.get(version) {
+            return Ok(Some(index.clone()));
+        }
+
+        // `--index-dir` holds a single version; never serve it as another one.
+        if let Some(dir) = &self.settings.index_dir_override {
+            if recorded_version(dir).as_deref() != Some(version) {
+                return Ok(None);
+            }
+        }
+        let index_info = IndexInfo::from_settings(&self.settings, version);
+        if !crate::initializer::index_ready(&index_info).await {
+            return Ok(None);
+        }
+        info!("Opening index for version {version}");
+        let sibling = Self::from_parts(
+            self.settings.clone(),
+            self.embedder.clone(),
+            self.reranker.clone(),
+        );
+        let index = Arc::new(sibling.open_stores(&index_info).await?);
+        indexes.put(version.to_string(), index.clone());
+        Ok(Some(index))
+    }
+
+    /// Load the code-block index, deriving it from the BM25 chunks when the
+    /// index was built before code indexing was enabled.
+    async fn init_code_store(&mut self, index_info: &IndexInfo) -> Result<()> {
+        let mut code_store =
+            BM25Store::new(index_info.code_index_dir()).with_title_boost(self.settings.title_boost);
+        if !code_store.load().await? {
+            let bm25 = self
+                .bm25_store
+                .as_ref()
+                .context("BM25 index is required to build the code index")?;
+            let chunks: Vec<TextChunk> = bm25
+                .all_chunks()
+                .await?
+                .into_iter()
+                .map(|r| TextChunk {
+                    text: r.text,
+                    metadata: DocMetadata {
+                        file_path: r.metadata.file_path,
+                        category: r.metadata.category,
+                        topic: r.metadata.topic,
+                        title: r.metadata.title,
+                        code_block_count: 0,
+                        has_code: r.metadata.has_code,
+                        chunk_id: r.metadata.chunk_id,
+                        chunk_index: r.metadata.chunk_index,
+                        char_offset: r.metadata.char_offset,
+                        breadcrumb: r.metadata.breadcrumb,
+                        custom: r.metadata.custom,
+                    },
+                })
+                .collect();
+            let code_chunks = code_block_chunks(&chunks);
+            info!(
+                "Building code index from {} code blocks...",
+                code_chunks.len()
+            );
+            code_store.build_from_chunks(&code_chunks).await?;
+        }
+        self.code_store = Some(code_store);
+        Ok(())
     }
 
     /// Async initialization for vector store (call after init).
     pub async fn init_vector_store(&mut self, index_info: &IndexInfo) -> Result<()> {
-        if self.embedder.is_none() {
+        let Some(ref embedder) = self.embedder else {
             return Ok(());
-        }
+        };
 
         let vector_dir = index_info.vector_db_dir();
-        // Determine embedding dimension via a test embed.
-        let dim = if let Some(ref embedder) = self.embedder {
-            let test = embedder.embed(&["test"], EmbedKind::Document).await?;
-            test.first()
-                .map(|v| v.len())
-                .unwrap_or(DEFAULT_EMBEDDING_DIM)
-        } else {
-            DEFAULT_EMBEDDING_DIM
-        };
+        let dim = embedding::embedding_dim(embedder.as_ref()).await?;
 
-        let vs = VectorStore::open(&vector_dir, dim).await?;
+        if !tokio::fs::try_exists(vector_dir.join("vectors.db"))
+            .await
+            .unwrap_or(false)
+        {
+            info!("Vector store not found, will be built during indexing");
+            return Ok(());
+        }
+        // Serving never writes, so a prebuilt index may sit on a read-only mount.
+        let vs = match VectorStore::open_read_only(&vector_dir, dim).await {
+            Ok(vs) => vs.with_min_score(self.settings.vector_score_threshold),
+            Err(e) => {
+                warn!(
+                    "Vector store unavailable, falling back to BM25-only search: {:#}",
+                    e
+                );
+                return Ok(());
+            }
+        };
         if vs.is_ready() {
             info!("Vector store loaded from {:?}", vector_dir);
-            self.vector_store = Some(vs);
+            let distance = self.recorded_distance(index_info).await;
+            self.vector_store = Some(vs.with_distance(distance));
         } else {
             info!("Vector store not found, will be built during indexing");
         }
@@ -172,25 +482,222 @@ impl LocalSearchIndex {
         Ok(())
     }
 
+    /// The distance the existing vector store was built with, so queries
+    /// score the same way the index was laid out. Indexes that predate
+    /// recording it were built with L2. A changed `--vector-distance`
+    /// rebuilds local indexes; this only differs from it for indexes that
+    /// are never rebuilt (`--prebuilt`, `--index-dir`, `--index-policy never`).
+    async fn recorded_distance(&self, index_info: &IndexInfo) -> VectorDistance {
+        let metadata_path = index_info.index_dir().join("index_metadata.json");
+        let Ok(content) = tokio::fs::read_to_string(&metadata_path).await else {
+            return self.settings.vector_distance;
+        };
+        let recorded = serde_json::from_str::<IndexMetadata>(&content)
+            .ok()
+            .and_then(|meta| meta.vector_distance)
+            .unwrap_or(VectorDistance::L2);
+        if recorded != self.settings.vector_distance {
+            warn!(
+                "Index was built with vector distance {recorded} and is not rebuilt here, \
+                 ignoring --vector-distance {}",
+                self.settings.vector_distance
+            );
+        }
+        recorded
+    }
+
+    /// Probe the OpenAI embedder and reranker once each so bad credentials
+    /// are reported at startup rather than on every query.
+    ///
+    /// A rejected key disables the affected component: hybrid search falls
+    /// back to BM25-only and reranking is turned off. Other failures are
+    /// treated as transient and only logged.
+    pub async fn preflight_credentials(&mut self) {
+        self.preflight_embedder().await;
+        if self.settings.rerank_type == RerankType::OpenAI {
+            self.warm_reranker().await;
+        }
+    }
+
+    async fn preflight_embedder(&mut self) {
+        if self.settings.embedding_type != EmbeddingType::OpenAI {
+            return;
+        }
+        let Some(ref embedder) = self.embedder else {
+            return;
+        };
+        if let Err(e) = embedder.embed(&[WARMUP_QUERY], EmbedKind::Query).await {
+            let message =
+                preflight_failure_message("embedding", &self.settings.openai_base_url, &e);
+            if is_auth_rejection(&e) {
+                error!("{message} Hybrid search is disabled; falling back to BM25-only.");
+                self.embedder = None;
+                self.vector_store = None;
+            } else {
+                warn!("{message}");
+            }
+        }
+    }
+
+    /// Run the reranker once and return how long it took. For the OpenAI
+    /// reranker this doubles as the credential check: a rejected key turns
+    /// reranking off.
+    pub async fn warm_reranker(&mut self) -> Option<u64> {
+        if !self.reranker.is_enabled() {
+            return None;
+        }
+        let started = Instant::now();
+        match self.reranker.warmup().await {
+            Ok(()) => {
+                info!("Warmup: reranker ready in {:?}", started.elapsed());
+                Some(elapsed_ms(started))
+            }
+            Err(e) if self.settings.rerank_type == RerankType::OpenAI => {
+                let message =
+                    preflight_failure_message("rerank", &self.settings.openai_base_url, &e);
+                if is_auth_rejection(&e) {
+                    error!("{message} Reranking is disabled.");
+                    self.reranker = Arc::new(RerankerKind::NoOp);
+                } else {
+                    warn!("{message}");
+                }
+                None
+            }
+            Err(e) => {
+                warn!("Warmup: reranker probe failed: {:#}", e);
+                None
+            }
+        }
+    }
+
+    /// Check credentials, open the vector store if it has not been loaded
+    /// yet, then warm every component so the first real query does not pay
+    /// the cold-start cost.
+    pub async fn warmup(&mut self, index_info: &IndexInfo) -> WarmupReport {
+        self.preflight_embedder().await;
+        let reranker_ms = if self.settings.rerank_warmup {
+            self.warm_reranker().await
+        } else {
+            None
+        };
+        if self.vector_store.is_none() && self.embedder.is_some() {
+            let started = Instant::now();
+            match self.init_vector_store(index_info).await {
+                Ok(()) => info!("Warmup: vector store opened in {:?}", started.elapsed()),
+                Err(e) => warn!("Warmup: failed to open vector store: {:#}", e),
+            }
+        }
+        WarmupReport {
+            reranker_ms,
+            ..self.warm_caches().await
+        }
+    }
+
+    /// Run a probe query through BM25, the embedder, and the vector store.
+    pub async fn warm_caches(&self) -> WarmupReport {
+        let started = Instant::now();
+        let mut report = WarmupReport::default();
+
+        if let Some(ref bm25) = self.bm25_store {
+            let phase = Instant::now();
+            match bm25.search(WARMUP_QUERY, 1, None).await {
+                Ok(_) => {
+                    report.bm25_ms = Some(elapsed_ms(phase));
+                    info!("Warmup: BM25 ready in {:?}", phase.elapsed());
+                }
+                Err(e) => warn!("Warmup: BM25 probe failed: {:#}", e),
+            }
+        }
+
+        let query_emb = match self.embedder {
+            Some(ref embedder) => {
+                let phase = Instant::now();
+                match embedder.embed(&[WARMUP_QUERY], EmbedKind::Query).await {
+                    Ok(embeddings) => {
+                        report.embedder_ms = Some(elapsed_ms(phase));
+                        info!("Warmup: embedder ready in {:?}", phase.elapsed());
+                        embeddings.into_iter().next()
+                    }
+                    Err(e) => {
+                        warn!("Warmup: embedder probe failed: {:#}", e);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        if let (Some(vs), Some(emb)) = (self.vector_store.as_ref(), query_emb) {
+            let phase = Instant::now();
+            match vs.search(&emb, 1, None).await {
+                Ok(_) => {
+                    report.vector_ms = Some(elapsed_ms(phase));
+                    info!("Warmup: vector store ready in {:?}", phase.elapsed());
+                }
+                Err(e) => warn!("Warmup: vector store probe failed: {:#}", e),
+            }
+        }
+
+        info!("Warmup complete in {:?}", started.elapsed());
+        report
+    }
+
+    /// Candidates to retrieve for a query returning `top_k` results: the
+    /// `rerank_initial_k` pool when a reranker will reorder them, otherwise
+    /// just `top_k`.
+    fn fetch_k(&self, top_k: usize, rerank: bool) -> usize {
+        if rerank && self.reranker.is_enabled() {
+            self.settings.rerank_initial_k.max(top_k)
+        } else {
+            top_k
+        }
+    }
+
+    /// Rerank one retriever's whole candidate list ahead of fusion, keeping
+    /// its original order if the reranker fails.
+    async fn rerank_candidates(
+        &self,
+        query: &str,
+        candidates: Vec<SearchResult>,
+    ) -> Vec<SearchResult> {
+        if candidates.is_empty() {
+            return candidates;
+        }
+        let keep = candidates.len();
+        match self.reranker.rerank(query, candidates.clone(), keep).await {
+            Ok(reranked) => reranked,
+            Err(e) => {
+                warn!(
+                    "Pre-fusion reranking failed, keeping retriever order: {}",
+                    e
+                );
+                candidates
+            }
+        }
+    }
+
+    /// Search the index. `top_k` is the effective result count (see
+    /// [`Settings::effective_top_k`]): reranking may fetch more candidates,
+    /// but at most `top_k` results are returned before window expansion.
     pub async fn query(
         &self,
         query: &str,
         top_k: usize,
         category: Option<&str>,
+        rerank: bool,
     ) -> Result<Vec<SearchResult>> {
+        if is_blank_query(query) {
+            anyhow::bail!(EMPTY_QUERY_MESSAGE);
+        }
         let has_bm25 = self.bm25_store.is_some();
         let has_vector = self.vector_store.is_some() && self.embedder.is_some();
-        let use_rerank = self.reranker.is_enabled();
+        let use_rerank = rerank && self.reranker.is_enabled();
 
         if !has_bm25 && !has_vector {
-            return Ok(Vec::new());
+            return Err(CangjieError::IndexNotReady.into());
         }
 
-        let fetch_k = if use_rerank {
-            self.settings.rerank_initial_k.max(top_k)
-        } else {
-            top_k
-        };
+        let fetch_k = self.fetch_k(top_k, rerank);
 
         let results = if has_bm25 && has_vector {
             // Hybrid search: BM25 + Vector -> RRF fusion (parallel)
@@ -204,8 +711,15 @@ impl LocalSearchIndex {
                 .as_ref()
                 .context("Vector store not initialized")?;
 
+            // Each retriever contributes a deeper list than the final count so
+            // fusion can surface results the two rank differently.
+            let candidate_k = self
+                .settings
+                .fusion_candidate_k
+                .min(MAX_FUSION_CANDIDATE_K)
+                .max(fetch_k);
             let bm25_future =
-                bm25_multi_query_search(bm25, query, fetch_k, category, self.settings.rrf_k);
+                bm25_multi_query_search(bm25, query, candidate_k, category, self.settings.rrf_k);
             let vector_future = async {
                 let query_emb = {
                     let cached = self.embedding_cache.lock().unwrap().get(query).cloned();
@@ -221,12 +735,22 @@ impl LocalSearchIndex {
                         vec
                     }
                 };
-                vector_store.search(&query_emb, fetch_k, category).await
+                vector_store.search(&query_emb, candidate_k, category).await
             };
 
             let (bm25_res, vector_res) = tokio::join!(bm25_future, vector_future);
-            let bm25_results = bm25_res?;
-            let vector_results = vector_res?;
+            let mut bm25_results = bm25_res?;
+            let mut vector_results = vector_res?;
+
+            let pre_fusion = self.settings.rerank_stage == RerankStage::PreFusion;
+            if use_rerank && pre_fusion {
+                let (bm25_reranked, vector_reranked) = tokio::join!(
+                    self.rerank_candidates(query, bm25_results),
+                    self.rerank_candidates(query, vector_results),
+                );
+                bm25_results = bm25_reranked;
+                vector_results = vector_reranked;
+            }
 
             let mut fused = reciprocal_rank_fusion(
                 &[bm25_results, vector_results],
@@ -234,7 +758,7 @@ impl LocalSearchIndex {
                 fetch_k,
             );
 
-            if use_rerank && !fused.is_empty() {
+            if use_rerank && !pre_fusion && !fused.is_empty() {
                 let fallback = fused.clone();
                 fused = self
                     .reranker
@@ -271,6 +795,9 @@ impl LocalSearchIndex {
         } else {
             Vec::new()
         };
+        // A failed rerank falls back to the wider candidate list.
+        let mut results = results;
+        results.truncate(top_k);
 
         // Sentence window expansion: fetch adjacent chunks for context
         let results = if let Some(ref vs) = self.vector_store {
@@ -281,17 +808,138 @@ impl LocalSearchIndex {
 
         Ok(results)
     }
+
+    /// Search the code-block index; each result is one code block with the
+    /// heading and paragraph that introduce it.
+    pub async fn query_code(
+        &self,
+        query: &str,
+        top_k: usize,
+        category: Option<&str>,
+    ) -> Result<Vec<SearchResult>> {
+        if is_blank_query(query) {
+            anyhow::bail!(EMPTY_QUERY_MESSAGE);
+        }
+        let code_store = self.code_store.as_ref().context(
+            "Code search is not available; build the index with --index-code (CANGJIE_INDEX_CODE=true)",
+        )?;
+        bm25_multi_query_search(code_store, query, top_k, category, self.settings.rrf_k).await
+    }
+
+    /// All indexed chunks of the document at `file_path`, in order.
+    pub async fn document_chunks(&self, file_path: &str) -> Result<Vec<SearchResult>> {
+        match self.bm25_store {
+            Some(ref bm25) => bm25.file_chunks(file_path).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Topic name → categories for everything in the index.
+    pub async fn topic_categories(&self) -> Result<TopicCategoryMap> {
+        match self.bm25_store {
+            Some(ref bm25) => bm25.topic_categories().await,
+            None => Ok(TopicCategoryMap::new()),
+        }
+    }
+
+    /// Category → topics with their titles for everything in the index.
+    pub async fn topic_catalog(&self) -> Result<TopicCatalog> {
+        match self.bm25_store {
+            Some(ref bm25) => bm25.topic_catalog().await,
+            None => Ok(TopicCatalog::new()),
+        }
+    }
+
+    /// Log `--pinned-topics` entries the index does not contain.
+    pub async fn warn_missing_pinned_topics(&self) {
+        let pinned = &self.settings.pinned_topics;
+        if pinned.is_empty() {
+            return;
+        }
+        match self.topic_categories().await {
+            Ok(topics) => {
+                for entry in resolve_pinned_topics(pinned, &topics).1 {
+                    warn!("Pinned topic '{entry}' is not in the index");
+                }
+            }
+            Err(e) => warn!("Could not check pinned topics: {e}"),
+        }
+    }
+
+    /// Up to `k` other topics closest to `topic`, or `None` when the topic is
+    /// not indexed. Hybrid indexes compare the mean of the topic's chunk
+    /// vectors against every chunk; BM25-only indexes search for the topic's
+    /// title and name instead. When `allowed` is set, only topics in those
+    /// categories count towards `k`.
+    pub async fn related_topics(
+        &self,
+        topic: &str,
+        category: Option<&str>,
+        allowed: Option<&BTreeSet<String>>,
+        k: usize,
+    ) -> Result<Option<Vec<RelatedTopic>>> {
+        if self.bm25_store.is_none() && self.vector_store.is_none() {
+            return Err(CangjieError::IndexNotReady.into());
+        }
+        let source = self.topic_chunks(topic, category).await?;
+        let Some(first) = source.first() else {
+            return Ok(None);
+        };
+        let source_category = first.metadata.category.clone();
+        // The source topic's own chunks rank near the top; fetch past them.
+        let fetch_k = k.saturating_mul(RELATED_TOPIC_CANDIDATES) + source.len();
+
+        let topic_vector = match self.vector_store {
+            Some(ref vs) if self.search_mode() == SearchMode::Hybrid => vs
+                .topic_vector(topic, Some(&source_category))
+                .await?
+                .map(|v| (vs, v)),
+            _ => None,
+        };
+        let (candidates, matched_by) = match topic_vector {
+            Some((vs, v)) => (vs.search(&v, fetch_k, None).await?, RelatedBy::Vector),
+            None => {
+                let bm25 = self
+                    .bm25_store
+                    .as_ref()
+                    .context("BM25 store not initialized")?;
+                let query = format!("{} {}", first.metadata.title, topic.replace('_', " "));
+                let candidates =
+                    bm25_multi_query_search(bm25, &query, fetch_k, None, self.settings.rrf_k)
+                        .await?;
+                (candidates, RelatedBy::Keyword)
+            }
+        };
+        Ok(Some(group_related_topics(
+            candidates,
+            topic,
+            &source_category,
+            allowed,
+            k,
+            matched_by,
+        )))
+    }
+
+    /// All indexed chunks of documents named `topic`, grouped by file in order.
+    pub async fn topic_chunks(
+        &self,
+        topic: &str,
+        category: Option<&str>,
+    ) -> Result<Vec<SearchResult>> {
+        match self.bm25_store {
+            Some(ref bm25) => bm25.topic_chunks(topic, category).await,
+            None => Ok(Vec::new()),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::search::test_settings;
-    use crate::TextChunk;
     use std::path::PathBuf;
 
     fn make_chunk(text: &str, category: &str, topic: &str) -> TextChunk {
-        use crate::DocMetadata;
         let file_path = format!("{category}/{topic}.md");
         TextChunk {
             text: text.to_string(),
@@ -303,6 +951,10 @@ mod tests {
                 has_code: false,
                 code_block_count: 0,
                 chunk_id: format!("{file_path}#0"),
+                custom: Default::default(),
+                chunk_index: 0,
+                char_offset: 0,
+                breadcrumb: String::new(),
             },
         }
     }
@@ -321,6 +973,12 @@ mod tests {
         ]
     }
 
+    /// An index over `settings` with no stores or models; tests set the
+    /// fields they need on top of it.
+    fn bare_index(settings: Settings) -> LocalSearchIndex {
+        LocalSearchIndex::from_parts(settings, None, Arc::new(RerankerKind::NoOp))
+    }
+
     async fn build_bm25_with_chunks(chunks: &[TextChunk]) -> BM25Store {
         let tmp = tempfile::tempdir().unwrap();
         let bm25_dir = tmp.path().join("bm25_index");
@@ -331,21 +989,416 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_local_search_query_no_stores() {
-        let settings = test_settings(PathBuf::from("/tmp/test-search"));
+    async fn test_init_serves_index_dir_without_building() {
+        let tmp = tempfile::tempdir().unwrap();
+        let index_dir = tmp.path().join("mounted-index");
+        let mut store = BM25Store::new(index_dir.join("bm25_index"));
+        store.build_from_chunks(&sample_chunks()).await.unwrap();
+        let metadata = crate::IndexMetadata {
+            version: "v1.0.0".to_string(),
+            lang: "zh".to_string(),
+            embedding_model: "none".to_string(),
+            document_count: sample_chunks().len(),
+            search_mode: crate::SearchMode::Bm25,
+            index_code: false,
+            extra_docs: None,
+            chunking: None,
+            source_commit: None,
+            vector_distance: None,
+            tokenizer_version: crate::search::TOKENIZER_VERSION,
+        };
+        std::fs::write(
+            index_dir.join("index_metadata.json"),
+            serde_json::to_string(&metadata).unwrap(),
+        )
+        .unwrap();
+
+        // The data dir holds no docs checkout, so any build attempt would fail.
+        let mut settings = test_settings(tmp.path().join("data"));
+        settings.index_dir_override = Some(index_dir.clone());
+        let mut index = LocalSearchIndex::new(settings).await;
+        let index_info = index.init().await.unwrap();
+
+        assert_eq!(index_info.version, "v1.0.0");
+        assert_eq!(index_info.bm25_index_dir(), index_dir.join("bm25_index"));
+        assert!(!tmp.path().join("data").exists());
+        let results = index
+            .query("\u{53d8}\u{91cf}", 3, None, false)
+            .await
+            .unwrap();
+        assert!(!results.is_empty());
+    }
+
+    struct FixedEmbedder;
+
+    #[async_trait::async_trait]
+    impl Embedder for FixedEmbedder {
+        async fn embed(&self, texts: &[&str], _kind: EmbedKind) -> Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|_| vec![1.0, 0.0, 0.0, 0.0]).collect())
+        }
+
+        fn model_name(&self) -> &str {
+            "fixed"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_init_vector_store_open_failure_falls_back_to_bm25() {
+        let tmp = tempfile::tempdir().unwrap();
+        // A regular file where the data dir should be makes the vector dir uncreatable.
+        let blocker = tmp.path().join("not-a-dir");
+        std::fs::write(&blocker, "").unwrap();
+
+        let chunks = sample_chunks();
+        let bm25 = build_bm25_with_chunks(&chunks).await;
+        let settings = test_settings(blocker.clone());
+        let index_info = IndexInfo::from_settings(&settings, "test");
+
+        let mut index = LocalSearchIndex {
+            bm25_store: Some(bm25),
+            embedder: Some(Arc::new(FixedEmbedder)),
+            ..bare_index(settings)
+        };
+
+        index.init_vector_store(&index_info).await.unwrap();
+        assert!(index.vector_store.is_none());
+
+        let results = index
+            .query("\u{53d8}\u{91cf}", 3, None, true)
+            .await
+            .unwrap();
+        assert!(
+            !results.is_empty(),
+            "BM25 search should still serve queries without a vector store"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_vector_dir_reports_bm25_despite_hybrid_config() {
+        let tmp = tempfile::tempdir().unwrap();
+        let chunks = sample_chunks();
+        let bm25 = build_bm25_with_chunks(&chunks).await;
+        let mut settings = test_settings(tmp.path().to_path_buf());
+        settings.embedding_type = EmbeddingType::Local;
+        let index_info = IndexInfo::from_settings(&settings, "test");
+        assert!(!index_info.vector_db_dir().exists());
+
+        let mut index = LocalSearchIndex {
+            bm25_store: Some(bm25),
+            embedder: Some(Arc::new(FixedEmbedder)),
+            ..bare_index(settings.clone())
+        };
+        index.init_vector_store(&index_info).await.unwrap();
+
+        assert_eq!(index.search_mode(), SearchMode::Bm25);
+        let lines = cangjie_core::config::startup_info_lines(
+            &settings,
+            &index_info,
+            Some(index.search_mode()),
+        );
+        assert!(
+            lines.contains(&"Search: BM25 (vector store unavailable)".to_string()),
+            "{lines:?}"
+        );
+        assert!(!lines.iter().any(|l| l.contains("hybrid")), "{lines:?}");
+    }
+
+    struct WideEmbedder;
+
+    #[async_trait::async_trait]
+    impl Embedder for WideEmbedder {
+        async fn embed(&self, texts: &[&str], _kind: EmbedKind) -> Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|_| vec![0.1; 1024]).collect())
+        }
+
+        fn model_name(&self) -> &str {
+            "BAAI/bge-m3"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_init_vector_store_uses_detected_dimension() {
+        let tmp = tempfile::tempdir().unwrap();
+        let settings = test_settings(tmp.path().to_path_buf());
+        let index_info = IndexInfo::from_settings(&settings, "test");
+
+        let chunks = sample_chunks();
+        let mut vs = VectorStore::open(&index_info.vector_db_dir(), 1024)
+            .await
+            .unwrap();
+        vs.build_from_chunks(&chunks, &WideEmbedder, 8)
+            .await
+            .unwrap();
+        drop(vs);
+
+        let mut index = LocalSearchIndex {
+            bm25_store: Some(build_bm25_with_chunks(&chunks).await),
+            embedder: Some(Arc::new(WideEmbedder)),
+            ..bare_index(settings)
+        };
+
+        index.init_vector_store(&index_info).await.unwrap();
+        assert_eq!(
+            index.vector_store.as_ref().map(VectorStore::dim),
+            Some(1024)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_warmup_opens_vector_store_and_times_each_phase() {
+        let tmp = tempfile::tempdir().unwrap();
+        let chunks = sample_chunks();
+        let settings = test_settings(tmp.path().to_path_buf());
+        let index_info = IndexInfo::from_settings(&settings, "test");
+
+        let mut vs = VectorStore::open(&index_info.vector_db_dir(), 4)
+            .await
+            .unwrap();
+        vs.build_from_chunks(&chunks, &FixedEmbedder, 8)
+            .await
+            .unwrap();
+        drop(vs);
+
+        let mut index = LocalSearchIndex {
+            bm25_store: Some(build_bm25_with_chunks(&chunks).await),
+            embedder: Some(Arc::new(FixedEmbedder)),
+            ..bare_index(settings)
+        };
+
+        let report = index.warmup(&index_info).await;
+        assert!(index.vector_store.is_some());
+        assert!(report.bm25_ms.is_some());
+        assert!(report.embedder_ms.is_some());
+        assert!(report.vector_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_warm_caches_without_stores_is_empty() {
+        let index = bare_index(test_settings(PathBuf::from("/tmp/test-warmup-empty")));
+        let report = index.warm_caches().await;
+        assert!(report.bm25_ms.is_none());
+        assert!(report.embedder_ms.is_none());
+        assert!(report.vector_ms.is_none());
+    }
+
+    /// Mock rerank API that scores every document and counts requests.
+    async fn spawn_counting_rerank_server(
+        requests: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    ) -> String {
+        use std::sync::atomic::Ordering;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0_u8; 8192];
+                let _ = stream.read(&mut buf).await;
+                requests.fetch_add(1, Ordering::SeqCst);
+                let body = r#"{"results":[{"index":0,"relevance_score":0.9},{"index":1,"relevance_score":0.1}]}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    async fn openai_rerank_index(rerank_warmup: bool, base_url: &str) -> LocalSearchIndex {
+        let mut settings = test_settings(PathBuf::from("/tmp/test-rerank-warmup"));
+        settings.rerank_type = RerankType::OpenAI;
+        settings.rerank_warmup = rerank_warmup;
+        settings.openai_base_url = base_url.to_string();
+        let reranker =
+            rerank::openai::OpenAIReranker::new(&settings, "test-key", "test-model", base_url)
+                .unwrap();
+        LocalSearchIndex {
+            reranker: Arc::new(RerankerKind::OpenAI(reranker)),
+            ..bare_index(settings)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_k_skips_rerank_pool_for_noop_reranker() {
+        let mut index = openai_rerank_index(false, "http://127.0.0.1:9").await;
+        index.settings.rerank_initial_k = 50;
+        assert_eq!(index.fetch_k(5, true), 50);
+        assert_eq!(index.fetch_k(5, false), 5);
+
+        index.reranker = Arc::new(RerankerKind::NoOp);
+        assert_eq!(index.fetch_k(5, true), 5);
+    }
+
+    #[tokio::test]
+    async fn test_warmup_runs_reranker_exactly_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let base_url = spawn_counting_rerank_server(Arc::clone(&requests)).await;
+        let mut index = openai_rerank_index(true, &base_url).await;
+        let index_info = IndexInfo::from_settings(&index.settings, "test");
+
+        let report = index.warmup(&index_info).await;
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert!(report.reranker_ms.is_some());
+        assert!(index.reranker.is_enabled());
+    }
+
+    #[tokio::test]
+    async fn test_warmup_skips_reranker_when_disabled() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let base_url = spawn_counting_rerank_server(Arc::clone(&requests)).await;
+        let mut index = openai_rerank_index(false, &base_url).await;
+        let index_info = IndexInfo::from_settings(&index.settings, "test");
+
+        let report = index.warmup(&index_info).await;
+        assert_eq!(requests.load(Ordering::SeqCst), 0);
+        assert!(report.reranker_ms.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_warm_reranker_noop_reports_nothing() {
+        let mut index = bare_index(test_settings(PathBuf::from("/tmp/test-rerank-warmup-noop")));
+        assert!(index.warm_reranker().await.is_none());
+    }
+
+    #[test]
+    fn test_preflight_failure_message_for_rejected_key() {
+        let err = anyhow::anyhow!(
+            "POST /embeddings failed: HTTP 401 Unauthorized from https://api.example.com/embeddings; headers: <no headers>; body: {{}}"
+        );
+        let msg = preflight_failure_message("embedding", "https://api.example.com", &err);
+        assert!(msg.contains("credentials were rejected by https://api.example.com"));
+        assert!(msg.contains("OPENAI_API_KEY"));
+
+        let err = anyhow::anyhow!("Failed to POST /embeddings: connection refused");
+        let msg = preflight_failure_message("embedding", "https://api.example.com", &err);
+        assert!(!msg.contains("rejected"));
+        assert!(msg.contains("connection refused"));
+    }
+
+    #[tokio::test]
+    async fn test_preflight_rejected_embedding_key_downgrades_to_bm25() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0_u8; 4096];
+                let _ = stream.read(&mut buf).await;
+                let body = r#"{"error":{"message":"Incorrect API key provided"}}"#;
+                let response = format!(
+                    "HTTP/1.1 401 Unauthorized\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let mut settings = test_settings(PathBuf::from("/tmp/test-preflight"));
+        settings.embedding_type = EmbeddingType::OpenAI;
+        settings.openai_base_url = format!("http://{addr}");
+        let embedder = crate::embedding::openai::OpenAIEmbedder::new(
+            &settings,
+            "wrong-key",
+            "test-model",
+            &settings.openai_base_url,
+        )
+        .unwrap();
+
+        let chunks = sample_chunks();
+        let mut index = LocalSearchIndex {
+            bm25_store: Some(build_bm25_with_chunks(&chunks).await),
+            embedder: Some(Arc::new(embedder)),
+            ..bare_index(settings)
+        };
+
+        index.preflight_credentials().await;
+        assert!(index.embedder.is_none());
+
+        let results = index
+            .query("\u{53d8}\u{91cf}", 3, None, false)
+            .await
+            .unwrap();
+        assert!(!results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_local_search_query_rerank_false_skips_reranker() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&connections);
+        tokio::spawn(async move {
+            while listener.accept().await.is_ok() {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        let chunks = sample_chunks();
+        let bm25 = build_bm25_with_chunks(&chunks).await;
+        let settings = test_settings(PathBuf::from("/tmp/test-search-no-rerank"));
+        let reranker = rerank::openai::OpenAIReranker::new(
+            &settings,
+            "test-key",
+            "test-model",
+            &format!("http://{addr}"),
+        )
+        .unwrap();
+
         let index = LocalSearchIndex {
-            settings,
-            bm25_store: None,
-            vector_store: None,
-            embedder: None,
-            reranker: RerankerKind::NoOp,
-            embedding_cache: new_embedding_cache(),
+            bm25_store: Some(bm25),
+            reranker: Arc::new(RerankerKind::OpenAI(reranker)),
+            ..bare_index(settings)
+        };
+
+        let results = index
+            .query("\u{53d8}\u{91cf}", 3, None, false)
+            .await
+            .unwrap();
+        assert!(!results.is_empty());
+        assert_eq!(
+            connections.load(Ordering::SeqCst),
+            0,
+            "reranker endpoint must not be contacted when rerank=false"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_local_search_query_rejects_blank_queries() {
+        let index = LocalSearchIndex {
+            bm25_store: Some(build_bm25_with_chunks(&sample_chunks()).await),
+            ..bare_index(test_settings(PathBuf::from("/tmp/test-search-blank")))
         };
+        for query in ["", " \t\n", "?!;", "。，"] {
+            let err = index.query(query, 3, None, false).await.unwrap_err();
+            assert_eq!(err.to_string(), EMPTY_QUERY_MESSAGE, "query {query:?}");
+        }
+        assert!(index.query("let", 3, None, false).await.is_ok());
+    }
 
-        let results = index.query("test", 5, None).await.unwrap();
+    #[tokio::test]
+    async fn test_local_search_query_no_stores() {
+        let settings = test_settings(PathBuf::from("/tmp/test-search"));
+        let index = bare_index(settings);
+
+        let err = index.query("test", 5, None, true).await.unwrap_err();
         assert!(
-            results.is_empty(),
-            "Expected empty results when no stores are configured"
+            matches!(
+                err.downcast_ref::<CangjieError>(),
+                Some(CangjieError::IndexNotReady)
+            ),
+            "Expected IndexNotReady when no stores are configured, got {err:#}"
         );
     }
 
@@ -356,15 +1409,14 @@ mod tests {
         let settings = test_settings(PathBuf::from("/tmp/test-search"));
 
         let index = LocalSearchIndex {
-            settings,
             bm25_store: Some(bm25),
-            vector_store: None,
-            embedder: None,
-            reranker: RerankerKind::NoOp,
-            embedding_cache: new_embedding_cache(),
+            ..bare_index(settings)
         };
 
-        let results = index.query("\u{53d8}\u{91cf}", 3, None).await.unwrap();
+        let results = index
+            .query("\u{53d8}\u{91cf}", 3, None, true)
+            .await
+            .unwrap();
         assert!(
             !results.is_empty(),
             "BM25 search should return results for a matching query"
@@ -378,16 +1430,12 @@ mod tests {
         let settings = test_settings(PathBuf::from("/tmp/test-search"));
 
         let index = LocalSearchIndex {
-            settings,
             bm25_store: Some(bm25),
-            vector_store: None,
-            embedder: None,
-            reranker: RerankerKind::NoOp,
-            embedding_cache: new_embedding_cache(),
+            ..bare_index(settings)
         };
 
         let results = index
-            .query("\u{51fd}\u{6570}", 5, Some("basics"))
+            .query("\u{51fd}\u{6570}", 5, Some("basics"), true)
             .await
             .unwrap();
         for r in &results {
@@ -405,18 +1453,326 @@ mod tests {
         let settings = test_settings(PathBuf::from("/tmp/test-search"));
 
         let index = LocalSearchIndex {
-            settings,
             bm25_store: Some(bm25),
-            vector_store: None,
-            embedder: None,
-            reranker: RerankerKind::NoOp,
-            embedding_cache: new_embedding_cache(),
+            ..bare_index(settings)
         };
 
-        let results = index.query("\u{7f16}\u{7a0b}", 2, None).await.unwrap();
+        let results = index
+            .query("\u{7f16}\u{7a0b}", 2, None, true)
+            .await
+            .unwrap();
         assert!(results.len() <= 2, "Should return at most top_k results");
     }
 
+    #[tokio::test]
+    async fn test_query_request_top_k_bounds_rerank_fallback() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // A reranker that rejects every request forces the fallback to the
+        // full candidate list, which must still be cut to the request top_k.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0_u8; 8192];
+                let _ = stream.read(&mut buf).await;
+                let _ = stream
+                    .write_all(
+                        b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    )
+                    .await;
+            }
+        });
+
+        let mut index = openai_rerank_index(false, &format!("http://{addr}")).await;
+        index.settings.rerank_top_k = 4;
+        index.settings.rerank_initial_k = 5;
+        index.bm25_store = Some(build_bm25_with_chunks(&sample_chunks()).await);
+
+        let top_k = index.settings.effective_top_k(Some(1), true);
+        let results = index
+            .query("\u{4f7f}\u{7528}", top_k, None, true)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    /// Places each chunk (and the query) by marker words so vector ranks can
+    /// be set independently of BM25 ranks.
+    struct MarkerEmbedder;
+
+    #[async_trait::async_trait]
+    impl Embedder for MarkerEmbedder {
+        async fn embed(&self, texts: &[&str], _kind: EmbedKind) -> Result<Vec<Vec<f32>>> {
+            Ok(texts
+                .iter()
+                .map(|t| {
+                    if t.contains("zzz") {
+                        vec![1.0, 0.0, 0.0, 0.0]
+                    } else if t.contains("yyy") {
+                        vec![0.0, 0.0, 0.6, 0.8]
+                    } else {
+                        vec![0.0, 0.0, 0.0, 1.0]
+                    }
+                })
+                .collect())
+        }
+
+        fn model_name(&self) -> &str {
+            "marker"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_query_fetches_fusion_candidates_from_each_retriever() {
+        // BM25 ranks [bm25_top, shared]; vector ranks [vector_top, shared,
+        // bm25_top]. Only lists two deep let `shared` win fusion at top_k = 1.
+        let chunks = vec![
+            make_chunk("alpha alpha alpha zzz", "basics", "bm25_top"),
+            make_chunk("alpha yyy", "basics", "shared"),
+            make_chunk("unrelated words", "basics", "vector_top"),
+        ];
+        let tmp = tempfile::tempdir().unwrap();
+        let settings = test_settings(tmp.path().to_path_buf());
+        let index_info = IndexInfo::from_settings(&settings, "test");
+        let mut vs = VectorStore::open(&index_info.vector_db_dir(), 4)
+            .await
+            .unwrap();
+        vs.build_from_chunks(&chunks, &MarkerEmbedder, 8)
+            .await
+            .unwrap();
+
+        let mut index = LocalSearchIndex {
+            bm25_store: Some(build_bm25_with_chunks(&chunks).await),
+            vector_store: Some(vs),
+            embedder: Some(Arc::new(MarkerEmbedder)),
+            ..bare_index(settings)
+        };
+
+        index.settings.fusion_candidate_k = 2;
+        let results = index.query("alpha", 1, None, false).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].metadata.topic, "shared");
+
+        index.settings.fusion_candidate_k = 1;
+        let results = index.query("alpha", 1, None, false).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_ne!(results[0].metadata.topic, "shared");
+    }
+
+    /// Places chunks along one axis by a leading digit, so similarity falls
+    /// with the distance between digits.
+    struct AxisEmbedder;
+
+    #[async_trait::async_trait]
+    impl Embedder for AxisEmbedder {
+        async fn embed(&self, texts: &[&str], _kind: EmbedKind) -> Result<Vec<Vec<f32>>> {
+            Ok(texts
+                .iter()
+                .map(|t| {
+                    let x = t.chars().next().and_then(|c| c.to_digit(10)).unwrap_or(0) as f32;
+                    vec![x, 1.0, 0.0, 0.0]
+                })
+                .collect())
+        }
+
+        fn model_name(&self) -> &str {
+            "axis"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_related_topics_ranks_neighbours_by_vector_similarity() {
+        let chunks = vec![
+            make_chunk("1 source", "basics", "source"),
+            make_chunk("3 far", "basics", "far"),
+            make_chunk("1 twin", "basics", "twin"),
+            make_chunk("2 near", "advanced", "near"),
+        ];
+        let tmp = tempfile::tempdir().unwrap();
+        let settings = test_settings(tmp.path().to_path_buf());
+        let index_info = IndexInfo::from_settings(&settings, "test");
+        let mut vs = VectorStore::open(&index_info.vector_db_dir(), 4)
+            .await
+            .unwrap()
+            .with_distance(VectorDistance::L2)
+            .with_min_score(0.0);
+        vs.build_from_chunks(&chunks, &AxisEmbedder, 8)
+            .await
+            .unwrap();
+
+        let index = LocalSearchIndex {
+            bm25_store: Some(build_bm25_with_chunks(&chunks).await),
+            vector_store: Some(vs),
+            embedder: Some(Arc::new(AxisEmbedder)),
+            ..bare_index(settings)
+        };
+
+        let related = index
+            .related_topics("source", None, None, 10)
+            .await
+            .unwrap()
+            .unwrap();
+        let topics: Vec<&str> = related.iter().map(|r| r.topic.as_str()).collect();
+        assert_eq!(topics, ["twin", "near", "far"]);
+        assert!(related.iter().all(|r| r.matched_by == RelatedBy::Vector));
+        assert!(related.iter().all(|r| r.similarity == Some(r.score)));
+        assert!(related[0].score > related[1].score);
+        assert!(related[1].score > related[2].score);
+
+        let related = index
+            .related_topics("source", None, None, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(related.len(), 1);
+
+        // Disallowed categories are skipped before `k` is reached.
+        let advanced = BTreeSet::from(["advanced".to_string()]);
+        let related = index
+            .related_topics("source", None, Some(&advanced), 1)
+            .await
+            .unwrap()
+            .unwrap();
+        let topics: Vec<&str> = related.iter().map(|r| r.topic.as_str()).collect();
+        assert_eq!(topics, ["near"]);
+        assert!(index
+            .related_topics("missing", None, None, 5)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rerank_stage_controls_reranker_calls() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let chunks = vec![
+            make_chunk("alpha alpha alpha zzz", "basics", "bm25_top"),
+            make_chunk("alpha yyy", "basics", "shared"),
+            make_chunk("unrelated words", "basics", "vector_top"),
+        ];
+        let tmp = tempfile::tempdir().unwrap();
+        let settings = test_settings(tmp.path().to_path_buf());
+        let index_info = IndexInfo::from_settings(&settings, "test");
+        let mut vs = VectorStore::open(&index_info.vector_db_dir(), 4)
+            .await
+            .unwrap();
+        vs.build_from_chunks(&chunks, &MarkerEmbedder, 8)
+            .await
+            .unwrap();
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let base_url = spawn_counting_rerank_server(Arc::clone(&requests)).await;
+        let reranker =
+            rerank::openai::OpenAIReranker::new(&settings, "test-key", "test-model", &base_url)
+                .unwrap();
+        let mut index = LocalSearchIndex {
+            bm25_store: Some(build_bm25_with_chunks(&chunks).await),
+            vector_store: Some(vs),
+            embedder: Some(Arc::new(MarkerEmbedder)),
+            reranker: Arc::new(RerankerKind::OpenAI(reranker)),
+            ..bare_index(settings)
+        };
+
+        index.settings.rerank_stage = RerankStage::PostFusion;
+        let results = index.query("alpha", 2, None, true).await.unwrap();
+        assert!(!results.is_empty());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        requests.store(0, Ordering::SeqCst);
+        index.settings.rerank_stage = RerankStage::PreFusion;
+        let results = index.query("alpha", 2, None, true).await.unwrap();
+        assert!(!results.is_empty());
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_version_index_opens_other_indexed_version() {
+        let tmp = tempfile::tempdir().unwrap();
+        let settings = test_settings(tmp.path().to_path_buf());
+        let other = IndexInfo::from_settings(&settings, "v2");
+        BM25Store::new(other.bm25_index_dir())
+            .build_from_chunks(&[make_chunk("legacy generics text", "syntax", "generics")])
+            .await
+            .unwrap();
+        let metadata = crate::IndexMetadata {
+            version: "v2".to_string(),
+            lang: "zh".to_string(),
+            embedding_model: "none".to_string(),
+            document_count: 1,
+            search_mode: crate::SearchMode::Bm25,
+            index_code: false,
+            extra_docs: None,
+            chunking: None,
+            source_commit: Some("0a1b2c3".to_string()),
+            vector_distance: None,
+            tokenizer_version: crate::search::TOKENIZER_VERSION,
+        };
+        std::fs::create_dir_all(other.index_dir()).unwrap();
+        std::fs::write(
+            other.index_dir().join("index_metadata.json"),
+            serde_json::to_string(&metadata).unwrap(),
+        )
+        .unwrap();
+
+        let index =
+            LocalSearchIndex::with_bm25(settings, build_bm25_with_chunks(&sample_chunks()).await)
+                .await;
+        assert_eq!(index.indexed_versions().await.unwrap(), vec!["v2"]);
+        assert!(index.version_index("v9").await.unwrap().is_none());
+
+        let v2 = index.version_index("v2").await.unwrap().unwrap();
+        let results = v2.query("legacy", 5, None, false).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].metadata.topic, "generics");
+        assert_eq!(v2.source_commit(), Some("0a1b2c3"));
+
+        let again = index.version_index("v2").await.unwrap().unwrap();
+        assert!(Arc::ptr_eq(&v2, &again));
+        assert!(Arc::ptr_eq(&v2.reranker, &index.reranker));
+
+        assert!(index.version_index("..").await.is_err());
+        assert!(index.version_index("").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_version_index_never_serves_index_dir_as_another_version() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut settings = test_settings(tmp.path().join("data"));
+        let index_dir = tmp.path().join("mounted");
+        BM25Store::new(index_dir.join("bm25_index"))
+            .build_from_chunks(&[make_chunk("mounted text", "syntax", "generics")])
+            .await
+            .unwrap();
+        let metadata = crate::IndexMetadata {
+            version: "v1".to_string(),
+            lang: "zh".to_string(),
+            embedding_model: "none".to_string(),
+            document_count: 1,
+            search_mode: crate::SearchMode::Bm25,
+            index_code: false,
+            extra_docs: None,
+            chunking: None,
+            vector_distance: None,
+            source_commit: None,
+            tokenizer_version: crate::search::TOKENIZER_VERSION,
+        };
+        std::fs::write(
+            index_dir.join("index_metadata.json"),
+            serde_json::to_string(&metadata).unwrap(),
+        )
+        .unwrap();
+        settings.index_dir_override = Some(index_dir);
+
+        let index =
+            LocalSearchIndex::with_bm25(settings, build_bm25_with_chunks(&sample_chunks()).await)
+                .await;
+        assert!(index.version_index("v2").await.unwrap().is_none());
+        assert!(index.version_index("v1").await.unwrap().is_some());
+    }
+
     #[tokio::test]
     async fn test_local_search_query_bm25_category_no_match() {
         let chunks = sample_chunks();
@@ -424,16 +1780,12 @@ mod tests {
         let settings = test_settings(PathBuf::from("/tmp/test-search-cat-nomatch"));
 
         let index = LocalSearchIndex {
-            settings,
             bm25_store: Some(bm25),
-            vector_store: None,
-            embedder: None,
-            reranker: RerankerKind::NoOp,
-            embedding_cache: new_embedding_cache(),
+            ..bare_index(settings)
         };
 
         let results = index
-            .query("\u{53d8}\u{91cf}", 5, Some("nonexistent_category"))
+            .query("\u{53d8}\u{91cf}", 5, Some("nonexistent_category"), true)
             .await
             .unwrap();
         assert!(
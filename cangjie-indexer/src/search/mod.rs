@@ -3,6 +3,7 @@ pub mod fusion;
 mod local;
 mod remote;
 mod sqlite_vec_ext;
+pub mod stopwords;
 pub mod synonyms;
 pub mod vector;
 
@@ -10,12 +11,95 @@ use std::sync::{Arc, LazyLock};
 
 use jieba_rs::Jieba;
 
-pub use local::LocalSearchIndex;
+pub use local::{LocalSearchIndex, RelatedBy, RelatedTopic, WarmupReport};
 pub use remote::RemoteSearchIndex;
 
+/// Error message for queries with nothing to search for.
+pub const EMPTY_QUERY_MESSAGE: &str = "query must not be empty";
+
+/// Multi-character Cangjie operators, indexed and searched as tokens of
+/// their own so queries like `??` or `|>` find the code that uses them.
+const OPERATORS: &[&str] = &[
+    "??", "?.", "|>", "~>", "<:", "->", "=>", "**", "&&", "||", "<<", ">>", "==", "!=", "<=", ">=",
+    "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "**=", "<<=", ">>=", "&&=", "||=",
+    "..", "..=",
+];
+
+/// Known operators among the runs of ASCII punctuation in `text`.
+fn operator_tokens(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_ascii_punctuation())
+        .filter(|run| OPERATORS.contains(run))
+}
+
+/// Whether `query` has nothing to search for: no letters or digits and no
+/// operator, i.e. it is empty, whitespace-only, or other punctuation only.
+pub fn is_blank_query(query: &str) -> bool {
+    !query.chars().any(char::is_alphanumeric) && operator_tokens(query).next().is_none()
+}
+
 /// Global Jieba instance shared across all search components.
 pub static GLOBAL_JIEBA: LazyLock<Arc<Jieba>> = LazyLock::new(|| Arc::new(Jieba::new()));
 
+/// Version of [`tokenize`], recorded in index metadata. Bump it whenever the
+/// tokens change so that existing indexes are rebuilt to match queries.
+pub const TOKENIZER_VERSION: u32 = 1;
+
+/// Lowercased BM25 tokens of `text`, shared by indexing and queries so both
+/// agree on bilingual text: runs of ASCII letters, digits and `_` (API
+/// identifiers such as `HashMap`) stay whole, and the text between them is
+/// segmented with Jieba. Known operators such as `??` are kept; other
+/// punctuation is dropped.
+pub fn tokenize(text: &str) -> Vec<String> {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let lower = text.to_lowercase();
+    let mut tokens = Vec::new();
+    let mut rest = lower.as_str();
+    while !rest.is_empty() {
+        let end = if rest.starts_with(is_word) {
+            let end = rest.find(|c| !is_word(c)).unwrap_or(rest.len());
+            tokens.push(rest[..end].to_string());
+            end
+        } else {
+            let end = rest.find(is_word).unwrap_or(rest.len());
+            tokens.extend(
+                GLOBAL_JIEBA
+                    .cut_for_search(&rest[..end], true)
+                    .into_iter()
+                    .map(|t| t.word.trim())
+                    .filter(|w| w.chars().any(char::is_alphanumeric))
+                    .map(str::to_string),
+            );
+            tokens.extend(operator_tokens(&rest[..end]).map(str::to_string));
+            end
+        };
+        rest = &rest[end..];
+    }
+    tokens
+}
+
+/// The terms BM25 retrieval actually uses for a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryTerms {
+    /// Lowercased Jieba tokens of the query, without stop words.
+    pub terms: Vec<String>,
+    /// The tantivy query string after synonym expansion.
+    pub expanded: String,
+    /// Query variants searched and fused, starting with the original query.
+    pub variants: Vec<String>,
+}
+
+/// Tokenize `query` the way BM25 search does, with synonym expansion and the
+/// query variants that are searched alongside it.
+pub fn explain_query(query: &str) -> QueryTerms {
+    let tokens = tokenize(query);
+    let tokens = stopwords::stopwords().filter(tokens.iter().map(String::as_str).collect());
+    QueryTerms {
+        terms: tokens.iter().map(|t| t.to_string()).collect(),
+        expanded: synonyms::expand_query(&tokens),
+        variants: local::generate_query_variants(query, local::MAX_QUERY_VARIANTS),
+    }
+}
+
 /// Shared test fixture used by the `local` and `remote` submodule tests.
 #[cfg(test)]
 pub(crate) fn test_settings(data_dir: std::path::PathBuf) -> cangjie_core::config::Settings {
@@ -30,3 +114,60 @@ pub(crate) fn test_settings(data_dir: std::path::PathBuf) -> cangjie_core::confi
         ..Settings::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_query_reports_tokens_and_expansion() {
+        let terms = explain_query("HashMap \u{51fd}\u{6570}");
+        assert!(terms.terms.iter().any(|t| t == "hashmap"), "{terms:?}");
+        assert!(
+            terms.terms.iter().any(|t| t == "\u{51fd}\u{6570}"),
+            "{terms:?}"
+        );
+        assert!(terms.expanded.contains(" OR "), "{terms:?}");
+        assert_eq!(terms.variants[0], "HashMap \u{51fd}\u{6570}");
+        assert!(terms.variants.len() <= local::MAX_QUERY_VARIANTS);
+    }
+
+    #[test]
+    fn test_tokenize_keeps_identifiers_whole_in_chinese_text() {
+        let tokens = tokenize("使用HashMap存储 std.collection，键值对");
+        for expected in ["使用", "hashmap", "存储", "std", "collection"] {
+            assert!(tokens.iter().any(|t| t == expected), "{tokens:?}");
+        }
+        assert!(!tokens.iter().any(|t| t == "，" || t == "."), "{tokens:?}");
+    }
+
+    #[test]
+    fn test_operators_are_tokens_and_not_blank() {
+        let tokens = tokenize("let v = a ?? b |> f");
+        assert!(tokens.iter().any(|t| t == "??"), "{tokens:?}");
+        assert!(tokens.iter().any(|t| t == "|>"), "{tokens:?}");
+        assert!(!tokens.iter().any(|t| t == "="), "{tokens:?}");
+
+        for query in ["??", " |> ", "<:"] {
+            assert!(!is_blank_query(query), "{query:?}");
+        }
+        for query in ["", "?!;", "。，"] {
+            assert!(is_blank_query(query), "{query:?}");
+        }
+    }
+
+    #[test]
+    fn test_explain_query_drops_stop_words() {
+        let terms = explain_query("how to use the HashMap");
+        assert!(terms.terms.iter().any(|t| t == "hashmap"), "{terms:?}");
+        assert!(terms.terms.iter().any(|t| t == "use"), "{terms:?}");
+        assert!(
+            !terms.terms.iter().any(|t| t == "the" || t == "how"),
+            "{terms:?}"
+        );
+
+        // A query made only of stop words still has something to search for.
+        let terms = explain_query("the");
+        assert_eq!(terms.terms, vec!["the".to_string()]);
+    }
+}
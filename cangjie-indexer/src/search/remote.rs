@@ -1,10 +1,20 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
 use anyhow::Result;
-use tracing::info;
+use tracing::{info, warn};
 
-use crate::api_client::HttpClient;
+use crate::api_client::{is_server_unavailable, HttpClient};
+use crate::document::{
+    catalog_from_topic_categories, catalog_topic_categories, TopicCatalog, TopicCategoryMap,
+};
+use crate::search::{is_blank_query, EMPTY_QUERY_MESSAGE};
+use crate::ResolvedVersion;
 use crate::SearchResult;
 use crate::SearchResultMetadata;
-use cangjie_core::config::{DocLang, IndexInfo, Settings};
+use cangjie_core::config::{DocLang, IndexInfo, Settings, VectorDistance};
 
 #[derive(Debug, serde::Deserialize)]
 struct RemoteInfoResponse {
@@ -14,6 +24,8 @@ struct RemoteInfoResponse {
     lang: String,
     #[serde(default)]
     embedding_model: String,
+    #[serde(default)]
+    resolved_version: Option<ResolvedVersion>,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -22,12 +34,42 @@ struct RemoteSearchRequest {
     top_k: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     category: Option<String>,
+    rerank: bool,
+    /// Documentation language the query targets; the server's own when omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lang: Option<DocLang>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct RemoteDocumentRequest {
+    file_path: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct RemoteTopicRequest {
+    topic: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct RemoteTopicsResponse {
+    #[serde(default)]
+    topics: TopicCategoryMap,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct RemoteCatalogResponse {
+    categories: TopicCatalog,
 }
 
 #[derive(Debug, serde::Deserialize)]
 struct RemoteSearchResponse {
     #[serde(default)]
     results: Vec<RemoteSearchResultItem>,
+    /// Language of the index that answered; older servers omit it.
+    #[serde(default)]
+    lang: Option<String>,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -40,25 +82,124 @@ struct RemoteSearchResultItem {
     metadata: SearchResultMetadata,
 }
 
+impl RemoteSearchResponse {
+    fn into_results(self) -> Vec<SearchResult> {
+        self.results
+            .into_iter()
+            .map(|item| SearchResult {
+                text: item.text,
+                score: item.score,
+                metadata: item.metadata,
+            })
+            .collect()
+    }
+}
+
+/// Client for one or more remote cangjie-mcp servers.
+///
+/// Requests go to the server that last answered; when it is unreachable or
+/// returns a 5xx, the remaining servers are tried in order.
+///
+/// The topic → categories map and the topic catalog rarely change, so they
+/// are kept for `settings.remote_cache_ttl_secs`; search, document and topic
+/// requests always go to the server.
 pub struct RemoteSearchIndex {
-    http: HttpClient,
+    servers: Vec<HttpClient>,
+    active: AtomicUsize,
+    topics_ttl: Duration,
+    topics_cache: Mutex<Option<(Instant, TopicCategoryMap)>>,
+    catalog_cache: Mutex<Option<(Instant, TopicCatalog)>>,
+    /// Ask for MessagePack search responses (`settings.remote_msgpack`).
+    msgpack: bool,
 }
 
 impl RemoteSearchIndex {
+    /// `server_url` may list several servers separated by commas.
     pub fn new(settings: &Settings, server_url: &str) -> Result<Self> {
+        let urls: Vec<&str> = server_url
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .collect();
+        if urls.is_empty() {
+            anyhow::bail!("No remote server URL given");
+        }
+        // With a fallback available, fail over instead of retrying a dead server.
+        let failover = urls.len() > 1;
+        let servers = urls
+            .into_iter()
+            .map(|url| {
+                let http = HttpClient::new(settings, url, std::time::Duration::from_secs(60))?;
+                Ok(if failover {
+                    http.with_post_max_attempts(1)
+                } else {
+                    http
+                })
+            })
+            .collect::<Result<_>>()?;
         Ok(Self {
-            http: HttpClient::new(settings, server_url, std::time::Duration::from_secs(60))?,
+            servers,
+            active: AtomicUsize::new(0),
+            topics_ttl: Duration::from_secs(settings.remote_cache_ttl_secs),
+            topics_cache: Mutex::new(None),
+            catalog_cache: Mutex::new(None),
+            msgpack: settings.remote_msgpack,
         })
     }
 
+    /// Base URL of the server currently receiving requests.
     pub fn base_url(&self) -> &str {
-        self.http.base_url()
+        self.servers[self.active.load(Ordering::Relaxed)].base_url()
+    }
+
+    fn get_retries(&self, retries: u32) -> u32 {
+        if self.servers.len() > 1 {
+            0
+        } else {
+            retries
+        }
+    }
+
+    /// Run `request` against the active server, moving on to the next one
+    /// whenever a server is unavailable.
+    async fn with_failover<'a, T, F, Fut>(&'a self, request: F) -> Result<T>
+    where
+        F: Fn(&'a HttpClient) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let start = self.active.load(Ordering::Relaxed);
+        let count = self.servers.len();
+        let mut last_err = None;
+        for offset in 0..count {
+            let index = (start + offset) % count;
+            let server = &self.servers[index];
+            match request(server).await {
+                Ok(value) => {
+                    if index != start {
+                        info!("Remote server {} is now active", server.base_url());
+                        self.active.store(index, Ordering::Relaxed);
+                    }
+                    return Ok(value);
+                }
+                Err(e) if count > 1 && is_server_unavailable(&e) => {
+                    warn!("Remote server {} unavailable: {:#}", server.base_url(), e);
+                    last_err = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_err
+            .expect("at least one server was tried")
+            .context("All remote servers are unavailable"))
     }
 
     pub async fn init(&self) -> Result<IndexInfo> {
-        info!("Connecting to remote server: {}", self.http.base_url());
+        info!("Connecting to remote server: {}", self.base_url());
 
-        let data: RemoteInfoResponse = self.http.get_with_retry("info", 3).await?;
+        let retries = self.get_retries(3);
+        let data: RemoteInfoResponse = self
+            .with_failover(|http| http.get_with_retry("info", retries))
+            .await?;
         let lang = match data.lang.as_str() {
             "en" => DocLang::En,
             _ => DocLang::Zh,
@@ -68,32 +209,138 @@ impl RemoteSearchIndex {
             lang,
             embedding_model_name: data.embedding_model,
             data_dir: cangjie_core::config::get_default_data_dir(),
+            docs_repo_dir_override: None,
+            index_code: false,
+            index_dir_override: None,
+            extra_docs_dirs: Vec::new(),
+            extra_docs_prefix: String::new(),
+            max_chunk_chars: None,
+            chunk_overlap_chars: 0,
+            chunk_breadcrumbs: true,
+            strip_html: false,
+            vector_distance: VectorDistance::L2,
         })
     }
 
+    /// Ask the remote server which documentation commit it serves.
+    pub async fn resolved_version(&self) -> Result<Option<ResolvedVersion>> {
+        let retries = self.get_retries(1);
+        let data: RemoteInfoResponse = self
+            .with_failover(|http| http.get_with_retry("info", retries))
+            .await?;
+        Ok(data.resolved_version)
+    }
+
     pub async fn query(
         &self,
         query: &str,
         top_k: usize,
         category: Option<&str>,
+        rerank: bool,
+        lang: Option<DocLang>,
     ) -> Result<Vec<SearchResult>> {
+        if is_blank_query(query) {
+            anyhow::bail!(EMPTY_QUERY_MESSAGE);
+        }
         let payload = RemoteSearchRequest {
             query: query.to_string(),
             top_k,
             category: category.map(|s| s.to_string()),
+            rerank,
+            lang,
         };
 
-        let data: RemoteSearchResponse = self.http.post_json("search", &payload).await?;
+        let data: RemoteSearchResponse = if self.msgpack {
+            self.with_failover(|http| http.post_json_accept_msgpack("search", &payload))
+                .await?
+        } else {
+            self.with_failover(|http| http.post_json("search", &payload))
+                .await?
+        };
+        if let (Some(requested), Some(served)) = (lang, data.lang.as_deref()) {
+            if served != requested.to_string() {
+                anyhow::bail!(
+                    "Remote server answered with '{served}' documentation, but '{requested}' was requested"
+                );
+            }
+        }
+        Ok(data.into_results())
+    }
 
-        Ok(data
-            .results
-            .into_iter()
-            .map(|item| SearchResult {
-                text: item.text,
-                score: item.score,
-                metadata: item.metadata,
-            })
-            .collect())
+    /// Fetch all chunks of the document at `file_path`, in order.
+    pub async fn document_chunks(&self, file_path: &str) -> Result<Vec<SearchResult>> {
+        let payload = RemoteDocumentRequest {
+            file_path: file_path.to_string(),
+        };
+        let data: RemoteSearchResponse = self
+            .with_failover(|http| http.post_json("document", &payload))
+            .await?;
+        Ok(data.into_results())
+    }
+
+    /// Fetch the topic name → categories map from the remote server, or
+    /// from the cache while it is younger than the configured TTL.
+    pub async fn topic_categories(&self) -> Result<TopicCategoryMap> {
+        if let Some((fetched, topics)) = self.topics_cache.lock().unwrap().as_ref() {
+            if fetched.elapsed() < self.topics_ttl {
+                return Ok(topics.clone());
+            }
+        }
+        if let Some((fetched, catalog)) = self.catalog_cache.lock().unwrap().as_ref() {
+            if fetched.elapsed() < self.topics_ttl {
+                return Ok(catalog_topic_categories(catalog));
+            }
+        }
+        let retries = self.get_retries(1);
+        let data: RemoteTopicsResponse = self
+            .with_failover(|http| http.get_with_retry("topics", retries))
+            .await?;
+        if !self.topics_ttl.is_zero() {
+            *self.topics_cache.lock().unwrap() = Some((Instant::now(), data.topics.clone()));
+        }
+        Ok(data.topics)
+    }
+
+    /// Fetch every category's topics and titles in one `/catalog` request,
+    /// cached like [`Self::topic_categories`]. Servers without the endpoint
+    /// are asked for `/topics` instead, and their titles are left empty.
+    pub async fn topic_catalog(&self) -> Result<TopicCatalog> {
+        if let Some((fetched, catalog)) = self.catalog_cache.lock().unwrap().as_ref() {
+            if fetched.elapsed() < self.topics_ttl {
+                return Ok(catalog.clone());
+            }
+        }
+        let retries = self.get_retries(1);
+        let catalog = match self
+            .with_failover(|http| http.get_with_retry::<RemoteCatalogResponse>("catalog", retries))
+            .await
+        {
+            Ok(data) => data.categories,
+            Err(e) => {
+                warn!("Remote catalog unavailable, listing topics instead: {e:#}");
+                catalog_from_topic_categories(&self.topic_categories().await?)
+            }
+        };
+        if !self.topics_ttl.is_zero() {
+            *self.catalog_cache.lock().unwrap() = Some((Instant::now(), catalog.clone()));
+        }
+        Ok(catalog)
+    }
+
+    /// Fetch all chunks of documents named `topic`, grouped by file in order.
+    pub async fn topic_chunks(
+        &self,
+        topic: &str,
+        category: Option<&str>,
+    ) -> Result<Vec<SearchResult>> {
+        let payload = RemoteTopicRequest {
+            topic: topic.to_string(),
+            category: category.map(|s| s.to_string()),
+        };
+        let data: RemoteSearchResponse = self
+            .with_failover(|http| http.post_json("topic", &payload))
+            .await?;
+        Ok(data.into_results())
     }
 }
 
@@ -102,6 +349,57 @@ mod tests {
     use super::*;
     use crate::search::test_settings;
     use std::path::PathBuf;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Mock remote server answering every request with `status` and `body`.
+    async fn spawn_remote_server(status: &'static str, body: &'static str) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0_u8; 4096];
+                let _ = stream.read(&mut buf).await;
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    /// Like [`spawn_remote_server`], also counting the requests received.
+    async fn spawn_counting_server(body: &'static str) -> (String, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = [0_u8; 4096];
+                let _ = stream.read(&mut buf).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (format!("http://{addr}"), hits)
+    }
+
+    /// A URL nothing listens on, so connections are refused.
+    async fn dead_server_url() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        format!("http://{addr}")
+    }
+
+    const SEARCH_BODY: &str = r#"{"results":[{"text":"from backup","score":1.0}]}"#;
 
     #[test]
     fn test_remote_search_new_trailing_slash() {
@@ -130,4 +428,156 @@ mod tests {
             "All trailing slashes should be trimmed"
         );
     }
+
+    #[test]
+    fn test_remote_search_response_chunk_id_round_trip() {
+        let result = SearchResult {
+            text: "func add".to_string(),
+            score: 0.5,
+            metadata: SearchResultMetadata {
+                file_path: "syntax/functions.md".to_string(),
+                chunk_id: "syntax/functions.md#3".to_string(),
+                ..Default::default()
+            },
+        };
+        let body = serde_json::json!({ "results": [result] }).to_string();
+        let parsed: RemoteSearchResponse = serde_json::from_str(&body).unwrap();
+        let results = parsed.into_results();
+        assert_eq!(results[0].metadata.chunk_id, "syntax/functions.md#3");
+
+        // Servers that predate chunk ids still parse, with an empty id.
+        let legacy = r#"{"results":[{"text":"t","score":1.0,"metadata":{"file_path":"a.md","category":"c","topic":"a","title":"A","has_code":false}}]}"#;
+        let parsed: RemoteSearchResponse = serde_json::from_str(legacy).unwrap();
+        assert!(parsed.into_results()[0].metadata.chunk_id.is_empty());
+    }
+
+    #[test]
+    fn test_remote_search_request_lang_is_optional() {
+        let mut request = RemoteSearchRequest {
+            query: "函数".to_string(),
+            top_k: 3,
+            category: None,
+            rerank: true,
+            lang: None,
+        };
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("lang").is_none(), "{json}");
+
+        request.lang = Some(DocLang::En);
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["lang"], "en");
+    }
+
+    #[test]
+    fn test_remote_search_new_splits_server_list() {
+        let remote = RemoteSearchIndex::new(
+            &test_settings(PathBuf::from("/tmp")),
+            " http://a.example/ , ,http://b.example",
+        )
+        .unwrap();
+        assert_eq!(remote.servers.len(), 2);
+        assert_eq!(remote.base_url(), "http://a.example");
+        assert_eq!(remote.servers[1].base_url(), "http://b.example");
+
+        assert!(RemoteSearchIndex::new(&test_settings(PathBuf::from("/tmp")), " , ").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_remote_search_fails_over_on_connection_error() {
+        let dead = dead_server_url().await;
+        let backup = spawn_remote_server("200 OK", SEARCH_BODY).await;
+        let remote = RemoteSearchIndex::new(
+            &test_settings(PathBuf::from("/tmp")),
+            &format!("{dead},{backup}"),
+        )
+        .unwrap();
+
+        let results = remote.query("函数", 3, None, false, None).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].text, "from backup");
+        assert_eq!(
+            remote.base_url(),
+            backup,
+            "healthy server should stay active"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remote_search_fails_over_on_server_error() {
+        let broken = spawn_remote_server("500 Internal Server Error", r#"{"error":"boom"}"#).await;
+        let backup = spawn_remote_server("200 OK", SEARCH_BODY).await;
+        let remote = RemoteSearchIndex::new(
+            &test_settings(PathBuf::from("/tmp")),
+            &format!("{broken},{backup}"),
+        )
+        .unwrap();
+
+        let results = remote.query("函数", 3, None, false, None).await.unwrap();
+        assert_eq!(results[0].text, "from backup");
+    }
+
+    #[tokio::test]
+    async fn test_remote_search_does_not_fail_over_on_client_error() {
+        let rejecting = spawn_remote_server("400 Bad Request", r#"{"error":"bad"}"#).await;
+        let backup = spawn_remote_server("200 OK", SEARCH_BODY).await;
+        let remote = RemoteSearchIndex::new(
+            &test_settings(PathBuf::from("/tmp")),
+            &format!("{rejecting},{backup}"),
+        )
+        .unwrap();
+
+        assert!(remote.query("函数", 3, None, false, None).await.is_err());
+        assert_eq!(remote.base_url(), rejecting);
+    }
+
+    const TOPICS_BODY: &str = r#"{"topics":{"functions":["syntax"]}}"#;
+
+    #[tokio::test]
+    async fn test_topic_categories_cached_within_ttl() {
+        let (url, hits) = spawn_counting_server(TOPICS_BODY).await;
+        let remote = RemoteSearchIndex::new(&test_settings(PathBuf::from("/tmp")), &url).unwrap();
+
+        let first = remote.topic_categories().await.unwrap();
+        let second = remote.topic_categories().await.unwrap();
+        assert_eq!(first, second);
+        assert!(first["functions"].contains("syntax"));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_topic_categories_zero_ttl_disables_cache() {
+        let (url, hits) = spawn_counting_server(TOPICS_BODY).await;
+        let settings = Settings {
+            remote_cache_ttl_secs: 0,
+            ..test_settings(PathBuf::from("/tmp"))
+        };
+        let remote = RemoteSearchIndex::new(&settings, &url).unwrap();
+
+        remote.topic_categories().await.unwrap();
+        remote.topic_categories().await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_topic_catalog_falls_back_to_topics_listing() {
+        // The mock answers `/catalog` with a `/topics` body, like an older
+        // server would answer with its own shape or an error.
+        let (url, hits) = spawn_counting_server(TOPICS_BODY).await;
+        let remote = RemoteSearchIndex::new(&test_settings(PathBuf::from("/tmp")), &url).unwrap();
+
+        let catalog = remote.topic_catalog().await.unwrap();
+        assert_eq!(
+            catalog["syntax"],
+            vec![crate::document::CatalogTopic {
+                topic: "functions".to_string(),
+                title: String::new(),
+            }]
+        );
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        // Later listings are served from the cached catalog.
+        remote.topic_catalog().await.unwrap();
+        assert!(remote.topic_categories().await.unwrap()["functions"].contains("syntax"));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
 }
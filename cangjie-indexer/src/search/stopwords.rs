@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+
+/// Built-in Chinese and English function words. English words that are also
+/// Cangjie keywords (`is`, `as`, `in`, `for`, `do`, `this`, ...) are left
+/// out: they are what code queries search for.
+const DEFAULT_STOPWORDS: &[&str] = &[
+    // Chinese
+    "的", "了", "是", "在", "和", "与", "及", "或", "吗", "呢", "吧", "啊", "呀", "着", "过", "我",
+    "你", "他", "她", "它", "我们", "你们", "他们", "这", "那", "这个", "那个", "个", "把", "被",
+    "从", "到", "就", "都", "也", "还", "又", "请", "怎么", "怎样", "如何", "什么", "哪些", "一个",
+    "一下", "可以", "能", "要", "有", "中", "对", "为", "以", "之", // English
+    "a", "an", "the", "are", "was", "were", "be", "been", "to", "of", "on", "at", "with", "and",
+    "or", "how", "what", "which", "does", "can", "i", "you", "it", "that", "by", "from", "about",
+    "into", "my", "me", "please",
+];
+
+/// A set of query terms that carry no search signal.
+#[derive(Debug, Clone)]
+pub struct StopWords {
+    words: HashSet<String>,
+}
+
+impl Default for StopWords {
+    fn default() -> Self {
+        Self {
+            words: DEFAULT_STOPWORDS.iter().map(|w| w.to_string()).collect(),
+        }
+    }
+}
+
+impl StopWords {
+    /// Load stop words from a file with one word per line. Blank lines and
+    /// lines starting with `#` are ignored; words are lowercased.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read stop words from {}", path.display()))?;
+        let words = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_lowercase)
+            .collect();
+        Ok(Self { words })
+    }
+
+    pub fn contains(&self, term: &str) -> bool {
+        self.words.contains(term.trim())
+    }
+
+    /// Drop stop words from `terms`. When every term is a stop word the
+    /// terms are returned unchanged, so a query never loses all its terms.
+    pub fn filter<T: AsRef<str>>(&self, terms: Vec<T>) -> Vec<T> {
+        if terms.iter().all(|t| self.contains(t.as_ref())) {
+            return terms;
+        }
+        terms
+            .into_iter()
+            .filter(|t| !self.contains(t.as_ref()))
+            .collect()
+    }
+}
+
+static GLOBAL_STOPWORDS: OnceLock<StopWords> = OnceLock::new();
+
+/// The process-wide stop words: those installed by [`init_stopwords`], or
+/// the built-in list.
+pub fn stopwords() -> &'static StopWords {
+    GLOBAL_STOPWORDS.get_or_init(StopWords::default)
+}
+
+/// Install the stop words from `path` (the built-in list when `None`) for
+/// the rest of the process. Later calls keep the first list installed.
+pub fn init_stopwords(path: Option<&Path>) -> Result<()> {
+    let words = match path {
+        Some(path) => StopWords::from_file(path)?,
+        None => StopWords::default(),
+    };
+    let _ = GLOBAL_STOPWORDS.set(words);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_removes_stop_words() {
+        let words = StopWords::default();
+        let terms = vec!["如何", "使用", "hashmap", "的", "the"];
+        assert_eq!(words.filter(terms), vec!["使用", "hashmap"]);
+    }
+
+    #[test]
+    fn test_cangjie_keywords_are_not_stop_words() {
+        let words = StopWords::default();
+        for keyword in [
+            "is", "as", "in", "for", "do", "this", "let", "var", "func", "class", "struct", "where",
+        ] {
+            assert!(!words.contains(keyword), "{keyword}");
+        }
+    }
+
+    #[test]
+    fn test_stop_word_only_query_keeps_its_terms() {
+        let words = StopWords::default();
+        assert_eq!(words.filter(vec!["the"]), vec!["the"]);
+        assert_eq!(words.filter(vec!["如何", "的"]), vec!["如何", "的"]);
+        assert!(words.filter(Vec::<&str>::new()).is_empty());
+    }
+
+    #[test]
+    fn test_from_file_replaces_defaults() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(tmp.path(), "# custom list\nFoo\n\n  bar \n").unwrap();
+        let words = StopWords::from_file(tmp.path()).unwrap();
+        assert!(words.contains("foo"));
+        assert!(words.contains("bar"));
+        assert!(!words.contains("the"));
+    }
+}
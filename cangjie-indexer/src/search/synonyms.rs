@@ -200,8 +200,11 @@ pub fn expand_query(tokens: &[&str]) -> String {
             } else {
                 parts.push(token.to_string());
             }
-        } else {
+        } else if token.chars().all(|c| c.is_alphanumeric() || c == '_') {
             parts.push(token.to_string());
+        } else {
+            // Operators would be read as query syntax; quote them as terms.
+            parts.push(format!("\"{token}\""));
         }
     }
 
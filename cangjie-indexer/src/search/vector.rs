@@ -1,59 +1,183 @@
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
-use rusqlite::Connection;
+use rusqlite::{Connection, OpenFlags};
+use tokio_util::sync::CancellationToken;
 use tracing::info;
 use zerocopy::IntoBytes;
 
 use super::sqlite_vec_ext::register_sqlite_vec;
 use crate::embedding::{EmbedKind, Embedder};
 use crate::{SearchResult, SearchResultMetadata, TextChunk};
-use cangjie_core::config::{CATEGORY_FILTER_MULTIPLIER, DEFAULT_MIN_VECTOR_SCORE};
+use cangjie_core::config::{
+    VectorDistance, CATEGORY_FILTER_MULTIPLIER, DEFAULT_VECTOR_INSERT_BATCH_SIZE,
+    VECTOR_SCORE_THRESHOLD,
+};
+
+/// `user_version` of a store whose build finished writing every batch.
+const STORE_COMPLETE: i64 = 1;
+
+type ChunkRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    bool,
+    String,
+    String,
+    i64,
+    i64,
+    String,
+);
 
 pub struct VectorStore {
     conn: Arc<std::sync::Mutex<Connection>>,
     ready: bool,
     dim: usize,
+    distance: VectorDistance,
+    insert_batch_size: usize,
+    min_score: f64,
+    cancel: CancellationToken,
 }
 
 impl VectorStore {
     pub async fn open(path: &Path, dim: usize) -> Result<Self> {
         let path = path.to_path_buf();
         let d = dim;
-        tokio::task::spawn_blocking(move || Self::open_sync(&path, d))
+        tokio::task::spawn_blocking(move || Self::open_sync(&path, d, false))
             .await
             .context("spawn_blocking join error")?
     }
 
-    fn open_sync(path: &Path, dim: usize) -> Result<Self> {
-        std::fs::create_dir_all(path)
-            .with_context(|| format!("Failed to create vector store dir: {path:?}"))?;
+    /// Open an existing store for searching only: nothing is created or
+    /// migrated, so the store may live on a read-only mount.
+    pub async fn open_read_only(path: &Path, dim: usize) -> Result<Self> {
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || Self::open_sync(&path, dim, true))
+            .await
+            .context("spawn_blocking join error")?
+    }
+
+    fn open_sync(path: &Path, dim: usize, read_only: bool) -> Result<Self> {
+        if !read_only {
+            std::fs::create_dir_all(path)
+                .with_context(|| format!("Failed to create vector store dir: {path:?}"))?;
+        }
 
         register_sqlite_vec()?;
 
         let db_path = path.join("vectors.db");
-        let conn = Connection::open(&db_path)
-            .with_context(|| format!("Failed to open SQLite DB at {db_path:?}"))?;
+        let conn = if read_only {
+            Connection::open_with_flags(
+                &db_path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )
+        } else {
+            Connection::open(&db_path)
+        }
+        .with_context(|| format!("Failed to open SQLite DB at {db_path:?}"))?;
+
+        // The auto extension only takes effect on connections opened after it
+        // registers; probe it so a missing vec0 module fails here, not mid-query.
+        conn.query_row("SELECT vec_version()", [], |r| r.get::<_, String>(0))
+            .with_context(|| {
+                format!(
+                    "sqlite-vec extension is not available on {}-{}",
+                    std::env::consts::OS,
+                    std::env::consts::ARCH
+                )
+            })?;
+
+        if read_only {
+            // Older stores can't be upgraded in place; searching them would
+            // fail on the missing columns anyway.
+            for column in ["custom", "chunk_index", "breadcrumb"] {
+                conn.prepare(&format!("SELECT {column} FROM chunks LIMIT 0"))
+                    .with_context(|| {
+                        format!("Vector store at {db_path:?} predates `{column}`; rebuild it")
+                    })?;
+            }
+        } else {
+            add_missing_columns(&conn);
+        }
 
-        // Check whether the data tables already exist and contain rows.
-        let ready = conn
-            .prepare("SELECT COUNT(*) FROM chunks")
-            .and_then(|mut s| s.query_row([], |r| r.get::<_, i64>(0)))
+        // Ready only once a build wrote its last batch: batches commit one by
+        // one, so rows alone may be what an interrupted build left behind.
+        let complete = conn
+            .query_row("PRAGMA user_version", [], |r| r.get::<_, i64>(0))
             .unwrap_or(0)
-            > 0;
+            == STORE_COMPLETE;
+        let ready = complete
+            && conn
+                .prepare("SELECT COUNT(*) FROM chunks")
+                .and_then(|mut s| s.query_row([], |r| r.get::<_, i64>(0)))
+                .unwrap_or(0)
+                > 0;
 
         Ok(Self {
             conn: Arc::new(std::sync::Mutex::new(conn)),
             ready,
             dim,
+            distance: VectorDistance::default(),
+            insert_batch_size: DEFAULT_VECTOR_INSERT_BATCH_SIZE,
+            min_score: VECTOR_SCORE_THRESHOLD,
+            cancel: CancellationToken::new(),
         })
     }
 
+    /// Distance to build and search with. Searching must use the distance the
+    /// store was built with.
+    pub fn with_distance(mut self, distance: VectorDistance) -> Self {
+        self.distance = distance;
+        self
+    }
+
+    /// Number of rows written per transaction by `build_from_chunks`.
+    pub fn with_insert_batch_size(mut self, insert_batch_size: usize) -> Self {
+        self.insert_batch_size = insert_batch_size.max(1);
+        self
+    }
+
+    /// Stop `build_from_chunks` before its next embedding batch, or before
+    /// writing, once `cancel` fires.
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Drop search hits scoring below `min_score`.
+    pub fn with_min_score(mut self, min_score: f64) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
     pub fn is_ready(&self) -> bool {
         self.ready
     }
 
+    /// Embedding dimension of the vector table.
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    /// Number of chunks currently stored (0 when the tables do not exist).
+    pub async fn chunk_count(&self) -> Result<usize> {
+        let conn = Arc::clone(&self.conn);
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().expect("mutex poisoned");
+            let count = conn
+                .prepare("SELECT COUNT(*) FROM chunks")
+                .and_then(|mut s| s.query_row([], |r| r.get::<_, i64>(0)))
+                .unwrap_or(0);
+            Ok(count as usize)
+        })
+        .await
+        .context("spawn_blocking join error")?
+    }
+
     pub async fn build_from_chunks(
         &mut self,
         chunks: &[TextChunk],
@@ -73,6 +197,9 @@ impl VectorStore {
         // Phase 1: embed all chunks (async)
         let mut all_embeddings: Vec<Vec<f32>> = Vec::with_capacity(chunks.len());
         for (i, batch_chunks) in chunks.chunks(batch_size).enumerate() {
+            if self.cancel.is_cancelled() {
+                anyhow::bail!("Vector index build cancelled");
+            }
             let texts: Vec<&str> = batch_chunks.iter().map(|c| c.text.as_str()).collect();
             let embeddings = embedder
                 .embed(&texts, EmbedKind::Document)
@@ -90,11 +217,15 @@ impl VectorStore {
         if all_embeddings.is_empty() {
             anyhow::bail!("No embeddings generated");
         }
+        if self.cancel.is_cancelled() {
+            anyhow::bail!("Vector index build cancelled");
+        }
 
         // Phase 2: insert into SQLite (blocking)
         let conn = Arc::clone(&self.conn);
         let dim = self.dim;
-        let chunks_owned: Vec<(String, String, String, String, String, bool, String)> = chunks
+        let distance = self.distance;
+        let chunks_owned: Vec<ChunkRow> = chunks
             .iter()
             .map(|c| {
                 (
@@ -105,73 +236,45 @@ impl VectorStore {
                     c.metadata.title.clone(),
                     c.metadata.has_code,
                     c.metadata.chunk_id.clone(),
+                    custom_json(&c.metadata.custom),
+                    c.metadata.chunk_index as i64,
+                    c.metadata.char_offset as i64,
+                    c.metadata.breadcrumb.clone(),
                 )
             })
             .collect();
 
-        tokio::task::spawn_blocking(move || {
+        let insert_batch_size = self.insert_batch_size;
+        let written = tokio::task::spawn_blocking(move || {
             let conn = conn.lock().expect("mutex poisoned");
-
-            conn.execute_batch("DROP TABLE IF EXISTS chunks_vec; DROP TABLE IF EXISTS chunks;")
-                .context("Failed to drop old tables")?;
-
-            conn.execute_batch(&format!(
-                "CREATE TABLE chunks (
-                    id        INTEGER PRIMARY KEY,
-                    text      TEXT NOT NULL,
-                    file_path TEXT NOT NULL,
-                    category  TEXT NOT NULL,
-                    topic     TEXT NOT NULL,
-                    title     TEXT NOT NULL,
-                    has_code  INTEGER NOT NULL,
-                    chunk_id  TEXT NOT NULL DEFAULT ''
+            let result = write_tables(
+                &conn,
+                dim,
+                distance,
+                &chunks_owned,
+                &all_embeddings,
+                insert_batch_size,
+            );
+            if result.is_err() {
+                // Undo the failed batch and drop whatever was committed before
+                // it, so the store reopens as not ready and a retry starts clean.
+                if !conn.is_autocommit() {
+                    let _ = conn.execute_batch("ROLLBACK");
+                }
+                let _ = conn.execute_batch(
+                    "DROP TABLE IF EXISTS chunks_vec; DROP TABLE IF EXISTS chunks;
+                     DROP TABLE IF EXISTS vec_params;",
                 );
-                CREATE INDEX idx_chunks_category ON chunks(category);
-                CREATE INDEX idx_chunks_chunk_id ON chunks(chunk_id);
-                CREATE VIRTUAL TABLE chunks_vec USING vec0(
-                    embedding float[{dim}]
-                );"
-            ))
-            .context("Failed to create tables")?;
-
-            conn.execute_batch("BEGIN")?;
-
-            let mut insert_chunk = conn
-                .prepare_cached(
-                    "INSERT INTO chunks (id, text, file_path, category, topic, title, has_code, chunk_id)
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-                )
-                .context("Failed to prepare chunk insert")?;
-
-            let mut insert_vec = conn
-                .prepare_cached("INSERT INTO chunks_vec (rowid, embedding) VALUES (?1, ?2)")
-                .context("Failed to prepare vec insert")?;
-
-            for (idx, ((text, file_path, category, topic, title, has_code, chunk_id), emb)) in
-                chunks_owned.iter().zip(all_embeddings.iter()).enumerate()
-            {
-                let rowid = (idx + 1) as i64;
-                insert_chunk.execute(rusqlite::params![
-                    rowid,
-                    text,
-                    file_path,
-                    category,
-                    topic,
-                    title,
-                    *has_code as i32,
-                    chunk_id,
-                ])?;
-                insert_vec.execute(rusqlite::params![rowid, emb.as_bytes()])?;
             }
-
-            drop(insert_chunk);
-            drop(insert_vec);
-            conn.execute_batch("COMMIT")?;
-
-            Ok::<(), anyhow::Error>(())
+            result
         })
         .await
-        .context("spawn_blocking join error")??;
+        .context("spawn_blocking join error")?;
+
+        if let Err(e) = written {
+            self.ready = false;
+            return Err(e);
+        }
 
         self.ready = true;
         info!("Vector index built successfully.");
@@ -189,38 +292,30 @@ impl VectorStore {
         }
 
         let conn = Arc::clone(&self.conn);
-        let query_bytes = query_emb.as_bytes().to_vec();
+        let query_emb = query_emb.to_vec();
         let fetch_limit = if category.is_some() {
             top_k * CATEGORY_FILTER_MULTIPLIER
         } else {
             top_k
         };
         let category_owned = category.map(|s| s.to_string());
+        let min_score = self.min_score;
+        let distance_kind = self.distance;
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.lock().expect("mutex poisoned");
 
-            // KNN search via sqlite-vec
-            let mut knn_stmt = conn
-                .prepare(
-                    "SELECT v.rowid, v.distance
-                     FROM chunks_vec v
-                     WHERE v.embedding MATCH ?1
-                     ORDER BY v.distance
-                     LIMIT ?2",
-                )
-                .context("Failed to prepare KNN query")?;
-
-            let matches: Vec<(i64, f32)> = knn_stmt
-                .query_map(rusqlite::params![query_bytes, fetch_limit as i64], |row| {
-                    Ok((row.get(0)?, row.get(1)?))
-                })?
-                .filter_map(|r| r.ok())
-                .collect();
+            let matches = match distance_kind {
+                VectorDistance::Dot => dot_product_matches(&conn, &query_emb, fetch_limit)?,
+                VectorDistance::Cosine | VectorDistance::L2 => {
+                    knn_matches(&conn, query_emb.as_bytes(), fetch_limit)?
+                }
+            };
 
             let mut meta_stmt = conn
                 .prepare_cached(
-                    "SELECT text, file_path, category, topic, title, has_code, chunk_id
+                    "SELECT text, file_path, category, topic, title, has_code, chunk_id, custom,
+                            chunk_index, char_offset, breadcrumb
                      FROM chunks WHERE id = ?1",
                 )
                 .context("Failed to prepare metadata query")?;
@@ -236,18 +331,41 @@ impl VectorStore {
                         r.get::<_, String>(4)?,
                         r.get::<_, bool>(5)?,
                         r.get::<_, String>(6)?,
+                        r.get::<_, String>(7)?,
+                        r.get::<_, i64>(8)?,
+                        r.get::<_, i64>(9)?,
+                        r.get::<_, String>(10)?,
                     ))
                 });
 
-                if let Ok((text, file_path, cat, topic, title, has_code, chunk_id)) = row {
+                if let Ok((
+                    text,
+                    file_path,
+                    cat,
+                    topic,
+                    title,
+                    has_code,
+                    chunk_id,
+                    custom,
+                    chunk_index,
+                    char_offset,
+                    breadcrumb,
+                )) = row
+                {
                     if let Some(ref filter_cat) = category_owned {
                         if cat != *filter_cat {
                             continue;
                         }
                     }
 
-                    let score = 1.0 / (1.0 + *distance as f64);
-                    if score < DEFAULT_MIN_VECTOR_SCORE {
+                    let score = match distance_kind {
+                        // `distance` is the negated dot product.
+                        VectorDistance::Dot => 1.0 / (1.0 + (*distance as f64).exp()),
+                        VectorDistance::Cosine | VectorDistance::L2 => {
+                            1.0 / (1.0 + *distance as f64)
+                        }
+                    };
+                    if score < min_score {
                         continue;
                     }
                     // Use stored chunk_id, fall back to synthesized one for legacy data
@@ -266,6 +384,10 @@ impl VectorStore {
                             title,
                             has_code,
                             chunk_id,
+                            chunk_index: chunk_index as usize,
+                            char_offset: char_offset as usize,
+                            custom: serde_json::from_str(&custom).unwrap_or_default(),
+                            breadcrumb,
                         },
                     });
 
@@ -281,6 +403,59 @@ impl VectorStore {
         .context("spawn_blocking join error")?
     }
 
+    /// Mean of the embeddings of every chunk of `topic`, or `None` when the
+    /// topic has no chunks.
+    pub async fn topic_vector(
+        &self,
+        topic: &str,
+        category: Option<&str>,
+    ) -> Result<Option<Vec<f32>>> {
+        if !self.ready {
+            return Ok(None);
+        }
+        let conn = Arc::clone(&self.conn);
+        let topic = topic.to_string();
+        let category = category.map(|s| s.to_string());
+        let dim = self.dim;
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.lock().expect("mutex poisoned");
+            let mut id_stmt = conn
+                .prepare_cached(
+                    "SELECT id FROM chunks WHERE topic = ?1 AND (?2 IS NULL OR category = ?2)",
+                )
+                .context("Failed to prepare topic chunk query")?;
+            let ids: Vec<i64> = id_stmt
+                .query_map(rusqlite::params![topic, category], |r| r.get(0))?
+                .filter_map(|r| r.ok())
+                .collect();
+
+            let mut emb_stmt = conn
+                .prepare_cached("SELECT embedding FROM chunks_vec WHERE rowid = ?1")
+                .context("Failed to prepare embedding query")?;
+            let mut sum = vec![0.0f32; dim];
+            let mut count = 0usize;
+            for id in ids {
+                let Ok(bytes) = emb_stmt.query_row([id], |r| r.get::<_, Vec<u8>>(0)) else {
+                    continue;
+                };
+                // Stops at `dim`, dropping the padding of dot-product stores.
+                for (acc, v) in sum.iter_mut().zip(f32_values(&bytes)) {
+                    *acc += v;
+                }
+                count += 1;
+            }
+            if count == 0 {
+                return Ok(None);
+            }
+            for v in &mut sum {
+                *v /= count as f32;
+            }
+            Ok(Some(sum))
+        })
+        .await
+        .context("spawn_blocking join error")?
+    }
+
     /// Fetch chunk text by its chunk_id (e.g. "file_path#idx").
     pub async fn get_chunk_text(&self, chunk_id: &str) -> Option<String> {
         if !self.ready {
@@ -300,6 +475,216 @@ impl VectorStore {
     }
 }
 
+/// KNN search via sqlite-vec, nearest first; the metric is the one the
+/// `chunks_vec` table was created with.
+fn knn_matches(conn: &Connection, query_bytes: &[u8], limit: usize) -> Result<Vec<(i64, f32)>> {
+    let mut knn_stmt = conn
+        .prepare(
+            "SELECT v.rowid, v.distance
+             FROM chunks_vec v
+             WHERE v.embedding MATCH ?1
+             ORDER BY v.distance
+             LIMIT ?2",
+        )
+        .context("Failed to prepare KNN query")?;
+
+    let matches = knn_stmt
+        .query_map(rusqlite::params![query_bytes, limit as i64], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(matches)
+}
+
+/// Maximum inner product search through vec0's L2 KNN. Stored embeddings
+/// carry an extra component `sqrt(M² - |x|²)`, where `M` is the largest
+/// norm, so `|q' - x'|² = |q|² + M² - 2·q·x` for the query padded with 0 and
+/// the nearest neighbour is the largest dot product. Distances are negated
+/// dot products, so the best match still comes first.
+fn dot_product_matches(conn: &Connection, query: &[f32], limit: usize) -> Result<Vec<(i64, f32)>> {
+    let max_norm: f32 = conn
+        .query_row("SELECT max_norm FROM vec_params", [], |r| r.get(0))
+        .context("Vector store lacks dot-product parameters; rebuild the index")?;
+    let mut padded = query.to_vec();
+    padded.push(0.0);
+    let query_norm_sq: f32 = query.iter().map(|v| v * v).sum();
+    let matches = knn_matches(conn, padded.as_bytes(), limit)?
+        .into_iter()
+        .map(|(rowid, l2)| {
+            let dot = (query_norm_sq + max_norm * max_norm - l2 * l2) / 2.0;
+            (rowid, -dot)
+        })
+        .collect();
+    Ok(matches)
+}
+
+/// Pad each embedding with the component that turns dot-product search into
+/// L2 search (see [`dot_product_matches`]), returning them with the largest
+/// norm.
+fn pad_for_dot_product(embeddings: &[Vec<f32>]) -> (Vec<Vec<f32>>, f32) {
+    let norms_sq: Vec<f32> = embeddings
+        .iter()
+        .map(|e| e.iter().map(|v| v * v).sum())
+        .collect();
+    let max_norm_sq = norms_sq.iter().copied().fold(0.0f32, f32::max);
+    let padded = embeddings
+        .iter()
+        .zip(&norms_sq)
+        .map(|(e, norm_sq)| {
+            let mut e = e.clone();
+            e.push((max_norm_sq - norm_sq).max(0.0).sqrt());
+            e
+        })
+        .collect();
+    (padded, max_norm_sq.sqrt())
+}
+
+fn f32_values(bytes: &[u8]) -> impl Iterator<Item = f32> + '_ {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Front-matter fields as stored in the `custom` column; empty when none.
+fn custom_json(custom: &HashMap<String, String>) -> String {
+    if custom.is_empty() {
+        String::new()
+    } else {
+        serde_json::to_string(custom).unwrap_or_default()
+    }
+}
+
+/// Recreate the tables and insert all rows, committing every
+/// `insert_batch_size` rows. Rowids are derived from chunk positions, so a
+/// retry after a failure writes exactly the same data.
+fn write_tables(
+    conn: &Connection,
+    dim: usize,
+    distance: VectorDistance,
+    rows: &[ChunkRow],
+    embeddings: &[Vec<f32>],
+    insert_batch_size: usize,
+) -> Result<()> {
+    conn.execute_batch(
+        "PRAGMA user_version = 0; DROP TABLE IF EXISTS chunks_vec; DROP TABLE IF EXISTS chunks;
+         DROP TABLE IF EXISTS vec_params;",
+    )
+    .context("Failed to drop old tables")?;
+
+    // vec0 defaults to L2; dot product is reduced to L2 over padded vectors.
+    let metric = match distance {
+        VectorDistance::Cosine => " distance_metric=cosine",
+        VectorDistance::L2 | VectorDistance::Dot => "",
+    };
+    let (padded, vec_dim) = if distance == VectorDistance::Dot {
+        let (padded, max_norm) = pad_for_dot_product(embeddings);
+        conn.execute_batch("CREATE TABLE vec_params (max_norm REAL NOT NULL)")
+            .context("Failed to create vec_params table")?;
+        conn.execute("INSERT INTO vec_params (max_norm) VALUES (?1)", [max_norm])
+            .context("Failed to record the largest embedding norm")?;
+        (Some(padded), dim + 1)
+    } else {
+        (None, dim)
+    };
+    let embeddings = padded.as_deref().unwrap_or(embeddings);
+
+    conn.execute_batch(&format!(
+        "CREATE TABLE chunks (
+            id        INTEGER PRIMARY KEY,
+            text      TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            category  TEXT NOT NULL,
+            topic     TEXT NOT NULL,
+            title     TEXT NOT NULL,
+            has_code  INTEGER NOT NULL,
+            chunk_id  TEXT NOT NULL DEFAULT '',
+            custom    TEXT NOT NULL DEFAULT '',
+            chunk_index INTEGER NOT NULL DEFAULT 0,
+            char_offset INTEGER NOT NULL DEFAULT 0,
+            breadcrumb  TEXT NOT NULL DEFAULT ''
+        );
+        CREATE INDEX idx_chunks_category ON chunks(category);
+        CREATE INDEX idx_chunks_chunk_id ON chunks(chunk_id);
+        CREATE VIRTUAL TABLE chunks_vec USING vec0(
+            embedding float[{vec_dim}]{metric}
+        );"
+    ))
+    .context("Failed to create tables")?;
+
+    let mut insert_chunk = conn
+        .prepare_cached(
+            "INSERT INTO chunks (id, text, file_path, category, topic, title, has_code, chunk_id, custom,
+                                 chunk_index, char_offset, breadcrumb)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        )
+        .context("Failed to prepare chunk insert")?;
+
+    let mut insert_vec = conn
+        .prepare_cached("INSERT INTO chunks_vec (rowid, embedding) VALUES (?1, ?2)")
+        .context("Failed to prepare vec insert")?;
+
+    let batch_count = rows.len().div_ceil(insert_batch_size);
+    for (batch_idx, (row_batch, emb_batch)) in rows
+        .chunks(insert_batch_size)
+        .zip(embeddings.chunks(insert_batch_size))
+        .enumerate()
+    {
+        conn.execute_batch("BEGIN")?;
+        let offset = batch_idx * insert_batch_size;
+        for (
+            i,
+            (
+                (
+                    text,
+                    file_path,
+                    category,
+                    topic,
+                    title,
+                    has_code,
+                    chunk_id,
+                    custom,
+                    chunk_index,
+                    char_offset,
+                    breadcrumb,
+                ),
+                emb,
+            ),
+        ) in row_batch.iter().zip(emb_batch).enumerate()
+        {
+            let rowid = (offset + i + 1) as i64;
+            insert_chunk.execute(rusqlite::params![
+                rowid,
+                text,
+                file_path,
+                category,
+                topic,
+                title,
+                *has_code as i32,
+                chunk_id,
+                custom,
+                chunk_index,
+                char_offset,
+                breadcrumb,
+            ])?;
+            insert_vec
+                .execute(rusqlite::params![rowid, emb.as_bytes()])
+                .with_context(|| format!("Failed to insert embedding for row {rowid}"))?;
+        }
+        conn.execute_batch("COMMIT")?;
+        info!(
+            "Committed vector batch {}/{} ({} rows)",
+            batch_idx + 1,
+            batch_count,
+            row_batch.len()
+        );
+    }
+
+    conn.execute_batch(&format!("PRAGMA user_version = {STORE_COMPLETE}"))
+        .context("Failed to mark the vector store complete")?;
+    Ok(())
+}
+
 /// Parse chunk_id format `"file_path#idx"`.
 fn parse_chunk_id(chunk_id: &str) -> Option<(&str, usize)> {
     let hash_pos = chunk_id.rfind('#')?;
@@ -353,3 +738,128 @@ pub async fn expand_with_window(
     }
     expanded
 }
+
+/// Bring a store built by an older version up to the current schema.
+fn add_missing_columns(conn: &Connection) {
+    // Stores built before front-matter fields were recorded lack the column.
+    if conn.prepare("SELECT custom FROM chunks LIMIT 0").is_err() {
+        let _ = conn.execute_batch("ALTER TABLE chunks ADD COLUMN custom TEXT NOT NULL DEFAULT ''");
+    }
+    // Likewise for chunk positions, which read back as 0 for older stores.
+    if conn
+        .prepare("SELECT chunk_index FROM chunks LIMIT 0")
+        .is_err()
+    {
+        let _ = conn.execute_batch(
+            "ALTER TABLE chunks ADD COLUMN chunk_index INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE chunks ADD COLUMN char_offset INTEGER NOT NULL DEFAULT 0;",
+        );
+    }
+    if conn
+        .prepare("SELECT breadcrumb FROM chunks LIMIT 0")
+        .is_err()
+    {
+        let _ =
+            conn.execute_batch("ALTER TABLE chunks ADD COLUMN breadcrumb TEXT NOT NULL DEFAULT ''");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(text: &str) -> ChunkRow {
+        (
+            text.to_string(),
+            "syntax/a.md".to_string(),
+            "syntax".to_string(),
+            "a".to_string(),
+            "a".to_string(),
+            false,
+            String::new(),
+            String::new(),
+            0,
+            0,
+            String::new(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_build_is_not_ready() {
+        let tmp = tempfile::tempdir().unwrap();
+        drop(VectorStore::open(tmp.path(), 2).await.unwrap());
+
+        // The first batch commits, the second fails on a wrong-sized vector,
+        // as if the build died part-way; no cleanup runs.
+        let conn = Connection::open(tmp.path().join("vectors.db")).unwrap();
+        let rows = vec![row("one"), row("two")];
+        let embeddings = vec![vec![0.1, 0.2], vec![0.1, 0.2, 0.3]];
+        assert!(write_tables(&conn, 2, VectorDistance::L2, &rows, &embeddings, 1).is_err());
+        let committed: i64 = conn
+            .query_row("SELECT COUNT(*) FROM chunks", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(committed, 1);
+        drop(conn);
+
+        assert!(!VectorStore::open(tmp.path(), 2).await.unwrap().is_ready());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_open_read_only_on_read_only_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let store_dir = tmp.path().join("vector_db");
+        drop(VectorStore::open(&store_dir, 2).await.unwrap());
+        let conn = Connection::open(store_dir.join("vectors.db")).unwrap();
+        write_tables(
+            &conn,
+            2,
+            VectorDistance::L2,
+            &[row("one")],
+            &[vec![0.1, 0.2]],
+            1,
+        )
+        .unwrap();
+        drop(conn);
+        // Like a read-only volume mount: nothing may be created or altered.
+        std::fs::set_permissions(
+            store_dir.join("vectors.db"),
+            PermissionsExt::from_mode(0o444),
+        )
+        .unwrap();
+        std::fs::set_permissions(&store_dir, PermissionsExt::from_mode(0o555)).unwrap();
+
+        let vs = VectorStore::open_read_only(&store_dir, 2).await;
+        std::fs::set_permissions(&store_dir, PermissionsExt::from_mode(0o755)).unwrap();
+        let vs = vs.unwrap();
+        assert!(vs.is_ready());
+        assert_eq!(vs.chunk_count().await.unwrap(), 1);
+        assert!(VectorStore::open_read_only(&tmp.path().join("missing"), 2)
+            .await
+            .is_err());
+        assert!(!tmp.path().join("missing").exists());
+    }
+
+    #[tokio::test]
+    async fn test_dot_product_ranks_by_inner_product() {
+        let tmp = tempfile::tempdir().unwrap();
+        drop(VectorStore::open(tmp.path(), 2).await.unwrap());
+        let conn = Connection::open(tmp.path().join("vectors.db")).unwrap();
+        // L2 would pick the first row; the inner product favours the longer one.
+        let rows = vec![row("near"), row("long"), row("opposite")];
+        let embeddings = vec![vec![1.0, 0.0], vec![3.0, 3.0], vec![-2.0, 0.0]];
+        write_tables(&conn, 2, VectorDistance::Dot, &rows, &embeddings, 8).unwrap();
+
+        let matches = dot_product_matches(&conn, &[1.0, 0.0], 3).unwrap();
+        let ids: Vec<i64> = matches.iter().map(|m| m.0).collect();
+        assert_eq!(ids, vec![2, 1, 3]);
+        for ((_, distance), expected) in matches.iter().zip([-3.0f32, -1.0, 2.0]) {
+            assert!(
+                (distance - expected).abs() < 1e-3,
+                "{distance} != {expected}"
+            );
+        }
+    }
+}
@@ -4,22 +4,25 @@ use std::path::Path;
 use std::process::Command;
 use tempfile::TempDir;
 
+/// Runs `git <args>` in `dir` as the test identity, panicking on failure.
+pub fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_AUTHOR_NAME", "test")
+        .env("GIT_AUTHOR_EMAIL", "test@test.com")
+        .env("GIT_COMMITTER_NAME", "test")
+        .env("GIT_COMMITTER_EMAIL", "test@test.com")
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
 /// Runs `git init`, `git add .`, `git commit` in `dir`.
 pub fn git_init_and_commit(dir: &Path) {
-    let run = |args: &[&str]| {
-        Command::new("git")
-            .args(args)
-            .current_dir(dir)
-            .env("GIT_AUTHOR_NAME", "test")
-            .env("GIT_AUTHOR_EMAIL", "test@test.com")
-            .env("GIT_COMMITTER_NAME", "test")
-            .env("GIT_COMMITTER_EMAIL", "test@test.com")
-            .status()
-            .unwrap();
-    };
-    run(&["init"]);
-    run(&["add", "."]);
-    run(&["commit", "-m", "initial commit"]);
+    git(dir, &["init"]);
+    git(dir, &["add", "."]);
+    git(dir, &["commit", "-m", "initial commit"]);
 }
 
 /// Create a test git repo with the standard cangjie doc structure.
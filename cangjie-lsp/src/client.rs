@@ -18,11 +18,11 @@ use crate::transport::{
 };
 use crate::types::{
     CallHierarchyIncomingCallsParams, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
-    ClientInfo, DidChangeTextDocumentParams, DidOpenTextDocumentParams, DocumentSymbolParams,
-    GotoDefinitionParams, HoverParams, InitializeParams, InitializedParams, Position,
-    ReferenceContext, ReferenceParams, TextDocumentContentChangeEvent, TextDocumentIdentifier,
-    TextDocumentItem, TextDocumentPositionParams, TraceValue, TypeHierarchyPrepareParams,
-    TypeHierarchySubtypesParams, TypeHierarchySupertypesParams, Uri,
+    ClientInfo, DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DocumentSymbolParams, GotoDefinitionParams, HoverParams, InitializeParams, InitializedParams,
+    Position, ReferenceContext, ReferenceParams, TextDocumentContentChangeEvent,
+    TextDocumentIdentifier, TextDocumentItem, TextDocumentPositionParams, TraceValue,
+    TypeHierarchyPrepareParams, TypeHierarchySubtypesParams, TypeHierarchySupertypesParams, Uri,
     VersionedTextDocumentIdentifier, WorkDoneProgressParams, WorkspaceFolder,
     WorkspaceSymbolParams,
 };
@@ -30,11 +30,13 @@ use crate::utils::{path_to_uri, uri_to_path};
 
 mod capabilities;
 mod command;
+mod open_files;
 
-pub use capabilities::SupportedOperation;
+pub use capabilities::{ServerReport, SupportedOperation};
 
-use capabilities::{build_client_capabilities, supports_capability};
+use capabilities::{build_client_capabilities, supports_pull_diagnostics};
 use command::build_shell_command;
+use open_files::OpenFiles;
 
 const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
 const DIAGNOSTIC_WAIT_TIMEOUT: Duration = Duration::from_secs(10);
@@ -42,7 +44,7 @@ const DIAGNOSTIC_WAIT_TIMEOUT: Duration = Duration::from_secs(10);
 #[derive(Debug, Clone)]
 enum ClientRuntimeState {
     Starting,
-    Ready { raw_capabilities: Box<Value> },
+    Ready { report: Box<ServerReport> },
     Shutdown,
 }
 
@@ -94,13 +96,13 @@ fn next_file_sync_action(previous_version: Option<i32>, content_changed: bool) -
 
 pub struct CangjieClient {
     client: jsonrpsee::core::client::Client,
-    open_files: Mutex<HashMap<String, i32>>,
-    file_hashes: Mutex<HashMap<String, u64>>,
+    open_files: Mutex<OpenFiles>,
     diagnostics: Arc<Mutex<HashMap<String, Vec<Value>>>>,
     diagnostic_versions: Arc<Mutex<HashMap<String, u64>>>,
     diagnostics_notify: Arc<Notify>,
     runtime: StdRwLock<ClientRuntimeState>,
     running: Arc<AtomicBool>,
+    max_workspace_symbols: usize,
 }
 
 impl CangjieClient {
@@ -134,6 +136,7 @@ impl CangjieClient {
         let diagnostic_versions = Arc::new(Mutex::new(HashMap::<String, u64>::new()));
         let diagnostics_notify = Arc::new(Notify::new());
         let running = Arc::new(AtomicBool::new(true));
+        let logs = crate::workspace_logs(&settings.workspace_path, settings.log_capture_lines);
 
         tokio::spawn(stdout_reader_task(stdout, incoming_tx, running.clone()));
 
@@ -154,18 +157,18 @@ impl CangjieClient {
 
         // Stdin writer: frames outbound messages with Content-Length headers.
         tokio::spawn(stdin_task(stdin, outbound_rx, running.clone()));
-        tokio::spawn(stderr_task(stderr));
+        tokio::spawn(stderr_task(stderr, logs));
         tokio::spawn(process_monitor(child, running.clone()));
 
         let client = Self {
             client: rpc_client,
-            open_files: Mutex::new(HashMap::new()),
-            file_hashes: Mutex::new(HashMap::new()),
+            open_files: Mutex::new(OpenFiles::new(settings.max_open_files)),
             diagnostics,
             diagnostic_versions,
             diagnostics_notify,
             runtime: StdRwLock::new(ClientRuntimeState::Starting),
             running,
+            max_workspace_symbols: settings.max_workspace_symbols,
         };
 
         client.lsp_initialize(settings, init_options).await?;
@@ -220,13 +223,10 @@ impl CangjieClient {
             .await
             .map_err(|e| anyhow::anyhow!("LSP initialized notification failed: {e}"))?;
 
-        let raw_capabilities = init_result_value
-            .get("capabilities")
-            .cloned()
-            .unwrap_or(Value::Null);
+        let report = ServerReport::from_initialize_result(&init_result_value);
         let mut runtime = self.runtime.write().unwrap_or_else(|e| e.into_inner());
         *runtime = ClientRuntimeState::Ready {
-            raw_capabilities: Box::new(raw_capabilities),
+            report: Box::new(report),
         };
         info!("LSP client initialized successfully");
         Ok(())
@@ -247,15 +247,38 @@ impl CangjieClient {
         self.is_initialized() && self.is_running()
     }
 
+    /// Cap on ranked `workspace_symbol` results (see `LSPSettings`).
+    pub fn max_workspace_symbols(&self) -> usize {
+        self.max_workspace_symbols
+    }
+
+    /// Number of documents currently open on the server.
+    pub async fn open_file_count(&self) -> usize {
+        self.open_files.lock().await.len()
+    }
+
+    /// The server's `initialize` response; `None` until initialization completes.
+    pub fn server_report(&self) -> Option<ServerReport> {
+        match &*self.runtime.read().unwrap_or_else(|e| e.into_inner()) {
+            ClientRuntimeState::Ready { report } => Some(report.as_ref().clone()),
+            _ => None,
+        }
+    }
+
     pub fn supports(&self, operation: SupportedOperation) -> bool {
         let runtime = self.runtime.read().unwrap_or_else(|e| e.into_inner());
-        let ClientRuntimeState::Ready {
-            raw_capabilities, ..
-        } = &*runtime
-        else {
+        let ClientRuntimeState::Ready { report } = &*runtime else {
             return false;
         };
-        supports_capability(raw_capabilities.as_ref(), operation)
+        report.supports(operation)
+    }
+
+    fn supports_pull_diagnostics(&self) -> bool {
+        let runtime = self.runtime.read().unwrap_or_else(|e| e.into_inner());
+        matches!(
+            &*runtime,
+            ClientRuntimeState::Ready { report } if supports_pull_diagnostics(&report.capabilities)
+        )
     }
 
     // -- Request / notification helpers --------------------------------------
@@ -320,9 +343,9 @@ impl CangjieClient {
             .await
             .context("Failed to read file")?;
         let content_hash = Self::hash_content(&content);
-        let previous_version = self.open_files.lock().await.get(&uri_str).copied();
-        let previous_hash = self.file_hashes.lock().await.get(&uri_str).copied();
-        let content_changed = previous_hash != Some(content_hash);
+        let previous = self.open_files.lock().await.touch(&uri_str);
+        let previous_version = previous.map(|(version, _)| version);
+        let content_changed = previous.map(|(_, hash)| hash) != Some(content_hash);
         let sync_action = next_file_sync_action(previous_version, content_changed);
 
         match sync_action {
@@ -338,11 +361,12 @@ impl CangjieClient {
 
                 debug!("[LSP] textDocument/didOpen: {}", uri_str);
                 self.notify("textDocument/didOpen", &params).await?;
-                self.open_files
+                let evicted = self
+                    .open_files
                     .lock()
                     .await
-                    .insert(uri_str.clone(), version);
-                self.file_hashes.lock().await.insert(uri_str, content_hash);
+                    .record(uri_str, version, content_hash);
+                self.close_files(evicted).await;
             }
             FileSyncAction::DidChange { version } => {
                 let params = DidChangeTextDocumentParams {
@@ -359,17 +383,38 @@ impl CangjieClient {
 
                 debug!("[LSP] textDocument/didChange: {}", uri_str);
                 self.notify("textDocument/didChange", &params).await?;
-                self.open_files
+                let evicted = self
+                    .open_files
                     .lock()
                     .await
-                    .insert(uri_str.clone(), version);
-                self.file_hashes.lock().await.insert(uri_str, content_hash);
+                    .record(uri_str, version, content_hash);
+                self.close_files(evicted).await;
             }
             FileSyncAction::Noop => {}
         }
         Ok(())
     }
 
+    /// Send `didClose` for documents evicted from the open-file cache.
+    async fn close_files(&self, uris: Vec<String>) {
+        for uri_str in uris {
+            let uri = match parse_uri(&uri_str) {
+                Ok(uri) => uri,
+                Err(e) => {
+                    warn!("Cannot close evicted document: {e}");
+                    continue;
+                }
+            };
+            debug!("[LSP] textDocument/didClose: {}", uri_str);
+            let params = DidCloseTextDocumentParams {
+                text_document: TextDocumentIdentifier { uri },
+            };
+            if let Err(e) = self.notify("textDocument/didClose", &params).await {
+                warn!("LSP didClose for {uri_str} failed: {e}");
+            }
+        }
+    }
+
     // -- LSP operations ------------------------------------------------------
 
     pub async fn definition(&self, file_path: &str, line: u32, character: u32) -> Result<Value> {
@@ -606,7 +651,35 @@ impl CangjieClient {
         }
     }
 
+    /// Ask for a file's diagnostics with `textDocument/diagnostic`. Returns
+    /// `None` when the server did not send a full report.
+    async fn pull_diagnostics(&self, file_path: &str) -> Option<Vec<Value>> {
+        let uri = parse_uri(&path_to_uri(Path::new(file_path))).ok()?;
+        let params = serde_json::json!({ "textDocument": { "uri": uri.as_str() } });
+        match self
+            .document_request("textDocument/diagnostic", &params, &uri)
+            .await
+        {
+            Ok(report) => crate::tools::parse_pull_diagnostics(&report),
+            Err(e) => {
+                debug!("Pull diagnostics failed, waiting for published diagnostics: {e}");
+                None
+            }
+        }
+    }
+
     pub async fn get_diagnostics(&self, file_path: &str) -> Result<DiagnosticsResponse> {
+        // Files the server already compiled as part of the workspace can be
+        // answered directly, without opening them and waiting for a push.
+        if self.supports_pull_diagnostics() {
+            if let Some(diagnostics) = self.pull_diagnostics(file_path).await {
+                return Ok(DiagnosticsResponse {
+                    diagnostics,
+                    status: DiagnosticsStatus::Ready,
+                });
+            }
+        }
+
         self.ensure_open(file_path).await?;
         let lookup_key = Self::diagnostics_key(file_path);
         let previous_version = self.diagnostics_version(&lookup_key).await;
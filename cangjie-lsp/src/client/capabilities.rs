@@ -1,6 +1,8 @@
+use serde::Serialize;
 use serde_json::Value;
 
 use crate::types::ClientCapabilities;
+use crate::utils::PositionEncoding;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SupportedOperation {
@@ -16,6 +18,33 @@ pub enum SupportedOperation {
     TypeSubtypes,
 }
 
+/// What the server reported in its `initialize` response.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ServerReport {
+    /// `serverInfo` (name and optional version), when the server sent it.
+    pub server_info: Option<Value>,
+    /// The raw `capabilities` object; `null` when the response had none.
+    pub capabilities: Value,
+    /// Unit the server counts `Position.character` in.
+    pub position_encoding: PositionEncoding,
+}
+
+impl ServerReport {
+    pub(super) fn from_initialize_result(result: &Value) -> Self {
+        let capabilities = result.get("capabilities").cloned().unwrap_or(Value::Null);
+        Self {
+            server_info: result.get("serverInfo").filter(|v| !v.is_null()).cloned(),
+            position_encoding: PositionEncoding::from_capabilities(&capabilities),
+            capabilities,
+        }
+    }
+
+    /// Whether the reported capabilities cover `operation`.
+    pub fn supports(&self, operation: SupportedOperation) -> bool {
+        supports_capability(&self.capabilities, operation)
+    }
+}
+
 // -- Client capabilities -----------------------------------------------------
 
 pub(super) fn build_client_capabilities() -> ClientCapabilities {
@@ -314,7 +343,7 @@ pub(super) fn build_client_capabilities() -> ClientCapabilities {
                 "parser": "marked",
                 "version": "1.1.0"
             },
-            "positionEncodings": ["utf-16"]
+            "positionEncodings": ["utf-16", "utf-8", "utf-32"]
         },
         "notebookDocument": {
             "synchronization": {
@@ -333,6 +362,11 @@ fn json_capability_enabled(raw_capabilities: &Value, key: &str) -> bool {
         .is_some_and(|value| !value.is_null() && *value != Value::Bool(false))
 }
 
+/// Whether the server answers `textDocument/diagnostic` (pull diagnostics).
+pub(super) fn supports_pull_diagnostics(raw_capabilities: &Value) -> bool {
+    json_capability_enabled(raw_capabilities, "diagnosticProvider")
+}
+
 pub(super) fn supports_capability(raw_capabilities: &Value, operation: SupportedOperation) -> bool {
     match operation {
         SupportedOperation::Definition => {
@@ -381,5 +415,40 @@ mod tests {
             &raw_capabilities,
             SupportedOperation::Diagnostics
         ));
+        assert!(!supports_pull_diagnostics(&raw_capabilities));
+        assert!(supports_pull_diagnostics(&serde_json::json!({
+            "diagnosticProvider": {"interFileDependencies": true, "workspaceDiagnostics": false}
+        })));
+    }
+
+    #[test]
+    fn test_server_report_from_initialize_result() {
+        let result = serde_json::json!({
+            "capabilities": {"hoverProvider": true, "callHierarchyProvider": false},
+            "serverInfo": {"name": "LSPServer", "version": "1.0.0"}
+        });
+        let report = ServerReport::from_initialize_result(&result);
+        assert_eq!(report.server_info.unwrap()["name"], "LSPServer");
+        assert!(report.supports(SupportedOperation::Hover));
+        assert!(!report.supports(SupportedOperation::IncomingCalls));
+
+        assert_eq!(report.position_encoding, PositionEncoding::Utf16);
+
+        let utf8 = ServerReport::from_initialize_result(&serde_json::json!({
+            "capabilities": {"positionEncoding": "utf-8"}
+        }));
+        assert_eq!(utf8.position_encoding, PositionEncoding::Utf8);
+
+        let bare = ServerReport::from_initialize_result(&serde_json::json!({}));
+        assert_eq!(bare, ServerReport::default());
+    }
+
+    #[test]
+    fn test_client_offers_every_converted_encoding() {
+        let caps = serde_json::to_value(build_client_capabilities()).unwrap();
+        assert_eq!(
+            caps["general"]["positionEncodings"],
+            serde_json::json!(["utf-16", "utf-8", "utf-32"])
+        );
     }
 }
@@ -1,8 +1,12 @@
 use std::collections::HashMap;
+use std::io::Read;
+use std::process::Stdio;
+use std::sync::mpsc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use tokio::process::Command;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::config::LSPSettings;
 
@@ -55,6 +59,10 @@ fn escape_powershell(s: &str) -> String {
     format!("'{}'", s.replace('\'', "''"))
 }
 
+/// Most PowerShell output accepted when capturing the envsetup environment;
+/// a real environment dump is a few tens of kilobytes.
+const MAX_ENVSETUP_OUTPUT_BYTES: u64 = 4 * 1024 * 1024;
+
 /// Captures the full environment (inherited + SDK modifications) after sourcing
 /// envsetup.ps1 in a separate PowerShell process, for `env_clear() + envs()` on
 /// the LSP server Command.
@@ -71,33 +79,119 @@ fn capture_envsetup_env(settings: &LSPSettings) -> Result<HashMap<String, String
     );
 
     info!("Capturing environment from envsetup.ps1");
-    let output = std::process::Command::new("powershell")
+    let mut child = std::process::Command::new("powershell")
         .args(["-NoProfile", "-ExecutionPolicy", "Bypass", "-Command"])
         .arg(&script)
-        .output()
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
         .context("Failed to run PowerShell to capture envsetup environment")?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = child.stdout.take().context("No PowerShell stdout")?;
+    let stderr = child.stderr.take().context("No PowerShell stderr")?;
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(read_capped(stdout, MAX_ENVSETUP_OUTPUT_BYTES));
+    });
+    let stderr_reader = std::thread::spawn(move || read_capped(stderr, MAX_ENVSETUP_OUTPUT_BYTES));
+
+    let timeout = Duration::from_millis(settings.envsetup_timeout_ms);
+    let stdout = match rx.recv_timeout(timeout) {
+        Ok(read) => read.context("Failed to read PowerShell envsetup output")?,
+        Err(_) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!(
+                "envsetup.ps1 did not finish within {timeout:?}; check {} or raise {}",
+                envsetup.display(),
+                crate::config::ENVSETUP_TIMEOUT_ENV
+            );
+        }
+    };
+    let Some(stdout) = stdout else {
+        let _ = child.kill();
+        let _ = child.wait();
+        anyhow::bail!(
+            "envsetup.ps1 produced more than {MAX_ENVSETUP_OUTPUT_BYTES} bytes of output; \
+             refusing to use it as the LSP environment"
+        );
+    };
+
+    let status = child
+        .wait()
+        .context("Failed to wait for PowerShell envsetup")?;
+    if !status.success() {
+        let stderr = stderr_reader
+            .join()
+            .ok()
+            .and_then(|read| read.ok().flatten())
+            .unwrap_or_default();
         return Err(anyhow::anyhow!(
             "PowerShell envsetup failed (exit {}): {}",
-            output.status,
-            stderr.trim()
+            status,
+            String::from_utf8_lossy(&stderr).trim()
         ));
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    let env = parse_env_dump(&String::from_utf8_lossy(&stdout));
+    for problem in check_captured_env(&env, &sdk_path) {
+        warn!("envsetup.ps1: {problem}");
+    }
+
+    info!("Captured {} environment variables from envsetup", env.len());
+    Ok(env)
+}
+
+/// Read all of `reader`, or `None` once it yields more than `cap` bytes.
+fn read_capped(reader: impl Read, cap: u64) -> std::io::Result<Option<Vec<u8>>> {
+    let mut buf = Vec::new();
+    reader.take(cap + 1).read_to_end(&mut buf)?;
+    Ok((buf.len() as u64 <= cap).then_some(buf))
+}
+
+/// Parse `NAME=value` lines. Lines without `=` or with an empty name (such
+/// as the continuation of a multi-line value) are skipped.
+fn parse_env_dump(dump: &str) -> HashMap<String, String> {
     let mut env = HashMap::new();
-    for line in stdout.lines() {
-        if let Some((key, value)) = line.split_once('=') {
-            if !key.is_empty() {
+    let mut rejected = 0;
+    for line in dump.lines().filter(|line| !line.trim().is_empty()) {
+        match line.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
                 env.insert(key.to_string(), value.to_string());
             }
+            _ => rejected += 1,
         }
     }
+    if rejected > 0 {
+        warn!("Skipped {rejected} malformed line(s) in the envsetup environment dump");
+    }
+    env
+}
 
-    info!("Captured {} environment variables from envsetup", env.len());
-    Ok(env)
+/// What the captured environment is missing for the LSP server to start:
+/// `CANGJIE_HOME`, and a `PATH` that includes the SDK.
+fn check_captured_env(env: &HashMap<String, String>, sdk_path: &str) -> Vec<String> {
+    let get = |name: &str| {
+        env.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+            .filter(|v| !v.trim().is_empty())
+    };
+    let mut problems = Vec::new();
+    if get("CANGJIE_HOME").is_none() {
+        problems.push("CANGJIE_HOME was not captured".to_string());
+    }
+    match get("PATH") {
+        None => problems.push("PATH was not captured".to_string()),
+        Some(path) => {
+            let sdk = sdk_path.trim_end_matches(['\\', '/']).to_ascii_lowercase();
+            if !path.to_ascii_lowercase().contains(&sdk) {
+                problems.push(format!("PATH does not include the SDK at {sdk_path}"));
+            }
+        }
+    }
+    problems
 }
 
 /// Spawns LSPServer.exe directly (no PowerShell wrapper for stdio) using
@@ -140,6 +234,10 @@ fn build_windows_direct_command(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{
+        DEFAULT_ENVSETUP_TIMEOUT_MS, DEFAULT_LOG_CAPTURE_LINES, DEFAULT_MAX_OPEN_FILES,
+        DEFAULT_MAX_WORKSPACE_SYMBOLS,
+    };
     use std::path::PathBuf;
 
     fn test_settings(sdk: &str) -> LSPSettings {
@@ -149,7 +247,11 @@ mod tests {
             log_enabled: false,
             log_path: None,
             init_timeout_ms: 30000,
+            envsetup_timeout_ms: DEFAULT_ENVSETUP_TIMEOUT_MS,
             disable_auto_import: false,
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+            log_capture_lines: DEFAULT_LOG_CAPTURE_LINES,
+            max_workspace_symbols: DEFAULT_MAX_WORKSPACE_SYMBOLS,
         }
     }
 
@@ -176,6 +278,36 @@ mod tests {
         assert_eq!(escape_powershell("a'b'c"), "'a''b''c'");
     }
 
+    #[test]
+    fn test_parse_env_dump_reads_variables() {
+        let env =
+            parse_env_dump("CANGJIE_HOME=C:\\sdk\r\nPath=C:\\sdk\\bin;C:\\Windows\r\nOPTS=a=b\r\n");
+        assert_eq!(env.len(), 3);
+        assert_eq!(env["CANGJIE_HOME"], "C:\\sdk");
+        assert_eq!(env["Path"], "C:\\sdk\\bin;C:\\Windows");
+        assert_eq!(env["OPTS"], "a=b");
+        assert!(check_captured_env(&env, "C:\\sdk\\").is_empty());
+    }
+
+    #[test]
+    fn test_parse_env_dump_rejects_malformed_lines() {
+        let env = parse_env_dump("PATH=/usr/bin\nsecond line of a value\n=orphan\n\n");
+        assert_eq!(env.len(), 1);
+        assert_eq!(env["PATH"], "/usr/bin");
+
+        let problems = check_captured_env(&env, "/opt/sdk");
+        assert!(problems.iter().any(|p| p.contains("CANGJIE_HOME")));
+        assert!(problems
+            .iter()
+            .any(|p| p.contains("does not include the SDK")));
+    }
+
+    #[test]
+    fn test_read_capped_rejects_oversized_output() {
+        assert_eq!(read_capped(&b"abc"[..], 3).unwrap(), Some(b"abc".to_vec()));
+        assert_eq!(read_capped(&b"abcd"[..], 3).unwrap(), None);
+    }
+
     #[cfg(not(windows))]
     mod unix_tests {
         use super::*;
@@ -210,6 +342,27 @@ mod tests {
             assert!(script.contains("\"$PATH\""));
         }
 
+        #[test]
+        fn test_build_unix_command_prepends_cangjie_path() {
+            let tmp = tempfile::TempDir::new().unwrap();
+            let mut settings = test_settings("/opt/cangjie-sdk");
+            settings.workspace_path = tmp.path().to_path_buf();
+            temp_env::with_var(
+                crate::config::CANGJIE_PATH_ENV,
+                Some("/opt/extra/bin:/opt/extra/lib"),
+                || {
+                    let (_options, require_path) = crate::config::build_init_options(&settings);
+                    let cmd = build_unix_command(&settings, &require_path).unwrap();
+                    let args: Vec<_> = cmd.as_std().get_args().collect();
+                    let script = args[1].to_string_lossy();
+                    assert!(
+                        script.contains("export PATH=/opt/extra/bin:/opt/extra/lib:\"$PATH\""),
+                        "CANGJIE_PATH should prefix PATH, got: {script}"
+                    );
+                },
+            );
+        }
+
         #[test]
         fn test_build_unix_command_with_spaces_in_path() {
             let settings = test_settings("/opt/my sdk/cangjie");
@@ -230,7 +383,11 @@ mod tests {
                 log_enabled: false,
                 log_path: None,
                 init_timeout_ms: 30000,
+                envsetup_timeout_ms: DEFAULT_ENVSETUP_TIMEOUT_MS,
                 disable_auto_import: true,
+                max_open_files: DEFAULT_MAX_OPEN_FILES,
+                log_capture_lines: DEFAULT_LOG_CAPTURE_LINES,
+                max_workspace_symbols: DEFAULT_MAX_WORKSPACE_SYMBOLS,
             };
             let cmd = build_unix_command(&settings, "").unwrap();
             let args: Vec<_> = cmd.as_std().get_args().collect();
@@ -304,7 +461,11 @@ mod tests {
                 log_enabled: false,
                 log_path: None,
                 init_timeout_ms: 30000,
+                envsetup_timeout_ms: DEFAULT_ENVSETUP_TIMEOUT_MS,
                 disable_auto_import: true,
+                max_open_files: DEFAULT_MAX_OPEN_FILES,
+                log_capture_lines: DEFAULT_LOG_CAPTURE_LINES,
+                max_workspace_symbols: DEFAULT_MAX_WORKSPACE_SYMBOLS,
             };
             let env = HashMap::new();
             let cmd = build_windows_direct_command(&settings, "", env);
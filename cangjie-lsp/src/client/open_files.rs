@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy)]
+struct OpenFile {
+    version: i32,
+    hash: u64,
+    last_used: u64,
+}
+
+/// Documents currently open on the LSP server, bounded by a least-recently-used cap.
+#[derive(Debug)]
+pub(super) struct OpenFiles {
+    files: HashMap<String, OpenFile>,
+    max_open: usize,
+    clock: u64,
+}
+
+impl OpenFiles {
+    pub(super) fn new(max_open: usize) -> Self {
+        Self {
+            files: HashMap::new(),
+            max_open: max_open.max(1),
+            clock: 0,
+        }
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    /// Return the version and content hash of an open document and mark it
+    /// as most recently used.
+    pub(super) fn touch(&mut self, uri: &str) -> Option<(i32, u64)> {
+        let now = self.tick();
+        let file = self.files.get_mut(uri)?;
+        file.last_used = now;
+        Some((file.version, file.hash))
+    }
+
+    /// Record a `didOpen`/`didChange` for `uri` and return the URIs evicted to
+    /// stay within the cap, least recently used first. The caller must send
+    /// `didClose` for each of them.
+    pub(super) fn record(&mut self, uri: String, version: i32, hash: u64) -> Vec<String> {
+        let now = self.tick();
+        self.files.insert(
+            uri,
+            OpenFile {
+                version,
+                hash,
+                last_used: now,
+            },
+        );
+
+        let mut evicted = Vec::new();
+        while self.files.len() > self.max_open {
+            let Some(oldest) = self
+                .files
+                .iter()
+                .min_by_key(|(_, file)| file.last_used)
+                .map(|(uri, _)| uri.clone())
+            else {
+                break;
+            };
+            self.files.remove(&oldest);
+            evicted.push(oldest);
+        }
+        evicted
+    }
+
+    #[cfg(test)]
+    fn contains(&self, uri: &str) -> bool {
+        self.files.contains_key(uri)
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.files.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri(i: usize) -> String {
+        format!("file:///workspace/src/f{i}.cj")
+    }
+
+    #[test]
+    fn test_record_past_cap_evicts_least_recently_used() {
+        let mut files = OpenFiles::new(3);
+        for i in 0..3 {
+            assert!(files.record(uri(i), 1, i as u64).is_empty());
+        }
+
+        let evicted = files.record(uri(3), 1, 3);
+        assert_eq!(evicted, vec![uri(0)]);
+        assert_eq!(files.len(), 3);
+        assert!(!files.contains(&uri(0)));
+        assert!(files.touch(&uri(0)).is_none());
+    }
+
+    #[test]
+    fn test_touch_updates_recency() {
+        let mut files = OpenFiles::new(2);
+        files.record(uri(0), 1, 0);
+        files.record(uri(1), 1, 1);
+        assert_eq!(files.touch(&uri(0)), Some((1, 0)));
+
+        let evicted = files.record(uri(2), 1, 2);
+        assert_eq!(evicted, vec![uri(1)]);
+        assert!(files.contains(&uri(0)));
+    }
+
+    #[test]
+    fn test_evicted_file_reopens_from_version_one() {
+        let mut files = OpenFiles::new(1);
+        files.record(uri(0), 3, 0);
+        assert_eq!(files.record(uri(1), 1, 1), vec![uri(0)]);
+
+        // A reopened document starts over as a fresh didOpen.
+        assert!(files.touch(&uri(0)).is_none());
+        assert_eq!(files.record(uri(0), 1, 0), vec![uri(1)]);
+        assert_eq!(files.touch(&uri(0)), Some((1, 0)));
+    }
+}
@@ -1,16 +1,54 @@
 use std::collections::HashMap;
+use std::ffi::OsStr;
 use std::path::PathBuf;
 
 use crate::dependency::DependencyResolver;
 use serde::Serialize;
 
+/// Extra directories to put on the LSP server's PATH, in the platform's PATH
+/// list format (`:`-separated on Unix, `;`-separated on Windows).
+pub const CANGJIE_PATH_ENV: &str = "CANGJIE_PATH";
+
+/// Overrides how many documents the client keeps open on the LSP server.
+pub const MAX_OPEN_FILES_ENV: &str = "CANGJIE_LSP_MAX_OPEN_FILES";
+
+/// Default cap on documents kept open; the least recently used one is closed
+/// once it is exceeded.
+pub const DEFAULT_MAX_OPEN_FILES: usize = 50;
+
+/// Overrides how many `workspace_symbol` results are returned after ranking.
+pub const MAX_WORKSPACE_SYMBOLS_ENV: &str = "CANGJIE_LSP_MAX_WORKSPACE_SYMBOLS";
+
+/// Default cap on ranked `workspace_symbol` results.
+pub const DEFAULT_MAX_WORKSPACE_SYMBOLS: usize = 50;
+
+/// Overrides how long `envsetup.ps1` may run while capturing the SDK
+/// environment on Windows, in milliseconds.
+pub const ENVSETUP_TIMEOUT_ENV: &str = "CANGJIE_LSP_ENVSETUP_TIMEOUT_MS";
+
+/// Default limit on the `envsetup.ps1` run before LSP startup gives up.
+pub const DEFAULT_ENVSETUP_TIMEOUT_MS: u64 = 20_000;
+
+/// Overrides how many lines of LSP server stderr are kept for `cangjie_lsp_logs`.
+pub const LOG_CAPTURE_LINES_ENV: &str = "CANGJIE_LSP_LOG_LINES";
+
+/// Default number of captured LSP server stderr lines; older lines are dropped.
+pub const DEFAULT_LOG_CAPTURE_LINES: usize = 500;
+
 pub struct LSPSettings {
     pub sdk_path: PathBuf,
     pub workspace_path: PathBuf,
     pub log_enabled: bool,
     pub log_path: Option<PathBuf>,
     pub init_timeout_ms: u64,
+    /// Limit on running `envsetup.ps1` to capture the SDK environment (Windows).
+    pub envsetup_timeout_ms: u64,
     pub disable_auto_import: bool,
+    pub max_open_files: usize,
+    /// Lines of server stderr kept in memory for `cangjie_lsp_logs`.
+    pub log_capture_lines: usize,
+    /// Ranked `workspace_symbol` results kept, best match first.
+    pub max_workspace_symbols: usize,
 }
 
 impl LSPSettings {
@@ -108,6 +146,47 @@ pub struct LSPInitOptions {
     pub extension_path: String,
 }
 
+/// Read the open-document cap from [`MAX_OPEN_FILES_ENV`], falling back to
+/// [`DEFAULT_MAX_OPEN_FILES`] when unset or not a positive integer.
+pub fn max_open_files_from_env() -> usize {
+    positive_from_env(MAX_OPEN_FILES_ENV, DEFAULT_MAX_OPEN_FILES)
+}
+
+/// Read the stderr capture size from [`LOG_CAPTURE_LINES_ENV`], falling back to
+/// [`DEFAULT_LOG_CAPTURE_LINES`] when unset or not a positive integer.
+pub fn log_capture_lines_from_env() -> usize {
+    positive_from_env(LOG_CAPTURE_LINES_ENV, DEFAULT_LOG_CAPTURE_LINES)
+}
+
+/// Read the `workspace_symbol` result cap from [`MAX_WORKSPACE_SYMBOLS_ENV`],
+/// falling back to [`DEFAULT_MAX_WORKSPACE_SYMBOLS`] when unset or not a
+/// positive integer.
+pub fn max_workspace_symbols_from_env() -> usize {
+    positive_from_env(MAX_WORKSPACE_SYMBOLS_ENV, DEFAULT_MAX_WORKSPACE_SYMBOLS)
+}
+
+/// Read the `envsetup.ps1` time limit from [`ENVSETUP_TIMEOUT_ENV`], falling
+/// back to [`DEFAULT_ENVSETUP_TIMEOUT_MS`] when unset or not a positive integer.
+pub fn envsetup_timeout_ms_from_env() -> u64 {
+    positive_from_env(ENVSETUP_TIMEOUT_ENV, DEFAULT_ENVSETUP_TIMEOUT_MS)
+}
+
+fn positive_from_env<T>(name: &str, default: T) -> T
+where
+    T: std::str::FromStr + PartialOrd + Default,
+{
+    match std::env::var(name) {
+        Ok(value) => match value.trim().parse::<T>() {
+            Ok(n) if n > T::default() => n,
+            _ => {
+                tracing::warn!("Ignoring invalid {name}={value:?}");
+                default
+            }
+        },
+        Err(_) => default,
+    }
+}
+
 pub fn build_init_options(settings: &LSPSettings) -> (LSPInitOptions, String) {
     let mut resolver = DependencyResolver::new(&settings.workspace_path);
     let multi_module = resolver.resolve();
@@ -117,7 +196,10 @@ pub fn build_init_options(settings: &LSPSettings) -> (LSPInitOptions, String) {
         .filter_map(|(k, v)| serde_json::to_value(v).ok().map(|val| (k, val)))
         .collect();
 
-    let require_path = resolver.get_require_path().to_string();
+    let require_path = append_search_paths(
+        resolver.get_require_path(),
+        std::env::var_os(CANGJIE_PATH_ENV).as_deref(),
+    );
 
     // Must be non-empty: the LSP server's IsInCjlibDir() uses
     // std::string::find(stdLibPath) — an empty string matches every path,
@@ -145,6 +227,33 @@ pub fn build_init_options(settings: &LSPSettings) -> (LSPInitOptions, String) {
     (options, require_path)
 }
 
+/// Append the entries of a PATH-style list to `require_path`, skipping empty
+/// entries and ones already present.
+fn append_search_paths(require_path: &str, extra: Option<&OsStr>) -> String {
+    let Some(extra) = extra else {
+        return require_path.to_string();
+    };
+
+    let mut paths: Vec<PathBuf> = if require_path.is_empty() {
+        Vec::new()
+    } else {
+        std::env::split_paths(require_path).collect()
+    };
+    for path in std::env::split_paths(extra) {
+        if !path.as_os_str().is_empty() && !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+
+    match std::env::join_paths(&paths) {
+        Ok(joined) => joined.to_string_lossy().to_string(),
+        Err(e) => {
+            tracing::warn!("Ignoring invalid {CANGJIE_PATH_ENV}: {e}");
+            require_path.to_string()
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,7 +265,11 @@ mod tests {
             log_enabled: false,
             log_path: None,
             init_timeout_ms: 30000,
+            envsetup_timeout_ms: DEFAULT_ENVSETUP_TIMEOUT_MS,
             disable_auto_import: false,
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+            log_capture_lines: DEFAULT_LOG_CAPTURE_LINES,
+            max_workspace_symbols: DEFAULT_MAX_WORKSPACE_SYMBOLS,
         }
     }
 
@@ -208,7 +321,11 @@ mod tests {
             log_enabled: false,
             log_path: None,
             init_timeout_ms: 30000,
+            envsetup_timeout_ms: DEFAULT_ENVSETUP_TIMEOUT_MS,
             disable_auto_import: false,
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+            log_capture_lines: DEFAULT_LOG_CAPTURE_LINES,
+            max_workspace_symbols: DEFAULT_MAX_WORKSPACE_SYMBOLS,
         };
         let errors = settings.validate();
         assert!(!errors.is_empty());
@@ -234,7 +351,11 @@ mod tests {
             log_enabled: false,
             log_path: None,
             init_timeout_ms: 30000,
+            envsetup_timeout_ms: DEFAULT_ENVSETUP_TIMEOUT_MS,
             disable_auto_import: false,
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+            log_capture_lines: DEFAULT_LOG_CAPTURE_LINES,
+            max_workspace_symbols: DEFAULT_MAX_WORKSPACE_SYMBOLS,
         };
         let errors = settings.validate();
         assert!(errors
@@ -263,7 +384,11 @@ mod tests {
             log_enabled: false,
             log_path: None,
             init_timeout_ms: 30000,
+            envsetup_timeout_ms: DEFAULT_ENVSETUP_TIMEOUT_MS,
             disable_auto_import: false,
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+            log_capture_lines: DEFAULT_LOG_CAPTURE_LINES,
+            max_workspace_symbols: DEFAULT_MAX_WORKSPACE_SYMBOLS,
         };
         let errors = settings.validate();
         // SDK path error should be present, but workspace should not
@@ -299,13 +424,79 @@ mod tests {
             log_enabled: false,
             log_path: None,
             init_timeout_ms: 30000,
+            envsetup_timeout_ms: DEFAULT_ENVSETUP_TIMEOUT_MS,
             disable_auto_import: false,
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+            log_capture_lines: DEFAULT_LOG_CAPTURE_LINES,
+            max_workspace_symbols: DEFAULT_MAX_WORKSPACE_SYMBOLS,
         };
         let (options, require_path) = build_init_options(&settings);
         assert!(options.multi_module_option.is_empty() || options.multi_module_option.len() == 1);
         assert!(require_path.is_empty());
     }
 
+    #[test]
+    fn test_build_init_options_appends_cangjie_path() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let extra = std::env::join_paths(["/opt/tools/bin", "/opt/other-sdk/lib"]).unwrap();
+        let settings = LSPSettings {
+            sdk_path: PathBuf::from("/opt/cangjie-sdk"),
+            workspace_path: tmp.path().to_path_buf(),
+            log_enabled: false,
+            log_path: None,
+            init_timeout_ms: 30000,
+            envsetup_timeout_ms: DEFAULT_ENVSETUP_TIMEOUT_MS,
+            disable_auto_import: false,
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+            log_capture_lines: DEFAULT_LOG_CAPTURE_LINES,
+            max_workspace_symbols: DEFAULT_MAX_WORKSPACE_SYMBOLS,
+        };
+        temp_env::with_var(CANGJIE_PATH_ENV, Some(&extra), || {
+            let (_options, require_path) = build_init_options(&settings);
+            assert_eq!(require_path, extra.to_string_lossy());
+        });
+    }
+
+    #[test]
+    fn test_append_search_paths_skips_duplicates_and_empty_entries() {
+        let base = std::env::join_paths(["/a", "/b"]).unwrap();
+        let extra = std::env::join_paths(["/b", "", "/c"]).unwrap();
+        let merged = append_search_paths(&base.to_string_lossy(), Some(&extra));
+        let paths: Vec<PathBuf> = std::env::split_paths(&merged).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/a"),
+                PathBuf::from("/b"),
+                PathBuf::from("/c")
+            ]
+        );
+        assert_eq!(append_search_paths("/a", None), "/a");
+    }
+
+    #[test]
+    fn test_max_open_files_from_env() {
+        temp_env::with_var(MAX_OPEN_FILES_ENV, Some("8"), || {
+            assert_eq!(max_open_files_from_env(), 8);
+        });
+        temp_env::with_var(MAX_OPEN_FILES_ENV, Some("0"), || {
+            assert_eq!(max_open_files_from_env(), DEFAULT_MAX_OPEN_FILES);
+        });
+        temp_env::with_var(MAX_OPEN_FILES_ENV, None::<&str>, || {
+            assert_eq!(max_open_files_from_env(), DEFAULT_MAX_OPEN_FILES);
+        });
+    }
+
+    #[test]
+    fn test_log_capture_lines_from_env() {
+        temp_env::with_var(LOG_CAPTURE_LINES_ENV, Some("20"), || {
+            assert_eq!(log_capture_lines_from_env(), 20);
+        });
+        temp_env::with_var(LOG_CAPTURE_LINES_ENV, Some("lots"), || {
+            assert_eq!(log_capture_lines_from_env(), DEFAULT_LOG_CAPTURE_LINES);
+        });
+    }
+
     #[test]
     fn test_build_init_options_with_basic_cjpm_toml() {
         let tmp = tempfile::TempDir::new().unwrap();
@@ -320,7 +511,11 @@ mod tests {
             log_enabled: false,
             log_path: None,
             init_timeout_ms: 30000,
+            envsetup_timeout_ms: DEFAULT_ENVSETUP_TIMEOUT_MS,
             disable_auto_import: false,
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+            log_capture_lines: DEFAULT_LOG_CAPTURE_LINES,
+            max_workspace_symbols: DEFAULT_MAX_WORKSPACE_SYMBOLS,
         };
         let (options, require_path) = build_init_options(&settings);
         assert!(!options.multi_module_option.is_empty());
@@ -342,7 +537,11 @@ mod tests {
             log_enabled: false,
             log_path: None,
             init_timeout_ms: 30000,
+            envsetup_timeout_ms: DEFAULT_ENVSETUP_TIMEOUT_MS,
             disable_auto_import: false,
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+            log_capture_lines: DEFAULT_LOG_CAPTURE_LINES,
+            max_workspace_symbols: DEFAULT_MAX_WORKSPACE_SYMBOLS,
         };
         let (options, _require_path) = build_init_options(&settings);
         // Always <sdk_path>/lib — must never be empty
@@ -360,7 +559,11 @@ mod tests {
             log_enabled: true,
             log_path: Some(PathBuf::from("/var/log/lsp.log")),
             init_timeout_ms: 60000,
+            envsetup_timeout_ms: DEFAULT_ENVSETUP_TIMEOUT_MS,
             disable_auto_import: true,
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+            log_capture_lines: DEFAULT_LOG_CAPTURE_LINES,
+            max_workspace_symbols: DEFAULT_MAX_WORKSPACE_SYMBOLS,
         };
         let args = settings.get_lsp_args();
         assert_eq!(args[0], "src");
@@ -1,11 +1,15 @@
+mod build_order;
+mod check;
 mod resolver;
 mod types;
 
 #[cfg(test)]
 mod tests;
 
+pub use build_order::{build_order, BuildOrder, BuildOrderModule};
+pub use check::{check_workspace, CjpmCheckReport};
 pub use resolver::DependencyResolver;
-pub use types::{Dependency, ModuleOption, PackageRequires};
+pub use types::{CjpmIssue, Dependency, IssueSeverity, ModuleOption, PackageRequires};
 
 #[cfg(test)]
 use crate::utils::*;
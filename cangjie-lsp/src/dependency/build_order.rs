@@ -0,0 +1,173 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::Serialize;
+
+use crate::dependency::types::ModuleOption;
+
+/// One module in a [`BuildOrder`].
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct BuildOrderModule {
+    pub name: String,
+    pub path: String, // file:// URI
+    /// Names of the modules this one depends on within the project.
+    pub depends_on: Vec<String>,
+    /// Set when the module is part of a dependency cycle, so no order
+    /// satisfies all of its edges.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub in_cycle: bool,
+}
+
+/// Modules of a project ordered so every module comes after its dependencies.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct BuildOrder {
+    pub order: Vec<BuildOrderModule>,
+    /// Each detected cycle as the module names it contains.
+    pub cycles: Vec<Vec<String>>,
+}
+
+/// Topologically sort the modules returned by
+/// [`DependencyResolver::resolve`](crate::dependency::DependencyResolver::resolve).
+///
+/// Only dependencies that resolved to another module in `modules` count as
+/// edges. Modules that are ready at the same time are emitted in name order,
+/// so disconnected modules appear in a stable order. Modules caught in a cycle
+/// are appended at the end, flagged, and the cycles listed.
+pub fn build_order(modules: &HashMap<String, ModuleOption>) -> BuildOrder {
+    // Keyed by URI; BTree collections keep the output deterministic.
+    let deps: BTreeMap<&str, BTreeSet<&str>> = modules
+        .iter()
+        .map(|(uri, module)| {
+            let edges = module
+                .requires
+                .values()
+                .map(|dep| dep.path.as_str())
+                .filter(|path| *path != uri.as_str() && modules.contains_key(*path))
+                .collect();
+            (uri.as_str(), edges)
+        })
+        .collect();
+    let sort_key = |uri: &str| (modules[uri].name.clone(), uri.to_string());
+    let entry = |uri: &str, in_cycle: bool| BuildOrderModule {
+        name: modules[uri].name.clone(),
+        path: uri.to_string(),
+        depends_on: deps[uri]
+            .iter()
+            .map(|dep| modules[*dep].name.clone())
+            .collect(),
+        in_cycle,
+    };
+
+    let mut pending: BTreeMap<&str, usize> = deps
+        .iter()
+        .map(|(uri, edges)| (*uri, edges.len()))
+        .collect();
+    let mut order = Vec::with_capacity(modules.len());
+    loop {
+        let mut ready: Vec<&str> = pending
+            .iter()
+            .filter(|(_, remaining)| **remaining == 0)
+            .map(|(uri, _)| *uri)
+            .collect();
+        if ready.is_empty() {
+            break;
+        }
+        ready.sort_by_key(|uri| sort_key(*uri));
+        for uri in &ready {
+            pending.remove(uri);
+        }
+        for (uri, remaining) in pending.iter_mut() {
+            *remaining -= deps[uri].iter().filter(|d| ready.contains(*d)).count();
+        }
+        order.extend(ready.into_iter().map(|uri| entry(uri, false)));
+    }
+
+    // Whatever is left depends, directly or not, on a cycle.
+    let cycles = find_cycles(&deps, &pending.keys().copied().collect());
+    let cyclic: BTreeSet<&str> = cycles.iter().flatten().copied().collect();
+    let mut rest: Vec<&str> = pending.into_keys().collect();
+    rest.sort_by_key(|uri| sort_key(*uri));
+    order.extend(rest.into_iter().map(|uri| entry(uri, cyclic.contains(uri))));
+
+    BuildOrder {
+        order,
+        cycles: cycles
+            .into_iter()
+            .map(|cycle| {
+                let mut names: Vec<String> =
+                    cycle.iter().map(|uri| modules[*uri].name.clone()).collect();
+                names.sort();
+                names
+            })
+            .collect(),
+    }
+}
+
+/// Strongly connected components of more than one module among `nodes`,
+/// found with Tarjan's algorithm.
+fn find_cycles<'a>(
+    deps: &BTreeMap<&'a str, BTreeSet<&'a str>>,
+    nodes: &BTreeSet<&'a str>,
+) -> Vec<Vec<&'a str>> {
+    struct Tarjan<'a, 'b> {
+        deps: &'b BTreeMap<&'a str, BTreeSet<&'a str>>,
+        nodes: &'b BTreeSet<&'a str>,
+        index: HashMap<&'a str, usize>,
+        low: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        cycles: Vec<Vec<&'a str>>,
+    }
+
+    impl<'a> Tarjan<'a, '_> {
+        fn visit(&mut self, node: &'a str) {
+            let idx = self.index.len();
+            self.index.insert(node, idx);
+            self.low.insert(node, idx);
+            self.stack.push(node);
+
+            let deps = self.deps;
+            for &next in &deps[node] {
+                if !self.nodes.contains(next) {
+                    continue;
+                }
+                if !self.index.contains_key(next) {
+                    self.visit(next);
+                    let low = self.low[node].min(self.low[next]);
+                    self.low.insert(node, low);
+                } else if self.stack.contains(&next) {
+                    let low = self.low[node].min(self.index[next]);
+                    self.low.insert(node, low);
+                }
+            }
+
+            if self.low[node] == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                if component.len() > 1 {
+                    component.sort();
+                    self.cycles.push(component);
+                }
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        deps,
+        nodes,
+        index: HashMap::new(),
+        low: HashMap::new(),
+        stack: Vec::new(),
+        cycles: Vec::new(),
+    };
+    for &node in nodes {
+        if !tarjan.index.contains_key(node) {
+            tarjan.visit(node);
+        }
+    }
+    tarjan.cycles.sort();
+    tarjan.cycles
+}
@@ -0,0 +1,33 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::dependency::resolver::DependencyResolver;
+use crate::dependency::types::{CjpmIssue, IssueSeverity};
+
+/// Every problem the resolver finds in a workspace's `cjpm.toml` files.
+#[derive(Debug, Clone, Serialize)]
+pub struct CjpmCheckReport {
+    pub workspace: String,
+    /// Modules the resolver reached, including dependencies.
+    pub modules: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<CjpmIssue>,
+}
+
+/// Resolve the workspace at `workspace_path` and collect the issues found
+/// along the way. Needs no SDK or LSP server.
+pub fn check_workspace(workspace_path: &Path) -> CjpmCheckReport {
+    let mut resolver = DependencyResolver::new(workspace_path);
+    let modules = resolver.resolve().len();
+    let issues = resolver.issues().to_vec();
+    let count = |severity| issues.iter().filter(|i| i.severity == severity).count();
+    CjpmCheckReport {
+        workspace: workspace_path.display().to_string(),
+        modules,
+        errors: count(IssueSeverity::Error),
+        warnings: count(IssueSeverity::Warning),
+        issues,
+    }
+}
@@ -3,7 +3,9 @@ use std::path::{Path, PathBuf};
 
 use tracing::warn;
 
-use crate::dependency::types::{Dependency, ModuleOption, PackageRequires};
+use crate::dependency::types::{
+    CjpmIssue, Dependency, IssueSeverity, ModuleOption, PackageRequires,
+};
 use crate::utils::*;
 
 pub struct DependencyResolver {
@@ -12,6 +14,7 @@ pub struct DependencyResolver {
     existed: Vec<String>, // cycle detection
     root_lock_data: Option<CjpmLock>,
     require_path: String,
+    issues: Vec<CjpmIssue>,
 }
 
 impl DependencyResolver {
@@ -22,6 +25,7 @@ impl DependencyResolver {
             existed: Vec::new(),
             root_lock_data: None,
             require_path: String::new(),
+            issues: Vec::new(),
         }
     }
 
@@ -35,11 +39,26 @@ impl DependencyResolver {
         &self.require_path
     }
 
+    /// Problems found in `cjpm.toml` files by the last [`Self::resolve`].
+    pub fn issues(&self) -> &[CjpmIssue] {
+        &self.issues
+    }
+
+    fn report(&mut self, severity: IssueSeverity, path: &Path, message: String) {
+        warn!("{message}");
+        self.issues.push(CjpmIssue {
+            severity,
+            path: path.display().to_string(),
+            message,
+        });
+    }
+
     fn clear_state(&mut self) {
         self.multi_module_option.clear();
         self.existed.clear();
         self.root_lock_data = None;
         self.require_path.clear();
+        self.issues.clear();
     }
 
     fn get_multi_module_option(&mut self) {
@@ -53,7 +72,11 @@ impl DependencyResolver {
         };
 
         if cjpm.workspace.is_some() && cjpm.package.is_some() {
-            warn!("Both workspace and package fields found in cjpm.toml");
+            self.report(
+                IssueSeverity::Error,
+                &toml_path,
+                "Both workspace and package fields found in cjpm.toml".to_string(),
+            );
             return;
         }
 
@@ -128,7 +151,7 @@ impl DependencyResolver {
         self.find_all_toml(&ws, "");
     }
 
-    fn get_members(&self, workspace: &CjpmWorkspace, base_path: &Path) -> Vec<PathBuf> {
+    fn get_members(&mut self, workspace: &CjpmWorkspace, base_path: &Path) -> Vec<PathBuf> {
         let mut valid = Vec::new();
         for member in &workspace.members {
             let resolved = get_real_path(member);
@@ -136,7 +159,12 @@ impl DependencyResolver {
             if path.exists() {
                 valid.push(path);
             } else {
-                warn!("Workspace member not found: {member}");
+                let toml_path = base_path.join(CJPM_TOML);
+                self.report(
+                    IssueSeverity::Error,
+                    &toml_path,
+                    format!("Workspace member not found: {member}"),
+                );
             }
         }
         valid
@@ -162,14 +190,19 @@ impl DependencyResolver {
         let cjpm = match load_cjpm_toml(&toml_path) {
             Some(c) => c,
             None => {
-                warn!("Invalid cjpm.toml in {module_uri}");
+                self.report(
+                    IssueSeverity::Error,
+                    &toml_path,
+                    format!("Invalid cjpm.toml in {module_uri}"),
+                );
                 self.multi_module_option.insert(module_uri, module_option);
                 return;
             }
         };
 
         if cjpm.workspace.is_some() {
-            warn!("workspace field not allowed in {}", toml_path.display());
+            let message = format!("workspace field not allowed in {}", toml_path.display());
+            self.report(IssueSeverity::Error, &toml_path, message);
             self.multi_module_option.insert(module_uri, module_option);
             return;
         }
@@ -177,9 +210,13 @@ impl DependencyResolver {
         if let Some(ref pkg) = cjpm.package {
             if !pkg.name.is_empty() {
                 if !expected_name.is_empty() && pkg.name != expected_name {
-                    warn!(
-                        "Module name mismatch: expected {expected_name}, got {}",
-                        pkg.name
+                    self.report(
+                        IssueSeverity::Warning,
+                        &toml_path,
+                        format!(
+                            "Module name mismatch: expected {expected_name}, got {}",
+                            pkg.name
+                        ),
                     );
                 }
                 module_option.name = pkg.name.clone();
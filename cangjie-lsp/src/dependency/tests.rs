@@ -1004,3 +1004,162 @@ commitId = ""
         "git dep with empty commitId should not resolve"
     );
 }
+
+fn write_module(dir: &std::path::Path, toml: &str) {
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(dir.join(CJPM_TOML), toml).unwrap();
+}
+
+/// Workspace with a chain of path dependencies plus a disconnected member:
+/// every module must come after the modules it depends on.
+#[test]
+fn test_build_order_is_topological() {
+    let tmp = TempDir::new().unwrap();
+    let ws = tmp.path().to_path_buf();
+
+    std::fs::write(
+        ws.join(CJPM_TOML),
+        r#"
+[workspace]
+members = ["app", "core", "net", "tools"]
+"#,
+    )
+    .unwrap();
+    write_module(
+        &ws.join("app"),
+        r#"
+[package]
+name = "app"
+
+[dependencies.net]
+path = "../net"
+
+[dependencies.core]
+path = "../core"
+"#,
+    );
+    write_module(
+        &ws.join("net"),
+        r#"
+[package]
+name = "net"
+
+[dependencies.core]
+path = "../core"
+"#,
+    );
+    write_module(&ws.join("core"), "[package]\nname = \"core\"\n");
+    write_module(&ws.join("tools"), "[package]\nname = \"tools\"\n");
+
+    let modules = DependencyResolver::new(&ws).resolve();
+    let result = build_order(&modules);
+
+    let names: Vec<&str> = result.order.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, vec!["core", "tools", "net", "app"]);
+    for (pos, module) in result.order.iter().enumerate() {
+        assert!(!module.in_cycle);
+        for dep in &module.depends_on {
+            let dep_pos = names.iter().position(|n| n == dep).unwrap();
+            assert!(dep_pos < pos, "{} must follow {dep}", module.name);
+        }
+    }
+    assert!(result.cycles.is_empty());
+}
+
+/// Modules in a dependency cycle are still listed, flagged, and the cycle reported.
+#[test]
+fn test_build_order_flags_cycles() {
+    let tmp = TempDir::new().unwrap();
+    let ws = tmp.path().to_path_buf();
+
+    std::fs::write(
+        ws.join(CJPM_TOML),
+        r#"
+[package]
+name = "root"
+
+[dependencies.pkg_a]
+path = "pkg_a"
+"#,
+    )
+    .unwrap();
+    write_module(
+        &ws.join("pkg_a"),
+        "[package]\nname = \"pkg_a\"\n\n[dependencies.pkg_b]\npath = \"../pkg_b\"\n",
+    );
+    write_module(
+        &ws.join("pkg_b"),
+        "[package]\nname = \"pkg_b\"\n\n[dependencies.pkg_a]\npath = \"../pkg_a\"\n",
+    );
+
+    let modules = DependencyResolver::new(&ws).resolve();
+    let result = build_order(&modules);
+
+    assert_eq!(
+        result.cycles,
+        vec![vec!["pkg_a".to_string(), "pkg_b".to_string()]]
+    );
+    let flagged: Vec<(&str, bool)> = result
+        .order
+        .iter()
+        .map(|m| (m.name.as_str(), m.in_cycle))
+        .collect();
+    assert_eq!(
+        flagged,
+        vec![("pkg_a", true), ("pkg_b", true), ("root", false)]
+    );
+}
+
+/// `check_workspace` reports a root cjpm.toml that declares both workspace
+/// and package as an error against that file.
+#[test]
+fn test_check_workspace_reports_workspace_and_package() {
+    let tmp = TempDir::new().unwrap();
+    let ws = tmp.path().to_path_buf();
+
+    std::fs::write(
+        ws.join(CJPM_TOML),
+        "[package]\nname = \"myapp\"\n\n[workspace]\nmembers = [\"sub\"]\n",
+    )
+    .unwrap();
+
+    let report = check_workspace(&ws);
+
+    assert_eq!(report.errors, 1);
+    assert_eq!(report.warnings, 0);
+    assert_eq!(report.modules, 0);
+    let issue = &report.issues[0];
+    assert_eq!(issue.severity, IssueSeverity::Error);
+    assert_eq!(
+        issue.message,
+        "Both workspace and package fields found in cjpm.toml"
+    );
+    assert_eq!(issue.path, ws.join(CJPM_TOML).display().to_string());
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["issues"][0]["severity"], "error");
+}
+
+/// Warnings from dependencies are collected alongside the module count.
+#[test]
+fn test_check_workspace_collects_name_mismatch_warning() {
+    let tmp = TempDir::new().unwrap();
+    let ws = tmp.path().to_path_buf();
+
+    std::fs::write(
+        ws.join(CJPM_TOML),
+        "[package]\nname = \"root\"\n\n[dependencies.mylib]\npath = \"mylib\"\n",
+    )
+    .unwrap();
+    write_module(&ws.join("mylib"), "[package]\nname = \"other\"\n");
+
+    let report = check_workspace(&ws);
+
+    assert_eq!(report.modules, 2);
+    assert_eq!(report.errors, 0);
+    assert_eq!(report.warnings, 1);
+    assert_eq!(
+        report.issues[0].message,
+        "Module name mismatch: expected mylib, got other"
+    );
+}
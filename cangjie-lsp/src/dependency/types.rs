@@ -26,3 +26,19 @@ pub struct ModuleOption {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub java_requires: Option<Vec<String>>,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    Warning,
+    Error,
+}
+
+/// A problem found while resolving a `cjpm.toml`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CjpmIssue {
+    pub severity: IssueSeverity,
+    /// The `cjpm.toml` the problem was found in.
+    pub path: String,
+    pub message: String,
+}
@@ -6,18 +6,57 @@ pub mod transport;
 pub mod types;
 pub mod utils;
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex};
 
+use serde::Serialize;
 use tokio::sync::RwLock;
 use tracing::{error, info};
 
 use crate::client::CangjieClient;
-use crate::config::{build_init_options, LSPSettings};
+use crate::config::{
+    build_init_options, envsetup_timeout_ms_from_env, log_capture_lines_from_env,
+    max_open_files_from_env, max_workspace_symbols_from_env, LSPSettings,
+};
+use crate::transport::LogBuffer;
 
 static LSP_CLIENT: once_cell::sync::Lazy<Arc<RwLock<Option<CangjieClient>>>> =
     once_cell::sync::Lazy::new(|| Arc::new(RwLock::new(None)));
 
+static LAST_ERROR: StdMutex<Option<String>> = StdMutex::new(None);
+
+fn set_last_error(error: Option<String>) {
+    *LAST_ERROR.lock().unwrap_or_else(|e| e.into_inner()) = error;
+}
+
+/// Server stderr per workspace. Kept apart from the clients so the output of
+/// a server that crashed or never finished initializing can still be read;
+/// a restarted server appends to the same buffer.
+static WORKSPACE_LOGS: once_cell::sync::Lazy<StdMutex<HashMap<PathBuf, Arc<LogBuffer>>>> =
+    once_cell::sync::Lazy::new(|| StdMutex::new(HashMap::new()));
+
+/// The stderr buffer of LSP servers started in `workspace`, created with
+/// `capacity` lines on first use.
+pub(crate) fn workspace_logs(workspace: &Path, capacity: usize) -> Arc<LogBuffer> {
+    WORKSPACE_LOGS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(workspace.to_path_buf())
+        .or_insert_with(|| Arc::new(LogBuffer::new(capacity)))
+        .clone()
+}
+
+/// The last `n` lines LSP servers in `workspace` wrote to stderr, oldest
+/// first; `None` when no server was ever started there.
+pub fn recent_logs(workspace: &Path, n: usize) -> Option<Vec<String>> {
+    WORKSPACE_LOGS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(workspace)
+        .map(|logs| logs.tail(n))
+}
+
 fn detect_cangjie_home_from_vscode_settings(workspace: &Path) -> Option<PathBuf> {
     let settings_path = workspace.join(".vscode").join("settings.json");
     let content = std::fs::read_to_string(settings_path).ok()?;
@@ -94,6 +133,7 @@ pub async fn init(settings: LSPSettings) -> bool {
     match start_client(&settings).await {
         Ok(client) => {
             *LSP_CLIENT.write().await = Some(client);
+            set_last_error(None);
             info!("LSP client initialized successfully");
             if let Some(ref log_path) = settings.log_path {
                 info!("LSP server log directory: {}", log_path.display());
@@ -102,6 +142,7 @@ pub async fn init(settings: LSPSettings) -> bool {
         }
         Err(e) => {
             error!("{e}");
+            set_last_error(Some(e));
             false
         }
     }
@@ -133,6 +174,51 @@ pub async fn get_client() -> Option<tokio::sync::RwLockReadGuard<'static, Option
     }
 }
 
+/// Health of the global LSP client, as reported by `cangjie_lsp_status`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LspStatus {
+    pub available: bool,
+    pub initialized: bool,
+    pub running: bool,
+    pub workspace: Option<String>,
+    pub sdk_path: Option<String>,
+    pub open_file_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// Inspect the global LSP client. The detected settings are reported even
+/// when no client has been started.
+pub async fn status() -> LspStatus {
+    let settings = detect_settings(None);
+    let guard = get_client().await;
+    let client = guard.as_ref().and_then(|g| g.as_ref());
+    status_of(settings.as_ref(), client).await
+}
+
+/// Inspect `client`, the pooled client serving `workspace` in daemon mode.
+/// The settings detected for `workspace` are reported even without a client.
+pub async fn workspace_status(workspace: PathBuf, client: Option<&CangjieClient>) -> LspStatus {
+    let settings = detect_settings(Some(workspace));
+    status_of(settings.as_ref(), client).await
+}
+
+async fn status_of(settings: Option<&LSPSettings>, client: Option<&CangjieClient>) -> LspStatus {
+    let last_error = LAST_ERROR.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    LspStatus {
+        available: settings.is_some(),
+        initialized: client.is_some_and(|c| c.is_initialized()),
+        running: client.is_some_and(|c| c.is_running()),
+        workspace: settings.map(|s| s.workspace_path.display().to_string()),
+        sdk_path: settings.map(|s| s.sdk_path.display().to_string()),
+        open_file_count: match client {
+            Some(c) => c.open_file_count().await,
+            None => 0,
+        },
+        last_error,
+    }
+}
+
 /// Try to auto-detect and create LSP settings.
 pub fn detect_settings(workspace_path: Option<PathBuf>) -> Option<LSPSettings> {
     let workspace = workspace_path
@@ -147,7 +233,11 @@ pub fn detect_settings(workspace_path: Option<PathBuf>) -> Option<LSPSettings> {
         log_enabled: true,
         log_path: Some(log_path),
         init_timeout_ms: 45000,
+        envsetup_timeout_ms: envsetup_timeout_ms_from_env(),
         disable_auto_import: true,
+        max_open_files: max_open_files_from_env(),
+        log_capture_lines: log_capture_lines_from_env(),
+        max_workspace_symbols: max_workspace_symbols_from_env(),
     })
 }
 
@@ -172,6 +262,33 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn test_status_without_client() {
+        let status = status_of(None, None).await;
+        assert!(!status.available);
+        assert!(!status.initialized);
+        assert!(!status.running);
+        assert_eq!(status.open_file_count, 0);
+
+        let json = serde_json::to_value(&status).unwrap();
+        assert_eq!(json["available"], false);
+        assert!(json["workspace"].is_null());
+        assert!(json["sdk_path"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_detected_settings_without_client() {
+        let settings = temp_env::with_var("CANGJIE_HOME", Some("/tmp/fake-cangjie-sdk"), || {
+            detect_settings(Some(PathBuf::from("/tmp/workspace")))
+        });
+        let status = status_of(settings.as_ref(), None).await;
+        assert!(status.available);
+        assert!(!status.initialized);
+        assert!(!status.running);
+        assert_eq!(status.workspace.as_deref(), Some("/tmp/workspace"));
+        assert_eq!(status.sdk_path.as_deref(), Some("/tmp/fake-cangjie-sdk"));
+    }
+
     #[test]
     fn test_detect_settings_no_cangjie_home() {
         temp_env::with_var("CANGJIE_HOME", None::<&str>, || {
@@ -236,4 +353,27 @@ mod tests {
             assert_eq!(result.unwrap().sdk_path, PathBuf::from("/from/env"));
         });
     }
+
+    #[test]
+    fn test_workspace_logs_outlive_the_client() {
+        let workspace = TempDir::new().unwrap();
+        assert!(recent_logs(workspace.path(), 10).is_none());
+
+        // A server that wrote to stderr and then died with its client.
+        let logs = workspace_logs(workspace.path(), 2);
+        logs.push("starting");
+        logs.push("fatal: bad config");
+        drop(logs);
+
+        assert_eq!(
+            recent_logs(workspace.path(), 10).unwrap(),
+            vec!["starting", "fatal: bad config"]
+        );
+        // A restarted server appends to the same buffer.
+        workspace_logs(workspace.path(), 2).push("restarted");
+        assert_eq!(
+            recent_logs(workspace.path(), 10).unwrap(),
+            vec!["fatal: bad config", "restarted"]
+        );
+    }
 }
@@ -3,9 +3,10 @@ mod process;
 mod types;
 
 pub use process::{
-    get_validate_error, parse_hover, process_definition, process_diagnostics, process_hover,
-    process_incoming_calls, process_outgoing_calls, process_references, process_symbols,
-    process_type_hierarchy, process_workspace_symbols,
+    attach_definition_previews, get_validate_error, parse_hover, parse_pull_diagnostics,
+    process_definition, process_diagnostics, process_hover, process_incoming_calls,
+    process_outgoing_calls, process_references, process_symbols, process_type_hierarchy,
+    process_workspace_symbols,
 };
 pub use types::{
     CallHierarchyItemOutput, DefinitionResult, DiagnosticOutput, DiagnosticsResult, HoverOutput,
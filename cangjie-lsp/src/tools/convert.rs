@@ -106,6 +106,7 @@ pub(super) fn location_to_result(loc: &Location) -> LocationResult {
         character: loc.range.start.character + 1,
         end_line: Some(loc.range.end.line + 1),
         end_character: Some(loc.range.end.character + 1),
+        preview: None,
     }
 }
 
@@ -118,6 +119,7 @@ pub(super) fn location_link_to_result(link: &LocationLink) -> LocationResult {
         character: link.target_selection_range.start.character + 1,
         end_line: Some(link.target_range.end.line + 1),
         end_character: Some(link.target_range.end.character + 1),
+        preview: None,
     }
 }
 
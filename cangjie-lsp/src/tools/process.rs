@@ -36,6 +36,49 @@ pub fn process_definition(result: &Value) -> DefinitionResult {
     DefinitionResult { locations, count }
 }
 
+/// Context lines shown before and after a definition's own lines.
+const PREVIEW_CONTEXT_LINES: usize = 2;
+/// A definition spanning more lines (e.g. a whole class body) is cut here.
+const MAX_PREVIEW_LINES: usize = 20;
+const MAX_PREVIEW_CHARS: usize = 2000;
+
+/// Fill each location's `preview` with its source lines plus a little
+/// context, read from disk. Locations outside the workspace (e.g. SDK
+/// sources) are read the same way; unreadable files get no preview.
+pub fn attach_definition_previews(result: &mut DefinitionResult) {
+    for location in &mut result.locations {
+        location.preview = std::fs::read_to_string(&location.file_path)
+            .ok()
+            .and_then(|content| source_preview(&content, location.line, location.end_line));
+    }
+}
+
+/// Numbered lines `line..=end_line` (1-based) of `content` with context,
+/// bounded by [`MAX_PREVIEW_LINES`] and [`MAX_PREVIEW_CHARS`].
+fn source_preview(content: &str, line: u32, end_line: Option<u32>) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let first = (line as usize).checked_sub(1)?;
+    if first >= lines.len() {
+        return None;
+    }
+    let last = (end_line.unwrap_or(line) as usize)
+        .saturating_sub(1)
+        .clamp(first, first + MAX_PREVIEW_LINES - 1);
+    let start = first.saturating_sub(PREVIEW_CONTEXT_LINES);
+    let end = (last + PREVIEW_CONTEXT_LINES + 1).min(lines.len());
+
+    let mut preview = String::new();
+    for (index, text) in lines[start..end].iter().enumerate() {
+        let numbered = format!("{:>5} | {text}\n", start + index + 1);
+        if preview.len() + numbered.len() > MAX_PREVIEW_CHARS {
+            preview.push_str("  ...\n");
+            break;
+        }
+        preview.push_str(&numbered);
+    }
+    Some(preview)
+}
+
 pub fn process_references(result: &Value) -> ReferencesResult {
     let locations: Vec<Location> = serde_json::from_value(result.clone()).unwrap_or_default();
     let locations: Vec<LocationResult> = locations.iter().map(location_to_result).collect();
@@ -70,6 +113,7 @@ pub fn parse_hover(result: &Value, file_path: &str) -> Option<HoverOutput> {
         character: r.start.character + 1,
         end_line: Some(r.end.line + 1),
         end_character: Some(r.end.character + 1),
+        preview: None,
     });
 
     Some(HoverOutput { content, range })
@@ -109,6 +153,18 @@ pub fn process_symbols(result: &Value, _file_path: &str) -> SymbolsResult {
     SymbolsResult { symbols, count }
 }
 
+/// Diagnostics from a `textDocument/diagnostic` (pull) response.
+///
+/// Only a `full` report carries the file's diagnostics; an `unchanged`
+/// report, or anything else, yields `None` so the caller can fall back to
+/// pushed diagnostics.
+pub fn parse_pull_diagnostics(report: &Value) -> Option<Vec<Value>> {
+    if report.get("kind").and_then(Value::as_str) != Some("full") {
+        return None;
+    }
+    Some(report.get("items")?.as_array()?.clone())
+}
+
 pub fn process_diagnostics(diags: &[Value]) -> DiagnosticsResult {
     let typed_diags: Vec<Diagnostic> = diags
         .iter()
@@ -151,11 +207,33 @@ pub fn process_diagnostics(diags: &[Value]) -> DiagnosticsResult {
     }
 }
 
-pub fn process_workspace_symbols(result: &Value) -> WorkspaceSymbolResult {
+/// How well a symbol name matches a workspace-symbol query, ignoring case:
+/// exact (4) > prefix (3) > substring (2) > Jaro-Winkler similarity (0..1).
+fn symbol_match_score(name: &str, query: &str) -> f64 {
+    let name = name.to_lowercase();
+    let query = query.to_lowercase();
+    if name == query {
+        4.0
+    } else if name.starts_with(&query) {
+        3.0
+    } else if name.contains(&query) {
+        2.0
+    } else {
+        strsim::jaro_winkler(&name, &query)
+    }
+}
+
+/// Convert a `workspace/symbol` response, ranked by how well each name
+/// matches `query` (server order breaks ties) and capped at `max_results`.
+pub fn process_workspace_symbols(
+    result: &Value,
+    query: &str,
+    max_results: usize,
+) -> WorkspaceSymbolResult {
     // workspace/symbol can return SymbolInformation[] or WorkspaceSymbol[]
     let empty = [];
     let arr: &[Value] = result.as_array().map(Vec::as_slice).unwrap_or(&empty);
-    let symbols: Vec<WorkspaceSymbolOutput> = arr
+    let mut symbols: Vec<WorkspaceSymbolOutput> = arr
         .iter()
         .filter_map(|item| {
             let name = item.get("name")?.as_str()?.to_string();
@@ -199,6 +277,17 @@ pub fn process_workspace_symbols(result: &Value) -> WorkspaceSymbolResult {
         })
         .collect();
 
+    let query = query.trim();
+    if !query.is_empty() {
+        let mut scored: Vec<(f64, WorkspaceSymbolOutput)> = symbols
+            .into_iter()
+            .map(|s| (symbol_match_score(&s.name, query), s))
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        symbols = scored.into_iter().map(|(_, s)| s).collect();
+    }
+    symbols.truncate(max_results);
+
     let count = symbols.len();
     WorkspaceSymbolResult { symbols, count }
 }
@@ -220,6 +309,7 @@ pub fn process_incoming_calls(result: &Value) -> IncomingCallsResult {
                     character: range.start.character + 1,
                     end_line: Some(range.end.line + 1),
                     end_character: Some(range.end.character + 1),
+                    preview: None,
                 })
                 .collect();
             IncomingCallOutput {
@@ -249,6 +339,7 @@ pub fn process_outgoing_calls(result: &Value) -> OutgoingCallsResult {
                     character: range.start.character + 1,
                     end_line: Some(range.end.line + 1),
                     end_character: Some(range.end.character + 1),
+                    preview: None,
                 })
                 .collect();
             OutgoingCallOutput {
@@ -324,6 +415,45 @@ mod tests {
         assert_eq!(def.locations[0].line, 6);
     }
 
+    #[test]
+    fn test_attach_definition_previews_reads_resolved_line() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("lib.cj");
+        let source: String = (1..=10).map(|i| format!("line {i}\n")).collect();
+        std::fs::write(&path, source).unwrap();
+
+        let result = json!([{
+            "uri": crate::utils::path_to_uri(&path),
+            "range": {
+                "start": {"line": 4, "character": 0},
+                "end": {"line": 4, "character": 6}
+            }
+        }]);
+        let mut def = process_definition(&result);
+        attach_definition_previews(&mut def);
+
+        let preview = def.locations[0].preview.as_deref().unwrap();
+        assert!(preview.contains("    5 | line 5\n"), "{preview}");
+        assert!(preview.starts_with("    3 | line 3"), "{preview}");
+        assert!(preview.ends_with("    7 | line 7\n"), "{preview}");
+    }
+
+    #[test]
+    fn test_source_preview_bounds_long_definitions() {
+        let source: String = (1..=200).map(|i| format!("line {i}\n")).collect();
+        let preview = source_preview(&source, 10, Some(150)).unwrap();
+        assert_eq!(
+            preview.lines().count(),
+            MAX_PREVIEW_LINES + 2 * PREVIEW_CONTEXT_LINES
+        );
+        assert!(source_preview(&source, 500, None).is_none());
+
+        let wide = "x".repeat(MAX_PREVIEW_CHARS);
+        let preview = source_preview(&format!("{wide}\n{wide}\n"), 1, Some(2)).unwrap();
+        assert!(preview.len() <= MAX_PREVIEW_CHARS + "  ...\n".len());
+        assert!(preview.ends_with("  ...\n"));
+    }
+
     #[test]
     fn test_process_definition_empty() {
         let def = process_definition(&json!(null));
@@ -475,6 +605,39 @@ mod tests {
         assert!(content.contains("func foo()"));
     }
 
+    #[test]
+    fn test_parse_pull_diagnostics_full_report() {
+        // Recorded `textDocument/diagnostic` response.
+        let report = json!({
+            "kind": "full",
+            "resultId": "3",
+            "items": [
+                {
+                    "range": {
+                        "start": {"line": 4, "character": 8},
+                        "end": {"line": 4, "character": 13}
+                    },
+                    "severity": 1,
+                    "code": "semantic",
+                    "source": "Cangjie",
+                    "message": "undeclared identifier 'value'"
+                }
+            ]
+        });
+        let items = parse_pull_diagnostics(&report).unwrap();
+        let result = process_diagnostics(&items);
+        assert_eq!(result.error_count, 1);
+        assert_eq!(result.diagnostics[0].line, 5);
+        assert_eq!(result.diagnostics[0].character, 9);
+        assert_eq!(
+            result.diagnostics[0].message,
+            "undeclared identifier 'value'"
+        );
+
+        assert!(parse_pull_diagnostics(&json!({"kind": "unchanged", "resultId": "3"})).is_none());
+        assert!(parse_pull_diagnostics(&Value::Null).is_none());
+    }
+
     #[test]
     fn test_process_diagnostics_info_and_hint() {
         let diags = vec![
@@ -536,4 +699,34 @@ mod tests {
         assert_eq!(syms.symbols[0].name, "globalVar");
         assert_eq!(syms.symbols[0].kind, "variable");
     }
+
+    fn workspace_symbol(name: &str, line: u32) -> Value {
+        json!({
+            "name": name,
+            "kind": 12,
+            "location": {
+                "uri": "file:///test/main.cj",
+                "range": {
+                    "start": {"line": line, "character": 0},
+                    "end": {"line": line, "character": 10}
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_workspace_symbols_rank_prefix_above_substring() {
+        let result = json!([
+            workspace_symbol("resetParser", 1),
+            workspace_symbol("unrelated", 2),
+            workspace_symbol("parseExpr", 3),
+        ]);
+        let ranked = process_workspace_symbols(&result, "Parse", 10);
+        let names: Vec<&str> = ranked.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["parseExpr", "resetParser", "unrelated"]);
+
+        let capped = process_workspace_symbols(&result, "parse", 1);
+        assert_eq!(capped.count, 1);
+        assert_eq!(capped.symbols[0].name, "parseExpr");
+    }
 }
@@ -9,6 +9,9 @@ pub struct LocationResult {
     pub end_line: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub end_character: Option<u32>,
+    /// Numbered source lines around the location, when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
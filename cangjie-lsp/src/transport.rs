@@ -1,6 +1,6 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex};
 
 use jsonrpsee::core::client::{ReceivedMessage, TransportReceiverT, TransportSenderT};
 use serde_json::{json, value::RawValue, Value};
@@ -323,7 +323,43 @@ pub(crate) async fn stdin_task(
     debug!("LSP stdin task exited");
 }
 
-pub(crate) async fn stderr_task(stderr: tokio::process::ChildStderr) {
+/// The most recent lines of LSP server stderr, dropping the oldest once
+/// `capacity` is reached.
+#[derive(Debug)]
+pub struct LogBuffer {
+    lines: StdMutex<VecDeque<String>>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            lines: StdMutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    pub fn push(&self, line: impl Into<String>) {
+        let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line.into());
+    }
+
+    /// The last `n` captured lines, oldest first.
+    pub fn tail(&self, n: usize) -> Vec<String> {
+        let lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        lines
+            .iter()
+            .skip(lines.len().saturating_sub(n))
+            .cloned()
+            .collect()
+    }
+}
+
+pub(crate) async fn stderr_task(stderr: tokio::process::ChildStderr, logs: Arc<LogBuffer>) {
     let reader = BufReader::new(stderr);
     let mut lines = reader.lines();
     loop {
@@ -332,6 +368,7 @@ pub(crate) async fn stderr_task(stderr: tokio::process::ChildStderr) {
                 let trimmed = line.trim();
                 if !trimmed.is_empty() {
                     warn!("[LSP stderr] {}", trimmed);
+                    logs.push(trimmed);
                 }
             }
             Ok(None) => {
@@ -370,3 +407,19 @@ pub(crate) async fn process_monitor(mut child: tokio::process::Child, running: A
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_buffer_keeps_newest_lines_in_order() {
+        let logs = LogBuffer::new(3);
+        for i in 1..=5 {
+            logs.push(format!("line {i}"));
+        }
+        assert_eq!(logs.tail(10), vec!["line 3", "line 4", "line 5"]);
+        assert_eq!(logs.tail(2), vec!["line 4", "line 5"]);
+        assert!(logs.tail(0).is_empty());
+    }
+}
@@ -1,5 +1,7 @@
 mod cjpm;
 mod path;
+mod position;
 
 pub use cjpm::*;
 pub use path::*;
+pub use position::{positions_to_chars, PositionEncoding};
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::uri_to_path;
+
+/// Unit the server counts `Position.character` in, negotiated through
+/// `capabilities.positionEncoding` in the `initialize` response. LSP falls
+/// back to UTF-16 when the server does not say.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum PositionEncoding {
+    #[serde(rename = "utf-8")]
+    Utf8,
+    #[default]
+    #[serde(rename = "utf-16")]
+    Utf16,
+    #[serde(rename = "utf-32")]
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Encoding chosen in a server capabilities object; UTF-16 when absent
+    /// or unrecognized.
+    pub fn from_capabilities(capabilities: &Value) -> Self {
+        match capabilities.get("positionEncoding").and_then(Value::as_str) {
+            Some("utf-8") => Self::Utf8,
+            Some("utf-32") => Self::Utf32,
+            _ => Self::Utf16,
+        }
+    }
+
+    fn char_units(self, c: char) -> u32 {
+        match self {
+            Self::Utf8 => c.len_utf8() as u32,
+            Self::Utf16 => c.len_utf16() as u32,
+            Self::Utf32 => 1,
+        }
+    }
+
+    /// Offset, in this encoding, of the character at 0-based index `chars`
+    /// in `line`. Past the end of the line each character counts as one unit.
+    pub fn units_for_chars(self, line: &str, chars: u32) -> u32 {
+        let mut units = 0;
+        let mut seen = 0;
+        for c in line.chars().take(chars as usize) {
+            units += self.char_units(c);
+            seen += 1;
+        }
+        units + (chars - seen)
+    }
+
+    /// 0-based character index of the offset `units` (in this encoding) in
+    /// `line`; the inverse of [`Self::units_for_chars`]. An offset inside a
+    /// multi-unit character maps to that character.
+    pub fn chars_for_units(self, line: &str, units: u32) -> u32 {
+        let mut consumed = 0;
+        let mut chars = 0;
+        for c in line.chars() {
+            if consumed >= units {
+                return chars;
+            }
+            consumed += self.char_units(c);
+            chars += 1;
+        }
+        chars + units.saturating_sub(consumed)
+    }
+}
+
+/// Rewrite every `Position` (an object with numeric `line` and `character`)
+/// in the LSP response `value` from `encoding` units to character offsets, the
+/// unit tool callers give columns in. A position's line text comes from the
+/// file named by the nearest enclosing `uri` or `targetUri`, else from
+/// `default_path`; positions in files that cannot be read are left as is.
+pub fn positions_to_chars(
+    value: &mut Value,
+    encoding: PositionEncoding,
+    default_path: Option<&Path>,
+) {
+    if encoding == PositionEncoding::Utf32 {
+        return;
+    }
+    let mut files = HashMap::new();
+    convert_positions(
+        value,
+        encoding,
+        default_path.map(Path::to_path_buf),
+        &mut files,
+    );
+}
+
+fn convert_positions(
+    value: &mut Value,
+    encoding: PositionEncoding,
+    path: Option<PathBuf>,
+    files: &mut HashMap<PathBuf, Option<Vec<String>>>,
+) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                convert_positions(item, encoding, path.clone(), files);
+            }
+        }
+        Value::Object(map) => {
+            let path = map
+                .get("uri")
+                .or_else(|| map.get("targetUri"))
+                .and_then(Value::as_str)
+                .map(uri_to_path)
+                .or(path);
+            let line = map.get("line").and_then(Value::as_u64);
+            let character = map.get("character").and_then(Value::as_u64);
+            if let (Some(line), Some(character)) = (line, character) {
+                let text = path.and_then(|path| {
+                    files
+                        .entry(path)
+                        .or_insert_with_key(|path| {
+                            std::fs::read_to_string(path)
+                                .ok()
+                                .map(|content| content.lines().map(str::to_string).collect())
+                        })
+                        .as_ref()
+                        .and_then(|lines| lines.get(line as usize))
+                });
+                if let Some(text) = text {
+                    let chars = encoding.chars_for_units(text, character as u32);
+                    map.insert("character".to_string(), chars.into());
+                }
+                return;
+            }
+            for child in map.values_mut() {
+                convert_positions(child, encoding, path.clone(), files);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_capabilities_defaults_to_utf16() {
+        let caps = serde_json::json!({"positionEncoding": "utf-8"});
+        assert_eq!(
+            PositionEncoding::from_capabilities(&caps),
+            PositionEncoding::Utf8
+        );
+        assert_eq!(
+            PositionEncoding::from_capabilities(&serde_json::json!({})),
+            PositionEncoding::Utf16
+        );
+    }
+
+    #[test]
+    fn test_surrogate_pair_offsets_round_trip() {
+        // '😀' is one character, two UTF-16 units, and four UTF-8 bytes.
+        let line = "let s = \"😀\" + name";
+        let name = line.chars().position(|c| c == 'n').unwrap() as u32;
+        assert_eq!(PositionEncoding::Utf32.units_for_chars(line, name), name);
+        assert_eq!(
+            PositionEncoding::Utf16.units_for_chars(line, name),
+            name + 1
+        );
+        assert_eq!(PositionEncoding::Utf8.units_for_chars(line, name), name + 3);
+
+        for encoding in [
+            PositionEncoding::Utf8,
+            PositionEncoding::Utf16,
+            PositionEncoding::Utf32,
+        ] {
+            let units = encoding.units_for_chars(line, name);
+            assert_eq!(encoding.chars_for_units(line, units), name, "{encoding:?}");
+        }
+        // Past the end of the line offsets map one to one.
+        assert_eq!(PositionEncoding::Utf16.units_for_chars("", 4), 4);
+        assert_eq!(PositionEncoding::Utf16.chars_for_units("", 4), 4);
+    }
+
+    #[test]
+    fn test_positions_to_chars_reads_each_locations_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let emoji = dir.path().join("emoji.cj");
+        std::fs::write(&emoji, "main() {\n    let s = \"😀\" + name\n}\n").unwrap();
+        let plain = dir.path().join("plain.cj");
+        std::fs::write(&plain, "main() {\n    let s = \"a\" + name\n}\n").unwrap();
+        let uri = |path: &Path| format!("file://{}", path.display());
+
+        // `name` is character 18 in both files, but UTF-16 offset 19 in the
+        // one with the emoji.
+        let mut result = serde_json::json!([
+            {"uri": uri(&emoji), "range": {
+                "start": {"line": 1, "character": 19},
+                "end": {"line": 1, "character": 23}
+            }},
+            {"uri": uri(&plain), "range": {
+                "start": {"line": 1, "character": 18},
+                "end": {"line": 1, "character": 22}
+            }},
+            {"range": {"start": {"line": 1, "character": 19}, "end": {"line": 9, "character": 2}}}
+        ]);
+        positions_to_chars(&mut result, PositionEncoding::Utf16, Some(&emoji));
+
+        assert_eq!(result[0]["range"]["start"]["character"], 18);
+        assert_eq!(result[0]["range"]["end"]["character"], 22);
+        assert_eq!(result[1]["range"]["start"]["character"], 18);
+        // Without a uri the default path is used; lines past the end are kept.
+        assert_eq!(result[2]["range"]["start"]["character"], 18);
+        assert_eq!(result[2]["range"]["end"]["character"], 2);
+    }
+}
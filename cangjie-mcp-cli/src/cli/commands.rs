@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use cangjie_server::lsp_tools::{LspOperation, LspRequest, LspTarget};
 use rmcp::model::{CallToolRequestParams, Meta};
 use serde_json::{json, Map, Value};
@@ -12,6 +14,27 @@ fn make_params(name: &str, arguments: Value) -> CallToolRequestParams {
     CallToolRequestParams::new(name.to_string()).with_arguments(args)
 }
 
+/// Pass `dir` (or the current directory) via _meta (header-style, not
+/// visible in tool schema).
+fn with_working_directory(
+    mut params: CallToolRequestParams,
+    dir: Option<&Path>,
+) -> CallToolRequestParams {
+    let dir = match dir {
+        Some(dir) => std::path::absolute(dir).ok(),
+        None => std::env::current_dir().ok(),
+    };
+    if let Some(dir) = dir {
+        let mut meta = Meta::new();
+        meta.0.insert(
+            cangjie_server::lsp_tools::META_WORKING_DIRECTORY.to_string(),
+            json!(dir.to_string_lossy()),
+        );
+        params.meta = Some(meta);
+    }
+    params
+}
+
 fn build_lsp_target(
     symbol: &Option<String>,
     line: &Option<u32>,
@@ -39,11 +62,13 @@ fn lsp_command_to_request(cmd: &LspCommand) -> LspRequest {
             symbol,
             line,
             character,
+            preview,
         } => LspRequest {
             operation: LspOperation::Definition,
             file_path: Some(file.clone()),
             target: build_lsp_target(symbol, line, character),
             query: None,
+            preview: *preview,
         },
         LspCommand::References {
             file,
@@ -55,6 +80,7 @@ fn lsp_command_to_request(cmd: &LspCommand) -> LspRequest {
             file_path: Some(file.clone()),
             target: build_lsp_target(symbol, line, character),
             query: None,
+            preview: false,
         },
         LspCommand::Hover {
             file,
@@ -66,24 +92,28 @@ fn lsp_command_to_request(cmd: &LspCommand) -> LspRequest {
             file_path: Some(file.clone()),
             target: build_lsp_target(symbol, line, character),
             query: None,
+            preview: false,
         },
         LspCommand::Symbols { file } => LspRequest {
             operation: LspOperation::DocumentSymbol,
             file_path: Some(file.clone()),
             target: None,
             query: None,
+            preview: false,
         },
         LspCommand::Diagnostics { file } => LspRequest {
             operation: LspOperation::Diagnostics,
             file_path: Some(file.clone()),
             target: None,
             query: None,
+            preview: false,
         },
         LspCommand::WorkspaceSymbol { query } => LspRequest {
             operation: LspOperation::WorkspaceSymbol,
             file_path: None,
             target: None,
             query: Some(query.clone()),
+            preview: false,
         },
         LspCommand::IncomingCalls {
             file,
@@ -95,6 +125,7 @@ fn lsp_command_to_request(cmd: &LspCommand) -> LspRequest {
             file_path: Some(file.clone()),
             target: build_lsp_target(symbol, line, character),
             query: None,
+            preview: false,
         },
         LspCommand::OutgoingCalls {
             file,
@@ -106,6 +137,7 @@ fn lsp_command_to_request(cmd: &LspCommand) -> LspRequest {
             file_path: Some(file.clone()),
             target: build_lsp_target(symbol, line, character),
             query: None,
+            preview: false,
         },
         LspCommand::TypeSupertypes {
             file,
@@ -117,6 +149,7 @@ fn lsp_command_to_request(cmd: &LspCommand) -> LspRequest {
             file_path: Some(file.clone()),
             target: build_lsp_target(symbol, line, character),
             query: None,
+            preview: false,
         },
         LspCommand::TypeSubtypes {
             file,
@@ -128,6 +161,7 @@ fn lsp_command_to_request(cmd: &LspCommand) -> LspRequest {
             file_path: Some(file.clone()),
             target: build_lsp_target(symbol, line, character),
             query: None,
+            preview: false,
         },
     }
 }
@@ -157,20 +191,17 @@ pub fn command_to_tool_call(cmd: &Commands) -> Option<CallToolRequestParams> {
         Commands::Lsp { operation } => {
             let request = lsp_command_to_request(operation);
             let args = serde_json::to_value(&request).unwrap_or_default();
-            let mut params = make_params("cangjie_lsp", args);
-            // Pass working directory via _meta (header-style, not visible in tool schema)
-            if let Ok(cwd) = std::env::current_dir() {
-                let mut meta = Meta::new();
-                meta.0.insert(
-                    cangjie_server::lsp_tools::META_WORKING_DIRECTORY.to_string(),
-                    json!(cwd.to_string_lossy()),
-                );
-                params.meta = Some(meta);
-            }
-            Some(params)
+            let params = make_params("cangjie_lsp", args);
+            Some(with_working_directory(params, None))
         }
-        Commands::Serve | Commands::Index | Commands::Daemon { .. } | Commands::Config { .. } => {
-            None
+        Commands::CjpmCheck { dir } => {
+            let params = make_params("cangjie_cjpm_check", json!({}));
+            Some(with_working_directory(params, dir.as_deref()))
         }
+        Commands::Serve
+        | Commands::Index
+        | Commands::Export { .. }
+        | Commands::Daemon { .. }
+        | Commands::Config { .. } => None,
     }
 }
@@ -6,11 +6,22 @@ use std::path::PathBuf;
 use clap::{Args, Parser, Subcommand};
 
 use cangjie_core::config::{
-    self, DocLang, EmbeddingType, RerankType, Settings, DEFAULT_CHUNK_OVERLAP_CHARS,
-    DEFAULT_DOCS_VERSION, DEFAULT_HTTP_ENABLE_HTTP2, DEFAULT_HTTP_POOL_IDLE_TIMEOUT_SECS,
-    DEFAULT_HTTP_POOL_MAX_IDLE_PER_HOST, DEFAULT_HTTP_TCP_KEEPALIVE_SECS, DEFAULT_LOCAL_MODEL,
-    DEFAULT_MAX_PER_FILE, DEFAULT_OPENAI_BASE_URL, DEFAULT_OPENAI_MODEL, DEFAULT_RERANK_INITIAL_K,
-    DEFAULT_RERANK_MODEL, DEFAULT_RERANK_TOP_K, DEFAULT_RRF_K,
+    self, CategoryWeight, DocLang, EmbeddingType, IndexPolicy, OutputFormat, RerankStage,
+    RerankType, Settings, VectorDistance, DEFAULT_CHUNK_BREADCRUMBS, DEFAULT_CHUNK_OVERLAP_CHARS,
+    DEFAULT_DECOMPRESS_DOCS, DEFAULT_DOCS_VERSION, DEFAULT_DOCS_WEB_BASE,
+    DEFAULT_EXTRA_DOCS_PREFIX, DEFAULT_FUSION_CANDIDATE_K, DEFAULT_GIT_RETRIES,
+    DEFAULT_HTTP_CONNECT_TIMEOUT_SECS, DEFAULT_HTTP_ENABLE_HTTP2,
+    DEFAULT_HTTP_POOL_IDLE_TIMEOUT_SECS, DEFAULT_HTTP_POOL_MAX_IDLE_PER_HOST,
+    DEFAULT_HTTP_RETRY_BASE_MS, DEFAULT_HTTP_RETRY_CAP_MS, DEFAULT_HTTP_TCP_KEEPALIVE_SECS,
+    DEFAULT_HTTP_USER_AGENT, DEFAULT_INDEX_BUILD_TIMEOUT_SECS, DEFAULT_INDEX_CODE,
+    DEFAULT_LENGTH_PENALTY, DEFAULT_LOCAL_MODEL, DEFAULT_MAX_CONCURRENT_GIT_OPS,
+    DEFAULT_MAX_CONCURRENT_TOOLS, DEFAULT_MAX_FETCH_CANDIDATES, DEFAULT_MAX_PER_FILE,
+    DEFAULT_OPENAI_BASE_URL, DEFAULT_OPENAI_MODEL, DEFAULT_REFRESH_INTERVAL_MINS,
+    DEFAULT_REMOTE_CACHE_TTL_SECS, DEFAULT_REMOTE_MSGPACK, DEFAULT_RERANK_INITIAL_K,
+    DEFAULT_RERANK_MAX_DOC_CHARS, DEFAULT_RERANK_MODEL, DEFAULT_RERANK_TOP_K,
+    DEFAULT_RERANK_WARMUP, DEFAULT_RERANK_WINDOW_TOKENS, DEFAULT_RRF_K, DEFAULT_SKIP_DISK_CHECK,
+    DEFAULT_STRIP_HTML, DEFAULT_TITLE_BOOST, DEFAULT_VECTOR_INSERT_BATCH_SIZE, DEFAULT_WATCH,
+    TOPIC_SUGGESTION_THRESHOLD, VECTOR_SCORE_THRESHOLD,
 };
 
 pub const DEFAULT_DAEMON_TIMEOUT_MINUTES: u64 = 30;
@@ -117,6 +128,47 @@ pub struct ServerOptions {
     #[arg(long = "rerank-initial-k", env = "CANGJIE_RERANK_INITIAL_K", default_value_t = DEFAULT_RERANK_INITIAL_K, global = true)]
     pub rerank_initial_k: usize,
 
+    /// Truncate each rerank candidate to this many characters before sending it to the API reranker
+    #[arg(long = "rerank-max-doc-chars", env = "CANGJIE_RERANK_MAX_DOC_CHARS", default_value_t = DEFAULT_RERANK_MAX_DOC_CHARS, global = true)]
+    pub rerank_max_doc_chars: usize,
+
+    /// Estimated token budget per API rerank request; larger candidate lists are reranked in overlapping windows
+    #[arg(long = "rerank-window-tokens", env = "CANGJIE_RERANK_WINDOW_TOKENS", default_value_t = DEFAULT_RERANK_WINDOW_TOKENS, global = true)]
+    pub rerank_window_tokens: usize,
+
+    /// Warm up the reranker at startup (disable for a faster start; also skips the OpenAI rerank credential check)
+    #[arg(long = "rerank-warmup", env = "CANGJIE_RERANK_WARMUP", default_value_t = DEFAULT_RERANK_WARMUP, global = true)]
+    pub rerank_warmup: bool,
+
+    /// Also index each code block with its surrounding prose for code search
+    #[arg(long = "index-code", env = "CANGJIE_INDEX_CODE", default_value_t = DEFAULT_INDEX_CODE, global = true)]
+    pub index_code: bool,
+
+    /// Transparently gunzip gzip-compressed markdown files in the docs repositories
+    #[arg(long = "decompress-docs", env = "CANGJIE_DECOMPRESS_DOCS", default_value_t = DEFAULT_DECOMPRESS_DOCS, global = true)]
+    pub decompress_docs: bool,
+
+    /// Remove HTML tags embedded in markdown docs, keeping their text, before indexing; changing it rebuilds the index
+    #[arg(long = "strip-html", env = "CANGJIE_STRIP_HTML", default_value_t = DEFAULT_STRIP_HTML, global = true)]
+    pub strip_html: bool,
+
+    /// Additional markdown directory to index alongside the official docs (repeatable)
+    #[arg(
+        long = "extra-docs-dir",
+        env = "CANGJIE_EXTRA_DOCS_DIRS",
+        value_delimiter = ',',
+        global = true
+    )]
+    pub extra_docs_dirs: Vec<PathBuf>,
+
+    /// Category prefix for documents loaded from --extra-docs-dir
+    #[arg(long = "extra-docs-prefix", env = "CANGJIE_EXTRA_DOCS_PREFIX", default_value = DEFAULT_EXTRA_DOCS_PREFIX, global = true)]
+    pub extra_docs_prefix: String,
+
+    /// Web base URL used to build source_url links to the upstream docs
+    #[arg(long = "docs-web-base", env = "CANGJIE_DOCS_WEB_BASE", default_value = DEFAULT_DOCS_WEB_BASE, global = true)]
+    pub docs_web_base: String,
+
     /// Max chunk size in characters (omit to use dynamic detection)
     #[arg(long = "chunk-size", env = "CANGJIE_CHUNK_MAX_SIZE", global = true)]
     pub max_chunk_chars: Option<usize>,
@@ -125,18 +177,131 @@ pub struct ServerOptions {
     #[arg(long = "chunk-overlap", env = "CANGJIE_CHUNK_OVERLAP", default_value_t = DEFAULT_CHUNK_OVERLAP_CHARS, global = true)]
     pub chunk_overlap_chars: usize,
 
+    /// Prefix each chunk's indexed text with its enclosing headings (e.g. [A > B > C])
+    #[arg(long = "chunk-breadcrumbs", env = "CANGJIE_CHUNK_BREADCRUMBS", default_value_t = DEFAULT_CHUNK_BREADCRUMBS, global = true)]
+    pub chunk_breadcrumbs: bool,
+
+    /// Rebuild an existing index when its chunking parameters differ (auto) or always reuse it (never)
+    #[arg(
+        long = "index-policy",
+        env = "CANGJIE_INDEX_POLICY",
+        default_value = "auto",
+        global = true
+    )]
+    pub index_policy: IndexPolicy,
+
+    /// Build the index without first checking for free space on the data dir's filesystem
+    #[arg(long = "skip-disk-check", env = "CANGJIE_SKIP_DISK_CHECK", default_value_t = DEFAULT_SKIP_DISK_CHECK, global = true)]
+    pub skip_disk_check: bool,
+
     /// Maximum search results per file
     #[arg(long = "max-per-file", env = "CANGJIE_MAX_PER_FILE", default_value_t = DEFAULT_MAX_PER_FILE, global = true)]
     pub max_per_file: usize,
 
+    /// Maximum number of search and LSP tool calls run at once; further calls wait
+    #[arg(long = "max-concurrent-tools", env = "CANGJIE_MAX_CONCURRENT_TOOLS", default_value_t = DEFAULT_MAX_CONCURRENT_TOOLS, global = true)]
+    pub max_concurrent_tools: usize,
+
     /// LLM model for generating chunk context summaries
     #[arg(long = "summary-model", env = "CANGJIE_SUMMARY_MODEL", global = true)]
     pub summary_model: Option<String>,
 
+    /// Stop words dropped from search queries, one per line (replaces the built-in zh/en list)
+    #[arg(long = "stopwords-file", env = "CANGJIE_STOPWORDS_FILE", global = true)]
+    pub stopwords_file: Option<PathBuf>,
+
+    /// MCP tools to expose; all others are hidden (comma-separated or repeatable)
+    #[arg(
+        long = "enabled-tools",
+        env = "CANGJIE_ENABLED_TOOLS",
+        value_delimiter = ',',
+        global = true
+    )]
+    pub enabled_tools: Vec<String>,
+
+    /// MCP tools to hide (comma-separated or repeatable)
+    #[arg(
+        long = "disabled-tools",
+        env = "CANGJIE_DISABLED_TOOLS",
+        value_delimiter = ',',
+        global = true
+    )]
+    pub disabled_tools: Vec<String>,
+
+    /// Topics (`category/topic`) listed first by the HTTP `/topics` endpoint and the MCP resource list, in this order (comma-separated or repeatable)
+    #[arg(
+        long = "pinned-topics",
+        env = "CANGJIE_PINNED_TOPICS",
+        value_delimiter = ',',
+        global = true
+    )]
+    pub pinned_topics: Vec<String>,
+
+    /// Multiply the scores of a category's results, e.g. `stdlib=1.5` (comma-separated or repeatable; unlisted categories weigh 1.0)
+    #[arg(
+        long = "category-weight",
+        env = "CANGJIE_CATEGORY_WEIGHTS",
+        value_delimiter = ',',
+        global = true
+    )]
+    pub category_weights: Vec<CategoryWeight>,
+
+    /// Format of search_docs/get_topics results when a request does not set response_format: json (default) or markdown
+    #[arg(long = "output-format", env = "CANGJIE_OUTPUT_FORMAT", global = true)]
+    pub output_format: Option<OutputFormat>,
+
     /// RRF constant k for hybrid search fusion
     #[arg(long = "rrf-k", env = "CANGJIE_RRF_K", default_value_t = DEFAULT_RRF_K, global = true)]
     pub rrf_k: u32,
 
+    /// Candidates fetched from each retriever before hybrid fusion (capped at 200)
+    #[arg(long = "fusion-candidate-k", env = "CANGJIE_FUSION_CANDIDATE_K", default_value_t = DEFAULT_FUSION_CANDIDATE_K, global = true)]
+    pub fusion_candidate_k: usize,
+
+    /// Where the reranker runs in hybrid search: post-fusion (once, on the
+    /// fused list) or pre-fusion (on the BM25 and vector lists separately;
+    /// two reranker calls). Experimental
+    #[arg(
+        long = "rerank-stage",
+        env = "CANGJIE_RERANK_STAGE",
+        default_value = "post-fusion",
+        global = true
+    )]
+    pub rerank_stage: RerankStage,
+
+    /// Upper bound on candidates fetched per search_docs call, whatever the offset and filters
+    #[arg(long = "max-fetch-candidates", env = "CANGJIE_MAX_FETCH_CANDIDATES", default_value_t = DEFAULT_MAX_FETCH_CANDIDATES, global = true)]
+    pub max_fetch_candidates: usize,
+
+    /// BM25 weight of title/topic matches relative to body text (0 disables)
+    #[arg(long = "title-boost", env = "CANGJIE_TITLE_BOOST", default_value_t = DEFAULT_TITLE_BOOST, global = true)]
+    pub title_boost: f32,
+
+    /// Discount for search_docs results much longer than the median candidate (0 disables)
+    #[arg(long = "length-penalty", env = "CANGJIE_LENGTH_PENALTY", default_value_t = DEFAULT_LENGTH_PENALTY, global = true)]
+    pub length_penalty: f64,
+
+    /// Rows written per transaction when building the vector store
+    #[arg(long = "vector-insert-batch-size", env = "CANGJIE_VECTOR_INSERT_BATCH_SIZE", default_value_t = DEFAULT_VECTOR_INSERT_BATCH_SIZE, global = true)]
+    pub vector_insert_batch_size: usize,
+
+    /// Minimum similarity score for vector search hits
+    #[arg(long = "vector-score-threshold", env = "CANGJIE_VECTOR_SCORE_THRESHOLD", default_value_t = VECTOR_SCORE_THRESHOLD, global = true)]
+    pub vector_score_threshold: f64,
+
+    /// Vector similarity used to build and search the vector store: cosine, l2, or dot
+    #[arg(
+        long = "vector-distance",
+        env = "CANGJIE_VECTOR_DISTANCE",
+        default_value = "l2",
+        global = true
+    )]
+    pub vector_distance: VectorDistance,
+
+    /// Minimum name similarity for suggesting topics when a lookup misses
+    #[arg(long = "topic-suggestion-threshold", env = "CANGJIE_TOPIC_SUGGESTION_THRESHOLD", default_value_t = TOPIC_SUGGESTION_THRESHOLD, global = true)]
+    pub topic_suggestion_threshold: f64,
+
     /// Data directory path
     #[arg(
         long = "data-dir",
@@ -146,10 +311,26 @@ pub struct ServerOptions {
     )]
     pub data_dir: Option<PathBuf>,
 
-    /// URL of a remote cangjie-mcp server to forward queries to
+    /// Documentation git checkout directory (default: <data-dir>/docs_repo)
+    #[arg(long = "docs-repo-dir", env = "CANGJIE_DOCS_REPO_DIR", global = true)]
+    pub docs_repo_dir: Option<PathBuf>,
+
+    /// Serve a fully built index from this directory as-is, without deriving it from <data-dir> or building
+    #[arg(long = "index-dir", env = "CANGJIE_INDEX_DIR", global = true)]
+    pub index_dir: Option<PathBuf>,
+
+    /// URL of a remote cangjie-mcp server to forward queries to (comma-separated list for failover)
     #[arg(long = "server-url", env = "CANGJIE_SERVER_URL", global = true)]
     pub server_url: Option<String>,
 
+    /// Seconds to cache the remote server's topic list (0 disables caching)
+    #[arg(long = "remote-cache-ttl-secs", env = "CANGJIE_REMOTE_CACHE_TTL_SECS", default_value_t = DEFAULT_REMOTE_CACHE_TTL_SECS, global = true)]
+    pub remote_cache_ttl_secs: u64,
+
+    /// Ask the remote server for MessagePack instead of JSON search results
+    #[arg(long = "remote-msgpack", env = "CANGJIE_REMOTE_MSGPACK", default_value_t = DEFAULT_REMOTE_MSGPACK, global = true)]
+    pub remote_msgpack: bool,
+
     /// HTTP client pool idle timeout in seconds
     #[arg(long = "http-pool-idle-timeout-secs", env = "CANGJIE_HTTP_POOL_IDLE_TIMEOUT_SECS", default_value_t = DEFAULT_HTTP_POOL_IDLE_TIMEOUT_SECS, global = true)]
     pub http_pool_idle_timeout_secs: u64,
@@ -162,9 +343,45 @@ pub struct ServerOptions {
     #[arg(long = "http-tcp-keepalive-secs", env = "CANGJIE_HTTP_TCP_KEEPALIVE_SECS", default_value_t = DEFAULT_HTTP_TCP_KEEPALIVE_SECS, global = true)]
     pub http_tcp_keepalive_secs: u64,
 
+    /// TCP connect timeout for outbound HTTP in seconds
+    #[arg(long = "http-connect-timeout-secs", env = "CANGJIE_HTTP_CONNECT_TIMEOUT_SECS", default_value_t = DEFAULT_HTTP_CONNECT_TIMEOUT_SECS, global = true)]
+    pub http_connect_timeout_secs: u64,
+
     /// Enable HTTP/2 for outbound HTTP client
     #[arg(long = "http2", env = "CANGJIE_HTTP2", default_value_t = DEFAULT_HTTP_ENABLE_HTTP2, global = true)]
     pub http_enable_http2: bool,
+
+    /// User-Agent header sent on outbound HTTP requests
+    #[arg(long = "http-user-agent", env = "CANGJIE_HTTP_USER_AGENT", default_value = DEFAULT_HTTP_USER_AGENT, global = true)]
+    pub http_user_agent: String,
+
+    /// Retries for transient git clone/fetch failures
+    #[arg(long = "git-retries", env = "CANGJIE_GIT_RETRIES", default_value_t = DEFAULT_GIT_RETRIES, global = true)]
+    pub git_retries: usize,
+
+    /// Maximum number of git tree reads run at once on one repository
+    #[arg(long = "max-concurrent-git-ops", env = "CANGJIE_MAX_CONCURRENT_GIT_OPS", default_value_t = DEFAULT_MAX_CONCURRENT_GIT_OPS, global = true)]
+    pub max_concurrent_git_ops: usize,
+
+    /// Give up on initializing or building the index after this many seconds (0 = no limit)
+    #[arg(long = "index-build-timeout-secs", env = "CANGJIE_INDEX_BUILD_TIMEOUT_SECS", default_value_t = DEFAULT_INDEX_BUILD_TIMEOUT_SECS, global = true)]
+    pub index_build_timeout_secs: u64,
+
+    /// Periodically fetch the docs version and rebuild the index when its commit changes
+    #[arg(long = "watch", env = "CANGJIE_WATCH", default_value_t = DEFAULT_WATCH, global = true)]
+    pub watch: bool,
+
+    /// Minutes between docs refreshes in --watch mode
+    #[arg(long = "refresh-interval-mins", env = "CANGJIE_REFRESH_INTERVAL_MINS", default_value_t = DEFAULT_REFRESH_INTERVAL_MINS, global = true)]
+    pub refresh_interval_mins: u64,
+
+    /// Base delay in milliseconds for retried HTTP requests (jittered, doubling per attempt)
+    #[arg(long = "http-retry-base-ms", env = "CANGJIE_HTTP_RETRY_BASE_MS", default_value_t = DEFAULT_HTTP_RETRY_BASE_MS, global = true)]
+    pub http_retry_base_ms: u64,
+
+    /// Maximum delay in milliseconds between retried HTTP requests
+    #[arg(long = "http-retry-cap-ms", env = "CANGJIE_HTTP_RETRY_CAP_MS", default_value_t = DEFAULT_HTTP_RETRY_CAP_MS, global = true)]
+    pub http_retry_cap_ms: u64,
 }
 
 impl ServerOptions {
@@ -186,23 +403,64 @@ impl ServerOptions {
             rerank_model: self.rerank_model.clone(),
             rerank_top_k: self.rerank_top_k,
             rerank_initial_k: self.rerank_initial_k,
+            rerank_max_doc_chars: self.rerank_max_doc_chars,
+            rerank_window_tokens: self.rerank_window_tokens,
+            rerank_warmup: self.rerank_warmup,
+            index_code: self.index_code,
+            decompress_docs: self.decompress_docs,
+            strip_html: self.strip_html,
+            extra_docs_dirs: self.extra_docs_dirs.clone(),
+            extra_docs_prefix: self.extra_docs_prefix.clone(),
+            docs_web_base: self.docs_web_base.clone(),
             rrf_k: self.rrf_k,
+            fusion_candidate_k: self.fusion_candidate_k,
+            rerank_stage: self.rerank_stage,
+            max_fetch_candidates: self.max_fetch_candidates,
+            title_boost: self.title_boost,
+            length_penalty: self.length_penalty,
+            vector_insert_batch_size: self.vector_insert_batch_size,
+            vector_score_threshold: self.vector_score_threshold,
+            vector_distance: self.vector_distance,
+            topic_suggestion_threshold: self.topic_suggestion_threshold,
             max_chunk_chars: self.max_chunk_chars,
             chunk_overlap_chars: self.chunk_overlap_chars,
+            chunk_breadcrumbs: self.chunk_breadcrumbs,
+            index_policy: self.index_policy,
+            skip_disk_check: self.skip_disk_check,
             max_per_file: self.max_per_file,
+            max_concurrent_tools: self.max_concurrent_tools,
             summary_model: self.summary_model.clone(),
+            stopwords_file: self.stopwords_file.clone(),
+            enabled_tools: self.enabled_tools.clone(),
+            disabled_tools: self.disabled_tools.clone(),
+            pinned_topics: self.pinned_topics.clone(),
+            category_weights: self.category_weights.clone(),
+            output_format: self.output_format,
             data_dir: self
                 .data_dir
                 .clone()
                 .unwrap_or_else(config::get_default_data_dir),
+            docs_repo_dir_override: self.docs_repo_dir.clone(),
+            index_dir_override: self.index_dir.clone(),
             server_url: self.server_url.clone(),
+            remote_cache_ttl_secs: self.remote_cache_ttl_secs,
+            remote_msgpack: self.remote_msgpack,
             openai_api_key: self.openai_api_key.clone(),
             openai_base_url: self.openai_base_url.clone(),
             openai_model: self.openai_model.clone(),
             http_pool_idle_timeout_secs: self.http_pool_idle_timeout_secs,
             http_pool_max_idle_per_host: self.http_pool_max_idle_per_host,
             http_tcp_keepalive_secs: self.http_tcp_keepalive_secs,
+            http_connect_timeout_secs: self.http_connect_timeout_secs,
             http_enable_http2: self.http_enable_http2,
+            http_user_agent: self.http_user_agent.clone(),
+            git_retries: self.git_retries,
+            max_concurrent_git_ops: self.max_concurrent_git_ops,
+            index_build_timeout_secs: self.index_build_timeout_secs,
+            watch: self.watch,
+            refresh_interval_mins: self.refresh_interval_mins,
+            http_retry_base_ms: self.http_retry_base_ms,
+            http_retry_cap_ms: self.http_retry_cap_ms,
             ..Settings::default()
         }
     }
@@ -232,11 +490,22 @@ pub enum Commands {
         #[command(subcommand)]
         operation: LspCommand,
     },
+    /// Check the cjpm.toml files of a project and its dependencies (exits non-zero on errors)
+    CjpmCheck {
+        /// Project directory (default: current directory)
+        dir: Option<PathBuf>,
+    },
     /// Internal: run as daemon (hidden)
     #[command(hide = true)]
     Serve,
     /// Build the search index
     Index,
+    /// Export the search index as JSONL (metadata header, then one chunk per line)
+    Export {
+        /// Output file (default: stdout)
+        #[arg(long, short = 'o')]
+        output: Option<PathBuf>,
+    },
     /// Daemon management
     Daemon {
         #[command(subcommand)]
@@ -264,6 +533,9 @@ pub enum LspCommand {
         /// Character position (1-based)
         #[arg(long, alias = "char")]
         character: Option<u32>,
+        /// Include a source preview of each definition
+        #[arg(long)]
+        preview: bool,
     },
     /// Find references
     References {
@@ -18,6 +18,15 @@ pub fn print_tool_result(result: &CallToolResult) {
     println!("{}", extract_text(result));
 }
 
+/// The `errors` count of a `cangjie_cjpm_check` report, or 0 when the tool
+/// returned something else (e.g. a message that no project was found).
+pub fn cjpm_check_errors(result: &CallToolResult) -> u64 {
+    serde_json::from_str::<serde_json::Value>(&extract_text(result))
+        .ok()
+        .and_then(|report| report["errors"].as_u64())
+        .unwrap_or(0)
+}
+
 pub fn print_error(msg: &str) {
     eprintln!("error: {msg}");
 }
@@ -34,13 +34,53 @@ pub struct FileConfig {
     pub rerank_model: Option<String>,
     pub rerank_top_k: Option<usize>,
     pub rerank_initial_k: Option<usize>,
+    pub rerank_max_doc_chars: Option<usize>,
+    pub rerank_window_tokens: Option<usize>,
+    pub rerank_warmup: Option<bool>,
+    pub index_code: Option<bool>,
+    pub decompress_docs: Option<bool>,
+    pub strip_html: Option<bool>,
+    pub extra_docs_dirs: Option<Vec<String>>,
+    pub extra_docs_prefix: Option<String>,
+    pub docs_web_base: Option<String>,
     pub chunk_size: Option<usize>,
     pub chunk_overlap: Option<usize>,
+    pub chunk_breadcrumbs: Option<bool>,
+    pub index_policy: Option<String>,
+    pub skip_disk_check: Option<bool>,
     pub max_per_file: Option<usize>,
+    pub max_concurrent_tools: Option<usize>,
     pub summary_model: Option<String>,
+    pub stopwords_file: Option<PathBuf>,
+    pub enabled_tools: Option<Vec<String>>,
+    pub disabled_tools: Option<Vec<String>>,
+    pub pinned_topics: Option<Vec<String>>,
+    pub category_weights: Option<Vec<String>>,
+    pub output_format: Option<String>,
     pub rrf_k: Option<u32>,
+    pub fusion_candidate_k: Option<usize>,
+    pub rerank_stage: Option<String>,
+    pub max_fetch_candidates: Option<usize>,
+    pub title_boost: Option<f32>,
+    pub length_penalty: Option<f64>,
+    pub vector_insert_batch_size: Option<usize>,
+    pub vector_score_threshold: Option<f64>,
+    pub vector_distance: Option<String>,
+    pub topic_suggestion_threshold: Option<f64>,
     pub data_dir: Option<String>,
+    pub docs_repo_dir: Option<String>,
+    pub index_dir: Option<String>,
     pub server_url: Option<String>,
+    pub remote_cache_ttl_secs: Option<u64>,
+    pub remote_msgpack: Option<bool>,
+    pub http_user_agent: Option<String>,
+    pub git_retries: Option<usize>,
+    pub max_concurrent_git_ops: Option<usize>,
+    pub index_build_timeout_secs: Option<u64>,
+    pub watch: Option<bool>,
+    pub refresh_interval_mins: Option<u64>,
+    pub http_retry_base_ms: Option<u64>,
+    pub http_retry_cap_ms: Option<u64>,
     pub daemon_timeout: Option<u64>,
     pub debug: Option<bool>,
     pub log_file: Option<String>,
@@ -61,13 +101,62 @@ const FIELD_ENV_MAP: &[(&str, &str)] = &[
     ("rerank_model", "CANGJIE_RERANK_MODEL"),
     ("rerank_top_k", "CANGJIE_RERANK_TOP_K"),
     ("rerank_initial_k", "CANGJIE_RERANK_INITIAL_K"),
+    ("rerank_max_doc_chars", "CANGJIE_RERANK_MAX_DOC_CHARS"),
+    ("rerank_window_tokens", "CANGJIE_RERANK_WINDOW_TOKENS"),
+    ("rerank_warmup", "CANGJIE_RERANK_WARMUP"),
+    ("index_code", "CANGJIE_INDEX_CODE"),
+    ("decompress_docs", "CANGJIE_DECOMPRESS_DOCS"),
+    ("strip_html", "CANGJIE_STRIP_HTML"),
+    ("extra_docs_dirs", "CANGJIE_EXTRA_DOCS_DIRS"),
+    ("extra_docs_prefix", "CANGJIE_EXTRA_DOCS_PREFIX"),
+    ("docs_web_base", "CANGJIE_DOCS_WEB_BASE"),
     ("chunk_size", "CANGJIE_CHUNK_MAX_SIZE"),
     ("chunk_overlap", "CANGJIE_CHUNK_OVERLAP"),
+    ("chunk_breadcrumbs", "CANGJIE_CHUNK_BREADCRUMBS"),
+    ("index_policy", "CANGJIE_INDEX_POLICY"),
+    ("skip_disk_check", "CANGJIE_SKIP_DISK_CHECK"),
     ("max_per_file", "CANGJIE_MAX_PER_FILE"),
+    ("max_concurrent_tools", "CANGJIE_MAX_CONCURRENT_TOOLS"),
     ("summary_model", "CANGJIE_SUMMARY_MODEL"),
+    ("stopwords_file", "CANGJIE_STOPWORDS_FILE"),
+    ("enabled_tools", "CANGJIE_ENABLED_TOOLS"),
+    ("disabled_tools", "CANGJIE_DISABLED_TOOLS"),
+    ("pinned_topics", "CANGJIE_PINNED_TOPICS"),
+    ("category_weights", "CANGJIE_CATEGORY_WEIGHTS"),
+    ("output_format", "CANGJIE_OUTPUT_FORMAT"),
     ("rrf_k", "CANGJIE_RRF_K"),
+    ("fusion_candidate_k", "CANGJIE_FUSION_CANDIDATE_K"),
+    ("rerank_stage", "CANGJIE_RERANK_STAGE"),
+    ("max_fetch_candidates", "CANGJIE_MAX_FETCH_CANDIDATES"),
+    ("title_boost", "CANGJIE_TITLE_BOOST"),
+    ("length_penalty", "CANGJIE_LENGTH_PENALTY"),
+    (
+        "vector_insert_batch_size",
+        "CANGJIE_VECTOR_INSERT_BATCH_SIZE",
+    ),
+    ("vector_score_threshold", "CANGJIE_VECTOR_SCORE_THRESHOLD"),
+    ("vector_distance", "CANGJIE_VECTOR_DISTANCE"),
+    (
+        "topic_suggestion_threshold",
+        "CANGJIE_TOPIC_SUGGESTION_THRESHOLD",
+    ),
     ("data_dir", "CANGJIE_DATA_DIR"),
+    ("docs_repo_dir", "CANGJIE_DOCS_REPO_DIR"),
+    ("index_dir", "CANGJIE_INDEX_DIR"),
     ("server_url", "CANGJIE_SERVER_URL"),
+    ("remote_cache_ttl_secs", "CANGJIE_REMOTE_CACHE_TTL_SECS"),
+    ("remote_msgpack", "CANGJIE_REMOTE_MSGPACK"),
+    ("http_user_agent", "CANGJIE_HTTP_USER_AGENT"),
+    ("git_retries", "CANGJIE_GIT_RETRIES"),
+    ("max_concurrent_git_ops", "CANGJIE_MAX_CONCURRENT_GIT_OPS"),
+    (
+        "index_build_timeout_secs",
+        "CANGJIE_INDEX_BUILD_TIMEOUT_SECS",
+    ),
+    ("watch", "CANGJIE_WATCH"),
+    ("refresh_interval_mins", "CANGJIE_REFRESH_INTERVAL_MINS"),
+    ("http_retry_base_ms", "CANGJIE_HTTP_RETRY_BASE_MS"),
+    ("http_retry_cap_ms", "CANGJIE_HTTP_RETRY_CAP_MS"),
     ("daemon_timeout", "CANGJIE_DAEMON_TIMEOUT"),
     ("debug", "CANGJIE_DEBUG"),
     ("log_file", "CANGJIE_LOG_FILE"),
@@ -107,6 +196,11 @@ pub fn load_config_to_env() {
                 toml::Value::Integer(n) => n.to_string(),
                 toml::Value::Boolean(b) => b.to_string(),
                 toml::Value::Float(f) => f.to_string(),
+                toml::Value::Array(items) => items
+                    .iter()
+                    .filter_map(|item| item.as_str())
+                    .collect::<Vec<_>>()
+                    .join(","),
                 _ => continue,
             };
             std::env::set_var(env_var, &s);
@@ -125,6 +219,18 @@ pub fn settings_from_env() -> Settings {
         std::env::var(key).ok().filter(|s| !s.is_empty())
     }
 
+    fn env_list(key: &str) -> Vec<String> {
+        env_opt(key)
+            .map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     fn env_usize(key: &str, default: usize) -> usize {
         std::env::var(key)
             .ok()
@@ -146,6 +252,20 @@ pub fn settings_from_env() -> Settings {
             .unwrap_or(default)
     }
 
+    fn env_f32(key: &str, default: f32) -> f32 {
+        std::env::var(key)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default)
+    }
+
+    fn env_f64(key: &str, default: f64) -> f64 {
+        std::env::var(key)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default)
+    }
+
     fn env_bool(key: &str, default: bool) -> bool {
         std::env::var(key)
             .ok()
@@ -165,6 +285,22 @@ pub fn settings_from_env() -> Settings {
         _ => RerankType::None,
     };
 
+    let index_policy = match env_str("CANGJIE_INDEX_POLICY", "auto").as_str() {
+        "never" => IndexPolicy::Never,
+        _ => IndexPolicy::Auto,
+    };
+
+    let rerank_stage = match env_str("CANGJIE_RERANK_STAGE", "post-fusion").as_str() {
+        "pre-fusion" => RerankStage::PreFusion,
+        _ => RerankStage::PostFusion,
+    };
+
+    let vector_distance = match env_str("CANGJIE_VECTOR_DISTANCE", "l2").as_str() {
+        "cosine" => VectorDistance::Cosine,
+        "dot" => VectorDistance::Dot,
+        _ => VectorDistance::L2,
+    };
+
     let docs_lang = match env_str("CANGJIE_DOCS_LANG", "zh").as_str() {
         "en" => DocLang::En,
         _ => DocLang::Zh,
@@ -187,17 +323,76 @@ pub fn settings_from_env() -> Settings {
         rerank_model: env_str("CANGJIE_RERANK_MODEL", DEFAULT_RERANK_MODEL),
         rerank_top_k: env_usize("CANGJIE_RERANK_TOP_K", DEFAULT_RERANK_TOP_K),
         rerank_initial_k: env_usize("CANGJIE_RERANK_INITIAL_K", DEFAULT_RERANK_INITIAL_K),
+        rerank_max_doc_chars: env_usize(
+            "CANGJIE_RERANK_MAX_DOC_CHARS",
+            DEFAULT_RERANK_MAX_DOC_CHARS,
+        ),
+        rerank_window_tokens: env_usize(
+            "CANGJIE_RERANK_WINDOW_TOKENS",
+            DEFAULT_RERANK_WINDOW_TOKENS,
+        ),
+        rerank_warmup: env_bool("CANGJIE_RERANK_WARMUP", DEFAULT_RERANK_WARMUP),
+        index_code: env_bool("CANGJIE_INDEX_CODE", DEFAULT_INDEX_CODE),
+        decompress_docs: env_bool("CANGJIE_DECOMPRESS_DOCS", DEFAULT_DECOMPRESS_DOCS),
+        strip_html: env_bool("CANGJIE_STRIP_HTML", DEFAULT_STRIP_HTML),
+        extra_docs_dirs: env_list("CANGJIE_EXTRA_DOCS_DIRS")
+            .into_iter()
+            .map(PathBuf::from)
+            .collect(),
+        extra_docs_prefix: env_str("CANGJIE_EXTRA_DOCS_PREFIX", DEFAULT_EXTRA_DOCS_PREFIX),
+        docs_web_base: env_str("CANGJIE_DOCS_WEB_BASE", DEFAULT_DOCS_WEB_BASE),
         rrf_k: env_u32("CANGJIE_RRF_K", DEFAULT_RRF_K),
+        fusion_candidate_k: env_usize("CANGJIE_FUSION_CANDIDATE_K", DEFAULT_FUSION_CANDIDATE_K),
+        rerank_stage,
+        max_fetch_candidates: env_usize(
+            "CANGJIE_MAX_FETCH_CANDIDATES",
+            DEFAULT_MAX_FETCH_CANDIDATES,
+        ),
+        title_boost: env_f32("CANGJIE_TITLE_BOOST", DEFAULT_TITLE_BOOST),
+        length_penalty: env_f64("CANGJIE_LENGTH_PENALTY", DEFAULT_LENGTH_PENALTY),
+        vector_insert_batch_size: env_usize(
+            "CANGJIE_VECTOR_INSERT_BATCH_SIZE",
+            DEFAULT_VECTOR_INSERT_BATCH_SIZE,
+        ),
+        vector_score_threshold: env_f64("CANGJIE_VECTOR_SCORE_THRESHOLD", VECTOR_SCORE_THRESHOLD),
+        vector_distance,
+        topic_suggestion_threshold: env_f64(
+            "CANGJIE_TOPIC_SUGGESTION_THRESHOLD",
+            TOPIC_SUGGESTION_THRESHOLD,
+        ),
         max_chunk_chars: std::env::var("CANGJIE_CHUNK_MAX_SIZE")
             .ok()
             .and_then(|v| v.parse().ok()),
         chunk_overlap_chars: env_usize("CANGJIE_CHUNK_OVERLAP", DEFAULT_CHUNK_OVERLAP_CHARS),
+        chunk_breadcrumbs: env_bool("CANGJIE_CHUNK_BREADCRUMBS", DEFAULT_CHUNK_BREADCRUMBS),
+        index_policy,
+        skip_disk_check: env_bool("CANGJIE_SKIP_DISK_CHECK", DEFAULT_SKIP_DISK_CHECK),
         max_per_file: env_usize("CANGJIE_MAX_PER_FILE", DEFAULT_MAX_PER_FILE),
+        max_concurrent_tools: env_usize(
+            "CANGJIE_MAX_CONCURRENT_TOOLS",
+            DEFAULT_MAX_CONCURRENT_TOOLS,
+        ),
         summary_model: env_opt("CANGJIE_SUMMARY_MODEL"),
+        stopwords_file: env_opt("CANGJIE_STOPWORDS_FILE").map(PathBuf::from),
+        enabled_tools: env_list("CANGJIE_ENABLED_TOOLS"),
+        disabled_tools: env_list("CANGJIE_DISABLED_TOOLS"),
+        pinned_topics: env_list("CANGJIE_PINNED_TOPICS"),
+        category_weights: env_list("CANGJIE_CATEGORY_WEIGHTS")
+            .iter()
+            .filter_map(|w| w.parse().ok())
+            .collect(),
+        output_format: env_opt("CANGJIE_OUTPUT_FORMAT").and_then(|s| s.parse().ok()),
         data_dir: env_opt("CANGJIE_DATA_DIR")
             .map(PathBuf::from)
             .unwrap_or_else(get_default_data_dir),
+        docs_repo_dir_override: env_opt("CANGJIE_DOCS_REPO_DIR").map(PathBuf::from),
+        index_dir_override: env_opt("CANGJIE_INDEX_DIR").map(PathBuf::from),
         server_url: env_opt("CANGJIE_SERVER_URL"),
+        remote_cache_ttl_secs: env_u64(
+            "CANGJIE_REMOTE_CACHE_TTL_SECS",
+            DEFAULT_REMOTE_CACHE_TTL_SECS,
+        ),
+        remote_msgpack: env_bool("CANGJIE_REMOTE_MSGPACK", DEFAULT_REMOTE_MSGPACK),
         openai_api_key: env_opt("OPENAI_API_KEY"),
         openai_base_url: env_str("OPENAI_BASE_URL", DEFAULT_OPENAI_BASE_URL),
         openai_model: env_str("OPENAI_EMBEDDING_MODEL", DEFAULT_OPENAI_MODEL),
@@ -213,7 +408,28 @@ pub fn settings_from_env() -> Settings {
             "CANGJIE_HTTP_TCP_KEEPALIVE_SECS",
             DEFAULT_HTTP_TCP_KEEPALIVE_SECS,
         ),
+        http_connect_timeout_secs: env_u64(
+            "CANGJIE_HTTP_CONNECT_TIMEOUT_SECS",
+            DEFAULT_HTTP_CONNECT_TIMEOUT_SECS,
+        ),
         http_enable_http2: env_bool("CANGJIE_HTTP2", DEFAULT_HTTP_ENABLE_HTTP2),
+        http_user_agent: env_str("CANGJIE_HTTP_USER_AGENT", DEFAULT_HTTP_USER_AGENT),
+        git_retries: env_usize("CANGJIE_GIT_RETRIES", DEFAULT_GIT_RETRIES),
+        max_concurrent_git_ops: env_usize(
+            "CANGJIE_MAX_CONCURRENT_GIT_OPS",
+            DEFAULT_MAX_CONCURRENT_GIT_OPS,
+        ),
+        index_build_timeout_secs: env_u64(
+            "CANGJIE_INDEX_BUILD_TIMEOUT_SECS",
+            DEFAULT_INDEX_BUILD_TIMEOUT_SECS,
+        ),
+        watch: env_bool("CANGJIE_WATCH", DEFAULT_WATCH),
+        refresh_interval_mins: env_u64(
+            "CANGJIE_REFRESH_INTERVAL_MINS",
+            DEFAULT_REFRESH_INTERVAL_MINS,
+        ),
+        http_retry_base_ms: env_u64("CANGJIE_HTTP_RETRY_BASE_MS", DEFAULT_HTTP_RETRY_BASE_MS),
+        http_retry_cap_ms: env_u64("CANGJIE_HTTP_RETRY_CAP_MS", DEFAULT_HTTP_RETRY_CAP_MS),
         ..Settings::default()
     }
 }
@@ -254,23 +470,140 @@ pub fn generate_default_config() -> String {
 # rerank_top_k = 5
 # rerank_initial_k = 20
 
+# Truncate each rerank candidate to this many characters before sending it to the API reranker
+# rerank_max_doc_chars = 2000
+
+# Estimated token budget per API rerank request; larger candidate lists are reranked in overlapping windows
+# rerank_window_tokens = 8000
+
+# Warm up the reranker at startup (disable for a faster start; also skips the OpenAI rerank credential check)
+# rerank_warmup = true
+
+# Also index each code block with its surrounding prose for code search
+# index_code = false
+
+# Transparently gunzip gzip-compressed markdown files in the docs repositories
+# decompress_docs = false
+
+# Remove HTML tags embedded in markdown docs, keeping their text, before indexing; changing it rebuilds the index
+# strip_html = false
+
+# Additional markdown directories indexed alongside the official docs
+# extra_docs_dirs = ["/path/to/internal-guides"]
+
+# Category prefix for documents loaded from --extra-docs-dir
+# extra_docs_prefix = "internal"
+
+# Web base URL used to build source_url links to the upstream docs
+# docs_web_base = "https://gitcode.com/Cangjie/cangjie_docs/blob"
+
 # Chunk settings (omit chunk_size to enable dynamic detection: 800/1200/1600 based on code density)
 # chunk_size = 1200
 # chunk_overlap = 100
+
+# Prefix each chunk's indexed text with its enclosing headings (e.g. [A > B > C])
+# chunk_breadcrumbs = true
 # max_per_file = 2
 
+# Maximum number of search and LSP tool calls run at once; further calls wait
+# max_concurrent_tools = 8
+
+# Rebuild an existing index when its chunking parameters differ (auto) or always reuse it (never)
+# index_policy = "auto"
+
+# Build the index without first checking for free space on the data dir's filesystem
+# skip_disk_check = false
+
 # LLM model for chunk context summaries
 # summary_model = "gpt-4o-mini"
 
+# Stop words dropped from search queries, one per line (replaces the built-in zh/en list)
+# stopwords_file = "/path/to/stopwords.txt"
+
+# MCP tools to expose (all when unset) and tools to hide
+# enabled_tools = ["cangjie_search_docs", "cangjie_get_topics"]
+# disabled_tools = ["cangjie_lsp"]
+
+# Topics listed first by the /topics endpoint, in this order
+# pinned_topics = ["syntax/functions", "stdlib/collection_hashmap"]
+# category_weights = ["stdlib=1.5", "release-notes=0.5"]
+
+# Format of search_docs/get_topics results when a request does not set
+# response_format: "json" or "markdown" (default: json)
+# output_format = "markdown"
+
 # Reciprocal Rank Fusion constant
 # rrf_k = 60
 
+# Candidates fetched from each retriever before hybrid fusion (capped at 200)
+# fusion_candidate_k = 50
+
+# Where the reranker runs in hybrid search: post-fusion or pre-fusion (experimental)
+# rerank_stage = "post-fusion"
+
+# Upper bound on candidates fetched per search_docs call, whatever the offset and filters
+# max_fetch_candidates = 500
+
+# BM25 weight of title/topic matches relative to body text (0 disables)
+# title_boost = 2.0
+
+# Discount for search_docs results much longer than the median candidate (0 disables)
+# length_penalty = 0.0
+
+# Rows written per transaction when building the vector store
+# vector_insert_batch_size = 64
+
+# Minimum similarity score for vector search hits
+# vector_score_threshold = 0.3
+
+# Vector similarity used to build and search the vector store: cosine, l2, or dot
+# vector_distance = "l2"
+
+# Minimum name similarity for suggesting topics when a lookup misses
+# topic_suggestion_threshold = 0.6
+
 # Data directory (default: ~/.cangjie-mcp)
 # data_dir = "/path/to/data"
 
-# Remote server URL (skip local indexing, forward queries)
+# Documentation git checkout (default: <data_dir>/docs_repo)
+# docs_repo_dir = "/path/to/docs_repo"
+
+# Prebuilt index directory served as-is (contains bm25_index, index_metadata.json); no build is attempted
+# index_dir = "/mnt/index"
+
+# Remote server URL (skip local indexing, forward queries); comma-separate several for failover
 # server_url = "http://localhost:8765"
 
+# Seconds to cache the remote server's topic list (0 disables caching)
+# remote_cache_ttl_secs = 300
+
+# Ask the remote server for MessagePack instead of JSON search results
+# remote_msgpack = false
+
+# User-Agent header for outbound HTTP requests (default: cangjie-mcp/<version>)
+# http_user_agent = "cangjie-mcp/0.4.5"
+
+# Retries for transient git clone/fetch failures
+# git_retries = 2
+
+# Maximum number of git tree reads run at once on one repository
+# max_concurrent_git_ops = 4
+
+# Give up on initializing or building the index after this many seconds (0 = no limit)
+# index_build_timeout_secs = 1800
+
+# Periodically fetch the docs version and rebuild the index when its commit changes
+# watch = false
+
+# Minutes between docs refreshes in --watch mode
+# refresh_interval_mins = 60
+
+# Base delay in milliseconds for retried HTTP requests (jittered, doubling per attempt)
+# http_retry_base_ms = 2000
+
+# Maximum delay in milliseconds between retried HTTP requests
+# http_retry_cap_ms = 30000
+
 # Daemon idle timeout in minutes
 # daemon_timeout = 30
 
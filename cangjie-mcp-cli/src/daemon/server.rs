@@ -15,6 +15,8 @@ use super::paths;
 const LSP_IDLE_TIMEOUT_SECS: u64 = 10 * 60;
 
 pub async fn run_daemon(settings: Settings, timeout_minutes: u64) -> Result<()> {
+    CangjieServer::validate_tool_filter(&settings)?;
+    cangjie_indexer::search::stopwords::init_stopwords(settings.stopwords_file.as_deref())?;
     paths::ensure_runtime_dir()?;
     let pid = std::process::id();
     std::fs::write(paths::pid_file(), pid.to_string())?;
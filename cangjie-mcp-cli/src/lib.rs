@@ -6,7 +6,7 @@ pub mod daemon;
 use std::io::IsTerminal;
 use std::process::ExitCode;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
 use rmcp::ServiceExt;
 use tracing::info;
@@ -37,6 +37,9 @@ pub async fn run() -> ExitCode {
             daemon::server::run_daemon(settings, args.daemon_timeout).await
         }
         Some(Commands::Index) => run_index(args.server.to_settings()).await,
+        Some(Commands::Export { ref output }) => {
+            run_export(args.server.to_settings(), output.as_deref()).await
+        }
         Some(Commands::Daemon { action }) => run_daemon_action(action),
         Some(Commands::Config { action }) => run_config_action(action),
         Some(ref cmd) => run_tool_command(cmd, args.daemon_timeout).await,
@@ -77,6 +80,8 @@ async fn run_mcp_server(settings: Settings) -> Result<()> {
         info!("Using remote server - local index options are ignored.");
     }
 
+    cangjie_server::CangjieServer::validate_tool_filter(&settings)?;
+    cangjie_indexer::search::stopwords::init_stopwords(settings.stopwords_file.as_deref())?;
     let server = cangjie_server::CangjieServer::new(settings);
 
     let server_clone = server.clone();
@@ -115,12 +120,32 @@ async fn run_index(settings: Settings) -> Result<()> {
     let mut search_index = LocalSearchIndex::new(settings.clone()).await;
     let index_info = search_index.init().await?;
 
-    cangjie_core::config::log_startup_info(&settings, &index_info);
+    cangjie_core::config::log_startup_info(&settings, &index_info, None);
     info!("Index built successfully.");
 
     Ok(())
 }
 
+async fn run_export(settings: Settings, output: Option<&std::path::Path>) -> Result<()> {
+    let index_info = cangjie_indexer::initializer::open_existing_index(&settings).await?;
+
+    let count = match output {
+        Some(path) => {
+            let file = std::fs::File::create(path)
+                .with_context(|| format!("Failed to create {}", path.display()))?;
+            let mut writer = std::io::BufWriter::new(file);
+            cangjie_indexer::export::export_index(&index_info, &mut writer).await?
+        }
+        None => {
+            let mut writer = std::io::BufWriter::new(std::io::stdout());
+            cangjie_indexer::export::export_index(&index_info, &mut writer).await?
+        }
+    };
+    info!("Exported {count} chunks.");
+
+    Ok(())
+}
+
 fn run_daemon_action(action: DaemonAction) -> Result<()> {
     match action {
         DaemonAction::Stop => daemon::stop_daemon(),
@@ -164,5 +189,13 @@ async fn run_tool_command(cmd: &Commands, daemon_timeout: u64) -> Result<()> {
     }
 
     cli::output::print_tool_result(&result);
+
+    // Let `cjpm-check` fail a CI step like any other linter
+    if matches!(cmd, Commands::CjpmCheck { .. }) {
+        let errors = cli::output::cjpm_check_errors(&result);
+        if errors > 0 {
+            anyhow::bail!("cjpm.toml check found {errors} error(s)");
+        }
+    }
     Ok(())
 }
@@ -6,16 +6,26 @@ use clap::Parser;
 use tracing::info;
 
 use cangjie_core::config::{
-    self, DocLang, EmbeddingType, PrebuiltMode, RerankType, Settings, DEFAULT_CHUNK_OVERLAP_CHARS,
-    DEFAULT_DOCS_VERSION, DEFAULT_HTTP_ENABLE_HTTP2, DEFAULT_HTTP_POOL_IDLE_TIMEOUT_SECS,
-    DEFAULT_HTTP_POOL_MAX_IDLE_PER_HOST, DEFAULT_HTTP_TCP_KEEPALIVE_SECS, DEFAULT_LOCAL_MODEL,
-    DEFAULT_OPENAI_BASE_URL, DEFAULT_OPENAI_MODEL, DEFAULT_RERANK_INITIAL_K, DEFAULT_RERANK_MODEL,
-    DEFAULT_RERANK_TOP_K, DEFAULT_RRF_K, DEFAULT_SERVER_ENABLE_HTTP2, DEFAULT_SERVER_HOST,
-    DEFAULT_SERVER_PORT,
+    self, CategoryWeight, DocLang, EmbeddingType, IndexPolicy, OutputFormat, PrebuiltMode,
+    RerankStage, RerankType, Settings, VectorDistance, DEFAULT_CHUNK_BREADCRUMBS,
+    DEFAULT_CHUNK_OVERLAP_CHARS, DEFAULT_DECOMPRESS_DOCS, DEFAULT_DOCS_VERSION,
+    DEFAULT_DOCS_WEB_BASE, DEFAULT_EXTRA_DOCS_PREFIX, DEFAULT_FUSION_CANDIDATE_K,
+    DEFAULT_GIT_RETRIES, DEFAULT_HTTP_CONNECT_TIMEOUT_SECS, DEFAULT_HTTP_ENABLE_HTTP2,
+    DEFAULT_HTTP_POOL_IDLE_TIMEOUT_SECS, DEFAULT_HTTP_POOL_MAX_IDLE_PER_HOST,
+    DEFAULT_HTTP_RETRY_BASE_MS, DEFAULT_HTTP_RETRY_CAP_MS, DEFAULT_HTTP_TCP_KEEPALIVE_SECS,
+    DEFAULT_HTTP_USER_AGENT, DEFAULT_INDEX_BUILD_TIMEOUT_SECS, DEFAULT_INDEX_CODE,
+    DEFAULT_LENGTH_PENALTY, DEFAULT_LOCAL_MODEL, DEFAULT_MAX_CONCURRENT_GIT_OPS,
+    DEFAULT_MAX_CONCURRENT_TOOLS, DEFAULT_MAX_FETCH_CANDIDATES, DEFAULT_OPENAI_BASE_URL,
+    DEFAULT_OPENAI_MODEL, DEFAULT_REFRESH_INTERVAL_MINS, DEFAULT_RERANK_INITIAL_K,
+    DEFAULT_RERANK_MAX_DOC_CHARS, DEFAULT_RERANK_MODEL, DEFAULT_RERANK_TOP_K,
+    DEFAULT_RERANK_WARMUP, DEFAULT_RERANK_WINDOW_TOKENS, DEFAULT_RRF_K,
+    DEFAULT_SERVER_ENABLE_HTTP2, DEFAULT_SERVER_HOST, DEFAULT_SERVER_PORT, DEFAULT_SKIP_DISK_CHECK,
+    DEFAULT_STRIP_HTML, DEFAULT_TITLE_BOOST, DEFAULT_VECTOR_INSERT_BATCH_SIZE, DEFAULT_WATCH,
+    TOPIC_SUGGESTION_THRESHOLD, VECTOR_SCORE_THRESHOLD,
 };
 use cangjie_indexer::search::LocalSearchIndex;
 use cangjie_indexer::IndexMetadata;
-use cangjie_server::http::create_http_app;
+use cangjie_server::http::{bind_listener, create_http_app_for, create_reload_router, HttpIndex};
 use cangjie_server::sse::create_sse_router;
 use cangjie_server::streamable::{create_mcp_service, CancellationToken, McpServerConfig};
 use tower_http::cors::{AllowOrigin, CorsLayer};
@@ -84,6 +94,46 @@ struct Cli {
     #[arg(long = "rerank-initial-k", env = "CANGJIE_RERANK_INITIAL_K", default_value_t = DEFAULT_RERANK_INITIAL_K)]
     rerank_initial_k: usize,
 
+    /// Truncate each rerank candidate to this many characters before sending it to the API reranker
+    #[arg(long = "rerank-max-doc-chars", env = "CANGJIE_RERANK_MAX_DOC_CHARS", default_value_t = DEFAULT_RERANK_MAX_DOC_CHARS)]
+    rerank_max_doc_chars: usize,
+
+    /// Estimated token budget per API rerank request; larger candidate lists are reranked in overlapping windows
+    #[arg(long = "rerank-window-tokens", env = "CANGJIE_RERANK_WINDOW_TOKENS", default_value_t = DEFAULT_RERANK_WINDOW_TOKENS)]
+    rerank_window_tokens: usize,
+
+    /// Warm up the reranker at startup (disable for a faster start; also skips the OpenAI rerank credential check)
+    #[arg(long = "rerank-warmup", env = "CANGJIE_RERANK_WARMUP", default_value_t = DEFAULT_RERANK_WARMUP)]
+    rerank_warmup: bool,
+
+    /// Also index each code block with its surrounding prose for code search
+    #[arg(long = "index-code", env = "CANGJIE_INDEX_CODE", default_value_t = DEFAULT_INDEX_CODE)]
+    index_code: bool,
+
+    /// Transparently gunzip gzip-compressed markdown files in the docs repositories
+    #[arg(long = "decompress-docs", env = "CANGJIE_DECOMPRESS_DOCS", default_value_t = DEFAULT_DECOMPRESS_DOCS)]
+    decompress_docs: bool,
+
+    /// Remove HTML tags embedded in markdown docs, keeping their text, before indexing; changing it rebuilds the index
+    #[arg(long = "strip-html", env = "CANGJIE_STRIP_HTML", default_value_t = DEFAULT_STRIP_HTML)]
+    strip_html: bool,
+
+    /// Additional markdown directory to index alongside the official docs (repeatable)
+    #[arg(
+        long = "extra-docs-dir",
+        env = "CANGJIE_EXTRA_DOCS_DIRS",
+        value_delimiter = ','
+    )]
+    extra_docs_dirs: Vec<PathBuf>,
+
+    /// Category prefix for documents loaded from --extra-docs-dir
+    #[arg(long = "extra-docs-prefix", env = "CANGJIE_EXTRA_DOCS_PREFIX", default_value = DEFAULT_EXTRA_DOCS_PREFIX)]
+    extra_docs_prefix: String,
+
+    /// Web base URL used to build source_url links to the upstream docs
+    #[arg(long = "docs-web-base", env = "CANGJIE_DOCS_WEB_BASE", default_value = DEFAULT_DOCS_WEB_BASE)]
+    docs_web_base: String,
+
     /// Max chunk size in characters (omit to use dynamic detection)
     #[arg(long = "chunk-size", env = "CANGJIE_CHUNK_MAX_SIZE")]
     max_chunk_chars: Option<usize>,
@@ -92,22 +142,96 @@ struct Cli {
     #[arg(long = "chunk-overlap", env = "CANGJIE_CHUNK_OVERLAP", default_value_t = DEFAULT_CHUNK_OVERLAP_CHARS)]
     chunk_overlap_chars: usize,
 
+    /// Prefix each chunk's indexed text with its enclosing headings (e.g. [A > B > C])
+    #[arg(long = "chunk-breadcrumbs", env = "CANGJIE_CHUNK_BREADCRUMBS", default_value_t = DEFAULT_CHUNK_BREADCRUMBS)]
+    chunk_breadcrumbs: bool,
+
+    /// Rebuild an existing index when its chunking parameters differ (auto) or always reuse it (never)
+    #[arg(
+        long = "index-policy",
+        env = "CANGJIE_INDEX_POLICY",
+        default_value = "auto"
+    )]
+    index_policy: IndexPolicy,
+
+    /// Build the index without first checking for free space on the data dir's filesystem
+    #[arg(long = "skip-disk-check", env = "CANGJIE_SKIP_DISK_CHECK", default_value_t = DEFAULT_SKIP_DISK_CHECK)]
+    skip_disk_check: bool,
+
     /// RRF constant k for hybrid search fusion
     #[arg(long = "rrf-k", env = "CANGJIE_RRF_K", default_value_t = DEFAULT_RRF_K)]
     rrf_k: u32,
 
+    /// Candidates fetched from each retriever before hybrid fusion (capped at 200)
+    #[arg(long = "fusion-candidate-k", env = "CANGJIE_FUSION_CANDIDATE_K", default_value_t = DEFAULT_FUSION_CANDIDATE_K)]
+    fusion_candidate_k: usize,
+
+    /// Where the reranker runs in hybrid search: post-fusion (once, on the
+    /// fused list) or pre-fusion (on the BM25 and vector lists separately;
+    /// two reranker calls). Experimental
+    #[arg(
+        long = "rerank-stage",
+        env = "CANGJIE_RERANK_STAGE",
+        default_value = "post-fusion"
+    )]
+    rerank_stage: RerankStage,
+
+    /// Upper bound on candidates fetched per search_docs call, whatever the offset and filters
+    #[arg(long = "max-fetch-candidates", env = "CANGJIE_MAX_FETCH_CANDIDATES", default_value_t = DEFAULT_MAX_FETCH_CANDIDATES)]
+    max_fetch_candidates: usize,
+
+    /// BM25 weight of title/topic matches relative to body text (0 disables)
+    #[arg(long = "title-boost", env = "CANGJIE_TITLE_BOOST", default_value_t = DEFAULT_TITLE_BOOST)]
+    title_boost: f32,
+
+    /// Discount for search_docs results much longer than the median candidate (0 disables)
+    #[arg(long = "length-penalty", env = "CANGJIE_LENGTH_PENALTY", default_value_t = DEFAULT_LENGTH_PENALTY)]
+    length_penalty: f64,
+
+    /// Rows written per transaction when building the vector store
+    #[arg(long = "vector-insert-batch-size", env = "CANGJIE_VECTOR_INSERT_BATCH_SIZE", default_value_t = DEFAULT_VECTOR_INSERT_BATCH_SIZE)]
+    vector_insert_batch_size: usize,
+
+    /// Minimum similarity score for vector search hits
+    #[arg(long = "vector-score-threshold", env = "CANGJIE_VECTOR_SCORE_THRESHOLD", default_value_t = VECTOR_SCORE_THRESHOLD)]
+    vector_score_threshold: f64,
+
+    /// Vector similarity used to build and search the vector store: cosine, l2, or dot
+    #[arg(
+        long = "vector-distance",
+        env = "CANGJIE_VECTOR_DISTANCE",
+        default_value = "l2"
+    )]
+    vector_distance: VectorDistance,
+
+    /// Minimum name similarity for suggesting topics when a lookup misses
+    #[arg(long = "topic-suggestion-threshold", env = "CANGJIE_TOPIC_SUGGESTION_THRESHOLD", default_value_t = TOPIC_SUGGESTION_THRESHOLD)]
+    topic_suggestion_threshold: f64,
+
     /// Data directory path
     #[arg(long = "data-dir", short = 'd', env = "CANGJIE_DATA_DIR")]
     data_dir: Option<PathBuf>,
 
+    /// Documentation git checkout directory (default: <data-dir>/docs_repo)
+    #[arg(long = "docs-repo-dir", env = "CANGJIE_DOCS_REPO_DIR")]
+    docs_repo_dir: Option<PathBuf>,
+
+    /// Serve a fully built index from this directory as-is, without deriving it from <data-dir> or building
+    #[arg(long = "index-dir", env = "CANGJIE_INDEX_DIR")]
+    index_dir: Option<PathBuf>,
+
     /// Host to bind the HTTP server to
     #[arg(long, env = "CANGJIE_SERVER_HOST", default_value = DEFAULT_SERVER_HOST)]
     host: String,
 
-    /// Port to bind the HTTP server to
+    /// Port to bind the HTTP server to; 0 picks a free port
     #[arg(long, short = 'p', env = "CANGJIE_SERVER_PORT", default_value_t = DEFAULT_SERVER_PORT)]
     port: u16,
 
+    /// Write the port the HTTP server actually bound to this file
+    #[arg(long = "port-file", env = "CANGJIE_PORT_FILE")]
+    port_file: Option<PathBuf>,
+
     /// HTTP client pool idle timeout in seconds
     #[arg(long = "http-pool-idle-timeout-secs", env = "CANGJIE_HTTP_POOL_IDLE_TIMEOUT_SECS", default_value_t = DEFAULT_HTTP_POOL_IDLE_TIMEOUT_SECS)]
     http_pool_idle_timeout_secs: u64,
@@ -120,14 +244,94 @@ struct Cli {
     #[arg(long = "http-tcp-keepalive-secs", env = "CANGJIE_HTTP_TCP_KEEPALIVE_SECS", default_value_t = DEFAULT_HTTP_TCP_KEEPALIVE_SECS)]
     http_tcp_keepalive_secs: u64,
 
+    /// TCP connect timeout for outbound HTTP in seconds
+    #[arg(long = "http-connect-timeout-secs", env = "CANGJIE_HTTP_CONNECT_TIMEOUT_SECS", default_value_t = DEFAULT_HTTP_CONNECT_TIMEOUT_SECS)]
+    http_connect_timeout_secs: u64,
+
     /// Enable HTTP/2 for outbound HTTP client
     #[arg(long = "http2", env = "CANGJIE_HTTP2", default_value_t = DEFAULT_HTTP_ENABLE_HTTP2)]
     http_enable_http2: bool,
 
+    /// User-Agent header sent on outbound HTTP requests
+    #[arg(long = "http-user-agent", env = "CANGJIE_HTTP_USER_AGENT", default_value = DEFAULT_HTTP_USER_AGENT)]
+    http_user_agent: String,
+
+    /// Retries for transient git clone/fetch failures
+    #[arg(long = "git-retries", env = "CANGJIE_GIT_RETRIES", default_value_t = DEFAULT_GIT_RETRIES)]
+    git_retries: usize,
+
+    /// Maximum number of git tree reads run at once on one repository
+    #[arg(long = "max-concurrent-git-ops", env = "CANGJIE_MAX_CONCURRENT_GIT_OPS", default_value_t = DEFAULT_MAX_CONCURRENT_GIT_OPS)]
+    max_concurrent_git_ops: usize,
+
+    /// Give up on initializing or building the index after this many seconds (0 = no limit)
+    #[arg(long = "index-build-timeout-secs", env = "CANGJIE_INDEX_BUILD_TIMEOUT_SECS", default_value_t = DEFAULT_INDEX_BUILD_TIMEOUT_SECS)]
+    index_build_timeout_secs: u64,
+
+    /// Maximum number of search and LSP tool calls run at once across all MCP sessions; further calls wait
+    #[arg(long = "max-concurrent-tools", env = "CANGJIE_MAX_CONCURRENT_TOOLS", default_value_t = DEFAULT_MAX_CONCURRENT_TOOLS)]
+    max_concurrent_tools: usize,
+
+    /// Periodically fetch the docs version and rebuild the index when its commit changes
+    #[arg(long = "watch", env = "CANGJIE_WATCH", default_value_t = DEFAULT_WATCH)]
+    watch: bool,
+
+    /// Minutes between docs refreshes in --watch mode
+    #[arg(long = "refresh-interval-mins", env = "CANGJIE_REFRESH_INTERVAL_MINS", default_value_t = DEFAULT_REFRESH_INTERVAL_MINS)]
+    refresh_interval_mins: u64,
+
+    /// Base delay in milliseconds for retried HTTP requests (jittered, doubling per attempt)
+    #[arg(long = "http-retry-base-ms", env = "CANGJIE_HTTP_RETRY_BASE_MS", default_value_t = DEFAULT_HTTP_RETRY_BASE_MS)]
+    http_retry_base_ms: u64,
+
+    /// Maximum delay in milliseconds between retried HTTP requests
+    #[arg(long = "http-retry-cap-ms", env = "CANGJIE_HTTP_RETRY_CAP_MS", default_value_t = DEFAULT_HTTP_RETRY_CAP_MS)]
+    http_retry_cap_ms: u64,
+
     /// Enable HTTP/2 for the HTTP server
     #[arg(long = "server-http2", env = "CANGJIE_SERVER_HTTP2", default_value_t = DEFAULT_SERVER_ENABLE_HTTP2)]
     server_enable_http2: bool,
 
+    /// MCP tools to expose; all others are hidden (comma-separated or repeatable)
+    #[arg(
+        long = "enabled-tools",
+        env = "CANGJIE_ENABLED_TOOLS",
+        value_delimiter = ','
+    )]
+    enabled_tools: Vec<String>,
+
+    /// MCP tools to hide (comma-separated or repeatable)
+    #[arg(
+        long = "disabled-tools",
+        env = "CANGJIE_DISABLED_TOOLS",
+        value_delimiter = ','
+    )]
+    disabled_tools: Vec<String>,
+
+    /// Topics (`category/topic`) listed first by the HTTP `/topics` endpoint and the MCP resource list, in this order (comma-separated or repeatable)
+    #[arg(
+        long = "pinned-topics",
+        env = "CANGJIE_PINNED_TOPICS",
+        value_delimiter = ','
+    )]
+    pinned_topics: Vec<String>,
+
+    /// Multiply the scores of a category's results, e.g. `stdlib=1.5` (comma-separated or repeatable; unlisted categories weigh 1.0)
+    #[arg(
+        long = "category-weight",
+        env = "CANGJIE_CATEGORY_WEIGHTS",
+        value_delimiter = ','
+    )]
+    category_weights: Vec<CategoryWeight>,
+
+    /// Format of search_docs/get_topics results when a request does not set response_format: json (default) or markdown
+    #[arg(long = "output-format", env = "CANGJIE_OUTPUT_FORMAT")]
+    output_format: Option<OutputFormat>,
+
+    /// Stop words dropped from search queries, one per line (replaces the built-in zh/en list)
+    #[arg(long = "stopwords-file", env = "CANGJIE_STOPWORDS_FILE")]
+    stopwords_file: Option<PathBuf>,
+
     /// Log file path
     #[arg(long = "log-file", env = "CANGJIE_LOG_FILE")]
     log_file: Option<PathBuf>,
@@ -152,6 +356,10 @@ struct Cli {
     #[arg(long = "no-sse", env = "CANGJIE_NO_SSE")]
     no_sse: bool,
 
+    /// Enable POST /reload, which rebuilds the index and swaps it in once ready
+    #[arg(long = "enable-reload", env = "CANGJIE_ENABLE_RELOAD")]
+    enable_reload: bool,
+
     /// Comma-separated Host header allowlist for the MCP endpoint's DNS-rebinding
     /// guard. Leave empty (the default) to allow any Host — appropriate when the
     /// server runs behind a trusted reverse proxy, where rmcp's localhost-only
@@ -184,21 +392,60 @@ impl Cli {
             rerank_model: self.rerank_model.clone(),
             rerank_top_k: self.rerank_top_k,
             rerank_initial_k: self.rerank_initial_k,
+            rerank_max_doc_chars: self.rerank_max_doc_chars,
+            rerank_window_tokens: self.rerank_window_tokens,
+            rerank_warmup: self.rerank_warmup,
+            index_code: self.index_code,
+            decompress_docs: self.decompress_docs,
+            strip_html: self.strip_html,
+            extra_docs_dirs: self.extra_docs_dirs.clone(),
+            extra_docs_prefix: self.extra_docs_prefix.clone(),
+            docs_web_base: self.docs_web_base.clone(),
             rrf_k: self.rrf_k,
+            fusion_candidate_k: self.fusion_candidate_k,
+            rerank_stage: self.rerank_stage,
+            max_fetch_candidates: self.max_fetch_candidates,
+            title_boost: self.title_boost,
+            length_penalty: self.length_penalty,
+            vector_insert_batch_size: self.vector_insert_batch_size,
+            vector_score_threshold: self.vector_score_threshold,
+            vector_distance: self.vector_distance,
+            topic_suggestion_threshold: self.topic_suggestion_threshold,
             max_chunk_chars: self.max_chunk_chars,
             chunk_overlap_chars: self.chunk_overlap_chars,
+            chunk_breadcrumbs: self.chunk_breadcrumbs,
+            index_policy: self.index_policy,
+            skip_disk_check: self.skip_disk_check,
             data_dir: self
                 .data_dir
                 .clone()
                 .unwrap_or_else(config::get_default_data_dir),
+            docs_repo_dir_override: self.docs_repo_dir.clone(),
+            index_dir_override: self.index_dir.clone(),
             openai_api_key: self.openai_api_key.clone(),
             openai_base_url: self.openai_base_url.clone(),
             openai_model: self.openai_model.clone(),
             http_pool_idle_timeout_secs: self.http_pool_idle_timeout_secs,
             http_pool_max_idle_per_host: self.http_pool_max_idle_per_host,
             http_tcp_keepalive_secs: self.http_tcp_keepalive_secs,
+            http_connect_timeout_secs: self.http_connect_timeout_secs,
             http_enable_http2: self.http_enable_http2,
+            http_user_agent: self.http_user_agent.clone(),
+            git_retries: self.git_retries,
+            max_concurrent_git_ops: self.max_concurrent_git_ops,
+            index_build_timeout_secs: self.index_build_timeout_secs,
+            max_concurrent_tools: self.max_concurrent_tools,
+            watch: self.watch,
+            refresh_interval_mins: self.refresh_interval_mins,
+            http_retry_base_ms: self.http_retry_base_ms,
+            http_retry_cap_ms: self.http_retry_cap_ms,
             server_enable_http2: self.server_enable_http2,
+            enabled_tools: self.enabled_tools.clone(),
+            disabled_tools: self.disabled_tools.clone(),
+            pinned_topics: self.pinned_topics.clone(),
+            category_weights: self.category_weights.clone(),
+            output_format: self.output_format,
+            stopwords_file: self.stopwords_file.clone(),
             prebuilt: match &self.prebuilt {
                 None => PrebuiltMode::Off,
                 Some(v) if v == "true" || v.is_empty() => PrebuiltMode::Auto,
@@ -262,6 +509,8 @@ async fn main() -> Result<()> {
     let settings = cli.to_settings();
 
     validate_runtime(&settings)?;
+    cangjie_server::CangjieServer::validate_tool_filter(&settings)?;
+    cangjie_indexer::search::stopwords::init_stopwords(settings.stopwords_file.as_deref())?;
 
     info!(
         "Initializing index (version={}, lang={})...",
@@ -270,8 +519,9 @@ async fn main() -> Result<()> {
 
     let mut search_index = LocalSearchIndex::new(settings.clone()).await;
     let index_info = search_index.init().await?;
+    search_index.warmup(&index_info).await;
 
-    config::log_startup_info(&settings, &index_info);
+    config::log_startup_info(&settings, &index_info, Some(search_index.search_mode()));
 
     let metadata_path = index_info.index_dir().join("index_metadata.json");
     let index_metadata: IndexMetadata =
@@ -279,21 +529,29 @@ async fn main() -> Result<()> {
 
     let search_index = Arc::new(search_index);
 
-    let mut app = create_http_app(search_index.clone(), index_metadata).await;
+    let http_index = HttpIndex::new(search_index.clone(), index_metadata);
+    let mut app = create_http_app_for(http_index.clone()).await;
+
+    // One server shared by every transport, so a `--watch` rebuild reaches
+    // the HTTP routes and all MCP sessions alike.
+    let mcp_server = cangjie_server::CangjieServer::with_shared_state(settings, search_index)
+        .with_http_index(http_index);
+    mcp_server.start_watch();
+
+    if cli.enable_reload {
+        info!("Index reload enabled at POST /reload");
+        app = app.merge(create_reload_router(mcp_server.clone()));
+    }
 
     if !cli.no_sse {
-        let settings_clone = settings.clone();
-        let idx = search_index.clone();
-        let sse_router = create_sse_router(move || {
-            cangjie_server::CangjieServer::with_shared_state(settings_clone.clone(), idx.clone())
-        });
+        let server = mcp_server.clone();
+        let sse_router = create_sse_router(move || server.clone());
         info!("Legacy SSE transport enabled at /sse");
         app = app.merge(sse_router);
     }
 
     let ct = if !cli.no_mcp {
         let ct = CancellationToken::new();
-        let mcp_server = cangjie_server::CangjieServer::with_shared_state(settings, search_index);
 
         let mcp_config = McpServerConfig::default()
             .with_stateful_mode(true)
@@ -334,7 +592,7 @@ async fn main() -> Result<()> {
 
     let bind_addr = format!("{}:{}", cli.host, cli.port);
     info!("Starting HTTP server on {bind_addr}...");
-    let listener = tokio::net::TcpListener::bind(&bind_addr).await?;
+    let listener = bind_listener(&bind_addr, cli.port_file.as_deref()).await?;
     if cli.server_enable_http2 {
         info!("HTTP/2 enabled on server.");
     }
@@ -1,4 +1,5 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 use cangjie_core::config::{DocLang, EmbeddingType, RerankType, Settings};
 use cangjie_indexer::document::source::DocumentSource;
@@ -17,6 +18,20 @@ pub fn test_settings(data_dir: PathBuf) -> Settings {
     }
 }
 
+/// Runs `git <args>` in `dir` as the test identity, panicking on failure.
+pub fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_AUTHOR_NAME", "test")
+        .env("GIT_AUTHOR_EMAIL", "test@test.com")
+        .env("GIT_COMMITTER_NAME", "test")
+        .env("GIT_COMMITTER_EMAIL", "test@test.com")
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
 /// Return a set of realistic `TextChunk` values spanning multiple categories.
 pub fn sample_chunks() -> Vec<TextChunk> {
     vec![
@@ -1,5 +1,7 @@
+use cangjie_core::error::CangjieError;
 use cangjie_indexer::search::bm25::BM25Store;
 use cangjie_indexer::search::LocalSearchIndex;
+use cangjie_indexer::{DocMetadata, TextChunk};
 use cangjie_mcp_test::{sample_chunks, test_settings};
 use tempfile::TempDir;
 
@@ -176,7 +178,7 @@ async fn test_local_search_index_query_bm25_only() {
     let settings = test_settings(tmp.path().to_path_buf());
     let index = LocalSearchIndex::with_bm25(settings, bm25).await;
 
-    let results = index.query("函数定义", 5, None).await.unwrap();
+    let results = index.query("函数定义", 5, None, true).await.unwrap();
     assert!(!results.is_empty());
     assert!(results[0].score > 0.0);
     assert!(
@@ -197,19 +199,121 @@ async fn test_local_search_index_query_with_category() {
     let settings = test_settings(tmp.path().to_path_buf());
     let index = LocalSearchIndex::with_bm25(settings, bm25).await;
 
-    let results = index.query("函数", 10, Some("cjpm")).await.unwrap();
+    let results = index.query("函数", 10, Some("cjpm"), true).await.unwrap();
     for r in &results {
         assert_eq!(r.metadata.category, "cjpm");
     }
 }
 
-/// When no search stores are configured, query() should return empty vec.
+/// When no search stores are configured, query() should fail with IndexNotReady
+/// rather than look like a query without matches.
 #[tokio::test]
 async fn test_local_search_index_query_no_stores() {
     let tmp = TempDir::new().unwrap();
     let settings = test_settings(tmp.path().to_path_buf());
     let index = LocalSearchIndex::new(settings).await;
 
-    let results = index.query("anything", 5, None).await.unwrap();
-    assert!(results.is_empty(), "no stores = empty results");
+    let err = index.query("anything", 5, None, true).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<CangjieError>(),
+        Some(CangjieError::IndexNotReady)
+    ));
+}
+
+fn title_vs_body_chunks() -> Vec<TextChunk> {
+    vec![
+        TextChunk {
+            text: "本节介绍捕获变量的规则与生命周期。".to_string(),
+            metadata: DocMetadata {
+                file_path: "syntax/closure.md".to_string(),
+                category: "syntax".to_string(),
+                topic: "closure".to_string(),
+                title: "Closure".to_string(),
+                ..Default::default()
+            },
+        },
+        TextChunk {
+            text:
+                "函数可以返回 closure，也可以接受其他函数作为参数。本节详细描述高阶函数的各种用法、\
+                   参数传递方式以及使用时需要注意的事项。"
+                    .to_string(),
+            metadata: DocMetadata {
+                file_path: "syntax/functions.md".to_string(),
+                category: "syntax".to_string(),
+                topic: "functions".to_string(),
+                title: "函数".to_string(),
+                ..Default::default()
+            },
+        },
+    ]
+}
+
+/// A document whose title matches the query should outrank a document that
+/// only mentions the term in passing, at the BM25 retriever level.
+#[tokio::test]
+async fn test_title_match_outranks_body_match() {
+    let tmp = TempDir::new().unwrap();
+    let mut store = BM25Store::new(tmp.path().join("bm25_title"));
+    store
+        .build_from_chunks(&title_vs_body_chunks())
+        .await
+        .unwrap();
+
+    let results = store.search("closure", 5, None).await.unwrap();
+    assert_eq!(results.len(), 2, "both title and body matches expected");
+    assert_eq!(results[0].metadata.topic, "closure");
+}
+
+/// With a zero title boost only the body text is searched.
+#[tokio::test]
+async fn test_title_boost_zero_searches_body_only() {
+    let tmp = TempDir::new().unwrap();
+    let bm25_dir = tmp.path().join("bm25_title_off");
+    {
+        let mut store = BM25Store::new(bm25_dir.clone());
+        store
+            .build_from_chunks(&title_vs_body_chunks())
+            .await
+            .unwrap();
+    }
+
+    let mut store = BM25Store::new(bm25_dir).with_title_boost(0.0);
+    assert!(store.load().await.unwrap());
+
+    let results = store.search("closure", 5, None).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].metadata.topic, "functions");
+}
+
+/// Chinese prose with an embedded English identifier is indexed with the same
+/// segmentation as queries, so either language's term finds it.
+#[tokio::test]
+async fn test_mixed_zh_en_chunk_found_by_either_term() {
+    let chunk = |text: &str, topic: &str| TextChunk {
+        text: text.to_string(),
+        metadata: DocMetadata {
+            file_path: format!("stdlib/{topic}.md"),
+            category: "stdlib".to_string(),
+            topic: topic.to_string(),
+            ..Default::default()
+        },
+    };
+    let tmp = TempDir::new().unwrap();
+    let mut store = BM25Store::new(tmp.path().join("bm25_mixed"));
+    store
+        .build_from_chunks(&[
+            chunk("使用 HashMap 存储", "hashmap"),
+            chunk("使用 ArrayList 遍历元素", "arraylist"),
+        ])
+        .await
+        .unwrap();
+
+    for query in ["HashMap", "存储", "hashmap存储"] {
+        let results = store.search(query, 5, None).await.unwrap();
+        assert_eq!(
+            results.first().map(|r| r.metadata.topic.as_str()),
+            Some("hashmap"),
+            "query {query:?}"
+        );
+    }
 }
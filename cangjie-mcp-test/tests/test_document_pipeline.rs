@@ -6,7 +6,7 @@ use cangjie_mcp_test::{sample_documents, MockDocumentSource};
 #[test]
 fn test_load_and_chunk_pipeline() {
     let content = "# 测试文档\n\n这是第一段内容，描述了仓颉语言的基本特性。\n\n## 第二节\n\n这是第二段内容，包含更多细节。\n\n```cangjie\nfunc main() {\n    println(\"Hello\")\n}\n```\n".to_string();
-    let doc = load_document_from_content(content, "test/example.md", "test", "example");
+    let doc = load_document_from_content(content, "test/example.md", "test", "example", false);
     assert!(doc.is_some(), "document should be loaded");
     let doc = doc.unwrap();
 
@@ -15,7 +15,7 @@ fn test_load_and_chunk_pipeline() {
     assert_eq!(doc.metadata.title, "测试文档");
     assert!(doc.metadata.has_code);
 
-    let chunks = chunk_document(&doc, Some(6000), 200);
+    let chunks = chunk_document(&doc, Some(6000), 200, true);
     assert!(!chunks.is_empty(), "document should produce chunks");
     for chunk in &chunks {
         assert_eq!(chunk.metadata.category, "test");
@@ -27,16 +27,16 @@ fn test_load_and_chunk_pipeline() {
 fn test_chunk_preserves_code_detection() {
     let content_with_code =
         "# Code Doc\n\nSome text.\n\n```cangjie\nlet x = 1\n```\n\nMore text.\n".to_string();
-    let doc = load_document_from_content(content_with_code, "a/b.md", "a", "b").unwrap();
-    let chunks = chunk_document(&doc, Some(6000), 200);
+    let doc = load_document_from_content(content_with_code, "a/b.md", "a", "b", false).unwrap();
+    let chunks = chunk_document(&doc, Some(6000), 200, true);
     assert!(
         chunks.iter().any(|c| c.metadata.has_code),
         "at least one chunk should have has_code = true"
     );
 
     let content_no_code = "# Plain Doc\n\nJust text, no code blocks.\n".to_string();
-    let doc2 = load_document_from_content(content_no_code, "a/c.md", "a", "c").unwrap();
-    let chunks2 = chunk_document(&doc2, Some(6000), 200);
+    let doc2 = load_document_from_content(content_no_code, "a/c.md", "a", "c", false).unwrap();
+    let chunks2 = chunk_document(&doc2, Some(6000), 200, true);
     assert!(
         chunks2.iter().all(|c| !c.metadata.has_code),
         "chunks without code blocks should have has_code = false"
@@ -58,7 +58,7 @@ async fn test_mock_document_source() {
 #[test]
 fn test_load_document_multiple_code_blocks() {
     let content = "# Multi Code\n\n```cangjie\nlet a = 1\n```\n\ntext\n\n```python\nprint(1)\n```\n\nmore text\n\n```bash\necho hi\n```\n".to_string();
-    let doc = load_document_from_content(content, "test/multi.md", "test", "multi").unwrap();
+    let doc = load_document_from_content(content, "test/multi.md", "test", "multi", false).unwrap();
     assert!(doc.metadata.has_code);
 }
 
@@ -80,8 +80,9 @@ fn test_chunk_content_completeness() {
     let section = "这是一段较长的文本内容，用于测试分块功能是否完整。".repeat(20);
     let content =
         format!("# 完整性测试\n\n{section}\n\n## 第二节\n\n{section}\n\n## 第三节\n\n{section}");
-    let doc = load_document_from_content(content.clone(), "test/big.md", "test", "big").unwrap();
-    let chunks = chunk_document(&doc, Some(500), 200);
+    let doc =
+        load_document_from_content(content.clone(), "test/big.md", "test", "big", false).unwrap();
+    let chunks = chunk_document(&doc, Some(500), 200, true);
 
     assert!(chunks.len() > 1, "should split into multiple chunks");
 
@@ -95,7 +96,7 @@ fn test_chunk_content_completeness() {
 #[tokio::test]
 async fn test_chunk_documents_mixed_code_detection() {
     let docs = sample_documents();
-    let chunks = chunk_documents(docs, Some(6000), 200).await;
+    let chunks = chunk_documents(docs, Some(6000), 200, true).await;
 
     let code_chunks: Vec<_> = chunks.iter().filter(|c| c.metadata.has_code).collect();
     let no_code_chunks: Vec<_> = chunks.iter().filter(|c| !c.metadata.has_code).collect();
@@ -122,7 +123,7 @@ async fn test_chunk_documents_mixed_code_detection() {
 /// load_document_from_content should handle documents with only whitespace.
 #[test]
 fn test_load_whitespace_only_document() {
-    let doc = load_document_from_content("   \n\n   ".to_string(), "a/b.md", "a", "b");
+    let doc = load_document_from_content("   \n\n   ".to_string(), "a/b.md", "a", "b", false);
     // Should either return None (empty after trim) or a doc with empty-ish content
     if let Some(d) = doc {
         assert!(d.text.trim().is_empty() || d.text.len() < 10);
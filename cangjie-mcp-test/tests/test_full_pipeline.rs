@@ -50,7 +50,7 @@ async fn build_real_index() -> (TempDir, BM25Store, usize) {
     let doc_count = docs.len();
     assert!(doc_count > 10, "should load many documents");
 
-    let chunks = chunk_documents(docs, Some(6000), 200).await;
+    let chunks = chunk_documents(docs, Some(6000), 200, true).await;
     assert!(!chunks.is_empty(), "chunking should produce chunks");
 
     let bm25_dir = tmp.path().join("bm25_index");
@@ -136,14 +136,14 @@ async fn test_real_docs_local_search_index_query() {
 
     let source = GitDocumentSource::for_docs(repo_dir, DocLang::Zh).unwrap();
     let docs = source.load_all_documents().await.unwrap();
-    let chunks = chunk_documents(docs, Some(6000), 200).await;
+    let chunks = chunk_documents(docs, Some(6000), 200, true).await;
 
     let bm25_dir = tmp.path().join("bm25_index");
     let mut bm25 = BM25Store::new(bm25_dir);
     bm25.build_from_chunks(&chunks).await.unwrap();
 
     let index = LocalSearchIndex::with_bm25(settings, bm25).await;
-    let results = index.query("变量声明", 5, None).await.unwrap();
+    let results = index.query("变量声明", 5, None, true).await.unwrap();
     assert!(
         !results.is_empty(),
         "LocalSearchIndex query should return results"
@@ -163,6 +163,12 @@ async fn test_real_docs_http_app_search() {
         embedding_model: "none".to_string(),
         document_count: doc_count,
         search_mode: SearchMode::Bm25,
+        index_code: false,
+        extra_docs: None,
+        chunking: None,
+        source_commit: None,
+        vector_distance: None,
+        tokenizer_version: cangjie_indexer::search::TOKENIZER_VERSION,
     };
 
     let app = create_http_app(Arc::new(search_index), metadata).await;
@@ -3,10 +3,11 @@ use std::sync::Arc;
 use axum::body::Body;
 use axum::http::{Request, StatusCode};
 use cangjie_indexer::search::bm25::BM25Store;
-use cangjie_indexer::search::LocalSearchIndex;
-use cangjie_indexer::{IndexMetadata, SearchMode};
+use cangjie_indexer::search::{LocalSearchIndex, RemoteSearchIndex};
+use cangjie_indexer::{IndexMetadata, SearchMode, MSGPACK_CONTENT_TYPE};
 use cangjie_mcp_test::{sample_chunks, sample_documents, test_settings};
-use cangjie_server::http::create_http_app;
+use cangjie_server::http::{bind_listener, create_http_app, create_reload_router};
+use cangjie_server::CangjieServer;
 use http_body_util::BodyExt;
 use tempfile::TempDir;
 use tower::ServiceExt;
@@ -30,6 +31,12 @@ async fn build_test_app() -> (TempDir, axum::Router) {
         embedding_model: "none".to_string(),
         document_count: docs.len(),
         search_mode: SearchMode::Bm25,
+        index_code: false,
+        extra_docs: None,
+        chunking: None,
+        source_commit: None,
+        vector_distance: None,
+        tokenizer_version: cangjie_indexer::search::TOKENIZER_VERSION,
     };
 
     let app = create_http_app(Arc::new(search_index), metadata).await;
@@ -57,6 +64,154 @@ async fn post_json(app: axum::Router, uri: &str, json: &str) -> (StatusCode, Str
     (status, String::from_utf8_lossy(&body).to_string())
 }
 
+#[tokio::test]
+async fn test_topics_endpoint_lists_pinned_topics_first() {
+    let tmp = TempDir::new().unwrap();
+    let mut bm25 = BM25Store::new(tmp.path().join("bm25_index"));
+    bm25.build_from_chunks(&sample_chunks()).await.unwrap();
+    let mut settings = test_settings(tmp.path().to_path_buf());
+    settings.pinned_topics = vec![
+        "stdlib/collections".to_string(),
+        "syntax/no_such_topic".to_string(),
+        "syntax/functions".to_string(),
+    ];
+    let search_index = LocalSearchIndex::with_bm25(settings, bm25).await;
+    let metadata = IndexMetadata {
+        version: "test".to_string(),
+        lang: "zh".to_string(),
+        embedding_model: "none".to_string(),
+        document_count: 1,
+        search_mode: SearchMode::Bm25,
+        index_code: false,
+        extra_docs: None,
+        chunking: None,
+        vector_distance: None,
+        source_commit: None,
+        tokenizer_version: cangjie_indexer::search::TOKENIZER_VERSION,
+    };
+    let app = create_http_app(Arc::new(search_index), metadata).await;
+
+    let (status, body) = get(app, "/topics").await;
+    assert_eq!(status, StatusCode::OK);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        v["pinned"],
+        serde_json::json!([
+            {"category": "stdlib", "topic": "collections"},
+            {"category": "syntax", "topic": "functions"}
+        ])
+    );
+    // Pinned topics keep their place in the categorized listing.
+    assert_eq!(v["topics"]["functions"], serde_json::json!(["syntax"]));
+    assert_eq!(v["topics"]["collections"], serde_json::json!(["stdlib"]));
+}
+
+#[tokio::test]
+async fn test_catalog_matches_topics_and_topic_lookups() {
+    let (_tmp, app) = build_test_app().await;
+
+    let (status, body) = get(app.clone(), "/catalog").await;
+    assert_eq!(status, StatusCode::OK);
+    let catalog: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let catalog = catalog["categories"].as_object().unwrap();
+
+    // Rebuild the same structure from the granular endpoints.
+    let (_, body) = get(app.clone(), "/topics").await;
+    let topics: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let mut expected: std::collections::BTreeMap<String, Vec<serde_json::Value>> =
+        Default::default();
+    for (topic, categories) in topics["topics"].as_object().unwrap() {
+        for category in categories.as_array().unwrap() {
+            let category = category.as_str().unwrap();
+            let request = serde_json::json!({"topic": topic, "category": category});
+            let (_, body) = post_json(app.clone(), "/topic", &request.to_string()).await;
+            let chunks: serde_json::Value = serde_json::from_str(&body).unwrap();
+            let title = &chunks["results"][0]["metadata"]["title"];
+            expected
+                .entry(category.to_string())
+                .or_default()
+                .push(serde_json::json!({"topic": topic, "title": title}));
+        }
+    }
+
+    assert!(!expected.is_empty());
+    assert_eq!(catalog.len(), expected.len());
+    for (category, entries) in expected {
+        assert_eq!(catalog[&category], serde_json::Value::Array(entries));
+    }
+}
+
+#[tokio::test]
+async fn test_search_msgpack_round_trip_matches_json() {
+    let (tmp, app) = build_test_app().await;
+
+    let req = Request::builder()
+        .method("POST")
+        .uri("/search")
+        .header("content-type", "application/json")
+        .header("accept", MSGPACK_CONTENT_TYPE)
+        .body(Body::from(r#"{"query": "函数", "top_k": 3}"#))
+        .unwrap();
+    let resp = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()["content-type"], MSGPACK_CONTENT_TYPE);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let mut settings = test_settings(tmp.path().to_path_buf());
+    let json_client = RemoteSearchIndex::new(&settings, &url).unwrap();
+    settings.remote_msgpack = true;
+    let msgpack_client = RemoteSearchIndex::new(&settings, &url).unwrap();
+
+    let from_json = json_client
+        .query("函数", 3, None, false, None)
+        .await
+        .unwrap();
+    let from_msgpack = msgpack_client
+        .query("函数", 3, None, false, None)
+        .await
+        .unwrap();
+    assert!(!from_msgpack.is_empty());
+    assert_eq!(from_msgpack.len(), from_json.len());
+    for (a, b) in from_msgpack.iter().zip(&from_json) {
+        assert_eq!(a.text, b.text);
+        assert_eq!(a.score, b.score);
+        assert_eq!(a.metadata.file_path, b.metadata.file_path);
+        assert_eq!(a.metadata.chunk_id, b.metadata.chunk_id);
+    }
+}
+
+#[tokio::test]
+async fn test_bind_listener_reports_ephemeral_port() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (tmp, app) = build_test_app().await;
+    let port_file = tmp.path().join("port");
+    let listener = bind_listener("127.0.0.1:0", Some(&port_file))
+        .await
+        .unwrap();
+    let port = listener.local_addr().unwrap().port();
+    assert_ne!(port, 0);
+    assert_eq!(
+        std::fs::read_to_string(&port_file).unwrap(),
+        port.to_string()
+    );
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let mut stream = tokio::net::TcpStream::connect(("127.0.0.1", port))
+        .await
+        .unwrap();
+    stream
+        .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+}
+
 #[tokio::test]
 async fn test_info_endpoint() {
     let (_tmp, app) = build_test_app().await;
@@ -66,6 +221,8 @@ async fn test_info_endpoint() {
     assert_eq!(v["version"], "test");
     assert_eq!(v["lang"], "zh");
     assert!(v["document_count"].as_u64().unwrap() > 0);
+    // No docs repo is cloned in the test data dir.
+    assert!(v["resolved_version"].is_null());
 }
 
 #[tokio::test]
@@ -78,6 +235,20 @@ async fn test_search_endpoint() {
     assert!(!results.is_empty(), "search should return results");
 }
 
+#[tokio::test]
+async fn test_search_results_carry_chunk_ids() {
+    let (_tmp, app) = build_test_app().await;
+    let (status, body) = post_json(app, "/search", r#"{"query":"函数"}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    for r in v["results"].as_array().unwrap() {
+        assert!(
+            r["metadata"]["chunk_id"].is_string(),
+            "chunk_id missing from {r}"
+        );
+    }
+}
+
 #[tokio::test]
 async fn test_search_empty_query() {
     let (_tmp, app) = build_test_app().await;
@@ -89,13 +260,16 @@ async fn test_search_empty_query() {
 async fn test_search_whitespace_only_query() {
     let (_tmp, app) = build_test_app().await;
     let (status, body) = post_json(app, "/search", r#"{"query":"   "}"#).await;
-    assert_eq!(status, StatusCode::OK);
+    assert_eq!(status, StatusCode::BAD_REQUEST);
     let v: serde_json::Value = serde_json::from_str(&body).unwrap();
-    let results = v["results"].as_array().unwrap();
-    assert!(
-        results.is_empty(),
-        "whitespace-only query should return no results"
-    );
+    assert_eq!(v["error"], "query must not be empty");
+}
+
+#[tokio::test]
+async fn test_search_punctuation_only_query() {
+    let (_tmp, app) = build_test_app().await;
+    let (status, _body) = post_json(app, "/search", r#"{"query":" ?!，。 "}"#).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
 }
 
 #[tokio::test]
@@ -114,6 +288,37 @@ async fn test_search_with_category_filter() {
     }
 }
 
+#[tokio::test]
+async fn test_search_get_matches_post() {
+    let (_tmp, app) = build_test_app().await;
+    let (status, post_body) = post_json(
+        app.clone(),
+        "/search",
+        r#"{"query":"函数","top_k":3,"category":"syntax","rerank":false}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, get_body) = get(
+        app.clone(),
+        "/search?q=%E5%87%BD%E6%95%B0&top_k=3&category=syntax&rerank=false",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{get_body}");
+    let post: serde_json::Value = serde_json::from_str(&post_body).unwrap();
+    let got: serde_json::Value = serde_json::from_str(&get_body).unwrap();
+    assert!(!post["results"].as_array().unwrap().is_empty());
+    assert_eq!(got, post);
+
+    let (status, body) = get(app.clone(), "/search?q=%E5%87%BD%E6%95%B0&top_k=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("top_k must be between"), "{body}");
+
+    let (status, body) = get(app, "/search?top_k=3").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("query parameters"), "{body}");
+}
+
 #[tokio::test]
 async fn test_search_custom_top_k() {
     let (_tmp, app) = build_test_app().await;
@@ -147,6 +352,48 @@ async fn test_search_result_metadata_fields() {
     assert!(first["metadata"]["has_code"].is_boolean());
 }
 
+#[tokio::test]
+async fn test_search_results_carry_front_matter() {
+    let tmp = TempDir::new().unwrap();
+    let mut chunks = sample_chunks();
+    chunks[0]
+        .metadata
+        .custom
+        .insert("audience".to_string(), "internal".to_string());
+    let mut bm25 = BM25Store::new(tmp.path().join("bm25_index"));
+    bm25.build_from_chunks(&chunks).await.unwrap();
+    let settings = test_settings(tmp.path().to_path_buf());
+    let search_index = LocalSearchIndex::with_bm25(settings, bm25).await;
+    let metadata = IndexMetadata {
+        version: "test".to_string(),
+        lang: "zh".to_string(),
+        embedding_model: "none".to_string(),
+        document_count: 1,
+        search_mode: SearchMode::Bm25,
+        extra_docs: None,
+        chunking: None,
+        vector_distance: None,
+        source_commit: None,
+        index_code: false,
+        tokenizer_version: cangjie_indexer::search::TOKENIZER_VERSION,
+    };
+    let app = create_http_app(Arc::new(search_index), metadata).await;
+
+    let (status, body) = post_json(app, "/search", r#"{"query":"函数"}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let results = v["results"].as_array().unwrap();
+    let functions = results
+        .iter()
+        .find(|r| r["metadata"]["file_path"] == "syntax/functions.md")
+        .expect("functions chunk should match");
+    assert_eq!(functions["metadata"]["custom"]["audience"], "internal");
+    assert!(results
+        .iter()
+        .filter(|r| r["metadata"]["file_path"] != "syntax/functions.md")
+        .all(|r| r["metadata"].get("custom").is_none()));
+}
+
 #[tokio::test]
 async fn test_search_invalid_json() {
     let (_tmp, app) = build_test_app().await;
@@ -163,3 +410,167 @@ async fn test_search_invalid_json() {
         resp.status()
     );
 }
+
+#[tokio::test]
+async fn test_search_missing_content_type_explains_expected_body() {
+    let (_tmp, app) = build_test_app().await;
+    let req = Request::builder()
+        .method("POST")
+        .uri("/search")
+        .body(Body::from(r#"{"query":"函数"}"#))
+        .unwrap();
+    let resp = app.oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    let v: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let error = v["error"].as_str().unwrap();
+    assert!(error.contains("expected application/json body"), "{error}");
+}
+
+#[tokio::test]
+async fn test_search_malformed_json_returns_error_body() {
+    let (_tmp, app) = build_test_app().await;
+    let (status, body) = post_json(app, "/search", r#"{"query":"#).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(v["error"]
+        .as_str()
+        .unwrap()
+        .contains("fields {query, top_k"));
+}
+
+#[tokio::test]
+async fn test_search_empty_query_returns_error_body() {
+    let (_tmp, app) = build_test_app().await;
+    let (status, body) = post_json(app, "/search", r#"{"query":""}"#).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(v["error"], "query must not be empty");
+}
+
+#[tokio::test]
+async fn test_search_top_k_out_of_range() {
+    let (_tmp, app) = build_test_app().await;
+    let (status, body) = post_json(app.clone(), "/search", r#"{"query":"函数","top_k":0}"#).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(v["error"].as_str().unwrap().starts_with("top_k must be"));
+
+    let (status, _body) = post_json(app, "/search", r#"{"query":"函数","top_k":100000}"#).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_document_endpoint_returns_file_chunks() {
+    let (_tmp, app) = build_test_app().await;
+    let (status, body) =
+        post_json(app, "/document", r#"{"file_path":"syntax/functions.md"}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let results = v["results"].as_array().unwrap();
+    assert!(!results.is_empty());
+    for r in results {
+        assert_eq!(r["metadata"]["file_path"], "syntax/functions.md");
+    }
+}
+
+#[tokio::test]
+async fn test_document_and_topic_endpoints_reject_empty_keys_as_json() {
+    let (_tmp, app) = build_test_app().await;
+    let (status, body) = post_json(app.clone(), "/document", r#"{"file_path":""}"#).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(v["error"].as_str().unwrap().contains("file_path"), "{body}");
+
+    let (status, body) = post_json(app, "/topic", r#"{"topic":""}"#).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(v["error"].as_str().unwrap().contains("topic"), "{body}");
+}
+
+#[tokio::test]
+async fn test_topic_endpoint() {
+    let (_tmp, app) = build_test_app().await;
+    let (status, body) = post_json(
+        app.clone(),
+        "/topic",
+        r#"{"topic":"functions","category":"syntax"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let results = v["results"].as_array().unwrap();
+    assert!(!results.is_empty());
+    assert!(results
+        .iter()
+        .all(|r| r["metadata"]["topic"] == "functions"));
+
+    let (status, body) = post_json(app, "/topic", r#"{"topic":"missing"}"#).await;
+    assert_eq!(status, StatusCode::OK);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(v["results"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_topic_endpoint_etag_returns_not_modified() {
+    let (_tmp, app) = build_test_app().await;
+    let body = r#"{"topic":"functions","category":"syntax"}"#;
+    let topic_request = |if_none_match: Option<&str>| {
+        let mut builder = Request::builder()
+            .method("POST")
+            .uri("/topic")
+            .header("content-type", "application/json");
+        if let Some(tag) = if_none_match {
+            builder = builder.header("if-none-match", tag);
+        }
+        builder.body(Body::from(body)).unwrap()
+    };
+
+    let resp = app.clone().oneshot(topic_request(None)).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let etag = resp.headers()["etag"].to_str().unwrap().to_string();
+    assert!(etag.starts_with('"') && etag.ends_with('"'), "{etag}");
+
+    let resp = app
+        .clone()
+        .oneshot(topic_request(Some(&etag)))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(resp.headers()["etag"], etag.as_str());
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    assert!(bytes.is_empty());
+
+    let resp = app.oneshot(topic_request(Some("\"stale\""))).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_warmup_endpoint_reports_bm25_phase() {
+    let (_tmp, app) = build_test_app().await;
+    let (status, body) = post_json(app, "/warmup", "{}").await;
+    assert_eq!(status, StatusCode::OK);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(v["bm25_ms"].is_u64());
+    assert!(v["embedder_ms"].is_null());
+    assert!(v["vector_ms"].is_null());
+}
+
+#[tokio::test]
+async fn test_reload_endpoint_keeps_serving_an_index_dir_override() {
+    let tmp = TempDir::new().unwrap();
+    let mut bm25 = BM25Store::new(tmp.path().join("bm25_index"));
+    bm25.build_from_chunks(&sample_chunks()).await.unwrap();
+    let mut settings = test_settings(tmp.path().to_path_buf());
+    settings.index_dir_override = Some(tmp.path().to_path_buf());
+    let search_index = LocalSearchIndex::with_bm25(settings.clone(), bm25).await;
+    let server = CangjieServer::with_local_state(settings, search_index);
+
+    let (status, body) = post_json(create_reload_router(server), "/reload", "{}").await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    let v: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(
+        v["error"].as_str().unwrap().contains("not supported"),
+        "{body}"
+    );
+}
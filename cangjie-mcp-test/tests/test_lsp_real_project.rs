@@ -100,6 +100,7 @@ fn lsp_req(operation: LspOperation) -> LspRequest {
         file_path: None,
         target: None,
         query: None,
+        preview: false,
     }
 }
 
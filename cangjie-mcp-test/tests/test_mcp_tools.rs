@@ -1,12 +1,22 @@
-use cangjie_core::config::{MAX_TOP_K, MIN_TOP_K};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use cangjie_core::config::{OutputFormat, RerankType, Settings, MAX_TOP_K};
+use cangjie_indexer::document::chunker::chunk_documents;
+use cangjie_indexer::document::code_block_chunks;
+use cangjie_indexer::document::source::{DocumentSource as _, FsDocumentSource};
 use cangjie_indexer::search::bm25::BM25Store;
 use cangjie_indexer::search::LocalSearchIndex;
 use cangjie_indexer::{DocMetadata, TextChunk};
 use cangjie_mcp_test::{sample_chunks, test_settings};
 use cangjie_server::lsp_tools::{LspOperation, LspRequest};
-use cangjie_server::mcp_handler::SearchDocsParams;
+use cangjie_server::mcp_handler::{
+    prompt_templates, DiffVersionsParams, GetTopicsParams, RelatedTopicsParams, SearchDocsParams,
+    SearchGranularity, SearchOrder, TopicRequest, PROMPT_EXPLAIN_ERROR, PROMPT_REVIEW_FILE,
+    PROMPT_USE_MODULE,
+};
 use cangjie_server::{CangjieServer, Parameters};
-use rmcp::model::Meta;
+use rmcp::model::{Meta, ResourceContents};
 use tempfile::TempDir;
 
 async fn build_test_server() -> (TempDir, CangjieServer) {
@@ -38,10 +48,8 @@ async fn test_search_docs_basic() {
     let result = server
         .search_docs(Parameters(SearchDocsParams {
             query: "函数".into(),
-            top_k: 5,
-            offset: 0,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -54,6 +62,49 @@ async fn test_search_docs_basic() {
     assert!(result.contains("[score:"), "should include relevance score");
 }
 
+#[tokio::test]
+async fn test_search_docs_reports_unloaded_index() {
+    let params = || SearchDocsParams {
+        query: "xyzzy".into(),
+        response_format: Some(OutputFormat::Markdown),
+        ..Default::default()
+    };
+
+    let tmp = TempDir::new().unwrap();
+    let settings = test_settings(tmp.path().to_path_buf());
+    let unloaded = LocalSearchIndex::new(settings.clone()).await;
+    let server = CangjieServer::with_local_state(settings, unloaded);
+    let result = server.search_docs(Parameters(params())).await;
+    assert!(result.contains("search index is not available"), "{result}");
+
+    let (_tmp, server) = build_test_server().await;
+    let result = server.search_docs(Parameters(params())).await;
+    assert!(
+        !result.contains("search index is not available"),
+        "{result}"
+    );
+}
+
+#[tokio::test]
+async fn test_search_docs_reports_initialization_failure() {
+    let tmp = TempDir::new().unwrap();
+    let mut settings = test_settings(tmp.path().to_path_buf());
+    settings.index_dir_override = Some(tmp.path().join("missing"));
+    let server = CangjieServer::new(settings);
+    assert!(server.initialize().await.is_err());
+
+    let result = server
+        .search_docs(Parameters(SearchDocsParams {
+            query: "函数".into(),
+            rerank: false,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
+        }))
+        .await;
+    assert!(result.contains("Server failed to initialize"), "{result}");
+    assert!(result.contains("No index metadata"), "{result}");
+}
+
 #[tokio::test]
 async fn test_unified_lsp_tool_reports_validation_error() {
     let (_tmp, server) = build_test_server().await;
@@ -65,6 +116,7 @@ async fn test_unified_lsp_tool_reports_validation_error() {
                 file_path: None,
                 target: None,
                 query: None,
+                preview: false,
             }),
             Meta::default(),
         )
@@ -87,9 +139,9 @@ async fn test_search_docs_with_category() {
         .search_docs(Parameters(SearchDocsParams {
             query: "函数".into(),
             top_k: 10,
-            offset: 0,
             category: Some("syntax".into()),
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -109,9 +161,8 @@ async fn test_search_docs_pagination() {
         .search_docs(Parameters(SearchDocsParams {
             query: "仓颉".into(),
             top_k: 20,
-            offset: 0,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
     let all_count = all.matches("### [").count();
@@ -121,8 +172,8 @@ async fn test_search_docs_pagination() {
             query: "仓颉".into(),
             top_k: 2,
             offset: 2,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -149,9 +200,9 @@ async fn test_search_docs_package_filter() {
         .search_docs(Parameters(SearchDocsParams {
             query: "集合".into(),
             top_k: 10,
-            offset: 0,
-            category: None,
             package: Some("Array".into()),
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -161,6 +212,39 @@ async fn test_search_docs_package_filter() {
     );
 }
 
+#[tokio::test]
+async fn test_search_docs_notes_filters_that_remove_all_results() {
+    let (_tmp, server) = build_test_server().await;
+    let params = |category: Option<&str>, package: Option<&str>| SearchDocsParams {
+        query: "集合".into(),
+        category: category.map(Into::into),
+        package: package.map(Into::into),
+        response_format: Some(OutputFormat::Markdown),
+        ..Default::default()
+    };
+
+    let unfiltered = server.search_docs(Parameters(params(None, None))).await;
+    assert!(unfiltered.contains("集合类型"), "{unfiltered}");
+
+    let result = server
+        .search_docs(Parameters(params(Some("cjpm"), Some("Array"))))
+        .await;
+    assert!(result.contains("Found 0 results"), "{result}");
+    assert!(
+        result.contains("category 'cjpm' and package 'Array' filter removed all of them"),
+        "{result}"
+    );
+
+    let result = server
+        .search_docs(Parameters(params(None, Some("std.nonexistent"))))
+        .await;
+    assert!(result.contains("Found 0 results"), "{result}");
+    assert!(
+        result.contains("package 'std.nonexistent' filter removed all of them"),
+        "{result}"
+    );
+}
+
 #[tokio::test]
 async fn test_search_docs_allows_two_snippets_per_document_when_top_k_is_large() {
     let chunks = vec![
@@ -207,9 +291,8 @@ async fn test_search_docs_allows_two_snippets_per_document_when_top_k_is_large()
         .search_docs(Parameters(SearchDocsParams {
             query: "HashMap".into(),
             top_k: 10,
-            offset: 0,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -267,9 +350,8 @@ async fn test_search_docs_limits_to_one_snippet_per_document_when_top_k_is_small
         .search_docs(Parameters(SearchDocsParams {
             query: "HashMap".into(),
             top_k: 3,
-            offset: 0,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -281,6 +363,877 @@ async fn test_search_docs_limits_to_one_snippet_per_document_when_top_k_is_small
     assert!(result_count > 0, "should return at least one result");
 }
 
+#[tokio::test]
+async fn test_search_docs_rejects_blank_queries() {
+    let (_tmp, server) = build_test_server().await;
+    for query in ["", "   ", "?!...", " ，。？ "] {
+        let result = server
+            .search_docs(Parameters(SearchDocsParams {
+                query: query.into(),
+                rerank: false,
+                response_format: Some(OutputFormat::Markdown),
+                ..Default::default()
+            }))
+            .await;
+        assert_eq!(
+            result, "Search error: query must not be empty",
+            "query {query:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_search_docs_document_granularity_returns_whole_documents() {
+    let hashmap_chunk = |idx: usize, text: &str| TextChunk {
+        text: text.to_string(),
+        metadata: DocMetadata {
+            file_path: "stdlib/collection_hashmap.md".to_string(),
+            category: "stdlib".to_string(),
+            topic: "collection_hashmap".to_string(),
+            title: "HashMap 用法".to_string(),
+            has_code: false,
+            code_block_count: 0,
+            chunk_id: format!("stdlib/collection_hashmap.md#{idx}"),
+            custom: Default::default(),
+            chunk_index: idx,
+            char_offset: 0,
+            breadcrumb: String::new(),
+        },
+    };
+    let chunks = vec![
+        hashmap_chunk(1, "HashMap 遍历与删除"),
+        hashmap_chunk(0, "HashMap get set 示例"),
+        hashmap_chunk(2, "容量与负载因子"),
+        TextChunk {
+            text: "ArrayList 与 HashMap 的区别".to_string(),
+            metadata: DocMetadata {
+                file_path: "stdlib/collection_arraylist.md".to_string(),
+                category: "stdlib".to_string(),
+                topic: "collection_arraylist".to_string(),
+                title: "ArrayList 用法".to_string(),
+                has_code: false,
+                code_block_count: 0,
+                chunk_id: "stdlib/collection_arraylist.md#0".to_string(),
+                custom: Default::default(),
+                chunk_index: 0,
+                char_offset: 0,
+                breadcrumb: String::new(),
+            },
+        },
+    ];
+    let (_tmp, server) = build_test_server_with_chunks(chunks).await;
+
+    let result = server
+        .search_docs(Parameters(SearchDocsParams {
+            query: "HashMap".into(),
+            top_k: 10,
+            granularity: SearchGranularity::Document,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
+        }))
+        .await;
+
+    assert_eq!(
+        result.matches("(stdlib/collection_hashmap)").count(),
+        1,
+        "each document should appear at most once, got:\n{result}"
+    );
+    assert!(result.matches("(stdlib/collection_arraylist)").count() <= 1);
+    // Full content, including the chunk that does not mention the query, in chunk order.
+    let get_set = result
+        .find("HashMap get set 示例")
+        .expect("chunk #0 missing");
+    let traverse = result.find("HashMap 遍历与删除").expect("chunk #1 missing");
+    let capacity = result.find("容量与负载因子").expect("chunk #2 missing");
+    assert!(get_set < traverse && traverse < capacity);
+}
+
+#[tokio::test]
+async fn test_search_docs_extract_code_filters_by_language() {
+    let chunk = |file: &str, idx: usize, text: &str| TextChunk {
+        text: text.to_string(),
+        metadata: DocMetadata {
+            file_path: format!("cjpm/{file}.md"),
+            category: "cjpm".to_string(),
+            topic: file.to_string(),
+            title: file.to_string(),
+            has_code: true,
+            code_block_count: 2,
+            chunk_id: format!("cjpm/{file}.md#{idx}"),
+            custom: Default::default(),
+            chunk_index: 0,
+            char_offset: 0,
+            breadcrumb: String::new(),
+        },
+    };
+    let chunks = vec![
+        chunk(
+            "build",
+            0,
+            "构建项目\n\n```bash\ncjpm build\n```\n\n```cangjie\nmain() {\n    println(\"构建\")\n}\n```\n",
+        ),
+        chunk("install", 0, "安装构建工具\n\n```shell\ncjpm install\n```\n"),
+    ];
+    let (_tmp, server) = build_test_server_with_chunks(chunks).await;
+
+    let result = server
+        .search_docs(Parameters(SearchDocsParams {
+            query: "构建".into(),
+            rerank: false,
+            extract_code: true,
+            code_language: Some("cj".into()),
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
+        }))
+        .await;
+
+    assert!(result.contains("println(\"构建\")"), "got:\n{result}");
+    assert!(
+        !result.contains("cjpm build"),
+        "bash block leaked:\n{result}"
+    );
+    assert!(
+        !result.contains("构建项目"),
+        "prose should be dropped:\n{result}"
+    );
+    assert!(
+        !result.contains("(cjpm/install)"),
+        "documents without Cangjie code should be skipped:\n{result}"
+    );
+}
+
+#[tokio::test]
+async fn test_search_docs_path_order_sorts_by_file_path() {
+    let (_tmp, server) = build_test_server().await;
+
+    let result = server
+        .search_docs(Parameters(SearchDocsParams {
+            query: "仓颉".into(),
+            top_k: 10,
+            rerank: false,
+            order: SearchOrder::Path,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
+        }))
+        .await;
+
+    let paths: Vec<String> = result
+        .lines()
+        .filter(|line| line.starts_with("### ["))
+        .filter_map(|line| {
+            let start = line.rfind(" (")? + 2;
+            let end = start + line[start..].find(')')?;
+            Some(format!("{}.md", &line[start..end]))
+        })
+        .collect();
+    assert!(paths.len() > 1, "expected several results:\n{result}");
+    let mut sorted = paths.clone();
+    sorted.sort();
+    assert_eq!(paths, sorted, "results should follow file path order");
+}
+
+fn code_search_params(query: &str) -> SearchDocsParams {
+    SearchDocsParams {
+        query: query.into(),
+        rerank: false,
+        search_code: true,
+        response_format: Some(OutputFormat::Markdown),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_search_docs_search_code_returns_code_block_document() {
+    let tmp = TempDir::new().unwrap();
+    let chunks = sample_chunks();
+    let mut bm25 = BM25Store::new(tmp.path().join("bm25"));
+    bm25.build_from_chunks(&chunks).await.unwrap();
+    let mut code_store = BM25Store::new(tmp.path().join("code"));
+    code_store
+        .build_from_chunks(&code_block_chunks(&chunks))
+        .await
+        .unwrap();
+    let settings = test_settings(tmp.path().to_path_buf());
+    let search = LocalSearchIndex::with_bm25(settings.clone(), bm25)
+        .await
+        .with_code_store(code_store);
+    let server = CangjieServer::with_local_state(settings, search);
+
+    let result = server
+        .search_docs(Parameters(code_search_params("readAll")))
+        .await;
+
+    assert!(result.contains("(stdlib/io)"), "got:\n{result}");
+    assert!(result.contains("file.readAll()"), "got:\n{result}");
+    assert!(
+        result.contains("仓颉标准库提供文件读写和网络IO功能。"),
+        "introducing paragraph should be kept:\n{result}"
+    );
+    assert!(
+        !result.contains("支持异步IO操作"),
+        "prose after the block belongs to the chunk, not the code document:\n{result}"
+    );
+}
+
+#[tokio::test]
+async fn test_search_docs_search_code_checks_lang() {
+    let tmp = TempDir::new().unwrap();
+    let chunks = sample_chunks();
+    let mut bm25 = BM25Store::new(tmp.path().join("bm25"));
+    bm25.build_from_chunks(&chunks).await.unwrap();
+    let mut code_store = BM25Store::new(tmp.path().join("code"));
+    code_store
+        .build_from_chunks(&code_block_chunks(&chunks))
+        .await
+        .unwrap();
+    let settings = test_settings(tmp.path().to_path_buf());
+    let search = LocalSearchIndex::with_bm25(settings.clone(), bm25)
+        .await
+        .with_code_store(code_store);
+    let server = CangjieServer::with_local_state(settings, search);
+
+    let mut params = code_search_params("readAll");
+    params.lang = Some("en".into());
+    let result = server.search_docs(Parameters(params)).await;
+    assert!(
+        result.contains("requires a remote server"),
+        "got:\n{result}"
+    );
+
+    let mut params = code_search_params("readAll");
+    params.lang = Some("zh".into());
+    let result = server.search_docs(Parameters(params)).await;
+    assert!(result.contains("file.readAll()"), "got:\n{result}");
+}
+
+#[tokio::test]
+async fn test_search_docs_search_code_finds_operator() {
+    let tmp = TempDir::new().unwrap();
+    let chunk = |topic: &str, code: &str| TextChunk {
+        text: format!("示例：\n\n```cangjie\n{code}\n```"),
+        metadata: DocMetadata {
+            file_path: format!("syntax/{topic}.md"),
+            category: "syntax".to_string(),
+            topic: topic.to_string(),
+            title: topic.to_string(),
+            has_code: true,
+            code_block_count: 1,
+            ..Default::default()
+        },
+    };
+    let chunks = vec![
+        chunk("coalescing", "let name = user ?? \"guest\""),
+        chunk("pipeline", "let total = values |> sum"),
+    ];
+    let mut bm25 = BM25Store::new(tmp.path().join("bm25"));
+    bm25.build_from_chunks(&chunks).await.unwrap();
+    let mut code_store = BM25Store::new(tmp.path().join("code"));
+    code_store
+        .build_from_chunks(&code_block_chunks(&chunks))
+        .await
+        .unwrap();
+    let settings = test_settings(tmp.path().to_path_buf());
+    let search = LocalSearchIndex::with_bm25(settings.clone(), bm25)
+        .await
+        .with_code_store(code_store);
+    let server = CangjieServer::with_local_state(settings, search);
+
+    let result = server
+        .search_docs(Parameters(code_search_params("??")))
+        .await;
+
+    assert!(result.contains("user ?? \"guest\""), "got:\n{result}");
+    assert!(!result.contains("values |> sum"), "got:\n{result}");
+}
+
+#[tokio::test]
+async fn test_search_docs_search_code_without_code_index() {
+    let (_tmp, server) = build_test_server().await;
+    let result = server
+        .search_docs(Parameters(code_search_params("readAll")))
+        .await;
+    assert!(
+        result.starts_with("Search error: Code search is not available"),
+        "got:\n{result}"
+    );
+}
+
+#[tokio::test]
+async fn test_search_docs_debug_terms() {
+    let (_tmp, server) = build_test_server().await;
+    let params = |debug_terms| SearchDocsParams {
+        search_code: false,
+        debug_terms,
+        ..code_search_params("HashMap \u{51fd}\u{6570}")
+    };
+
+    let result = server.search_docs(Parameters(params(true))).await;
+    assert!(result.contains("Query terms: hashmap"), "got:\n{result}");
+    assert!(result.contains("Expanded query:"), "got:\n{result}");
+
+    let result = server.search_docs(Parameters(params(false))).await;
+    assert!(!result.contains("Query terms:"), "got:\n{result}");
+}
+
+#[tokio::test]
+async fn test_search_docs_unindexed_version_lists_available_versions() {
+    let (_tmp, server) = build_test_server().await;
+    let result = server
+        .search_docs(Parameters(SearchDocsParams {
+            search_code: false,
+            version: Some("v0.9".to_string()),
+            ..code_search_params("\u{51fd}\u{6570}")
+        }))
+        .await;
+    assert!(
+        result.contains("Version 'v0.9' is not indexed"),
+        "got:\n{result}"
+    );
+    assert!(result.contains("Indexed versions: none"), "got:\n{result}");
+
+    let output = server
+        .get_topics(Parameters(GetTopicsParams {
+            topics: vec![TopicRequest {
+                topic: "functions".to_string(),
+                category: None,
+            }],
+            version: Some("v0.9".to_string()),
+            response_format: None,
+        }))
+        .await;
+    assert!(output.contains("not indexed"), "{output}");
+}
+
+#[tokio::test]
+async fn test_get_topics_reports_missing_topic_in_order() {
+    let (_tmp, server) = build_test_server().await;
+    let request = |topic: &str, category: Option<&str>| TopicRequest {
+        topic: topic.to_string(),
+        category: category.map(|c| c.to_string()),
+    };
+
+    let output = server
+        .get_topics(Parameters(GetTopicsParams {
+            topics: vec![
+                request("functions", None),
+                request("no_such_topic", None),
+                request("collections", Some("stdlib")),
+            ],
+            version: None,
+            response_format: None,
+        }))
+        .await;
+
+    let entries: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0]["topic"], "functions");
+    assert_eq!(entries[0]["file_path"], "syntax/functions.md");
+    assert!(entries[0]["content"].as_str().unwrap().contains("func add"));
+    assert_eq!(entries[1]["topic"], "no_such_topic");
+    assert_eq!(entries[1]["not_found"], true);
+    assert!(entries[1].get("content").is_none());
+    assert_eq!(entries[2]["topic"], "collections");
+    assert_eq!(entries[2]["category"], "stdlib");
+}
+
+#[tokio::test]
+async fn test_related_topics_excludes_source_topic() {
+    let (_tmp, server) = build_test_server().await;
+
+    let output = server
+        .related_topics(Parameters(RelatedTopicsParams {
+            topic: "collections".to_string(),
+            category: None,
+            k: 3,
+        }))
+        .await;
+    let related: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap();
+    assert!(!related.is_empty(), "{output}");
+    assert!(related.len() <= 3);
+    assert!(related.iter().all(|r| r["topic"] != "collections"));
+    // The test index is BM25-only, so scores are ranks, not similarities.
+    assert!(related.iter().all(|r| r["matched_by"] == "keyword"));
+    assert!(related.iter().all(|r| r["similarity"].is_null()));
+
+    let output = server
+        .related_topics(Parameters(RelatedTopicsParams {
+            topic: "collections".to_string(),
+            category: None,
+            k: usize::MAX,
+        }))
+        .await;
+    let related: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap();
+    assert!(related.len() <= MAX_TOP_K, "{output}");
+
+    let output = server
+        .related_topics(Parameters(RelatedTopicsParams {
+            topic: "no_such_topic".to_string(),
+            category: None,
+            k: 3,
+        }))
+        .await;
+    assert!(output.contains("Topic not found"), "{output}");
+}
+
+#[tokio::test]
+async fn test_search_docs_exclude_topics_drops_top_hit() {
+    let (_tmp, server) = build_test_server().await;
+    let params = |exclude_topics: Vec<String>| SearchDocsParams {
+        query: "集合类型 HashMap".into(),
+        top_k: 3,
+        rerank: false,
+        response_format: Some(OutputFormat::Json),
+        exclude_topics,
+        ..Default::default()
+    };
+    let topics = |output: &str| -> Vec<String> {
+        let value: serde_json::Value = serde_json::from_str(output).unwrap();
+        value["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["topic"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let output = server.search_docs(Parameters(params(Vec::new()))).await;
+    assert_eq!(
+        topics(&output).first().map(String::as_str),
+        Some("collections")
+    );
+
+    let output = server
+        .search_docs(Parameters(params(vec!["collections".to_string()])))
+        .await;
+    let topics = topics(&output);
+    assert!(!topics.is_empty(), "{output}");
+    assert!(topics.iter().all(|t| t != "collections"), "{output}");
+}
+
+#[test]
+fn test_prompt_templates_listed() {
+    let names: Vec<String> = prompt_templates()
+        .iter()
+        .map(|p| p.name.to_string())
+        .collect();
+    assert_eq!(
+        names,
+        vec![PROMPT_EXPLAIN_ERROR, PROMPT_USE_MODULE, PROMPT_REVIEW_FILE]
+    );
+}
+
+#[tokio::test]
+async fn test_render_prompt_includes_retrieved_docs() {
+    let (_tmp, server) = build_test_server().await;
+
+    let mut args = serde_json::Map::new();
+    args.insert("module".into(), "HashMap".into());
+    args.insert("task".into(), "集合类型".into());
+    let rendered = server
+        .render_prompt(PROMPT_USE_MODULE, Some(&args), None)
+        .await
+        .unwrap()
+        .expect("known prompt");
+    assert!(!rendered.messages.is_empty());
+    let text = serde_json::to_string(&rendered.messages).unwrap();
+    assert!(text.contains("HashMap<String, Int>"), "{text}");
+
+    let err = server
+        .render_prompt(PROMPT_EXPLAIN_ERROR, None, None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("'error'"), "{err}");
+    assert!(server
+        .render_prompt("no_such_prompt", None, None)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_allowed_categories_hide_other_categories() {
+    let (_tmp, server) = build_test_server().await;
+    let server = server.with_allowed_categories(["syntax"]);
+
+    let result = server
+        .search_docs(Parameters(SearchDocsParams {
+            query: "集合类型 HashMap 字符串".into(),
+            top_k: 10,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
+        }))
+        .await;
+    assert!(!result.contains("stdlib/"), "{result}");
+
+    let output = server
+        .get_topics(Parameters(GetTopicsParams {
+            topics: vec![
+                TopicRequest {
+                    topic: "collections".to_string(),
+                    category: Some("stdlib".to_string()),
+                },
+                TopicRequest {
+                    topic: "functions".to_string(),
+                    category: None,
+                },
+            ],
+            version: None,
+            response_format: None,
+        }))
+        .await;
+    let entries: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap();
+    assert_eq!(entries[0]["not_found"], true);
+    assert_eq!(entries[1]["category"], "syntax");
+}
+
+#[tokio::test]
+async fn test_response_format_switches_between_markdown_and_json() {
+    let tmp = TempDir::new().unwrap();
+    let mut bm25 = BM25Store::new(tmp.path().join("bm25"));
+    bm25.build_from_chunks(&sample_chunks()).await.unwrap();
+    let mut settings = test_settings(tmp.path().to_path_buf());
+    settings.output_format = Some(OutputFormat::Json);
+    let search = LocalSearchIndex::with_bm25(settings.clone(), bm25).await;
+    let server = CangjieServer::with_local_state(settings, search);
+    let search_params = |response_format| SearchDocsParams {
+        query: "函数".into(),
+        response_format,
+        ..Default::default()
+    };
+
+    // --output-format json applies when the request does not pick a format.
+    let json = server.search_docs(Parameters(search_params(None))).await;
+    let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let first = &parsed["items"][0];
+    assert!(first["score"].as_f64().unwrap() > 0.0);
+
+    let markdown = server
+        .search_docs(Parameters(search_params(Some(OutputFormat::Markdown))))
+        .await;
+    let heading = format!(
+        "### [1] {} ({}/{}) [score: {:.2}]",
+        first["title"].as_str().unwrap(),
+        first["category"].as_str().unwrap(),
+        first["topic"].as_str().unwrap(),
+        first["score"].as_f64().unwrap()
+    );
+    assert!(markdown.contains(&heading), "{markdown}");
+    assert!(markdown.contains(&format!("Path: {}", first["file_path"].as_str().unwrap())));
+
+    let topics = server
+        .get_topics(Parameters(GetTopicsParams {
+            topics: vec![TopicRequest {
+                topic: "functions".to_string(),
+                category: None,
+            }],
+            version: None,
+            response_format: Some(OutputFormat::Markdown),
+        }))
+        .await;
+    assert!(topics.contains("(syntax/functions)"), "{topics}");
+    assert!(topics.contains("func add"), "{topics}");
+
+    // Without --output-format, JSON is the default.
+    let (_tmp, server) = build_test_server().await;
+    let json = server.search_docs(Parameters(search_params(None))).await;
+    assert!(
+        serde_json::from_str::<serde_json::Value>(&json).is_ok(),
+        "{json}"
+    );
+}
+
+#[tokio::test]
+async fn test_pinned_topics_lead_the_resource_list() {
+    let tmp = TempDir::new().unwrap();
+    let mut bm25 = BM25Store::new(tmp.path().join("bm25"));
+    bm25.build_from_chunks(&sample_chunks()).await.unwrap();
+    let mut settings = test_settings(tmp.path().to_path_buf());
+    settings.pinned_topics = vec![
+        "stdlib/collections".to_string(),
+        "syntax/no_such_topic".to_string(),
+        "syntax/functions".to_string(),
+    ];
+    let search = LocalSearchIndex::with_bm25(settings.clone(), bm25).await;
+    let server = CangjieServer::with_local_state(settings, search);
+
+    let resources = server.topic_resources().await.unwrap();
+    let uris: Vec<&str> = resources.iter().map(|r| r.uri.as_str()).collect();
+    assert_eq!(
+        uris[..2],
+        ["cangjie://stdlib/collections", "cangjie://syntax/functions"]
+    );
+    // Pinned topics are moved up, not listed twice.
+    assert_eq!(
+        uris.iter()
+            .filter(|u| **u == "cangjie://syntax/functions")
+            .count(),
+        1
+    );
+    assert!(uris.contains(&"cangjie://syntax/variables"), "{uris:?}");
+}
+
+#[tokio::test]
+async fn test_topics_are_listed_and_read_as_resources() {
+    let (_tmp, server) = build_test_server().await;
+
+    let resources = server.topic_resources().await.unwrap();
+    let uris: Vec<&str> = resources.iter().map(|r| r.uri.as_str()).collect();
+    assert!(uris.contains(&"cangjie://syntax/functions"), "{uris:?}");
+    assert!(uris.contains(&"cangjie://stdlib/collections"), "{uris:?}");
+    let functions = resources
+        .iter()
+        .find(|r| r.uri == "cangjie://syntax/functions")
+        .unwrap();
+    assert_eq!(functions.name, "functions");
+    assert_eq!(functions.mime_type.as_deref(), Some("text/markdown"));
+
+    let contents = server
+        .read_topic_resource("cangjie://syntax/functions")
+        .await
+        .unwrap()
+        .expect("functions resource should exist");
+    match contents {
+        ResourceContents::TextResourceContents { uri, text, .. } => {
+            assert_eq!(uri, "cangjie://syntax/functions");
+            assert!(text.contains("func add"), "{text}");
+        }
+        other => panic!("expected text contents, got {other:?}"),
+    }
+
+    // A topic under the wrong category, or a malformed URI, is not a resource.
+    assert!(server
+        .read_topic_resource("cangjie://stdlib/functions")
+        .await
+        .unwrap()
+        .is_none());
+    assert!(server
+        .read_topic_resource("cangjie://functions")
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_get_topics_resolves_extra_docs_topic() {
+    let extra = TempDir::new().unwrap();
+    std::fs::create_dir_all(extra.path().join("guides")).unwrap();
+    std::fs::write(
+        extra.path().join("guides/code_review.md"),
+        "# Code Review\n\nEvery change needs two approvals before merging.\n",
+    )
+    .unwrap();
+
+    let extra_docs = FsDocumentSource::new(extra.path().to_path_buf(), "internal")
+        .load_all_documents()
+        .await
+        .unwrap();
+    let mut chunks = sample_chunks();
+    chunks.extend(chunk_documents(extra_docs, Some(6000), 100, true).await);
+    let (_tmp, server) = build_test_server_with_chunks(chunks).await;
+
+    let output = server
+        .get_topics(Parameters(GetTopicsParams {
+            topics: vec![TopicRequest {
+                topic: "code_review".to_string(),
+                category: Some("internal/guides".to_string()),
+            }],
+            version: None,
+            response_format: None,
+        }))
+        .await;
+    let entries: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap();
+    assert_eq!(entries[0]["category"], "internal/guides");
+    assert_eq!(entries[0]["file_path"], "internal/guides/code_review.md");
+    assert!(entries[0]["content"]
+        .as_str()
+        .unwrap()
+        .contains("two approvals"));
+}
+
+#[tokio::test]
+async fn test_search_docs_metadata_filter_uses_front_matter() {
+    let extra = TempDir::new().unwrap();
+    std::fs::create_dir_all(extra.path().join("runbooks")).unwrap();
+    std::fs::write(
+        extra.path().join("runbooks/deploy.md"),
+        "---\naudience: internal\ntags: [release, ops]\n---\n# Deploy Runbook\n\nDeploy the service with cjpm build first.\n",
+    )
+    .unwrap();
+    std::fs::write(
+        extra.path().join("runbooks/rollback.md"),
+        "---\naudience: internal\ntags: [incident]\n---\n# Rollback Runbook\n\nRoll back the service with cjpm build of the previous tag.\n",
+    )
+    .unwrap();
+
+    let extra_docs = FsDocumentSource::new(extra.path().to_path_buf(), "internal")
+        .load_all_documents()
+        .await
+        .unwrap();
+    let mut chunks = sample_chunks();
+    chunks.extend(chunk_documents(extra_docs, Some(6000), 100, true).await);
+    let (_tmp, server) = build_test_server_with_chunks(chunks).await;
+
+    let search = |filter: &[(&str, &str)]| SearchDocsParams {
+        query: "service cjpm build".into(),
+        top_k: 10,
+        metadata_filter: filter
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        response_format: Some(OutputFormat::Markdown),
+        ..Default::default()
+    };
+
+    let unfiltered = server.search_docs(Parameters(search(&[]))).await;
+    assert!(unfiltered.contains("Deploy Runbook"), "{unfiltered}");
+    assert!(unfiltered.contains("Rollback Runbook"), "{unfiltered}");
+    assert!(
+        !unfiltered.contains("audience: internal"),
+        "front matter should not be indexed as text: {unfiltered}"
+    );
+
+    let ops = server
+        .search_docs(Parameters(search(&[("tags", "ops")])))
+        .await;
+    assert!(ops.contains("Deploy Runbook"), "{ops}");
+    assert!(!ops.contains("Rollback Runbook"), "{ops}");
+    assert!(!ops.contains("包管理器 CJPM"), "{ops}");
+
+    let internal = server
+        .search_docs(Parameters(search(&[("audience", "internal")])))
+        .await;
+    assert!(internal.contains("Deploy Runbook"), "{internal}");
+    assert!(internal.contains("Rollback Runbook"), "{internal}");
+}
+
+#[tokio::test]
+async fn test_topic_exists_exact_wrong_category_and_near_miss() {
+    let (_tmp, server) = build_test_server().await;
+    let check = |topic: &str, category: Option<&str>| {
+        let server = server.clone();
+        let params = TopicRequest {
+            topic: topic.to_string(),
+            category: category.map(|c| c.to_string()),
+        };
+        async move {
+            let output = server.topic_exists(Parameters(params)).await;
+            serde_json::from_str::<serde_json::Value>(&output).unwrap()
+        }
+    };
+
+    let exact = check("functions", Some("syntax")).await;
+    assert_eq!(exact["exists"], true);
+    assert_eq!(exact["matched_category"], "syntax");
+    assert!(exact["suggestions"].as_array().unwrap().is_empty());
+
+    let wrong_category = check("functions", Some("stdlib")).await;
+    assert_eq!(wrong_category["exists"], false);
+    assert!(wrong_category["matched_category"].is_null());
+    assert_eq!(
+        wrong_category["other_categories"],
+        serde_json::json!(["syntax"])
+    );
+
+    let near_miss = check("functons", None).await;
+    assert_eq!(near_miss["exists"], false);
+    assert_eq!(near_miss["suggestions"][0], "functions");
+}
+
+#[tokio::test]
+async fn test_get_topics_suggests_similar_topics_for_missing_entries() {
+    let (_tmp, server) = build_test_server().await;
+    let output = server
+        .get_topics(Parameters(GetTopicsParams {
+            topics: vec![TopicRequest {
+                topic: "variabels".to_string(),
+                category: None,
+            }],
+            version: None,
+            response_format: None,
+        }))
+        .await;
+    let entries: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap();
+    assert_eq!(entries[0]["not_found"], true);
+    assert_eq!(entries[0]["suggestions"][0], "variables");
+}
+
+#[tokio::test]
+async fn test_get_topics_notes_duplicate_topic_across_categories() {
+    let overview = |category: &str, text: &str| TextChunk {
+        text: text.to_string(),
+        metadata: DocMetadata {
+            file_path: format!("{category}/overview.md"),
+            category: category.to_string(),
+            topic: "overview".to_string(),
+            title: "概述".to_string(),
+            has_code: false,
+            code_block_count: 0,
+            chunk_id: format!("{category}/overview.md#0"),
+            custom: Default::default(),
+            chunk_index: 0,
+            char_offset: 0,
+            breadcrumb: String::new(),
+        },
+    };
+    let mut chunks = sample_chunks();
+    chunks.push(overview("syntax", "语法概述"));
+    chunks.push(overview("stdlib", "标准库概述"));
+    let (_tmp, server) = build_test_server_with_chunks(chunks).await;
+
+    let output = server
+        .get_topics(Parameters(GetTopicsParams {
+            topics: vec![
+                TopicRequest {
+                    topic: "overview".to_string(),
+                    category: None,
+                },
+                TopicRequest {
+                    topic: "overview".to_string(),
+                    category: Some("syntax".to_string()),
+                },
+            ],
+            version: None,
+            response_format: None,
+        }))
+        .await;
+    let entries: Vec<serde_json::Value> = serde_json::from_str(&output).unwrap();
+    assert_eq!(entries[0]["category"], "stdlib");
+    assert_eq!(
+        entries[0]["other_categories"],
+        serde_json::json!(["syntax"])
+    );
+    assert_eq!(entries[1]["category"], "syntax");
+    assert_eq!(entries[1]["content"], "语法概述");
+    assert!(entries[1].get("other_categories").is_none());
+
+    let ambiguous: serde_json::Value =
+        serde_json::from_str(&server.ambiguous_topics().await).unwrap();
+    assert_eq!(
+        ambiguous,
+        serde_json::json!({"overview": ["stdlib", "syntax"]})
+    );
+}
+
+#[tokio::test]
+async fn test_get_topics_rejects_too_many_topics() {
+    let (_tmp, server) = build_test_server().await;
+    let topics = (0..=cangjie_core::config::MAX_TOPICS_PER_REQUEST)
+        .map(|i| TopicRequest {
+            topic: format!("topic_{i}"),
+            category: None,
+        })
+        .collect();
+
+    let output = server
+        .get_topics(Parameters(GetTopicsParams {
+            topics,
+            version: None,
+            response_format: None,
+        }))
+        .await;
+    assert!(output.contains("Too many topics"), "{output}");
+}
+
 #[tokio::test]
 async fn test_search_docs_not_initialized() {
     let tmp = TempDir::new().unwrap();
@@ -290,10 +1243,8 @@ async fn test_search_docs_not_initialized() {
     let result = server
         .search_docs(Parameters(SearchDocsParams {
             query: "函数".into(),
-            top_k: 5,
-            offset: 0,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -311,9 +1262,8 @@ async fn test_search_docs_max_top_k_clamped() {
         .search_docs(Parameters(SearchDocsParams {
             query: "仓颉".into(),
             top_k: 999,
-            offset: 0,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -327,24 +1277,107 @@ async fn test_search_docs_max_top_k_clamped() {
 }
 
 #[tokio::test]
-async fn test_search_docs_min_top_k_clamped() {
-    let (_tmp, server) = build_test_server().await;
+async fn test_search_docs_zero_top_k_counts_without_reranking() {
+    // Reranker endpoint that only counts the requests it receives.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let rerank_calls = Arc::new(AtomicUsize::new(0));
+    let counter = rerank_calls.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            drop(stream);
+        }
+    });
+
+    let tmp = TempDir::new().unwrap();
+    let mut bm25 = BM25Store::new(tmp.path().join("bm25"));
+    bm25.build_from_chunks(&sample_chunks()).await.unwrap();
+    let mut settings = test_settings(tmp.path().to_path_buf());
+    settings.rerank_type = RerankType::OpenAI;
+    settings.openai_api_key = Some("test-key".to_string());
+    settings.openai_base_url = format!("http://{addr}");
+    let search = LocalSearchIndex::with_bm25(settings.clone(), bm25).await;
+    let server = CangjieServer::with_local_state(settings, search);
 
     let result = server
         .search_docs(Parameters(SearchDocsParams {
             query: "函数".into(),
             top_k: 0,
-            offset: 0,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Json),
+            ..Default::default()
         }))
         .await;
 
-    let result_count = result.matches("### [").count();
-    assert!(
-        result_count == MIN_TOP_K,
-        "result count ({}) should be exactly MIN_TOP_K ({}) when top_k is clamped from 0",
-        result_count,
-        MIN_TOP_K
+    let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+    assert!(json["items"].as_array().unwrap().is_empty());
+    assert_eq!(json["count"], 0);
+    assert_eq!(json["has_more"], false);
+    let total = json["total"].as_u64().unwrap();
+    assert!(total > 0, "count-only search should still count matches");
+    let counted: u64 = json["category_counts"]
+        .as_object()
+        .unwrap()
+        .values()
+        .map(|v| v.as_u64().unwrap())
+        .sum();
+    assert_eq!(counted, total);
+    assert_eq!(rerank_calls.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_diff_versions_lists_added_and_removed_topics() {
+    let tmp = tempfile::tempdir().unwrap();
+    let base = tmp.path().join("docs/dev-guide/source_zh_cn/syntax");
+    std::fs::create_dir_all(&base).unwrap();
+    std::fs::write(base.join("functions.md"), "# Functions").unwrap();
+    std::fs::write(base.join("variables.md"), "# Variables").unwrap();
+    std::fs::write(tmp.path().join("README.md"), "# Docs").unwrap();
+    let git = |args: &[&str]| cangjie_mcp_test::git(tmp.path(), args);
+    git(&["init", "-q"]);
+    git(&["add", "-A"]);
+    git(&["commit", "-q", "-m", "v1"]);
+    git(&["tag", "v1.0.0"]);
+    std::fs::remove_file(base.join("variables.md")).unwrap();
+    std::fs::write(base.join("lambda.md"), "# Lambda").unwrap();
+    git(&["add", "-A"]);
+    git(&["commit", "-q", "-m", "v2"]);
+    git(&["tag", "v2.0.0"]);
+
+    let settings = Settings {
+        data_dir: tmp.path().join("data"),
+        docs_repo_dir_override: Some(tmp.path().to_path_buf()),
+        ..Settings::default()
+    };
+    let server = CangjieServer::new(settings);
+    let diff_of = |from: &str, to: &str| {
+        let server = server.clone();
+        let params = DiffVersionsParams {
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        async move {
+            let output = server.diff_versions(Parameters(params)).await;
+            serde_json::from_str::<serde_json::Value>(&output)
+                .unwrap_or_else(|e| panic!("{e}: {output}"))
+        }
+    };
+
+    let diff = diff_of("v1.0.0", "v2.0.0").await;
+    assert_eq!(diff["added"], serde_json::json!(["syntax/lambda"]));
+    assert_eq!(diff["removed"], serde_json::json!(["syntax/variables"]));
+    assert_eq!(diff["common"], serde_json::json!(["syntax/functions"]));
+    assert!(diff.get("missing_source").is_none());
+
+    // A version without the source directory counts as having no topics.
+    git(&["checkout", "-q", "--orphan", "bare"]);
+    git(&["rm", "-rq", "--cached", "docs"]);
+    git(&["commit", "-q", "-m", "no docs"]);
+    let diff = diff_of("bare", "v2.0.0").await;
+    assert_eq!(diff["missing_source"], serde_json::json!(["bare"]));
+    assert_eq!(
+        diff["added"],
+        serde_json::json!(["syntax/functions", "syntax/lambda"])
     );
+    assert!(diff["removed"].as_array().unwrap().is_empty());
 }
@@ -54,6 +54,10 @@ fn make_result(text: &str, score: f64) -> SearchResult {
             title: "Test".to_string(),
             has_code: false,
             chunk_id: String::new(),
+            custom: Default::default(),
+            chunk_index: 0,
+            char_offset: 0,
+            breadcrumb: String::new(),
         },
     }
 }
@@ -7,6 +7,7 @@ use std::sync::Arc;
 
 use axum::body::Body;
 use axum::http::{Request, StatusCode};
+use cangjie_core::config::OutputFormat;
 use cangjie_indexer::document::chunker::chunk_documents;
 use cangjie_indexer::search::bm25::BM25Store;
 use cangjie_indexer::search::LocalSearchIndex;
@@ -49,6 +50,12 @@ async fn build_http_app(chunks: &[TextChunk]) -> (TempDir, axum::Router) {
         embedding_model: "none".to_string(),
         document_count: chunks.len(),
         search_mode: SearchMode::Bm25,
+        index_code: false,
+        extra_docs: None,
+        chunking: None,
+        source_commit: None,
+        vector_distance: None,
+        tokenizer_version: cangjie_indexer::search::TOKENIZER_VERSION,
     };
     let app = create_http_app(Arc::new(search_index), metadata).await;
     (tmp, app)
@@ -85,10 +92,8 @@ async fn test_search_relevance_error_handling() {
     let result = server
         .search_docs(Parameters(SearchDocsParams {
             query: "错误处理 异常".into(),
-            top_k: 5,
-            offset: 0,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -108,10 +113,8 @@ async fn test_search_mixed_language_query() {
     let result = server
         .search_docs(Parameters(SearchDocsParams {
             query: "HashMap".into(),
-            top_k: 5,
-            offset: 0,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -130,9 +133,9 @@ async fn test_search_category_filter_strict() {
         .search_docs(Parameters(SearchDocsParams {
             query: "仓颉".into(),
             top_k: 20,
-            offset: 0,
             category: Some("cjpm".into()),
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -152,10 +155,8 @@ async fn test_search_no_match_graceful() {
     let result = server
         .search_docs(Parameters(SearchDocsParams {
             query: "量子计算加密区块链".into(),
-            top_k: 5,
-            offset: 0,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -175,9 +176,10 @@ async fn test_search_package_filter_std_collection() {
         .search_docs(Parameters(SearchDocsParams {
             query: "集合 容器".into(),
             top_k: 10,
-            offset: 0,
-            category: None,
             package: Some("std.collection".into()),
+            response_format: Some(OutputFormat::Markdown),
+            explain_package: true,
+            ..Default::default()
         }))
         .await;
 
@@ -186,6 +188,11 @@ async fn test_search_package_filter_std_collection() {
             result.contains("std.collection") || result.contains("collection"),
             "package filter should narrow to collection results"
         );
+        assert_eq!(
+            result.matches("### [").count(),
+            result.matches("Package match: ").count(),
+            "explain_package should report a heuristic for every result"
+        );
     }
 }
 
@@ -199,9 +206,9 @@ async fn test_search_package_filter_excludes_others() {
         .search_docs(Parameters(SearchDocsParams {
             query: "HTTP 网络请求".into(),
             top_k: 10,
-            offset: 0,
-            category: None,
             package: Some("std.fs".into()),
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -228,10 +235,8 @@ async fn test_concurrent_searches() {
         set.spawn(async move {
             s.search_docs(Parameters(SearchDocsParams {
                 query: q,
-                top_k: 5,
-                offset: 0,
-                category: None,
-                package: None,
+                response_format: Some(OutputFormat::Markdown),
+                ..Default::default()
             }))
             .await
         });
@@ -264,10 +269,8 @@ async fn test_search_unicode_edge_cases() {
         let result = server
             .search_docs(Parameters(SearchDocsParams {
                 query: query.into(),
-                top_k: 5,
-                offset: 0,
-                category: None,
-                package: None,
+                response_format: Some(OutputFormat::Markdown),
+                ..Default::default()
             }))
             .await;
 
@@ -286,10 +289,9 @@ async fn test_search_offset_beyond_results() {
     let result = server
         .search_docs(Parameters(SearchDocsParams {
             query: "函数".into(),
-            top_k: 5,
             offset: 1000,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -305,20 +307,17 @@ async fn test_search_empty_category_treated_as_none() {
     let with_empty_cat = server
         .search_docs(Parameters(SearchDocsParams {
             query: "函数".into(),
-            top_k: 5,
-            offset: 0,
             category: Some("".into()),
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
     let without_cat = server
         .search_docs(Parameters(SearchDocsParams {
             query: "函数".into(),
-            top_k: 5,
-            offset: 0,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -396,7 +395,7 @@ async fn test_http_search_category_filter_json() {
 async fn test_large_document_chunking_and_search() {
     let large_doc = large_document();
     let docs = vec![large_doc];
-    let chunks = chunk_documents(docs, Some(500), 100).await;
+    let chunks = chunk_documents(docs, Some(500), 100, true).await;
 
     assert!(
         chunks.len() > 3,
@@ -499,9 +498,8 @@ async fn test_search_deduplication_across_chunks() {
         .search_docs(Parameters(SearchDocsParams {
             query: "函数".into(),
             top_k: 3,
-            offset: 0,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
 
@@ -526,10 +524,8 @@ async fn test_all_tools_uninitialized_error() {
     let search = server
         .search_docs(Parameters(SearchDocsParams {
             query: "test".into(),
-            top_k: 5,
-            offset: 0,
-            category: None,
-            package: None,
+            response_format: Some(OutputFormat::Markdown),
+            ..Default::default()
         }))
         .await;
     assert!(
@@ -15,6 +15,10 @@ fn make_result(text: &str, score: f64, file: &str, category: &str) -> SearchResu
             title: "Test".to_string(),
             has_code: false,
             chunk_id: format!("{}#0", file),
+            custom: Default::default(),
+            chunk_index: 0,
+            char_offset: 0,
+            breadcrumb: String::new(),
         },
     }
 }
@@ -4,6 +4,7 @@ use std::hash::{Hash, Hasher};
 use anyhow::Result;
 use async_trait::async_trait;
 
+use cangjie_core::config::VectorDistance;
 use cangjie_indexer::embedding::{EmbedKind, Embedder};
 use cangjie_indexer::search::vector::VectorStore;
 use cangjie_indexer::{DocMetadata, TextChunk};
@@ -56,6 +57,10 @@ fn make_chunk(text: &str, category: &str, topic: &str) -> TextChunk {
             has_code: false,
             code_block_count: 0,
             chunk_id: String::new(),
+            custom: Default::default(),
+            chunk_index: 0,
+            char_offset: 0,
+            breadcrumb: String::new(),
         },
     }
 }
@@ -108,6 +113,34 @@ async fn test_vector_store_search_basic() {
     }
 }
 
+#[tokio::test]
+async fn test_vector_store_search_with_each_distance() {
+    for distance in [
+        VectorDistance::Cosine,
+        VectorDistance::L2,
+        VectorDistance::Dot,
+    ] {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut vs = VectorStore::open(tmp.path(), DIM)
+            .await
+            .unwrap()
+            .with_distance(distance)
+            .with_min_score(0.0);
+        vs.build_from_chunks(&sample_chunks(), &MockEmbedder, 64)
+            .await
+            .unwrap();
+
+        let query_emb = MockEmbedder::hash_to_vec("泛型允许编写灵活可复用的代码");
+        let results = vs.search(&query_emb, 3, None).await.unwrap();
+        assert_eq!(results.len(), 3, "{distance}");
+        assert_eq!(results[0].metadata.topic, "generics", "{distance}");
+        assert!(
+            results.windows(2).all(|w| w[0].score >= w[1].score),
+            "{distance}: results should be ordered by score"
+        );
+    }
+}
+
 #[tokio::test]
 async fn test_vector_store_search_returns_top_k() {
     let tmp = tempfile::tempdir().unwrap();
@@ -258,3 +291,90 @@ async fn test_vector_store_empty_chunks_error() {
     let result = vs.build_from_chunks(&[], &embedder, 64).await;
     assert!(result.is_err(), "Building from empty chunks should error");
 }
+
+#[tokio::test]
+async fn test_vector_store_batched_insert_row_count() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut vs = VectorStore::open(tmp.path(), DIM)
+        .await
+        .unwrap()
+        .with_insert_batch_size(2);
+    let chunks = sample_chunks();
+    vs.build_from_chunks(&chunks, &MockEmbedder, 64)
+        .await
+        .unwrap();
+    assert_eq!(vs.chunk_count().await.unwrap(), chunks.len());
+}
+
+/// Embedder that returns a vector of the wrong dimension for texts containing
+/// "broken", making the vec0 insert for that row fail.
+struct BrokenEmbedder;
+
+#[async_trait]
+impl Embedder for BrokenEmbedder {
+    async fn embed(&self, texts: &[&str], _kind: EmbedKind) -> Result<Vec<Vec<f32>>> {
+        Ok(texts
+            .iter()
+            .map(|t| {
+                if t.contains("broken") {
+                    vec![0.5; DIM + 1]
+                } else {
+                    MockEmbedder::hash_to_vec(t)
+                }
+            })
+            .collect())
+    }
+    fn model_name(&self) -> &str {
+        "broken"
+    }
+}
+
+#[tokio::test]
+async fn test_vector_store_failed_batch_leaves_no_partial_store() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().to_path_buf();
+    let mut chunks = sample_chunks();
+    chunks.push(make_chunk("broken embedding row", "extra", "broken"));
+
+    let mut vs = VectorStore::open(&path, DIM)
+        .await
+        .unwrap()
+        .with_insert_batch_size(2);
+    let result = vs.build_from_chunks(&chunks, &BrokenEmbedder, 64).await;
+    assert!(result.is_err(), "Wrong-dimension embedding should fail");
+    assert!(!vs.is_ready());
+    assert_eq!(vs.chunk_count().await.unwrap(), 0);
+    drop(vs);
+
+    let reopened = VectorStore::open(&path, DIM).await.unwrap();
+    assert!(!reopened.is_ready(), "Failed build must not look ready");
+
+    // Retrying with a working embedder rebuilds from scratch.
+    let mut vs = reopened.with_insert_batch_size(2);
+    vs.build_from_chunks(&chunks, &MockEmbedder, 64)
+        .await
+        .unwrap();
+    assert!(vs.is_ready());
+    assert_eq!(vs.chunk_count().await.unwrap(), chunks.len());
+}
+
+#[tokio::test]
+async fn test_vector_store_min_score_filters_hits() {
+    let tmp = tempfile::tempdir().unwrap();
+    let chunks = sample_chunks();
+    let mut vs = VectorStore::open(tmp.path(), DIM).await.unwrap();
+    vs.build_from_chunks(&chunks, &MockEmbedder, 64)
+        .await
+        .unwrap();
+    let query_emb = MockEmbedder::hash_to_vec(&chunks[0].text);
+
+    // An exact match has distance 0, i.e. score 1.0.
+    let vs = vs.with_min_score(0.99);
+    let results = vs.search(&query_emb, 5, None).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].text, chunks[0].text);
+
+    let vs = vs.with_min_score(1.1);
+    let results = vs.search(&query_emb, 5, None).await.unwrap();
+    assert!(results.is_empty(), "No hit can exceed a score of 1.0");
+}
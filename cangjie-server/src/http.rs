@@ -1,35 +1,257 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
 
-use axum::extract::State;
-use axum::http::StatusCode;
-use axum::response::Json;
+use anyhow::Context;
+
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
 use axum::routing::{get, post};
 use axum::Router;
 use serde::{Deserialize, Serialize};
 
-use cangjie_indexer::search::LocalSearchIndex;
-use cangjie_indexer::IndexMetadata;
+use cangjie_core::config::{DocLang, MAX_HTTP_SEARCH_TOP_K, MIN_TOP_K};
+use cangjie_indexer::document::{
+    resolve_pinned_topics, PinnedTopic, TopicCatalog, TopicCategoryMap,
+};
+use cangjie_indexer::initializer::resolved_docs_version;
+use cangjie_indexer::search::{
+    is_blank_query, LocalSearchIndex, WarmupReport, EMPTY_QUERY_MESSAGE,
+};
+use cangjie_indexer::{IndexMetadata, ResolvedVersion, SearchResult, MSGPACK_CONTENT_TYPE};
 
 struct AppState {
     search_index: Arc<LocalSearchIndex>,
     index_metadata: IndexMetadata,
+    /// Docs commit behind the served index, resolved on the first `/info`.
+    resolved_version: tokio::sync::OnceCell<Option<ResolvedVersion>>,
+}
+
+impl AppState {
+    fn new(search_index: Arc<LocalSearchIndex>, index_metadata: IndexMetadata) -> Self {
+        Self {
+            search_index,
+            index_metadata,
+            resolved_version: tokio::sync::OnceCell::new(),
+        }
+    }
+
+    async fn resolved_version(&self) -> Option<ResolvedVersion> {
+        self.resolved_version
+            .get_or_init(|| async {
+                resolved_docs_version(self.search_index.settings())
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::warn!("Failed to resolve docs version: {e:#}");
+                        None
+                    })
+            })
+            .await
+            .clone()
+    }
+}
+
+/// The index the HTTP routes serve. Clones share it, so a rebuilt index
+/// swapped in with [`HttpIndex::replace`] (e.g. by `--watch`) is served by
+/// every later request.
+#[derive(Clone)]
+pub struct HttpIndex {
+    current: Arc<RwLock<Arc<AppState>>>,
+}
+
+impl HttpIndex {
+    pub fn new(search_index: Arc<LocalSearchIndex>, index_metadata: IndexMetadata) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(Arc::new(AppState::new(
+                search_index,
+                index_metadata,
+            )))),
+        }
+    }
+
+    /// Serve `search_index` from now on; requests in flight finish on the
+    /// index they started with.
+    pub fn replace(&self, search_index: Arc<LocalSearchIndex>, index_metadata: IndexMetadata) {
+        *self.current.write().expect("lock poisoned") =
+            Arc::new(AppState::new(search_index, index_metadata));
+    }
+
+    fn current(&self) -> Arc<AppState> {
+        self.current.read().expect("lock poisoned").clone()
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct SearchRequest {
     query: String,
-    #[serde(default = "default_top_k")]
-    top_k: usize,
+    /// Bounds the returned count; defaults from settings when omitted.
+    top_k: Option<usize>,
+    category: Option<String>,
+    #[serde(default = "default_rerank")]
+    rerank: bool,
+    /// Must match the index language when given.
+    #[serde(default)]
+    lang: Option<DocLang>,
+}
+
+fn default_rerank() -> bool {
+    true
+}
+
+const SEARCH_REQUEST_SHAPE: &str =
+    "expected application/json body with fields {query, top_k, category, rerank, lang}";
+
+/// `GET /search` query string, the URL form of [`SearchRequest`].
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    q: String,
+    top_k: Option<usize>,
     category: Option<String>,
+    #[serde(default = "default_rerank")]
+    rerank: bool,
+    #[serde(default)]
+    lang: Option<DocLang>,
 }
 
-fn default_top_k() -> usize {
-    cangjie_core::config::DEFAULT_TOP_K
+impl From<SearchQuery> for SearchRequest {
+    fn from(query: SearchQuery) -> Self {
+        Self {
+            query: query.q,
+            top_k: query.top_k,
+            category: query.category,
+            rerank: query.rerank,
+            lang: query.lang,
+        }
+    }
+}
+
+const SEARCH_QUERY_SHAPE: &str = "expected query parameters q, top_k, category, rerank, lang";
+
+#[derive(Debug, Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+type ApiError = (StatusCode, Json<ErrorResponse>);
+
+fn bad_request(error: String) -> ApiError {
+    (StatusCode::BAD_REQUEST, Json(ErrorResponse { error }))
+}
+
+fn internal_error(error: String) -> ApiError {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ErrorResponse { error }),
+    )
+}
+
+impl SearchRequest {
+    /// Check the fields before any index work is done.
+    fn validate(&self) -> Result<(), String> {
+        if is_blank_query(&self.query) {
+            return Err(EMPTY_QUERY_MESSAGE.to_string());
+        }
+        if let Some(top_k) = self.top_k {
+            if !(MIN_TOP_K..=MAX_HTTP_SEARCH_TOP_K).contains(&top_k) {
+                return Err(format!(
+                    "top_k must be between {MIN_TOP_K} and {MAX_HTTP_SEARCH_TOP_K}, got {top_k}"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DocumentRequest {
+    file_path: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopicRequest {
+    topic: String,
+    category: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 struct SearchResponse {
     results: Vec<SearchResultResponse>,
+    /// Language of the index that answered a search.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lang: Option<String>,
+}
+
+impl SearchResponse {
+    fn from_results(results: Vec<SearchResult>) -> Self {
+        Self {
+            results: results
+                .into_iter()
+                .map(|r| SearchResultResponse {
+                    text: r.text,
+                    score: r.score,
+                    metadata: MetadataResponse {
+                        file_path: r.metadata.file_path,
+                        category: r.metadata.category,
+                        topic: r.metadata.topic,
+                        title: r.metadata.title,
+                        has_code: r.metadata.has_code,
+                        chunk_id: r.metadata.chunk_id,
+                        custom: r.metadata.custom,
+                        chunk_index: r.metadata.chunk_index,
+                        char_offset: r.metadata.char_offset,
+                        breadcrumb: r.metadata.breadcrumb,
+                    },
+                })
+                .collect(),
+            lang: None,
+        }
+    }
+}
+
+/// Strong ETag over the chunk content a document/topic response is built from.
+fn content_etag(results: &[SearchResult]) -> String {
+    let mut hasher = DefaultHasher::new();
+    for r in results {
+        r.metadata.chunk_id.hash(&mut hasher);
+        r.metadata.file_path.hash(&mut hasher);
+        r.metadata.category.hash(&mut hasher);
+        r.metadata.topic.hash(&mut hasher);
+        r.metadata.title.hash(&mut hasher);
+        r.text.hash(&mut hasher);
+        let mut custom: Vec<_> = r.metadata.custom.iter().collect();
+        custom.sort();
+        custom.hash(&mut hasher);
+    }
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+/// Whether an `If-None-Match` header lists `etag` (or `*`). Weak validators
+/// compare by their opaque tag, as allowed for `If-None-Match`.
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Serialize `results` with an `ETag`, or answer 304 when the client already
+/// holds that content. The tag is checked before the body is built.
+fn cached_results_response(headers: &HeaderMap, results: Vec<SearchResult>) -> Response {
+    let etag = content_etag(&results);
+    if etag_matches(headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+    (
+        [(header::ETAG, etag)],
+        Json(SearchResponse::from_results(results)),
+    )
+        .into_response()
 }
 
 #[derive(Debug, Serialize)]
@@ -46,6 +268,15 @@ struct MetadataResponse {
     topic: String,
     title: String,
     has_code: bool,
+    /// Stable `file_path#index` id, so clients can dedup across requests.
+    chunk_id: String,
+    chunk_index: usize,
+    char_offset: usize,
+    /// Ancestor headings of the chunk, `A > B > C`.
+    breadcrumb: String,
+    /// Front-matter key/values, so remote clients can apply `metadata_filter`.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    custom: HashMap<String, String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -54,70 +285,252 @@ struct InfoResponse {
     lang: String,
     embedding_model: String,
     document_count: usize,
+    /// Mode actually served, which is BM25 when a hybrid index's vector
+    /// store could not be opened
     search_mode: cangjie_indexer::SearchMode,
+    resolved_version: Option<ResolvedVersion>,
 }
 
 async fn health() -> Json<serde_json::Value> {
     Json(serde_json::json!({"status": "ok"}))
 }
 
-async fn info_handler(State(state): State<Arc<AppState>>) -> Json<InfoResponse> {
+async fn info_handler(State(index): State<HttpIndex>) -> Json<InfoResponse> {
+    let state = index.current();
+    let resolved_version = state.resolved_version().await;
     Json(InfoResponse {
         version: state.index_metadata.version.clone(),
         lang: state.index_metadata.lang.clone(),
         embedding_model: state.index_metadata.embedding_model.clone(),
         document_count: state.index_metadata.document_count,
-        search_mode: state.index_metadata.search_mode,
+        search_mode: state.search_index.search_mode(),
+        resolved_version,
     })
 }
 
+/// Whether the request's `Accept` header asks for MessagePack.
+fn accepts_msgpack(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|v| v.split(';').next().unwrap_or("").trim() == MSGPACK_CONTENT_TYPE)
+}
+
 async fn search_handler(
-    State(state): State<Arc<AppState>>,
-    Json(req): Json<SearchRequest>,
-) -> Result<Json<SearchResponse>, StatusCode> {
-    if req.query.is_empty() {
-        return Err(StatusCode::BAD_REQUEST);
+    State(index): State<HttpIndex>,
+    headers: HeaderMap,
+    payload: Result<Json<SearchRequest>, JsonRejection>,
+) -> Result<Response, ApiError> {
+    let state = index.current();
+    let Json(req) = payload.map_err(|rejection| {
+        bad_request(format!("{SEARCH_REQUEST_SHAPE}: {}", rejection.body_text()))
+    })?;
+    run_search(&state, &headers, req).await
+}
+
+/// `GET /search?q=...`: the same search as `POST /search`, for quick testing
+/// from curl or a browser.
+async fn search_get_handler(
+    State(index): State<HttpIndex>,
+    headers: HeaderMap,
+    query: Result<Query<SearchQuery>, QueryRejection>,
+) -> Result<Response, ApiError> {
+    let state = index.current();
+    let Query(query) = query.map_err(|rejection| {
+        bad_request(format!("{SEARCH_QUERY_SHAPE}: {}", rejection.body_text()))
+    })?;
+    run_search(&state, &headers, query.into()).await
+}
+
+async fn run_search(
+    state: &AppState,
+    headers: &HeaderMap,
+    req: SearchRequest,
+) -> Result<Response, ApiError> {
+    req.validate().map_err(bad_request)?;
+    let indexed_lang = state.search_index.settings().docs_lang;
+    if let Some(lang) = req.lang.filter(|l| *l != indexed_lang) {
+        return Err(bad_request(format!(
+            "this server indexes '{indexed_lang}' documentation, not '{lang}'"
+        )));
     }
 
     let category = req.category.as_deref();
+    let top_k = state
+        .search_index
+        .settings()
+        .effective_top_k(req.top_k, req.rerank);
     let results = state
         .search_index
-        .query(&req.query, req.top_k, category)
+        .query(&req.query, top_k, category, req.rerank)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    let response = SearchResponse {
-        results: results
-            .into_iter()
-            .map(|r| SearchResultResponse {
-                text: r.text,
-                score: r.score,
-                metadata: MetadataResponse {
-                    file_path: r.metadata.file_path,
-                    category: r.metadata.category,
-                    topic: r.metadata.topic,
-                    title: r.metadata.title,
-                    has_code: r.metadata.has_code,
-                },
-            })
-            .collect(),
-    };
+        .map_err(|e| internal_error(format!("search failed: {e}")))?;
+
+    let mut response = SearchResponse::from_results(results);
+    response.lang = Some(indexed_lang.to_string());
+    if accepts_msgpack(headers) {
+        let body = rmp_serde::to_vec_named(&response)
+            .map_err(|e| internal_error(format!("failed to encode MessagePack: {e}")))?;
+        return Ok(([(header::CONTENT_TYPE, MSGPACK_CONTENT_TYPE)], body).into_response());
+    }
+    Ok(Json(response).into_response())
+}
+
+async fn document_handler(
+    State(index): State<HttpIndex>,
+    headers: HeaderMap,
+    Json(req): Json<DocumentRequest>,
+) -> Result<Response, ApiError> {
+    let state = index.current();
+    if req.file_path.is_empty() {
+        return Err(bad_request("file_path must not be empty".to_string()));
+    }
+
+    let results = state
+        .search_index
+        .document_chunks(&req.file_path)
+        .await
+        .map_err(|e| internal_error(format!("document lookup failed: {e}")))?;
+
+    Ok(cached_results_response(&headers, results))
+}
+
+#[derive(Debug, Serialize)]
+struct TopicsResponse {
+    /// `--pinned-topics` that exist in the index, in configured order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pinned: Vec<PinnedTopic>,
+    topics: TopicCategoryMap,
+}
+
+async fn topics_handler(State(index): State<HttpIndex>) -> Result<Json<TopicsResponse>, ApiError> {
+    let state = index.current();
+    let topics = state
+        .search_index
+        .topic_categories()
+        .await
+        .map_err(|e| internal_error(format!("listing topics failed: {e}")))?;
+    let (pinned, _) = resolve_pinned_topics(&state.search_index.settings().pinned_topics, &topics);
+    Ok(Json(TopicsResponse { pinned, topics }))
+}
+
+#[derive(Debug, Serialize)]
+struct CatalogResponse {
+    categories: TopicCatalog,
+}
 
-    Ok(Json(response))
+/// Every category's topics with their titles, so clients need not list
+/// topics and then look each one up.
+async fn catalog_handler(
+    State(index): State<HttpIndex>,
+) -> Result<Json<CatalogResponse>, ApiError> {
+    let state = index.current();
+    let categories = state
+        .search_index
+        .topic_catalog()
+        .await
+        .map_err(|e| internal_error(format!("listing the catalog failed: {e}")))?;
+    Ok(Json(CatalogResponse { categories }))
+}
+
+async fn topic_handler(
+    State(index): State<HttpIndex>,
+    headers: HeaderMap,
+    Json(req): Json<TopicRequest>,
+) -> Result<Response, ApiError> {
+    let state = index.current();
+    if req.topic.is_empty() {
+        return Err(bad_request("topic must not be empty".to_string()));
+    }
+
+    let category = req.category.as_deref().filter(|s| !s.is_empty());
+    let results = state
+        .search_index
+        .topic_chunks(&req.topic, category)
+        .await
+        .map_err(|e| internal_error(format!("topic lookup failed: {e}")))?;
+
+    Ok(cached_results_response(&headers, results))
+}
+
+/// Re-run the warmup probes, e.g. after the index was reloaded.
+async fn warmup_handler(State(index): State<HttpIndex>) -> Json<WarmupReport> {
+    let state = index.current();
+    Json(state.search_index.warm_caches().await)
+}
+
+#[derive(Debug, Serialize)]
+struct ReloadResponse {
+    /// Version now served; `None` when a newer reload superseded this one.
+    version: Option<String>,
+}
+
+/// Rebuild the index and serve it once ready, superseding any rebuild still
+/// running. The current index keeps serving when the rebuild fails.
+async fn reload_handler(
+    State(server): State<crate::CangjieServer>,
+) -> Result<Json<ReloadResponse>, ApiError> {
+    let version = server
+        .reload()
+        .await
+        .map_err(|e| internal_error(format!("reload failed: {e:#}")))?;
+    Ok(Json(ReloadResponse { version }))
+}
+
+/// `POST /reload`, rebuilding through `server`. Kept out of
+/// [`create_http_app`] since a rebuild fetches the docs repositories and
+/// re-embeds every chunk; servers opt in with `--enable-reload`.
+pub fn create_reload_router(server: crate::CangjieServer) -> Router {
+    Router::new()
+        .route("/reload", post(reload_handler))
+        .with_state(server)
+}
+
+/// Bind the HTTP listener. Port 0 lets the OS pick a free port; the bound
+/// address is logged and, with `port_file`, its port is written there.
+pub async fn bind_listener(
+    bind_addr: &str,
+    port_file: Option<&Path>,
+) -> anyhow::Result<tokio::net::TcpListener> {
+    let listener = tokio::net::TcpListener::bind(bind_addr)
+        .await
+        .with_context(|| format!("Failed to bind {bind_addr}"))?;
+    let local_addr = listener.local_addr()?;
+    tracing::info!("HTTP server listening on {local_addr}");
+    if let Some(path) = port_file {
+        tokio::fs::write(path, local_addr.port().to_string())
+            .await
+            .with_context(|| format!("Failed to write port file {}", path.display()))?;
+    }
+    Ok(listener)
 }
 
 pub async fn create_http_app(
     search_index: Arc<LocalSearchIndex>,
     index_metadata: IndexMetadata,
 ) -> Router {
-    let state = Arc::new(AppState {
-        search_index,
-        index_metadata,
-    });
+    create_http_app_for(HttpIndex::new(search_index, index_metadata)).await
+}
+
+/// [`create_http_app`] serving whichever index `index` holds at the time of
+/// each request.
+pub async fn create_http_app_for(index: HttpIndex) -> Router {
+    index
+        .current()
+        .search_index
+        .warn_missing_pinned_topics()
+        .await;
 
     Router::new()
         .route("/health", get(health))
         .route("/info", get(info_handler))
-        .route("/search", post(search_handler))
-        .with_state(state)
+        .route("/search", post(search_handler).get(search_get_handler))
+        .route("/document", post(document_handler))
+        .route("/topic", post(topic_handler))
+        .route("/topics", get(topics_handler))
+        .route("/catalog", get(catalog_handler))
+        .route("/warmup", post(warmup_handler))
+        .with_state(index)
 }
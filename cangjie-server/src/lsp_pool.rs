@@ -92,6 +92,14 @@ impl LspPool {
         Ok(client)
     }
 
+    /// The client serving `workspace_path`, if one has been started. Unlike
+    /// [`Self::get_or_create`] this neither starts a client nor counts as
+    /// activity.
+    pub async fn get(&self, workspace_path: &Path) -> Option<Arc<CangjieClient>> {
+        let entries = self.entries.read().await;
+        entries.get(workspace_path).map(|e| e.client.clone())
+    }
+
     /// Shut down and remove idle entries.
     pub async fn evict_idle(&self) {
         let timeout_secs = self.idle_timeout.as_secs();
@@ -7,8 +7,8 @@ mod response;
 mod types;
 
 pub use types::{
-    LspOperation, LspRequest, LspResponse, LspResponseStatus, LspTarget, ResolvedTarget,
-    META_WORKING_DIRECTORY,
+    LspLogsParams, LspOperation, LspRequest, LspResponse, LspResponseStatus, LspTarget,
+    ResolvedTarget, META_WORKING_DIRECTORY,
 };
 
 use response::error_response;
@@ -32,10 +32,199 @@ use cangjie_lsp::tools as lsp_tools;
 #[cfg(feature = "lsp")]
 impl CangjieServer {
     pub(crate) fn lsp_tool_router() -> ToolRouter<Self> {
-        ToolRouter::<Self>::new().with_route((Self::lsp_tool_attr(), Self::lsp))
+        ToolRouter::<Self>::new()
+            .with_route((Self::lsp_tool_attr(), Self::lsp))
+            .with_route((Self::lsp_capabilities_tool_attr(), Self::lsp_capabilities))
+            .with_route((Self::lsp_logs_tool_attr(), Self::lsp_logs))
     }
 }
 
+/// Serialized [`cangjie_lsp::LspStatus`] for the `cangjie_lsp_status` tool:
+/// the pooled client for `working_dir` in daemon mode, else the global one.
+pub(crate) async fn lsp_status_json(
+    lsp_pool: Option<&crate::lsp_pool::LspPool>,
+    working_dir: Option<std::path::PathBuf>,
+) -> String {
+    #[cfg(feature = "lsp")]
+    let status = match lsp_pool {
+        Some(pool) => {
+            let workspace = match daemon_workspace(working_dir) {
+                Ok(workspace) => workspace,
+                Err(msg) => return msg,
+            };
+            let client = pool.get(&workspace).await;
+            cangjie_lsp::workspace_status(workspace, client.as_deref()).await
+        }
+        None => cangjie_lsp::status().await,
+    };
+    #[cfg(feature = "lsp")]
+    let status = serde_json::to_value(status).unwrap_or_default();
+    #[cfg(not(feature = "lsp"))]
+    let _ = (lsp_pool, working_dir);
+    #[cfg(not(feature = "lsp"))]
+    let status = serde_json::json!({
+        "available": false,
+        "initialized": false,
+        "running": false,
+        "workspace": null,
+        "sdk_path": null,
+        "open_file_count": 0,
+        "last_error": "LSP support is not compiled in. Enable the 'lsp' feature.",
+    });
+    serde_json::to_string_pretty(&status).unwrap_or_default()
+}
+
+/// The workspace named by `working_dir`, which daemon mode requires.
+#[cfg(feature = "lsp")]
+fn daemon_workspace(working_dir: Option<std::path::PathBuf>) -> Result<std::path::PathBuf, String> {
+    let Some(workspace) = working_dir else {
+        return Err(
+            "working directory is required in daemon mode for LSP operations. \
+             Pass it via _meta.workingDirectory in the tool call request."
+                .to_string(),
+        );
+    };
+    Ok(std::path::PathBuf::from(
+        cangjie_lsp::utils::normalize_msys2_path(&workspace.to_string_lossy()),
+    ))
+}
+
+/// The Cangjie project directory: `working_dir` or the current directory,
+/// which must contain a `cjpm.toml`.
+#[cfg(feature = "lsp")]
+fn project_dir(working_dir: Option<std::path::PathBuf>) -> Result<std::path::PathBuf, String> {
+    let workspace = match working_dir {
+        Some(wd) => std::path::PathBuf::from(cangjie_lsp::utils::normalize_msys2_path(
+            &wd.to_string_lossy(),
+        )),
+        None => std::env::current_dir()
+            .map_err(|e| format!("Failed to determine the project directory: {e}"))?,
+    };
+    if !workspace.join(cangjie_lsp::utils::CJPM_TOML).exists() {
+        return Err(format!("No cjpm.toml found in {}", workspace.display()));
+    }
+    Ok(workspace)
+}
+
+/// Module build order of the Cangjie project at `working_dir` (or the current
+/// directory) for the `cangjie_project_build_order` tool.
+pub(crate) fn project_build_order_json(working_dir: Option<std::path::PathBuf>) -> String {
+    #[cfg(feature = "lsp")]
+    {
+        let workspace = match project_dir(working_dir) {
+            Ok(dir) => dir,
+            Err(msg) => return msg,
+        };
+        let modules = cangjie_lsp::dependency::DependencyResolver::new(&workspace).resolve();
+        let order = cangjie_lsp::dependency::build_order(&modules);
+        serde_json::to_string_pretty(&order)
+            .unwrap_or_else(|e| format!("Failed to serialize build order: {e}"))
+    }
+    #[cfg(not(feature = "lsp"))]
+    {
+        let _ = working_dir;
+        "Project analysis is not compiled in. Enable the 'lsp' feature.".to_string()
+    }
+}
+
+/// `cjpm.toml` problems in the Cangjie project at `working_dir` (or the
+/// current directory) for the `cangjie_cjpm_check` tool.
+pub(crate) fn project_cjpm_check_json(working_dir: Option<std::path::PathBuf>) -> String {
+    #[cfg(feature = "lsp")]
+    {
+        let workspace = match project_dir(working_dir) {
+            Ok(dir) => dir,
+            Err(msg) => return msg,
+        };
+        let report = cangjie_lsp::dependency::check_workspace(&workspace);
+        serde_json::to_string_pretty(&report)
+            .unwrap_or_else(|e| format!("Failed to serialize cjpm.toml check: {e}"))
+    }
+    #[cfg(not(feature = "lsp"))]
+    {
+        let _ = working_dir;
+        "Project analysis is not compiled in. Enable the 'lsp' feature.".to_string()
+    }
+}
+
+/// Run `f` on the LSP client serving `working_dir` (daemon mode) or the global
+/// client, or return the message explaining why there is none.
+#[cfg(feature = "lsp")]
+async fn with_lsp_client<T>(
+    lsp_pool: Option<&crate::lsp_pool::LspPool>,
+    working_dir: Option<std::path::PathBuf>,
+    f: impl FnOnce(&CangjieClient) -> T,
+) -> Result<T, String> {
+    if let Some(pool) = lsp_pool {
+        let workspace = daemon_workspace(working_dir)?;
+        let client = pool.get_or_create(&workspace).await?;
+        Ok(f(&client))
+    } else {
+        let guard = cangjie_lsp::get_client().await;
+        match guard.as_ref().and_then(|g| g.as_ref()) {
+            Some(client) => Ok(f(client)),
+            None => Err(lsp_unavailable_message()),
+        }
+    }
+}
+
+/// The LSP server's `initialize` response for the `cangjie_lsp_capabilities`
+/// tool: server info, raw capabilities, and the `cangjie_lsp` operations
+/// those capabilities allow.
+#[cfg(feature = "lsp")]
+pub(crate) async fn lsp_capabilities_json(
+    lsp_pool: Option<&crate::lsp_pool::LspPool>,
+    working_dir: Option<std::path::PathBuf>,
+) -> String {
+    let report = match with_lsp_client(lsp_pool, working_dir, |c| c.server_report()).await {
+        Ok(report) => report,
+        Err(msg) => return msg,
+    };
+    let Some(report) = report else {
+        return "LSP client has not finished initializing".to_string();
+    };
+    capabilities_json(&report)
+}
+
+/// The last `lines` lines of LSP server stderr for the `cangjie_lsp_logs`
+/// tool. Read by workspace rather than through a client, so the output of a
+/// server that crashed or failed to start is still there.
+#[cfg(feature = "lsp")]
+pub(crate) fn lsp_logs_json(
+    lsp_pool: Option<&crate::lsp_pool::LspPool>,
+    working_dir: Option<std::path::PathBuf>,
+    lines: usize,
+) -> String {
+    let workspace = if lsp_pool.is_some() {
+        match daemon_workspace(working_dir) {
+            Ok(workspace) => workspace,
+            Err(msg) => return msg,
+        }
+    } else {
+        std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."))
+    };
+    match cangjie_lsp::recent_logs(&workspace, lines) {
+        Some(lines) => {
+            serde_json::to_string_pretty(&serde_json::json!({ "lines": lines })).unwrap_or_default()
+        }
+        None => format!("No LSP server has been started for {}", workspace.display()),
+    }
+}
+
+#[cfg(feature = "lsp")]
+fn capabilities_json(report: &cangjie_lsp::client::ServerReport) -> String {
+    let supported: Vec<LspOperation> = LspOperation::ALL
+        .into_iter()
+        .filter(|op| report.supports((*op).into()))
+        .collect();
+    let json = serde_json::json!({
+        "server_info": report.server_info,
+        "capabilities": report.capabilities,
+        "supported_operations": supported,
+    });
+    serde_json::to_string_pretty(&json).unwrap_or_default()
+}
+
 pub(crate) async fn execute_lsp_request(
     params: LspRequest,
     #[cfg(feature = "lsp")] lsp_pool: Option<&crate::lsp_pool::LspPool>,
@@ -121,6 +310,19 @@ async fn execute_lsp_request_impl(
     let file_path = params.file_path.as_deref();
     let mut resolved_target = None;
 
+    // Report positions in characters, the unit targets are given in.
+    let encoding = client
+        .server_report()
+        .map(|report| report.position_encoding)
+        .unwrap_or_default();
+    let to_chars = |result: &mut serde_json::Value| {
+        cangjie_lsp::utils::positions_to_chars(
+            result,
+            encoding,
+            file_path.map(std::path::Path::new),
+        )
+    };
+
     let resolved_position =
         if let (Some(file_path), Some(target)) = (file_path, params.target.as_ref()) {
             match resolve_target_position(client, file_path, target).await {
@@ -146,7 +348,8 @@ async fn execute_lsp_request_impl(
                 )
                 .await
             {
-                Ok(result) => {
+                Ok(mut result) => {
+                    to_chars(&mut result);
                     let data = lsp_tools::$processor(&result);
                     response_with_data(
                         params.operation,
@@ -164,7 +367,8 @@ async fn execute_lsp_request_impl(
         (file_only, $client_method:ident, $processor:ident) => {{
             let file = file_path.expect("validated file");
             match client.$client_method(file).await {
-                Ok(result) => {
+                Ok(mut result) => {
+                    to_chars(&mut result);
                     let data = lsp_tools::$processor(&result, file);
                     response_with_data(
                         params.operation,
@@ -178,14 +382,14 @@ async fn execute_lsp_request_impl(
             }
         }};
 
-        // query_only — client.method(query), processor(&result)
+        // query_only — client.method(query), processor(&result, query, max)
         (query_only, $client_method:ident, $processor:ident) => {{
-            match client
-                .$client_method(params.query.as_deref().unwrap_or_default())
-                .await
-            {
-                Ok(result) => {
-                    let data = lsp_tools::$processor(&result);
+            let query = params.query.as_deref().unwrap_or_default();
+            match client.$client_method(query).await {
+                Ok(mut result) => {
+                    to_chars(&mut result);
+                    let data =
+                        lsp_tools::$processor(&result, query, client.max_workspace_symbols());
                     response_with_data(
                         params.operation,
                         status_from_count(data.count),
@@ -200,7 +404,6 @@ async fn execute_lsp_request_impl(
     }
 
     match params.operation {
-        LspOperation::Definition => lsp_op!(positioned, definition, process_definition),
         LspOperation::References => lsp_op!(positioned, references, process_references),
         LspOperation::IncomingCalls => lsp_op!(positioned, incoming_calls, process_incoming_calls),
         LspOperation::OutgoingCalls => lsp_op!(positioned, outgoing_calls, process_outgoing_calls),
@@ -214,6 +417,33 @@ async fn execute_lsp_request_impl(
         }
 
         // Special cases — kept manual
+        LspOperation::Definition => {
+            let position = resolved_position.expect("validated target");
+            match client
+                .definition(
+                    file_path.expect("validated file"),
+                    position.zero_based_line,
+                    position.zero_based_character,
+                )
+                .await
+            {
+                Ok(mut result) => {
+                    to_chars(&mut result);
+                    let mut data = lsp_tools::process_definition(&result);
+                    if params.preview {
+                        lsp_tools::attach_definition_previews(&mut data);
+                    }
+                    response_with_data(
+                        params.operation,
+                        status_from_count(data.count),
+                        resolved_target,
+                        &data,
+                        None,
+                    )
+                }
+                Err(error) => error_response(params.operation, format!("Error: {error}")),
+            }
+        }
         LspOperation::Hover => {
             let position = resolved_position.expect("validated target");
             match client
@@ -224,7 +454,8 @@ async fn execute_lsp_request_impl(
                 )
                 .await
             {
-                Ok(result) => {
+                Ok(mut result) => {
+                    to_chars(&mut result);
                     match lsp_tools::parse_hover(&result, file_path.expect("validated file")) {
                         None => response_with_data(
                             params.operation,
@@ -249,7 +480,10 @@ async fn execute_lsp_request_impl(
             .get_diagnostics(file_path.expect("validated file"))
             .await
         {
-            Ok(result) => {
+            Ok(mut result) => {
+                for diagnostic in &mut result.diagnostics {
+                    to_chars(diagnostic);
+                }
                 let data = lsp_tools::process_diagnostics(&result.diagnostics);
                 let status = match result.status {
                     DiagnosticsStatus::Timeout => LspResponseStatus::Timeout,
@@ -1,9 +1,21 @@
+#[cfg(feature = "lsp")]
+use std::future::Future;
+#[cfg(feature = "lsp")]
+use std::path::Path;
+#[cfg(feature = "lsp")]
+use std::time::Duration;
+
 #[cfg(feature = "lsp")]
 use cangjie_lsp::client::CangjieClient;
 #[cfg(feature = "lsp")]
 use cangjie_lsp::tools as lsp_tools;
 #[cfg(feature = "lsp")]
 use cangjie_lsp::tools::{SymbolOutput, SymbolsResult};
+#[cfg(feature = "lsp")]
+use cangjie_lsp::utils::{positions_to_chars, PositionEncoding};
+
+#[cfg(feature = "lsp")]
+use serde_json::Value;
 
 #[cfg(feature = "lsp")]
 use super::types::{LspOperation, LspRequest, LspTarget, ResolvedPosition, ResolvedTarget};
@@ -105,12 +117,95 @@ fn select_symbol_match(
     ))
 }
 
+/// Wait before asking again for the symbols of a file the server reported
+/// none for, which it does until a fresh workspace has been analyzed.
+#[cfg(feature = "lsp")]
+const EMPTY_SYMBOLS_RETRY_DELAY: Duration = Duration::from_millis(1500);
+
+/// Symbols of `file_path` from `fetch`, with columns converted from
+/// `encoding` to characters, retried once after `retry_delay` when the
+/// server returns none for a file that has content. Still getting
+/// none is reported as the workspace not being analyzed yet rather than as
+/// the symbol being absent.
+#[cfg(feature = "lsp")]
+async fn fetch_document_symbols<F, Fut>(
+    mut fetch: F,
+    file_path: &str,
+    encoding: PositionEncoding,
+    retry_delay: Duration,
+) -> Result<SymbolsResult, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<Value>>,
+{
+    let mut attempts = 0;
+    loop {
+        let mut result = fetch()
+            .await
+            .map_err(|e| format!("Failed to get symbols: {e}"))?;
+        positions_to_chars(&mut result, encoding, Some(Path::new(file_path)));
+        let symbols = lsp_tools::process_symbols(&result, file_path);
+        attempts += 1;
+        if !symbols.symbols.is_empty() || !has_content(file_path) {
+            return Ok(symbols);
+        }
+        if attempts > 1 {
+            return Err(format!(
+                "The LSP server returned no symbols for {file_path}, although the file is not empty. \
+                 The workspace may not be built or analyzed yet: run `cjpm build` in the project root \
+                 or wait for the server to finish initializing, then retry."
+            ));
+        }
+        tokio::time::sleep(retry_delay).await;
+    }
+}
+
+/// Whether `file_path` holds anything besides whitespace.
+#[cfg(feature = "lsp")]
+fn has_content(file_path: &str) -> bool {
+    std::fs::read_to_string(file_path).is_ok_and(|content| !content.trim().is_empty())
+}
+
+/// Text of the 0-based `line` of `file_path`; empty when it cannot be read.
+#[cfg(feature = "lsp")]
+fn line_text(file_path: &str, line: u32) -> String {
+    std::fs::read_to_string(file_path)
+        .ok()
+        .and_then(|content| content.lines().nth(line as usize).map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Position for a 1-based `line`/`character` counted in characters, as tool
+/// callers give it, with the character offset converted to `encoding`.
+#[cfg(feature = "lsp")]
+fn position_from_chars(
+    file_path: &str,
+    line_text: &str,
+    encoding: PositionEncoding,
+    line: u32,
+    character: u32,
+) -> ResolvedPosition {
+    ResolvedPosition {
+        zero_based_line: line - 1,
+        zero_based_character: encoding.units_for_chars(line_text, character - 1),
+        display: ResolvedTarget {
+            file_path: file_path.to_string(),
+            line,
+            character,
+        },
+    }
+}
+
 #[cfg(feature = "lsp")]
 pub(crate) async fn resolve_target_position(
     client: &CangjieClient,
     file_path: &str,
     target: &LspTarget,
 ) -> Result<ResolvedPosition, String> {
+    let encoding = client
+        .server_report()
+        .map(|report| report.position_encoding)
+        .unwrap_or_default();
     match target {
         LspTarget::Position { line, character } => {
             if *line == 0 || *character == 0 {
@@ -119,32 +214,24 @@ pub(crate) async fn resolve_target_position(
                         .to_string(),
                 );
             }
-            Ok(ResolvedPosition {
-                zero_based_line: line - 1,
-                zero_based_character: character - 1,
-                display: ResolvedTarget {
-                    file_path: file_path.to_string(),
-                    line: *line,
-                    character: *character,
-                },
-            })
+            let text = line_text(file_path, line - 1);
+            Ok(position_from_chars(
+                file_path, &text, encoding, *line, *character,
+            ))
         }
         LspTarget::Symbol { symbol, line_hint } => {
-            let result = client
-                .document_symbol(file_path)
-                .await
-                .map_err(|e| format!("Failed to get symbols: {e}"))?;
-            let symbols = lsp_tools::process_symbols(&result, file_path);
+            let symbols = fetch_document_symbols(
+                || client.document_symbol(file_path),
+                file_path,
+                encoding,
+                EMPTY_SYMBOLS_RETRY_DELAY,
+            )
+            .await?;
             let (line, character) = select_symbol_match(&symbols, symbol, *line_hint, file_path)?;
-            Ok(ResolvedPosition {
-                zero_based_line: line - 1,
-                zero_based_character: character - 1,
-                display: ResolvedTarget {
-                    file_path: file_path.to_string(),
-                    line,
-                    character,
-                },
-            })
+            let text = line_text(file_path, line - 1);
+            Ok(position_from_chars(
+                file_path, &text, encoding, line, character,
+            ))
         }
     }
 }
@@ -218,6 +305,7 @@ mod tests {
             file_path: None,
             target: None,
             query: None,
+            preview: false,
         };
         assert!(validate_request(&params).is_err());
     }
@@ -237,6 +325,75 @@ mod tests {
         assert_eq!(result, (16, 5));
     }
 
+    #[cfg(feature = "lsp")]
+    #[test]
+    fn test_positions_account_for_surrogate_pairs() {
+        // `name` is character 14 (0-based) but UTF-16 offset 15, since the
+        // emoji takes two units.
+        let text = "let s = \"😀\" + name";
+        let chars_column = text.chars().position(|c| c == 'n').unwrap() as u32 + 1;
+
+        let from_caller =
+            position_from_chars("/tmp/a.cj", text, PositionEncoding::Utf16, 3, chars_column);
+        assert_eq!(from_caller.zero_based_line, 2);
+        assert_eq!(from_caller.zero_based_character, chars_column);
+        assert_eq!(from_caller.display.character, chars_column);
+
+        let utf32 =
+            position_from_chars("/tmp/a.cj", text, PositionEncoding::Utf32, 3, chars_column);
+        assert_eq!(utf32.zero_based_character, chars_column - 1);
+    }
+
+    #[cfg(feature = "lsp")]
+    #[tokio::test]
+    async fn test_empty_symbols_are_retried_then_reported_as_not_built() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("main.cj");
+        std::fs::write(&file, "main() {\n    println(\"hi\")\n}\n").unwrap();
+        let file_path = file.to_str().unwrap();
+        let symbols = serde_json::json!([{
+            "name": "main",
+            "kind": 12,
+            "range": {"start": {"line": 0, "character": 0}, "end": {"line": 2, "character": 1}},
+            "selectionRange": {"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": 4}}
+        }]);
+
+        // Empty on the first request only: the retry finds the symbols.
+        let calls = std::cell::Cell::new(0);
+        let found = fetch_document_symbols(
+            || {
+                calls.set(calls.get() + 1);
+                let value = if calls.get() == 1 {
+                    serde_json::json!([])
+                } else {
+                    symbols.clone()
+                };
+                async move { Ok(value) }
+            },
+            file_path,
+            PositionEncoding::Utf16,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap();
+        assert_eq!(calls.get(), 2);
+        let absent = select_symbol_match(&found, "helper", None, file_path).unwrap_err();
+        assert!(absent.contains("Symbol 'helper' not found"), "{absent}");
+        assert!(!absent.contains("cjpm build"));
+
+        // Empty every time: reported as an unbuilt workspace.
+        let not_built = fetch_document_symbols(
+            || async { Ok(serde_json::json!([])) },
+            file_path,
+            PositionEncoding::Utf16,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap_err();
+        assert!(not_built.contains("cjpm build"), "{not_built}");
+        assert!(!not_built.contains("not found"));
+    }
+
     #[cfg(feature = "lsp")]
     #[test]
     fn test_select_symbol_match_requires_disambiguation() {
@@ -42,6 +42,19 @@ pub enum LspOperation {
 }
 
 impl LspOperation {
+    pub(crate) const ALL: [LspOperation; 10] = [
+        Self::Definition,
+        Self::References,
+        Self::Hover,
+        Self::DocumentSymbol,
+        Self::Diagnostics,
+        Self::WorkspaceSymbol,
+        Self::IncomingCalls,
+        Self::OutgoingCalls,
+        Self::TypeSupertypes,
+        Self::TypeSubtypes,
+    ];
+
     #[cfg(feature = "lsp")]
     pub(crate) fn requires_file_path(self) -> bool {
         !matches!(self, Self::WorkspaceSymbol)
@@ -85,6 +98,23 @@ pub struct LspRequest {
     pub target: Option<LspTarget>,
     #[serde(default)]
     pub query: Option<String>,
+    /// For `definition`: attach the source lines around each resolved location.
+    #[serde(default)]
+    pub preview: bool,
+}
+
+/// Default number of lines returned by `cangjie_lsp_logs`.
+pub const DEFAULT_LSP_LOG_LINES: usize = 100;
+
+#[derive(Debug, Clone, Deserialize, Serialize, schemars::JsonSchema)]
+pub struct LspLogsParams {
+    /// How many of the most recent LSP server stderr lines to return.
+    #[serde(default = "default_log_lines")]
+    pub lines: usize,
+}
+
+fn default_log_lines() -> usize {
+    DEFAULT_LSP_LOG_LINES
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
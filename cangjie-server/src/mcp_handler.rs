@@ -1,4 +1,5 @@
-use std::sync::Arc;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, OnceLock};
 
 #[cfg(feature = "lsp")]
 use crate::lsp_pool::LspPool;
@@ -6,24 +7,54 @@ use anyhow::Result;
 use rmcp::handler::server::router::tool::ToolRouter;
 use rmcp::handler::server::wrapper::Parameters;
 use rmcp::model::*;
-use rmcp::{tool, tool_handler, tool_router, ServerHandler};
-use tokio::sync::RwLock;
-use tracing::info;
-#[cfg(feature = "lsp")]
-use tracing::warn;
+use rmcp::service::RequestContext;
+use rmcp::{tool, tool_handler, tool_router, RoleServer, ServerHandler};
+use tokio::sync::{RwLock, Semaphore, SemaphorePermit};
+use tracing::{info, warn};
 
-use cangjie_core::config::{Settings, MAX_TOP_K, MIN_TOP_K, PACKAGE_FETCH_MULTIPLIER};
+use cangjie_core::config::{
+    DocLang, IndexInfo, OutputFormat, RerankType, Settings, MAX_TOPICS_PER_REQUEST, MAX_TOP_K,
+    MIN_TOP_K,
+};
+use cangjie_core::error::CangjieError;
 use cangjie_core::prompts::get_prompt;
 use cangjie_indexer::document::chunker::strip_chunk_artifacts;
-use cangjie_indexer::search::{LocalSearchIndex, RemoteSearchIndex};
-use cangjie_indexer::SearchResult;
+use cangjie_indexer::document::loader::custom_fields_match;
+use cangjie_indexer::document::{ambiguous_topics, TopicCatalog, TopicCategoryMap};
+use cangjie_indexer::initializer::{
+    diff_docs_versions, resolved_docs_version, IndexRefresher, RebuildCoordinator, RefreshOutcome,
+};
+use cangjie_indexer::search::{
+    explain_query, is_blank_query, LocalSearchIndex, RelatedTopic, RemoteSearchIndex,
+    EMPTY_QUERY_MESSAGE,
+};
+use cangjie_indexer::{ResolvedVersion, SearchResult};
 
+mod prompts;
+mod provenance;
 mod ranking;
+mod resources;
 mod results;
 
-pub use results::{DocsSearchResult, SearchDocsParams, SearchResultItem};
+pub use prompts::{prompt_templates, PROMPT_EXPLAIN_ERROR, PROMPT_REVIEW_FILE, PROMPT_USE_MODULE};
+pub use resources::{parse_topic_resource_uri, topic_resource_uri};
+
+pub use results::{
+    DiffVersionsParams, DocsSearchResult, GetTopicsParams, QueryTermsDebug, RelatedTopicsParams,
+    SearchDocsParams, SearchGranularity, SearchOrder, SearchResultItem, TopicExistence, TopicGroup,
+    TopicLookup, TopicRequest, TopicResult, TopicSnippet,
+};
+
+use provenance::source_url;
+use results::{format_results_markdown, format_topics_markdown, render_response};
+
+/// Key in the `_meta` of a client's `initialize` request listing the only
+/// categories (a JSON array of names) its session may search and read.
+pub const META_ALLOWED_CATEGORIES: &str = "allowedCategories";
 
-use results::format_results_markdown;
+/// Reported instead of an empty result when the local index has no stores.
+const INDEX_UNAVAILABLE_MESSAGE: &str =
+    "search index is not available — the index may still be building or failed to load";
 
 #[derive(Clone)]
 enum SearchBackend {
@@ -31,6 +62,173 @@ enum SearchBackend {
     Remote(Arc<RemoteSearchIndex>),
 }
 
+/// Fail when `lang` asks for documentation other than the local index holds.
+fn check_local_lang(local: &LocalSearchIndex, lang: Option<DocLang>) -> Result<()> {
+    let indexed = local.settings().docs_lang;
+    if let Some(lang) = lang.filter(|l| *l != indexed) {
+        anyhow::bail!(
+            "The local index holds '{indexed}' documentation; '{lang}' requires a remote server"
+        );
+    }
+    Ok(())
+}
+
+impl SearchBackend {
+    /// Docs commit the serving index was built from, for source links.
+    async fn source_ref(&self) -> Option<String> {
+        match self {
+            SearchBackend::Local(local) => local.source_commit().map(str::to_string),
+            SearchBackend::Remote(remote) => match remote.resolved_version().await {
+                Ok(resolved) => resolved.map(|v| v.commit_oid),
+                Err(e) => {
+                    warn!("Failed to ask the remote server for its docs commit: {e:#}");
+                    None
+                }
+            },
+        }
+    }
+
+    /// Look up a topic by name; `None` when no document has that topic.
+    async fn topic(&self, topic: &str, category: Option<&str>) -> Result<Option<TopicResult>> {
+        let chunks = match self {
+            SearchBackend::Local(local) => local.topic_chunks(topic, category).await?,
+            SearchBackend::Remote(remote) => remote.topic_chunks(topic, category).await?,
+        };
+        // Chunks arrive grouped by file; take the first matching document.
+        let Some(first) = chunks.first() else {
+            return Ok(None);
+        };
+        let file_path = first.metadata.file_path.clone();
+        let doc_chunks: Vec<SearchResult> = chunks
+            .iter()
+            .filter(|c| c.metadata.file_path == file_path)
+            .cloned()
+            .collect();
+        let mut other_categories: Vec<String> = chunks
+            .iter()
+            .map(|c| &c.metadata.category)
+            .filter(|c| **c != first.metadata.category)
+            .cloned()
+            .collect();
+        other_categories.sort();
+        other_categories.dedup();
+        if !other_categories.is_empty() {
+            warn!(
+                "Topic '{topic}' is ambiguous: using category '{}', also found in {}",
+                first.metadata.category,
+                other_categories.join(", ")
+            );
+        }
+        Ok(Some(TopicResult {
+            topic: first.metadata.topic.clone(),
+            category: first.metadata.category.clone(),
+            title: first.metadata.title.clone(),
+            file_path,
+            content: join_chunk_text(&doc_chunks),
+            other_categories,
+            source_url: None,
+        }))
+    }
+
+    async fn topic_categories(&self) -> Result<TopicCategoryMap> {
+        match self {
+            SearchBackend::Local(local) => local.topic_categories().await,
+            SearchBackend::Remote(remote) => remote.topic_categories().await,
+        }
+    }
+
+    async fn topic_catalog(&self) -> Result<TopicCatalog> {
+        match self {
+            SearchBackend::Local(local) => local.topic_catalog().await,
+            SearchBackend::Remote(remote) => remote.topic_catalog().await,
+        }
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        top_k: usize,
+        category: Option<&str>,
+        rerank: bool,
+        lang: Option<DocLang>,
+    ) -> Result<Vec<SearchResult>> {
+        if is_blank_query(query) {
+            anyhow::bail!(EMPTY_QUERY_MESSAGE);
+        }
+        match self {
+            SearchBackend::Local(local) => {
+                check_local_lang(local, lang)?;
+                local.query(query, top_k, category, rerank).await
+            }
+            SearchBackend::Remote(remote) => {
+                remote.query(query, top_k, category, rerank, lang).await
+            }
+        }
+    }
+
+    async fn related_topics(
+        &self,
+        topic: &str,
+        category: Option<&str>,
+        allowed: Option<&BTreeSet<String>>,
+        k: usize,
+    ) -> Result<Option<Vec<RelatedTopic>>> {
+        match self {
+            SearchBackend::Local(local) => local.related_topics(topic, category, allowed, k).await,
+            SearchBackend::Remote(_) => {
+                anyhow::bail!("Related topics are not supported when using a remote server")
+            }
+        }
+    }
+
+    async fn code_search(
+        &self,
+        query: &str,
+        top_k: usize,
+        category: Option<&str>,
+        lang: Option<DocLang>,
+    ) -> Result<Vec<SearchResult>> {
+        match self {
+            SearchBackend::Local(local) => {
+                check_local_lang(local, lang)?;
+                local.query_code(query, top_k, category).await
+            }
+            SearchBackend::Remote(_) => {
+                anyhow::bail!("Code search is not supported when using a remote server")
+            }
+        }
+    }
+
+    async fn document_chunks(&self, file_path: &str) -> Result<Vec<SearchResult>> {
+        match self {
+            SearchBackend::Local(local) => local.document_chunks(file_path).await,
+            SearchBackend::Remote(remote) => remote.document_chunks(file_path).await,
+        }
+    }
+
+    /// Reassemble a document from its chunks, falling back to `fallback` when
+    /// the chunks cannot be fetched.
+    async fn document_content(&self, file_path: &str, fallback: &str) -> String {
+        match self.document_chunks(file_path).await {
+            Ok(chunks) if !chunks.is_empty() => join_chunk_text(&chunks),
+            Ok(_) => strip_chunk_artifacts(fallback).to_string(),
+            Err(e) => {
+                warn!("Failed to load document {file_path}: {e:#}");
+                strip_chunk_artifacts(fallback).to_string()
+            }
+        }
+    }
+}
+
+/// Join chunk texts back into document text, dropping chunk-only artifacts.
+fn join_chunk_text(chunks: &[SearchResult]) -> String {
+    chunks
+        .iter()
+        .map(|c| strip_chunk_artifacts(&c.text))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
 struct InnerState {
     search: SearchBackend,
 }
@@ -38,10 +236,26 @@ struct InnerState {
 #[derive(Clone)]
 pub struct CangjieServer {
     state: Arc<RwLock<Option<InnerState>>>,
+    /// Why initialization failed (e.g. the index build timed out), reported
+    /// by tools instead of a bare "not initialized".
+    init_error: Arc<RwLock<Option<String>>>,
+    /// Index builds from [`Self::initialize`], `--watch` and [`Self::reload`],
+    /// shared by every clone so a newer one supersedes the one in flight.
+    rebuilds: RebuildCoordinator,
     settings: Settings,
+    /// Slots for search and LSP tool calls, created once per constructor and
+    /// shared by every clone, so every MCP session of a server draws on one
+    /// `max_concurrent_tools` limit.
+    tool_permits: Arc<Semaphore>,
+    /// Categories this session is limited to; unset allows all. Not shared:
+    /// each session works on its own clone of the server.
+    allowed_categories: OnceLock<BTreeSet<String>>,
     tool_router: ToolRouter<Self>,
     #[cfg(feature = "lsp")]
     lsp_pool: Option<Arc<LspPool>>,
+    /// HTTP routes kept on the same index when `--watch` swaps in a new one.
+    #[cfg(feature = "http")]
+    http_index: Option<crate::http::HttpIndex>,
 }
 
 impl CangjieServer {
@@ -86,29 +300,88 @@ impl CangjieServer {
     }
 
     fn docs_tool_router() -> ToolRouter<Self> {
-        ToolRouter::<Self>::new().with_route((Self::search_docs_tool_attr(), Self::search_docs))
+        ToolRouter::<Self>::new()
+            .with_route((Self::search_docs_tool_attr(), Self::search_docs))
+            .with_route((Self::get_topics_tool_attr(), Self::get_topics))
+            .with_route((Self::topic_exists_tool_attr(), Self::topic_exists))
+            .with_route((Self::ambiguous_topics_tool_attr(), Self::ambiguous_topics))
+            .with_route((Self::related_topics_tool_attr(), Self::related_topics))
+            .with_route((Self::resolved_version_tool_attr(), Self::resolved_version))
+            .with_route((Self::diff_versions_tool_attr(), Self::diff_versions))
     }
 
-    fn build_tool_router() -> ToolRouter<Self> {
-        let router = Self::docs_tool_router();
+    /// Every tool this build can serve, whatever the settings or LSP availability.
+    fn known_tool_names() -> Vec<String> {
+        let mut names: Vec<String> = Self::tool_router()
+            .list_all()
+            .iter()
+            .map(|t| t.name.to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Reject `enabled_tools`/`disabled_tools` entries that name no tool.
+    pub fn validate_tool_filter(settings: &Settings) -> Result<()> {
+        let known = Self::known_tool_names();
+        let unknown: Vec<&str> = settings
+            .enabled_tools
+            .iter()
+            .chain(&settings.disabled_tools)
+            .map(String::as_str)
+            .filter(|name| !known.iter().any(|k| k == name))
+            .collect();
+        if !unknown.is_empty() {
+            anyhow::bail!(
+                "Unknown tool name(s): {}. Known tools: {}",
+                unknown.join(", "),
+                known.join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    fn build_tool_router(settings: &Settings) -> ToolRouter<Self> {
+        let router =
+            Self::docs_tool_router().with_route((Self::lsp_status_tool_attr(), Self::lsp_status));
         #[cfg(feature = "lsp")]
         let router = {
-            let mut router = router;
+            let mut router = router
+                .with_route((
+                    Self::project_build_order_tool_attr(),
+                    Self::project_build_order,
+                ))
+                .with_route((Self::cjpm_check_tool_attr(), Self::cjpm_check));
             if cangjie_lsp::is_available() {
                 router.merge(Self::lsp_tool_router());
             }
             router
         };
+        let mut router = router;
+        for tool in router.list_all() {
+            let name = tool.name.as_ref();
+            let enabled = settings.enabled_tools.is_empty()
+                || settings.enabled_tools.iter().any(|t| t == name);
+            if !enabled || settings.disabled_tools.iter().any(|t| t == name) {
+                router.remove_route(name);
+            }
+        }
         router
     }
 
     pub fn new(settings: Settings) -> Self {
         Self {
             state: Arc::new(RwLock::new(None)),
+            tool_router: Self::build_tool_router(&settings),
+            tool_permits: Self::tool_permits(&settings),
+            allowed_categories: OnceLock::new(),
+            init_error: Arc::default(),
+            rebuilds: RebuildCoordinator::default(),
             settings,
-            tool_router: Self::build_tool_router(),
             #[cfg(feature = "lsp")]
             lsp_pool: None,
+            #[cfg(feature = "http")]
+            http_index: None,
         }
     }
 
@@ -117,9 +390,33 @@ impl CangjieServer {
     pub fn with_lsp_pool(settings: Settings, idle_timeout: std::time::Duration) -> Self {
         Self {
             state: Arc::new(RwLock::new(None)),
+            tool_router: Self::build_tool_router(&settings),
+            tool_permits: Self::tool_permits(&settings),
+            allowed_categories: OnceLock::new(),
+            init_error: Arc::default(),
+            rebuilds: RebuildCoordinator::default(),
             settings,
-            tool_router: Self::build_tool_router(),
             lsp_pool: Some(Arc::new(LspPool::new(idle_timeout))),
+            #[cfg(feature = "http")]
+            http_index: None,
+        }
+    }
+
+    /// Whether this server serves the `cangjie_lsp` tool: LSP is available
+    /// and the tool was not filtered out by `enabled_tools`/`disabled_tools`.
+    fn lsp_tool_enabled(&self) -> bool {
+        #[cfg(feature = "lsp")]
+        {
+            cangjie_lsp::is_available()
+                && self
+                    .tool_router
+                    .list_all()
+                    .iter()
+                    .any(|t| t.name == "cangjie_lsp")
+        }
+        #[cfg(not(feature = "lsp"))]
+        {
+            false
         }
     }
 
@@ -129,6 +426,52 @@ impl CangjieServer {
         self.lsp_pool.as_ref()
     }
 
+    fn tool_permits(settings: &Settings) -> Arc<Semaphore> {
+        Arc::new(Semaphore::new(settings.max_concurrent_tools.max(1)))
+    }
+
+    /// Wait for one of the `max_concurrent_tools` slots; held for the
+    /// duration of an expensive tool call.
+    async fn acquire_tool_permit(&self) -> SemaphorePermit<'_> {
+        self.tool_permits
+            .acquire()
+            .await
+            .expect("tool semaphore is never closed")
+    }
+
+    /// Limit this server's session to `categories` for search, topic lookups
+    /// and resources. Has no effect once a limit is set.
+    pub fn with_allowed_categories<I, S>(self, categories: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let _ = self
+            .allowed_categories
+            .set(categories.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Whether this session may see documents in `category`.
+    fn category_allowed(&self, category: &str) -> bool {
+        self.allowed_categories
+            .get()
+            .is_none_or(|allowed| allowed.contains(category))
+    }
+
+    /// Topic → categories map of `backend`, restricted to the session's
+    /// allowed categories.
+    async fn scoped_topic_categories(&self, backend: &SearchBackend) -> Result<TopicCategoryMap> {
+        let mut map = backend.topic_categories().await?;
+        if let Some(allowed) = self.allowed_categories.get() {
+            map.retain(|_, categories| {
+                categories.retain(|c| allowed.contains(c));
+                !categories.is_empty()
+            });
+        }
+        Ok(map)
+    }
+
     /// Create a `CangjieServer` with pre-initialized shared state.
     pub fn with_shared_state(settings: Settings, search: Arc<LocalSearchIndex>) -> Self {
         let inner = InnerState {
@@ -136,13 +479,27 @@ impl CangjieServer {
         };
         Self {
             state: Arc::new(RwLock::new(Some(inner))),
+            tool_router: Self::build_tool_router(&settings),
+            tool_permits: Self::tool_permits(&settings),
+            allowed_categories: OnceLock::new(),
+            init_error: Arc::default(),
+            rebuilds: RebuildCoordinator::default(),
             settings,
-            tool_router: Self::build_tool_router(),
             #[cfg(feature = "lsp")]
             lsp_pool: None,
+            #[cfg(feature = "http")]
+            http_index: None,
         }
     }
 
+    /// Keep `http_index` serving the same index as this server when
+    /// `--watch` swaps in a rebuilt one.
+    #[cfg(feature = "http")]
+    pub fn with_http_index(mut self, http_index: crate::http::HttpIndex) -> Self {
+        self.http_index = Some(http_index);
+        self
+    }
+
     /// Create a `CangjieServer` with pre-initialized state (for testing).
     #[doc(hidden)]
     pub fn with_local_state(settings: Settings, search: LocalSearchIndex) -> Self {
@@ -151,6 +508,14 @@ impl CangjieServer {
 
     /// Initialize the server (clone repo, build index, etc.)
     pub async fn initialize(&self) -> Result<()> {
+        let result = self.initialize_state().await;
+        if let Err(ref e) = result {
+            *self.init_error.write().await = Some(format!("{e:#}"));
+        }
+        result
+    }
+
+    async fn initialize_state(&self) -> Result<()> {
         let settings = self.settings.clone();
         info!("Initializing index...");
 
@@ -175,42 +540,185 @@ impl CangjieServer {
             }
         }
 
-        let (search, index_info) = if let Some(ref url) = settings.server_url {
+        let (search, index_info, loaded) = if let Some(ref url) = settings.server_url {
             let remote = RemoteSearchIndex::new(&settings, url)?;
             let info = remote.init().await?;
-            (SearchBackend::Remote(Arc::new(remote)), info)
+            (SearchBackend::Remote(Arc::new(remote)), info, None)
         } else {
             let mut local = LocalSearchIndex::new(settings.clone()).await;
-            let info = local.init().await?;
-            (SearchBackend::Local(Arc::new(local)), info)
+            let Some(info) = local.init_with(&self.rebuilds).await? else {
+                info!("Initial index build superseded by a reload, which serves its own index");
+                self.start_watch();
+                return Ok(());
+            };
+            local.warmup(&info).await;
+            local.warn_missing_pinned_topics().await;
+            let loaded = Some(local.search_mode());
+            (SearchBackend::Local(Arc::new(local)), info, loaded)
         };
 
-        cangjie_core::config::log_startup_info(&settings, &index_info);
+        cangjie_core::config::log_startup_info(&settings, &index_info, loaded);
 
         let inner = InnerState { search };
         *self.state.write().await = Some(inner);
         info!("Initialization complete — tools are ready.");
+
+        self.start_watch();
         Ok(())
     }
 
-    async fn do_search(
-        &self,
-        query: &str,
-        top_k: usize,
-        category: Option<&str>,
-    ) -> Result<Vec<SearchResult>> {
-        let search = {
-            let state = self.state.read().await;
-            let inner = state
-                .as_ref()
-                .ok_or_else(|| anyhow::anyhow!("Server not initialized"))?;
-            inner.search.clone()
+    /// Start the `--watch` refresh loop when it applies: a local index built
+    /// from a docs checkout (not remote, prebuilt or `--index-dir`).
+    pub fn start_watch(&self) {
+        let settings = &self.settings;
+        if settings.watch
+            && settings.server_url.is_none()
+            && !settings.prebuilt.is_prebuilt()
+            && settings.index_dir_override.is_none()
+        {
+            let server = self.clone();
+            tokio::spawn(async move { server.refresh_loop().await });
+        }
+    }
+
+    /// `--watch` mode: periodically fetch the docs version and swap in the
+    /// index for its new commit. Failures are logged and the current index
+    /// keeps serving.
+    async fn refresh_loop(&self) {
+        let interval =
+            std::time::Duration::from_secs(self.settings.refresh_interval_mins.max(1) * 60);
+        let mut refresher = match IndexRefresher::new(self.settings.clone()).await {
+            Ok(refresher) => refresher.with_coordinator(self.rebuilds.clone()),
+            Err(e) => {
+                warn!("Docs refresh disabled: {e:#}");
+                return;
+            }
         };
+        info!(
+            "Watching docs version {} every {} min",
+            self.settings.docs_version,
+            interval.as_secs() / 60
+        );
+        loop {
+            tokio::time::sleep(interval).await;
+            let index_info = match refresher.refresh().await {
+                Ok(RefreshOutcome::Unchanged) => {
+                    info!("Docs refresh: no change");
+                    continue;
+                }
+                Ok(RefreshOutcome::Rebuilt(index_info)) => index_info,
+                Ok(RefreshOutcome::Superseded) => {
+                    info!("Docs refresh: superseded by a newer rebuild");
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Docs refresh failed, keeping the current index: {e:#}");
+                    continue;
+                }
+            };
+            match self.swap_in_index(&index_info).await {
+                Ok(()) => info!("Docs refresh: rebuilt index for {}", index_info.version),
+                Err(e) => warn!(
+                    "Docs refresh built {} but could not load it, keeping the current index: {e:#}",
+                    index_info.version
+                ),
+            }
+        }
+    }
 
-        match search {
-            SearchBackend::Local(local) => local.query(query, top_k, category).await,
-            SearchBackend::Remote(remote) => remote.query(query, top_k, category).await,
+    /// Rebuild the local index now and serve it once ready, cancelling any
+    /// rebuild (including a `--watch` one) still running. Returns the version
+    /// now served, or `None` when a newer rebuild superseded this one; the
+    /// current index keeps serving on failure. Backs `POST /reload`.
+    pub async fn reload(&self) -> Result<Option<String>> {
+        if self.settings.server_url.is_some() {
+            anyhow::bail!("Reloading is not supported when using a remote server");
+        }
+        if self.settings.prebuilt.is_prebuilt() || self.settings.index_dir_override.is_some() {
+            anyhow::bail!("Reloading is not supported for a prebuilt index or --index-dir");
         }
+        let Some(index_info) = self.rebuilds.rebuild(&self.settings).await? else {
+            return Ok(None);
+        };
+        self.swap_in_index(&index_info).await?;
+        info!("Reload: serving index for {}", index_info.version);
+        Ok(Some(index_info.version))
+    }
+
+    /// Open and warm the index `index_info` names and make it the one tools use.
+    async fn swap_in_index(&self, index_info: &IndexInfo) -> Result<()> {
+        let mut local = LocalSearchIndex::open(self.settings.clone(), index_info).await?;
+        local.warmup(index_info).await;
+        let local = Arc::new(local);
+        #[cfg(feature = "http")]
+        if let Some(http_index) = &self.http_index {
+            let metadata_path = index_info.index_dir().join("index_metadata.json");
+            let metadata = serde_json::from_str(&tokio::fs::read_to_string(&metadata_path).await?)?;
+            http_index.replace(local.clone(), metadata);
+        }
+        *self.state.write().await = Some(InnerState {
+            search: SearchBackend::Local(local),
+        });
+        Ok(())
+    }
+
+    async fn search_backend(&self) -> Result<SearchBackend> {
+        if let Some(inner) = self.state.read().await.as_ref() {
+            return Ok(inner.search.clone());
+        }
+        match self.init_error.read().await.as_deref() {
+            Some(e) => anyhow::bail!("Server failed to initialize: {e}"),
+            None => anyhow::bail!("Server not initialized"),
+        }
+    }
+
+    /// The backend answering for `version`: the initialized index when no
+    /// version is given, otherwise that version's index from the same data dir.
+    async fn search_backend_for(&self, version: Option<&str>) -> Result<SearchBackend> {
+        let backend = self.search_backend().await?;
+        let Some(version) = version.filter(|v| !v.is_empty()) else {
+            return Ok(backend);
+        };
+        match backend {
+            SearchBackend::Local(local) => match local.version_index(version).await? {
+                Some(index) => Ok(SearchBackend::Local(index)),
+                None => {
+                    let available = local.indexed_versions().await.unwrap_or_default();
+                    let available = if available.is_empty() {
+                        "none".to_string()
+                    } else {
+                        available.join(", ")
+                    };
+                    anyhow::bail!(
+                        "Version '{version}' is not indexed. Indexed versions: {available}"
+                    )
+                }
+            },
+            SearchBackend::Remote(_) => {
+                anyhow::bail!(
+                    "Querying a specific version is not supported when using a remote server"
+                )
+            }
+        }
+    }
+
+    async fn do_resolved_version(&self) -> Result<Option<ResolvedVersion>> {
+        let remote = match self.state.read().await.as_ref().map(|s| s.search.clone()) {
+            Some(SearchBackend::Remote(remote)) => Some(remote),
+            _ => None,
+        };
+        match remote {
+            Some(remote) => remote.resolved_version().await,
+            None => resolved_docs_version(&self.settings).await,
+        }
+    }
+
+    /// Format a tool renders with: the request's, else `--output-format`,
+    /// else JSON.
+    fn response_format(&self, requested: Option<OutputFormat>) -> OutputFormat {
+        requested
+            .or(self.settings.output_format)
+            .unwrap_or_default()
     }
 }
 
@@ -235,6 +743,7 @@ impl CangjieServer {
             .get(crate::lsp_tools::META_WORKING_DIRECTORY)
             .and_then(|v| v.as_str())
             .map(std::path::PathBuf::from);
+        let _permit = self.acquire_tool_permit().await;
 
         #[cfg(feature = "lsp")]
         {
@@ -248,9 +757,119 @@ impl CangjieServer {
         }
     }
 
+    #[tool(
+        name = "cangjie_lsp_capabilities",
+        description = "Report what the Cangjie LSP server advertised in its initialize response, as JSON: server_info, the raw capabilities object, and supported_operations (the cangjie_lsp operations those capabilities allow). Use it to check whether e.g. call or type hierarchy is supported before calling cangjie_lsp.",
+        annotations(
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn lsp_capabilities(&self, meta: rmcp::model::Meta) -> String {
+        let working_dir = meta
+            .0
+            .get(crate::lsp_tools::META_WORKING_DIRECTORY)
+            .and_then(|v| v.as_str())
+            .map(std::path::PathBuf::from);
+        #[cfg(feature = "lsp")]
+        {
+            crate::lsp_tools::lsp_capabilities_json(self.lsp_pool.as_deref(), working_dir).await
+        }
+        #[cfg(not(feature = "lsp"))]
+        {
+            let _ = working_dir;
+            "LSP support is not compiled in. Enable the 'lsp' feature.".to_string()
+        }
+    }
+
+    #[tool(
+        name = "cangjie_lsp_logs",
+        description = "Return the most recent lines the Cangjie LSP server wrote to stderr, as JSON {lines: [...]} oldest first. Use it to diagnose LSP startup or runtime failures; set lines to control how many are returned (default 100).",
+        annotations(
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn lsp_logs(
+        &self,
+        Parameters(params): Parameters<crate::lsp_tools::LspLogsParams>,
+        meta: rmcp::model::Meta,
+    ) -> String {
+        let working_dir = meta
+            .0
+            .get(crate::lsp_tools::META_WORKING_DIRECTORY)
+            .and_then(|v| v.as_str())
+            .map(std::path::PathBuf::from);
+        #[cfg(feature = "lsp")]
+        {
+            crate::lsp_tools::lsp_logs_json(self.lsp_pool.as_deref(), working_dir, params.lines)
+        }
+        #[cfg(not(feature = "lsp"))]
+        {
+            let _ = (working_dir, params);
+            "LSP support is not compiled in. Enable the 'lsp' feature.".to_string()
+        }
+    }
+
+    #[tool(
+        name = "cangjie_lsp_status",
+        description = "Report Cangjie LSP health as JSON: whether an SDK was detected (available), whether the client (in daemon mode, the one for the working directory) is initialized and running, the workspace and SDK paths, the number of open files, and the last startup error if any.",
+        annotations(
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn lsp_status(&self, meta: rmcp::model::Meta) -> String {
+        let working_dir = meta
+            .0
+            .get(crate::lsp_tools::META_WORKING_DIRECTORY)
+            .and_then(|v| v.as_str())
+            .map(std::path::PathBuf::from);
+        crate::lsp_tools::lsp_status_json(self.lsp_pool.as_deref(), working_dir).await
+    }
+
+    #[tool(
+        name = "cangjie_project_build_order",
+        description = "Compute the module build order of the Cangjie project in the working directory from its cjpm.toml files. Returns JSON with order (each module after the modules it depends on, with name, path and depends_on) and cycles (groups of modules that depend on each other; their members are flagged in_cycle).",
+        annotations(
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn project_build_order(&self, meta: rmcp::model::Meta) -> String {
+        let working_dir = meta
+            .0
+            .get(crate::lsp_tools::META_WORKING_DIRECTORY)
+            .and_then(|v| v.as_str())
+            .map(std::path::PathBuf::from);
+        crate::lsp_tools::project_build_order_json(working_dir)
+    }
+
+    #[tool(
+        name = "cangjie_cjpm_check",
+        description = "Lint the cjpm.toml files of the Cangjie project in the working directory and of its path, git and version dependencies. Returns JSON with workspace, modules (how many were resolved), errors and warnings counts, and issues (each with severity, the cjpm.toml path and a message), e.g. a file declaring both [workspace] and [package], missing workspace members or a dependency whose package name differs from its key. Needs no SDK.",
+        annotations(
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn cjpm_check(&self, meta: rmcp::model::Meta) -> String {
+        let working_dir = meta
+            .0
+            .get(crate::lsp_tools::META_WORKING_DIRECTORY)
+            .and_then(|v| v.as_str())
+            .map(std::path::PathBuf::from);
+        crate::lsp_tools::project_cjpm_check_json(working_dir)
+    }
+
     #[tool(
         name = "cangjie_search_docs",
-        description = "Search Cangjie documentation using semantic search. Performs similarity search across all indexed documentation. Returns matching sections ranked by relevance with code examples and pagination support (use offset/top_k). Supports filtering by category (e.g. 'stdlib', 'syntax') and stdlib package name (e.g. 'std.collection', 'std.fs').",
+        description = "Search Cangjie documentation using semantic search. Performs similarity search across all indexed documentation. Returns matching sections ranked by relevance with code examples and pagination support (use offset/top_k; top_k = 0 returns only the match count and per-category counts). Supports filtering by category (e.g. 'stdlib', 'syntax') and stdlib package name (e.g. 'std.collection', 'std.fs').",
         annotations(
             read_only_hint = true,
             destructive_hint = false,
@@ -258,53 +877,225 @@ impl CangjieServer {
         )
     )]
     pub async fn search_docs(&self, Parameters(params): Parameters<SearchDocsParams>) -> String {
-        let top_k = params.top_k.clamp(MIN_TOP_K, MAX_TOP_K);
+        if is_blank_query(&params.query) {
+            return format!("Search error: {EMPTY_QUERY_MESSAGE}");
+        }
+        let _permit = self.acquire_tool_permit().await;
+        // `top_k: 0` only counts matches: the whole candidate pool is fetched
+        // and counted after dropping duplicate snippets, without reranking or
+        // content.
+        let count_only = params.top_k == 0;
+        let top_k = if count_only {
+            0
+        } else {
+            params.top_k.clamp(MIN_TOP_K, MAX_TOP_K)
+        };
+        let rerank = params.rerank && !count_only;
         let category = params.category.as_deref().filter(|s| !s.is_empty());
+        if let Some(c) = category.filter(|c| !self.category_allowed(c)) {
+            return format!("Search error: category '{c}' is not available in this session");
+        }
+        // A session limited to a single category searches just that one.
+        let allowed = self.allowed_categories.get();
+        let category = category.or_else(|| {
+            allowed
+                .filter(|a| a.len() == 1)
+                .and_then(|a| a.first())
+                .map(String::as_str)
+        });
+        let scoped = category.is_none() && allowed.is_some();
         let package = params.package.as_deref().filter(|s| !s.is_empty());
 
-        // Fetch extra candidates so reranking, dedup, and pagination have headroom.
-        let dedup_fetch_multiplier = 4;
-        let fetch_multiplier = if package.is_some() {
-            PACKAGE_FETCH_MULTIPLIER
+        let (fetch_count, mut candidates_capped) = if count_only {
+            (self.settings.max_fetch_candidates.max(1), false)
         } else {
-            1
+            Self::candidate_fetch_count(
+                params.offset,
+                top_k,
+                package.is_some()
+                    || !params.metadata_filter.is_empty()
+                    || !params.exclude_topics.is_empty()
+                    || scoped,
+                rerank && self.settings.rerank_type != RerankType::None,
+                self.settings.max_fetch_candidates,
+            )
         };
-        let fetch_count = (params.offset + top_k + 1) * fetch_multiplier * dedup_fetch_multiplier;
 
-        let results = match self.do_search(&params.query, fetch_count, category).await {
+        let backend = match self.search_backend_for(params.version.as_deref()).await {
+            Ok(b) => b,
+            Err(e) => return format!("Search error: {e}"),
+        };
+        let source_ref = backend.source_ref().await;
+        let lang = match params.lang.as_deref().filter(|s| !s.is_empty()) {
+            Some(lang) => match lang.parse::<DocLang>() {
+                Ok(lang) => Some(lang),
+                Err(e) => return format!("Search error: {e}"),
+            },
+            None => None,
+        };
+        let searched = if params.search_code {
+            backend
+                .code_search(&params.query, fetch_count, category, lang)
+                .await
+        } else {
+            backend
+                .search(&params.query, fetch_count, category, rerank, lang)
+                .await
+        };
+        let results = match searched {
             Ok(r) => r,
+            Err(e)
+                if matches!(
+                    e.downcast_ref::<CangjieError>(),
+                    Some(CangjieError::IndexNotReady)
+                ) =>
+            {
+                return format!("Search error: {INDEX_UNAVAILABLE_MESSAGE}");
+            }
             Err(e) => return format!("Search error: {e}"),
         };
 
-        let mut results =
-            Self::rerank_and_dedup_results(results, &params.query, top_k, params.offset);
+        let mut results = results;
+        if scoped {
+            results.retain(|r| self.category_allowed(&r.metadata.category));
+        }
+        if !params.exclude_topics.is_empty() {
+            results.retain(|r| !params.exclude_topics.contains(&r.metadata.topic));
+        }
+        Self::weight_categories(&mut results, &self.settings);
+        if params.auto_category_boost && category.is_none() && !count_only {
+            match self.scoped_topic_categories(&backend).await {
+                Ok(topics) => {
+                    if let Some(inferred) = Self::infer_category(&params.query, &topics) {
+                        Self::boost_category(&mut results, &inferred);
+                    }
+                }
+                Err(e) => warn!("Skipping category inference: {e:#}"),
+            }
+        }
+
+        let mut results = if count_only {
+            candidates_capped = results.len() >= fetch_count;
+            Self::dedup_snippets(results)
+        } else {
+            Self::rerank_and_dedup_results(
+                results,
+                &params.query,
+                top_k,
+                params.offset,
+                self.settings.length_penalty,
+            )
+        };
+        let retrieved_any = !results.is_empty();
 
         if let Some(pkg) = package {
             results.retain(|r| Self::has_package(r, pkg));
         }
 
+        if !params.metadata_filter.is_empty() {
+            results.retain(|r| custom_fields_match(&r.metadata.custom, &params.metadata_filter));
+        }
+
+        // Tell apart "nothing matches" from "the filters removed every match",
+        // probing without the category when the retriever itself came back
+        // empty.
+        let metadata_filtered = !params.metadata_filter.is_empty();
+        let filter_note = if results.is_empty()
+            && (category.is_some() || package.is_some() || metadata_filtered)
+        {
+            let matched_unfiltered = if retrieved_any {
+                package.is_some() || metadata_filtered
+            } else if category.is_some() {
+                let probe_k = top_k.max(MIN_TOP_K);
+                let probe = if params.search_code {
+                    backend
+                        .code_search(&params.query, probe_k, None, lang)
+                        .await
+                } else {
+                    backend
+                        .search(&params.query, probe_k, None, false, lang)
+                        .await
+                };
+                probe.is_ok_and(|r| !r.is_empty())
+            } else {
+                false
+            };
+            matched_unfiltered
+                .then(|| Self::filter_note(category, package, &params.metadata_filter))
+        } else {
+            None
+        };
+
+        if params.extract_code {
+            results.retain(|r| !params.code_blocks(&r.text).is_empty());
+        }
+
+        if params.granularity == SearchGranularity::Document {
+            results = Self::group_by_document(results);
+        }
+
+        if params.order == SearchOrder::Path {
+            Self::sort_by_path(&mut results);
+        }
+
         let total = results.len();
+        let mut category_counts = BTreeMap::new();
+        if count_only {
+            for r in &results {
+                *category_counts
+                    .entry(r.metadata.category.clone())
+                    .or_insert(0) += 1;
+            }
+        }
         let paginated: Vec<_> = results
             .into_iter()
             .skip(params.offset)
             .take(top_k)
             .collect();
-        let has_more = total > params.offset + top_k;
+        let has_more = !count_only && total > params.offset + top_k;
 
-        let items: Vec<SearchResultItem> = paginated
-            .into_iter()
-            .map(|r| SearchResultItem {
-                content: strip_chunk_artifacts(&r.text).to_string(),
+        let mut items: Vec<SearchResultItem> = Vec::with_capacity(paginated.len());
+        for r in paginated {
+            let package_match = package
+                .filter(|_| params.explain_package)
+                .and_then(|pkg| Self::package_match(&r, pkg))
+                .map(|m| m.to_string());
+            let mut content = match params.granularity {
+                SearchGranularity::Chunk => strip_chunk_artifacts(&r.text).to_string(),
+                SearchGranularity::Document => {
+                    backend
+                        .document_content(&r.metadata.file_path, &r.text)
+                        .await
+                }
+            };
+            if params.extract_code {
+                content = params
+                    .code_blocks(&content)
+                    .iter()
+                    .map(|block| block.to_markdown())
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+            }
+            items.push(SearchResultItem {
+                content,
                 score: r.score,
+                raw_score: None,
+                source_url: source_ref
+                    .as_deref()
+                    .and_then(|git_ref| source_url(&self.settings, git_ref, &r.metadata.file_path)),
                 file_path: r.metadata.file_path,
                 category: r.metadata.category,
                 topic: r.metadata.topic,
                 title: r.metadata.title,
-            })
-            .collect();
+                chunk_index: r.metadata.chunk_index,
+                char_offset: r.metadata.char_offset,
+                breadcrumb: r.metadata.breadcrumb,
+                package_match,
+            });
+        }
 
         let count = items.len();
-        let result = DocsSearchResult {
+        let mut result = DocsSearchResult {
             items,
             total,
             count,
@@ -315,27 +1106,386 @@ impl CangjieServer {
             } else {
                 None
             },
+            query_terms: params
+                .debug_terms
+                .then(|| explain_query(&params.query).into()),
+            groups: Vec::new(),
+            candidates_capped,
+            filter_note,
+            category_counts,
+        };
+        if params.normalize_scores {
+            result.normalize_scores();
+        }
+        if params.group_by_topic {
+            result.group_by_topic();
+        }
+
+        let format = self.response_format(params.response_format);
+        render_response(&result, format, format_results_markdown)
+    }
+
+    #[tool(
+        name = "cangjie_get_topics",
+        description = "Fetch the full content of several documentation topics in one call. Each entry names a topic (the document file name without extension, e.g. 'functions') and an optional category. Returns a JSON array in request order (or one Markdown section per topic with response_format = 'markdown'); topics that do not exist are reported with not_found = true. At most 10 topics per call.",
+        annotations(
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn get_topics(&self, Parameters(params): Parameters<GetTopicsParams>) -> String {
+        if params.topics.is_empty() {
+            return "At least one topic is required.".to_string();
+        }
+        if params.topics.len() > MAX_TOPICS_PER_REQUEST {
+            return format!(
+                "Too many topics: {} requested, at most {MAX_TOPICS_PER_REQUEST} per call.",
+                params.topics.len()
+            );
+        }
+
+        let backend = match self.search_backend_for(params.version.as_deref()).await {
+            Ok(b) => b,
+            Err(e) => return format!("Topic lookup error: {e}"),
         };
+        let source_ref = backend.source_ref().await;
+
+        let handles: Vec<_> = params
+            .topics
+            .into_iter()
+            .map(|req| {
+                let backend = backend.clone();
+                let allowed = self.allowed_categories.get().cloned();
+                tokio::spawn(async move {
+                    let category = req.category.as_deref().filter(|s| !s.is_empty());
+                    let found = match (&allowed, category) {
+                        (Some(allowed), Some(c)) if !allowed.contains(c) => None,
+                        (Some(allowed), None) => {
+                            let mut found = None;
+                            for c in allowed {
+                                found = backend.topic(&req.topic, Some(c)).await?;
+                                if found.is_some() {
+                                    break;
+                                }
+                            }
+                            found
+                        }
+                        _ => backend.topic(&req.topic, category).await?,
+                    };
+                    Ok::<_, anyhow::Error>(match found {
+                        Some(result) => TopicLookup::Found(result),
+                        None => TopicLookup::NotFound {
+                            topic: req.topic,
+                            category: req.category,
+                            not_found: true,
+                            suggestions: Vec::new(),
+                        },
+                    })
+                })
+            })
+            .collect();
+
+        let mut entries = Vec::with_capacity(handles.len());
+        for handle in handles {
+            match handle.await {
+                Ok(Ok(entry)) => entries.push(entry),
+                Ok(Err(e)) => return format!("Topic lookup error: {e}"),
+                Err(e) => return format!("Topic lookup task failed: {e}"),
+            }
+        }
+        for entry in &mut entries {
+            if let TopicLookup::Found(result) = entry {
+                result.source_url = source_ref
+                    .as_deref()
+                    .and_then(|git_ref| source_url(&self.settings, git_ref, &result.file_path));
+            }
+        }
+
+        if entries
+            .iter()
+            .any(|entry| matches!(entry, TopicLookup::NotFound { .. }))
+        {
+            match self.scoped_topic_categories(&backend).await {
+                Ok(map) => {
+                    for entry in &mut entries {
+                        if let TopicLookup::NotFound {
+                            topic,
+                            category,
+                            suggestions,
+                            ..
+                        } = entry
+                        {
+                            let category = category.as_deref().filter(|s| !s.is_empty());
+                            *suggestions = TopicExistence::resolve(
+                                &map,
+                                topic,
+                                category,
+                                self.settings.topic_suggestion_threshold,
+                            )
+                            .suggestions;
+                        }
+                    }
+                }
+                Err(e) => warn!("Failed to load topics for suggestions: {e}"),
+            }
+        }
 
-        format_results_markdown(&result)
+        let format = self.response_format(params.response_format);
+        render_response(entries.as_slice(), format, format_topics_markdown)
+    }
+
+    #[tool(
+        name = "cangjie_topic_exists",
+        description = "Check whether a documentation topic exists without fetching its content. Takes a topic name (document file name without extension) and an optional category. Returns JSON with exists, matched_category, other_categories where the name also exists, and suggestions for similarly named topics when it does not exist.",
+        annotations(
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn topic_exists(&self, Parameters(params): Parameters<TopicRequest>) -> String {
+        let map = match self.search_backend().await {
+            Ok(backend) => self.scoped_topic_categories(&backend).await,
+            Err(e) => Err(e),
+        };
+        let map = match map {
+            Ok(map) => map,
+            Err(e) => return format!("Topic lookup error: {e}"),
+        };
+        let category = params.category.as_deref().filter(|s| !s.is_empty());
+        let existence = TopicExistence::resolve(
+            &map,
+            &params.topic,
+            category,
+            self.settings.topic_suggestion_threshold,
+        );
+        serde_json::to_string_pretty(&existence)
+            .unwrap_or_else(|e| format!("Failed to serialize topic status: {e}"))
+    }
+
+    #[tool(
+        name = "cangjie_ambiguous_topics",
+        description = "List topic names that exist in more than one documentation category, with the categories for each. Pass a category to cangjie_get_topics for these topics to pick the right document.",
+        annotations(
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn ambiguous_topics(&self) -> String {
+        let map = match self.search_backend().await {
+            Ok(backend) => self.scoped_topic_categories(&backend).await,
+            Err(e) => Err(e),
+        };
+        match map {
+            Ok(map) => serde_json::to_string_pretty(&ambiguous_topics(map))
+                .unwrap_or_else(|e| format!("Failed to serialize topics: {e}")),
+            Err(e) => format!("Topic lookup error: {e}"),
+        }
+    }
+
+    #[tool(
+        name = "cangjie_related_topics",
+        description = "List the documentation topics most related to a given topic, excluding the topic itself. Takes a topic name, an optional category, and k (default 5, at most 20). Uses vector similarity of the topic's content when the index has embeddings, otherwise a keyword search on its title. Returns a JSON array of {topic, category, title, similarity, score, matched_by}, best first; matched_by is 'vector' when score is a similarity (also given as similarity) and 'keyword' when it is a fused BM25 rank score (similarity is null).",
+        annotations(
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn related_topics(
+        &self,
+        Parameters(params): Parameters<RelatedTopicsParams>,
+    ) -> String {
+        let category = params.category.as_deref().filter(|s| !s.is_empty());
+        if category.is_some_and(|c| !self.category_allowed(c)) {
+            return format!("Topic not found: {}", params.topic);
+        }
+        let k = params.k.clamp(MIN_TOP_K, MAX_TOP_K);
+        let _permit = self.acquire_tool_permit().await;
+        let related = match self.search_backend().await {
+            Ok(backend) => {
+                backend
+                    .related_topics(&params.topic, category, self.allowed_categories.get(), k)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        match related {
+            Ok(Some(related)) => serde_json::to_string_pretty(&related)
+                .unwrap_or_else(|e| format!("Failed to serialize related topics: {e}")),
+            Ok(None) => format!("Topic not found: {}", params.topic),
+            Err(e)
+                if matches!(
+                    e.downcast_ref::<CangjieError>(),
+                    Some(CangjieError::IndexNotReady)
+                ) =>
+            {
+                format!("Topic lookup error: {INDEX_UNAVAILABLE_MESSAGE}")
+            }
+            Err(e) => format!("Topic lookup error: {e}"),
+        }
+    }
+
+    #[tool(
+        name = "cangjie_resolved_version",
+        description = "Report which documentation commit the index was built from: the requested version (tag, branch, or 'latest'), the ref it resolved to, and the full and short commit hash.",
+        annotations(
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn resolved_version(&self) -> String {
+        match self.do_resolved_version().await {
+            Ok(Some(resolved)) => serde_json::to_string_pretty(&resolved)
+                .unwrap_or_else(|e| format!("Failed to serialize resolved version: {e}")),
+            Ok(None) if self.settings.server_url.is_some() => {
+                "The remote server did not report a resolved documentation version.".to_string()
+            }
+            Ok(None) => format!(
+                "Documentation repository is not cloned at {} (requested version: {}). \
+                 Run `cangjie-mcp index` to clone it, or use a prebuilt index.",
+                self.settings.docs_repo_dir().display(),
+                self.settings.docs_version
+            ),
+            Err(e) => format!("Failed to resolve documentation version: {e}"),
+        }
+    }
+
+    #[tool(
+        name = "cangjie_diff_versions",
+        description = "Compare the topic lists of two documentation versions (tags, branches, commits, or 'latest') in the local docs repository. Returns JSON with the topics added in 'to', removed since 'from', and common to both, as paths relative to the docs source directory.",
+        annotations(
+            read_only_hint = true,
+            destructive_hint = false,
+            idempotent_hint = true
+        )
+    )]
+    pub async fn diff_versions(
+        &self,
+        Parameters(params): Parameters<DiffVersionsParams>,
+    ) -> String {
+        if self.settings.server_url.is_some() {
+            return "Version diffs need the local documentation repository and are not available with a remote server.".to_string();
+        }
+        let _permit = self.acquire_tool_permit().await;
+        match diff_docs_versions(&self.settings, &params.from, &params.to).await {
+            Ok(diff) => serde_json::to_string_pretty(&diff)
+                .unwrap_or_else(|e| format!("Failed to serialize version diff: {e}")),
+            Err(e) => format!("Failed to diff documentation versions: {e:#}"),
+        }
     }
 }
 
 // rmcp 1.7's `#[tool_handler]` defaults to the static `Self::tool_router()`, which
 // would expose every tool unconditionally. Point it at the instance field so the
 // conditional router from `build_tool_router()` (LSP tool only when
-// `cangjie_lsp::is_available()`) is what's actually served.
+// `cangjie_lsp::is_available()`, tools filtered by settings removed) is what's
+// actually served.
 #[tool_handler(router = self.tool_router)]
 impl ServerHandler for CangjieServer {
     fn get_info(&self) -> ServerInfo {
-        #[cfg(feature = "lsp")]
-        let lsp_enabled = cangjie_lsp::is_available();
-        #[cfg(not(feature = "lsp"))]
-        let lsp_enabled = false;
+        let lsp_enabled = self.lsp_tool_enabled();
+        ServerInfo::new(
+            ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .enable_prompts()
+                .build(),
+        )
+        .with_server_info(Implementation::new("cangjie-mcp", cangjie_core::VERSION))
+        .with_instructions(get_prompt(lsp_enabled))
+    }
+
+    async fn initialize(
+        &self,
+        request: InitializeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<InitializeResult, ErrorData> {
+        if let Some(categories) = context
+            .meta
+            .0
+            .get(META_ALLOWED_CATEGORIES)
+            .and_then(|v| v.as_array())
+        {
+            let categories: BTreeSet<String> = categories
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(str::to_string)
+                .collect();
+            info!("Session limited to categories: {categories:?}");
+            let _ = self.allowed_categories.set(categories);
+        }
+        if context.peer.peer_info().is_none() {
+            context.peer.set_peer_info(request);
+        }
+        Ok(self.get_info())
+    }
 
-        ServerInfo::new(ServerCapabilities::builder().enable_tools().build())
-            .with_server_info(Implementation::new("cangjie-mcp", cangjie_core::VERSION))
-            .with_instructions(get_prompt(lsp_enabled))
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, ErrorData> {
+        Ok(ListPromptsResult::with_all_items(self.available_prompts()))
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, ErrorData> {
+        let working_dir = context
+            .meta
+            .0
+            .get(crate::lsp_tools::META_WORKING_DIRECTORY)
+            .and_then(|v| v.as_str())
+            .map(std::path::PathBuf::from);
+        match self
+            .render_prompt(&request.name, request.arguments.as_ref(), working_dir)
+            .await
+        {
+            Ok(Some(result)) => Ok(result),
+            Ok(None) => Err(ErrorData::invalid_params(
+                format!("Unknown prompt: {}", request.name),
+                None,
+            )),
+            Err(e) => Err(ErrorData::invalid_params(
+                format!("Failed to render {}: {e:#}", request.name),
+                None,
+            )),
+        }
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, ErrorData> {
+        self.topic_resources()
+            .await
+            .map(ListResourcesResult::with_all_items)
+            .map_err(|e| ErrorData::internal_error(format!("Failed to list topics: {e}"), None))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, ErrorData> {
+        match self.read_topic_resource(&request.uri).await {
+            Ok(Some(contents)) => Ok(ReadResourceResult::new(vec![contents])),
+            Ok(None) => Err(ErrorData::resource_not_found(
+                format!("No documentation topic at {}", request.uri),
+                None,
+            )),
+            Err(e) => Err(ErrorData::internal_error(
+                format!("Failed to read {}: {e}", request.uri),
+                None,
+            )),
+        }
     }
 }
 
@@ -363,9 +1513,13 @@ mod tests {
             );
             let instructions = info.instructions.unwrap();
             assert!(!instructions.is_empty(), "instructions should not be empty");
+            assert!(info.capabilities.resources.is_some());
 
             let tools = server.tool_router.list_all();
             let tool_names: Vec<String> = tools.iter().map(|t| t.name.to_string()).collect();
+            assert!(tool_names.iter().any(|n| n == "cangjie_lsp_status"));
+            assert!(!tool_names.iter().any(|n| n == "cangjie_lsp_capabilities"));
+            assert!(!tool_names.iter().any(|n| n == "cangjie_lsp_logs"));
             assert!(
                 !tool_names.iter().any(|n| n == "cangjie_lsp"),
                 "No LSP tools should be registered without CANGJIE_HOME, but found: {:?}",
@@ -377,6 +1531,193 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn test_tool_permits_bound_concurrent_calls() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let server = CangjieServer::new(Settings {
+            max_concurrent_tools: 2,
+            ..Settings::default()
+        });
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let tasks: Vec<_> = (0..6)
+            .map(|_| {
+                let server = server.clone();
+                let running = running.clone();
+                let peak = peak.clone();
+                tokio::spawn(async move {
+                    let _permit = server.acquire_tool_permit().await;
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_share_one_tool_limit() {
+        let server = CangjieServer::new(Settings {
+            max_concurrent_tools: 2,
+            ..Settings::default()
+        });
+        // Transports hand each session a clone of the one server.
+        let first = server.clone();
+        let second = server.clone().with_allowed_categories(["syntax"]);
+        let _a = first.acquire_tool_permit().await;
+        let _b = first.acquire_tool_permit().await;
+        let blocked = tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            second.acquire_tool_permit(),
+        )
+        .await;
+        assert!(blocked.is_err(), "a second session must wait for a slot");
+    }
+
+    #[tokio::test]
+    async fn test_resolved_version_reports_missing_repo() {
+        let tmp = tempfile::tempdir().unwrap();
+        let settings = Settings {
+            data_dir: tmp.path().to_path_buf(),
+            docs_version: "v1.0.0".to_string(),
+            ..Settings::default()
+        };
+
+        let server = CangjieServer::new(settings);
+        let tool_names: Vec<String> = server
+            .tool_router
+            .list_all()
+            .iter()
+            .map(|t| t.name.to_string())
+            .collect();
+        assert!(tool_names.iter().any(|n| n == "cangjie_resolved_version"));
+
+        let output = server.resolved_version().await;
+        assert!(output.contains("not cloned"), "{output}");
+        assert!(output.contains("v1.0.0"), "{output}");
+    }
+
+    #[test]
+    fn test_disabled_tool_is_not_advertised() {
+        let settings = Settings {
+            data_dir: std::path::PathBuf::from("/tmp/test-tool-filter"),
+            disabled_tools: vec!["cangjie_ambiguous_topics".to_string()],
+            ..Settings::default()
+        };
+        CangjieServer::validate_tool_filter(&settings).unwrap();
+
+        let server = CangjieServer::new(settings);
+        let tool_names: Vec<String> = server
+            .tool_router
+            .list_all()
+            .iter()
+            .map(|t| t.name.to_string())
+            .collect();
+        assert!(!tool_names.iter().any(|n| n == "cangjie_ambiguous_topics"));
+        assert!(tool_names.iter().any(|n| n == "cangjie_search_docs"));
+    }
+
+    #[test]
+    fn test_enabled_tools_limits_router() {
+        let settings = Settings {
+            data_dir: std::path::PathBuf::from("/tmp/test-tool-filter"),
+            enabled_tools: vec![
+                "cangjie_search_docs".to_string(),
+                "cangjie_get_topics".to_string(),
+            ],
+            ..Settings::default()
+        };
+
+        let server = CangjieServer::new(settings);
+        let mut tool_names: Vec<String> = server
+            .tool_router
+            .list_all()
+            .iter()
+            .map(|t| t.name.to_string())
+            .collect();
+        tool_names.sort();
+        assert_eq!(
+            tool_names,
+            vec!["cangjie_get_topics", "cangjie_search_docs"]
+        );
+    }
+
+    #[test]
+    fn test_router_serves_every_tool_but_lsp_ones() {
+        temp_env::with_var("CANGJIE_HOME", None::<&str>, || {
+            let server = CangjieServer::new(Settings {
+                data_dir: std::path::PathBuf::from("/tmp/test-tool-router"),
+                ..Settings::default()
+            });
+            let mut served: Vec<String> = server
+                .tool_router
+                .list_all()
+                .iter()
+                .map(|t| t.name.to_string())
+                .collect();
+            served.sort();
+
+            // Only the LSP tools depend on CANGJIE_HOME; project analysis
+            // additionally needs the `lsp` feature.
+            let mut gated = vec![
+                "cangjie_lsp",
+                "cangjie_lsp_capabilities",
+                "cangjie_lsp_logs",
+            ];
+            if !cfg!(feature = "lsp") {
+                gated.extend(["cangjie_project_build_order", "cangjie_cjpm_check"]);
+            }
+            let expected: Vec<String> = CangjieServer::known_tool_names()
+                .into_iter()
+                .filter(|name| !gated.contains(&name.as_str()))
+                .collect();
+            assert_eq!(served, expected);
+            assert!(served.iter().any(|n| n == "cangjie_related_topics"));
+        });
+    }
+
+    #[test]
+    fn test_validate_tool_filter_rejects_unknown_name() {
+        let settings = Settings {
+            disabled_tools: vec!["cangjie_rename".to_string()],
+            ..Settings::default()
+        };
+        let err = CangjieServer::validate_tool_filter(&settings)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("cangjie_rename"), "{err}");
+        assert!(err.contains("cangjie_search_docs"), "{err}");
+    }
+
+    #[cfg(feature = "lsp")]
+    #[tokio::test]
+    async fn test_lsp_status_in_daemon_mode_reports_the_workspace_client() {
+        let server =
+            CangjieServer::with_lsp_pool(Settings::default(), std::time::Duration::from_secs(60));
+        let msg = server.lsp_status(rmcp::model::Meta::default()).await;
+        assert!(msg.contains("working directory is required"), "{msg}");
+
+        let workspace = tempfile::tempdir().unwrap();
+        let mut meta = rmcp::model::Meta::new();
+        meta.0.insert(
+            crate::lsp_tools::META_WORKING_DIRECTORY.to_string(),
+            serde_json::Value::String(workspace.path().display().to_string()),
+        );
+        let status: serde_json::Value =
+            serde_json::from_str(&server.lsp_status(meta).await).unwrap();
+        assert_eq!(status["running"], false);
+        assert_eq!(status["open_file_count"], 0);
+        if status["available"] == true {
+            assert_eq!(status["workspace"], workspace.path().display().to_string());
+        }
+    }
+
     #[cfg(feature = "lsp")]
     #[test]
     fn test_get_info_with_cangjie_home() {
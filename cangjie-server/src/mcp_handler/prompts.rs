@@ -0,0 +1,366 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use rmcp::model::{
+    GetPromptResult, JsonObject, Prompt, PromptArgument, PromptMessage, PromptMessageRole,
+};
+
+use cangjie_indexer::document::chunker::strip_chunk_artifacts;
+
+use crate::lsp_tools::{LspOperation, LspRequest};
+
+use super::CangjieServer;
+
+pub const PROMPT_EXPLAIN_ERROR: &str = "cangjie_explain_error";
+pub const PROMPT_USE_MODULE: &str = "cangjie_use_module";
+pub const PROMPT_REVIEW_FILE: &str = "cangjie_review_file";
+
+/// Documentation sections retrieved into a rendered prompt.
+const PROMPT_DOC_RESULTS: usize = 3;
+
+fn argument(name: &str, description: &str, required: bool) -> PromptArgument {
+    PromptArgument::new(name)
+        .with_description(description)
+        .with_required(required)
+}
+
+/// Every prompt template the server offers, in listing order.
+pub fn prompt_templates() -> Vec<Prompt> {
+    vec![
+        Prompt::new(
+            PROMPT_EXPLAIN_ERROR,
+            Some("Explain a Cangjie compiler or runtime error and how to fix it, using the relevant documentation."),
+            Some(vec![
+                argument("error", "The error message, as printed", true),
+                argument("code", "The code that produced the error", false),
+            ]),
+        ),
+        Prompt::new(
+            PROMPT_USE_MODULE,
+            Some("Show how to use a Cangjie standard library package, grounded in its documentation."),
+            Some(vec![
+                argument("module", "Package name, e.g. 'std.collection'", true),
+                argument("task", "What you want to do with it", false),
+            ]),
+        ),
+        Prompt::new(
+            PROMPT_REVIEW_FILE,
+            Some("Review a .cj file using its LSP diagnostics and the documentation of the packages it imports."),
+            Some(vec![argument("file_path", "Path of the .cj file to review", true)]),
+        ),
+    ]
+}
+
+/// Canonical path of the `.cj` file `file_path` names, which must lie inside
+/// `workspace` once symlinks are resolved. Relative paths are taken from the
+/// workspace.
+async fn review_file_path(workspace: &Path, file_path: &str) -> Result<PathBuf> {
+    let workspace = tokio::fs::canonicalize(workspace)
+        .await
+        .with_context(|| format!("Failed to resolve workspace {}", workspace.display()))?;
+    let path = tokio::fs::canonicalize(workspace.join(file_path))
+        .await
+        .with_context(|| format!("Failed to read {file_path}"))?;
+    if path.extension().is_none_or(|ext| ext != "cj") {
+        bail!("{file_path} is not a Cangjie (.cj) file");
+    }
+    if !path.starts_with(&workspace) {
+        bail!(
+            "{file_path} is outside the workspace {}",
+            workspace.display()
+        );
+    }
+    Ok(path)
+}
+
+fn user_message(text: String) -> PromptMessage {
+    PromptMessage::new_text(PromptMessageRole::User, text)
+}
+
+/// Packages a Cangjie source file imports, in order of first import:
+/// `import std.collection.*` and `import std.fs.{File, Path}` both name the
+/// package before the last segment.
+fn imported_packages(source: &str) -> Vec<String> {
+    let mut packages: Vec<String> = Vec::new();
+    for line in source.lines() {
+        let line = line.trim_start();
+        let Some(rest) = line
+            .strip_prefix("import ")
+            .or_else(|| line.strip_prefix("public import "))
+        else {
+            continue;
+        };
+        let path = rest.split(" as ").next().unwrap_or(rest).trim();
+        let package = path
+            .rsplit_once('.')
+            .map_or(path, |(package, _)| package)
+            .trim();
+        if !package.is_empty() && !packages.iter().any(|p| p == package) {
+            packages.push(package.to_string());
+        }
+    }
+    packages
+}
+
+impl CangjieServer {
+    /// Prompt templates this server can render. Reviewing a file needs the
+    /// `cangjie_lsp` tool for its diagnostics, so it is left out without it.
+    pub fn available_prompts(&self) -> Vec<Prompt> {
+        let lsp = self.lsp_tool_enabled();
+        prompt_templates()
+            .into_iter()
+            .filter(|p| lsp || p.name != PROMPT_REVIEW_FILE)
+            .collect()
+    }
+
+    /// Render prompt `name` with `arguments` into messages carrying the
+    /// retrieved context. `working_dir` is the client's workspace
+    /// (`_meta.workingDirectory`). `None` when this server offers no prompt
+    /// with that name.
+    pub async fn render_prompt(
+        &self,
+        name: &str,
+        arguments: Option<&JsonObject>,
+        working_dir: Option<PathBuf>,
+    ) -> Result<Option<GetPromptResult>> {
+        let arg = |key: &str| {
+            arguments
+                .and_then(|a| a.get(key))
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+        };
+        let required = |key: &str| {
+            arg(key).ok_or_else(|| anyhow::anyhow!("Prompt '{name}' requires the '{key}' argument"))
+        };
+
+        let (description, messages) = match name {
+            PROMPT_EXPLAIN_ERROR => {
+                let error = required("error")?;
+                let mut request = format!(
+                    "Explain this Cangjie error: what it means, what usually causes it and how to fix it.\n\n```\n{error}\n```"
+                );
+                if let Some(code) = arg("code") {
+                    write!(
+                        request,
+                        "\n\nCode that produced it:\n\n```cangjie\n{code}\n```"
+                    )
+                    .unwrap();
+                }
+                // The first line carries the message; the rest is usually a
+                // source excerpt that only adds noise to the query.
+                let query = error.lines().next().unwrap_or(error);
+                let docs = self.prompt_docs(query, None).await;
+                ("Explain a Cangjie error", vec![request, docs])
+            }
+            PROMPT_USE_MODULE => {
+                let module = required("module")?;
+                let task = arg("task");
+                let request = match task {
+                    Some(task) => format!(
+                        "Show how to use the Cangjie package `{module}` to {task}. Include a complete, compilable example with its imports."
+                    ),
+                    None => format!(
+                        "Give an overview of the Cangjie package `{module}`: its main types and functions, with a short, compilable example of each."
+                    ),
+                };
+                let query = task.map_or_else(|| module.to_string(), |t| format!("{module} {t}"));
+                let docs = self.prompt_docs(&query, Some(module)).await;
+                (
+                    "Use a Cangjie standard library package",
+                    vec![request, docs],
+                )
+            }
+            PROMPT_REVIEW_FILE if self.lsp_tool_enabled() => {
+                let file_path = required("file_path")?;
+                let workspace = self.prompt_workspace(working_dir)?;
+                #[cfg(feature = "lsp")]
+                let file_path = &cangjie_lsp::utils::normalize_msys2_path(file_path);
+                let path = review_file_path(&workspace, file_path).await?;
+                let source = tokio::fs::read_to_string(&path)
+                    .await
+                    .with_context(|| format!("Failed to read {file_path}"))?;
+                let request = format!(
+                    "Review this Cangjie file for bugs, compile errors and unidiomatic code. Address every diagnostic and suggest concrete fixes.\n\n`{file_path}`:\n\n```cangjie\n{source}\n```"
+                );
+                let diagnostics = format!(
+                    "LSP diagnostics for `{file_path}`:\n\n```json\n{}\n```",
+                    self.prompt_diagnostics(&path, workspace).await
+                );
+                let packages = imported_packages(&source);
+                let mut messages = vec![request, diagnostics];
+                if !packages.is_empty() {
+                    messages.push(self.prompt_docs(&packages.join(" "), None).await);
+                }
+                ("Review a Cangjie source file", messages)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(
+            GetPromptResult::new(messages.into_iter().map(user_message).collect())
+                .with_description(description),
+        ))
+    }
+
+    /// The top documentation sections for `query` as Markdown, preferring
+    /// sections tied to `package`. Search failures become a note so the
+    /// prompt still renders.
+    async fn prompt_docs(&self, query: &str, package: Option<&str>) -> String {
+        let searched = match self.search_backend().await {
+            Ok(backend) => {
+                let _permit = self.acquire_tool_permit().await;
+                backend
+                    .search(query, PROMPT_DOC_RESULTS * 4, None, false, None)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        let mut results = match searched {
+            Ok(results) => results,
+            Err(e) => {
+                return format!(
+                    "Documentation search is unavailable ({e}); answer from general Cangjie knowledge and say so."
+                )
+            }
+        };
+        results.retain(|r| self.category_allowed(&r.metadata.category));
+        if let Some(pkg) = package {
+            if results.iter().any(|r| Self::has_package(r, pkg)) {
+                results.retain(|r| Self::has_package(r, pkg));
+            }
+        }
+        if results.is_empty() {
+            return "No matching Cangjie documentation was found; answer from general Cangjie knowledge and say so.".to_string();
+        }
+
+        let mut out = String::from("Relevant Cangjie documentation:\n");
+        for r in results.iter().take(PROMPT_DOC_RESULTS) {
+            write!(
+                out,
+                "\n### {} ({}/{})\n\n{}\n",
+                r.metadata.title,
+                r.metadata.category,
+                r.metadata.topic,
+                strip_chunk_artifacts(&r.text)
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    /// The workspace a reviewed file must lie in: `working_dir`, else the
+    /// current directory. Daemon mode serves many workspaces, so there the
+    /// client has to name one, as for the LSP tools.
+    fn prompt_workspace(&self, working_dir: Option<PathBuf>) -> Result<PathBuf> {
+        #[cfg(feature = "lsp")]
+        let working_dir = working_dir.map(|wd| {
+            PathBuf::from(cangjie_lsp::utils::normalize_msys2_path(
+                &wd.to_string_lossy(),
+            ))
+        });
+        match working_dir {
+            Some(wd) => Ok(wd),
+            #[cfg(feature = "lsp")]
+            None if self.lsp_pool.is_some() => bail!(
+                "Prompt '{PROMPT_REVIEW_FILE}' needs the working directory in daemon mode. \
+                 Pass it via _meta.workingDirectory in the prompt request."
+            ),
+            None => std::env::current_dir().context("Failed to determine the workspace"),
+        }
+    }
+
+    /// `cangjie_lsp` diagnostics JSON for `path` in `workspace`; reports the
+    /// failure itself when LSP is unavailable.
+    async fn prompt_diagnostics(&self, path: &Path, workspace: PathBuf) -> String {
+        let request = LspRequest {
+            operation: LspOperation::Diagnostics,
+            file_path: Some(path.to_string_lossy().into_owned()),
+            target: None,
+            query: None,
+            preview: false,
+        };
+        let _permit = self.acquire_tool_permit().await;
+        #[cfg(feature = "lsp")]
+        {
+            crate::lsp_tools::execute_lsp_request(
+                request,
+                self.lsp_pool.as_deref(),
+                Some(workspace),
+            )
+            .await
+        }
+        #[cfg(not(feature = "lsp"))]
+        {
+            let _ = workspace;
+            crate::lsp_tools::execute_lsp_request(request).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_review_file_path_stays_in_workspace() {
+        let tmp = tempfile::tempdir().unwrap();
+        let workspace = tmp.path().join("project");
+        std::fs::create_dir_all(workspace.join("src")).unwrap();
+        std::fs::write(workspace.join("src/main.cj"), "main() {}\n").unwrap();
+        std::fs::write(workspace.join("cjpm.toml"), "").unwrap();
+        std::fs::write(tmp.path().join("outside.cj"), "main() {}\n").unwrap();
+
+        let path = review_file_path(&workspace, "src/main.cj").await.unwrap();
+        assert!(path.ends_with("src/main.cj"));
+        let absolute = workspace.join("src/main.cj");
+        assert_eq!(
+            review_file_path(&workspace, absolute.to_str().unwrap())
+                .await
+                .unwrap(),
+            path
+        );
+
+        let err = review_file_path(&workspace, "../outside.cj")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("outside the workspace"), "{err}");
+        let err = review_file_path(&workspace, "cjpm.toml").await.unwrap_err();
+        assert!(err.to_string().contains("not a Cangjie"), "{err}");
+        assert!(review_file_path(&workspace, "src/missing.cj")
+            .await
+            .is_err());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(tmp.path().join("outside.cj"), workspace.join("link.cj"))
+                .unwrap();
+            let err = review_file_path(&workspace, "link.cj").await.unwrap_err();
+            assert!(err.to_string().contains("outside the workspace"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_available_prompts_need_the_lsp_tool() {
+        let server = CangjieServer::new(cangjie_core::config::Settings {
+            disabled_tools: vec!["cangjie_lsp".to_string()],
+            ..Default::default()
+        });
+        let names: Vec<String> = server
+            .available_prompts()
+            .iter()
+            .map(|p| p.name.to_string())
+            .collect();
+        assert!(!names.iter().any(|n| n == PROMPT_REVIEW_FILE), "{names:?}");
+        assert!(names.iter().any(|n| n == PROMPT_EXPLAIN_ERROR));
+    }
+
+    #[test]
+    fn test_imported_packages_strips_item_and_alias() {
+        let source = "package demo\n\nimport std.collection.*\nimport std.fs.{File, Path}\npublic import std.math.abs as mabs\nimport std.collection.HashMap\n\nmain() {}\n";
+        assert_eq!(
+            imported_packages(source),
+            vec!["std.collection", "std.fs", "std.math"]
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use cangjie_core::config::Settings;
+
+/// Web link to the upstream source of an indexed document, built from
+/// `settings.docs_web_base` as `{base}/{ref}/{repo path}`.
+///
+/// `git_ref` is the docs commit the serving index was built from, so the
+/// link shows the text that was indexed. Returns `None` for documents that
+/// do not come from the docs repository: stdlib and stdx pages (runtime and
+/// stdx repos) and `--extra-docs-dir` documents.
+pub(crate) fn source_url(settings: &Settings, git_ref: &str, file_path: &str) -> Option<String> {
+    let (root, rest) = file_path.split_once('/')?;
+    let extra_prefix = settings.extra_docs_prefix.trim_matches('/');
+    if !settings.extra_docs_dirs.is_empty() && (extra_prefix.is_empty() || root == extra_prefix) {
+        return None;
+    }
+    let lang_dir = settings.docs_lang.source_dir_name();
+    let repo_path = match root {
+        "stdlib" | "stdx" => return None,
+        "tools" => format!("docs/tools/{lang_dir}/{rest}"),
+        "release-notes" => format!("release-notes/{rest}"),
+        _ => format!("docs/dev-guide/{lang_dir}/{file_path}"),
+    };
+    Some(format!(
+        "{}/{}/{}",
+        settings.docs_web_base.trim_end_matches('/'),
+        encode_path(git_ref),
+        encode_path(&repo_path)
+    ))
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters and '/'.
+fn encode_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for b in path.bytes() {
+        if matches!(b, b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cangjie_core::config::DocLang;
+
+    #[test]
+    fn test_source_url_for_dev_guide_page() {
+        let settings = Settings::default();
+        assert_eq!(
+            source_url(&settings, "v1.0.0", "syntax/functions.md").as_deref(),
+            Some(
+                "https://gitcode.com/Cangjie/cangjie_docs/blob/v1.0.0/docs/dev-guide/source_zh_cn/syntax/functions.md"
+            )
+        );
+    }
+
+    #[test]
+    fn test_source_url_uses_commit_and_encodes_path() {
+        let settings = Settings {
+            docs_lang: DocLang::En,
+            docs_web_base: "https://example.com/docs/blob/".to_string(),
+            ..Settings::default()
+        };
+        assert_eq!(
+            source_url(&settings, "0a1b2c3", "tools/cjpm/cjpm 用法.md").as_deref(),
+            Some(
+                "https://example.com/docs/blob/0a1b2c3/docs/tools/source_en/cjpm/cjpm%20%E7%94%A8%E6%B3%95.md"
+            )
+        );
+    }
+
+    #[test]
+    fn test_source_url_skips_other_repositories() {
+        let settings = Settings {
+            extra_docs_dirs: vec!["/team-docs".into()],
+            ..Settings::default()
+        };
+        assert_eq!(
+            source_url(&settings, "dev", "stdlib/collection/hashmap.md"),
+            None
+        );
+        assert_eq!(source_url(&settings, "dev", "stdx/net/http.md"), None);
+        assert_eq!(source_url(&settings, "dev", "internal/guide.md"), None);
+        assert_eq!(
+            source_url(&settings, "dev", "release-notes/notes.md").as_deref(),
+            Some("https://gitcode.com/Cangjie/cangjie_docs/blob/dev/release-notes/notes.md")
+        );
+    }
+}
@@ -1,34 +1,182 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
+use cangjie_core::config::{Settings, INFERRED_CATEGORY_BOOST, PACKAGE_FETCH_MULTIPLIER};
+use cangjie_indexer::document::TopicCategoryMap;
+use cangjie_indexer::search::bm25::chunk_index;
 use cangjie_indexer::SearchResult;
 
 use super::CangjieServer;
 
 impl CangjieServer {
+    /// Candidates to fetch for the page at `offset`, with headroom for
+    /// reranking, dedup, and package filtering, capped at `max`. The flag is
+    /// set when the cap applied.
+    ///
+    /// Without a reranker the retriever's order is final, so only enough
+    /// extra candidates for dedup and pagination are fetched.
+    pub(super) fn candidate_fetch_count(
+        offset: usize,
+        top_k: usize,
+        package_filter: bool,
+        reranked: bool,
+        max: usize,
+    ) -> (usize, bool) {
+        let dedup_fetch_multiplier = if reranked { 4 } else { 2 };
+        let fetch_multiplier = if package_filter {
+            PACKAGE_FETCH_MULTIPLIER
+        } else {
+            1
+        };
+        let wanted = (offset + top_k + 1)
+            .saturating_mul(fetch_multiplier)
+            .saturating_mul(dedup_fetch_multiplier);
+        let max = max.max(1);
+        (wanted.min(max), wanted > max)
+    }
+
+    /// Explanation for a search that matched documents but lost every one
+    /// of them to the `category`, `package` and front-matter filters.
+    pub(super) fn filter_note(
+        category: Option<&str>,
+        package: Option<&str>,
+        metadata_filter: &HashMap<String, String>,
+    ) -> String {
+        let mut active = Vec::new();
+        if let Some(c) = category {
+            active.push(format!("category '{c}'"));
+        }
+        if let Some(p) = package {
+            active.push(format!("package '{p}'"));
+        }
+        if !metadata_filter.is_empty() {
+            let mut pairs: Vec<_> = metadata_filter
+                .iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect();
+            pairs.sort();
+            active.push(format!("front matter '{}'", pairs.join(", ")));
+        }
+        let suggestion = match (category, package) {
+            _ if !metadata_filter.is_empty() => {
+                "Check the front-matter values, or search without the metadata filter."
+            }
+            (Some(_), Some(_)) => "Try dropping the package filter, or search without a category.",
+            (Some(_), None) => "Try another category, or search without one.",
+            (None, Some(_)) => "Try a shorter package name, or search without the package filter.",
+            (None, None) => "",
+        };
+        format!(
+            "The query matched documents, but the {} filter removed all of them. {suggestion}",
+            active.join(" and ")
+        )
+    }
+
     pub(super) fn has_package(result: &SearchResult, package: &str) -> bool {
-        result.text.contains(package) || result.text.contains(&format!("import {package}"))
+        Self::package_match(result, package).is_some()
     }
 
-    fn query_terms(query: &str) -> Vec<String> {
-        let jieba = &**cangjie_indexer::search::GLOBAL_JIEBA;
-        let lower = query.to_lowercase();
-        let mut terms: Vec<String> = jieba
-            .cut_for_search(&lower, true)
-            .into_iter()
-            .map(|t| t.word.trim().to_string())
-            .filter(|t| !t.is_empty())
-            .collect();
+    /// Which heuristic ties `result` to `package`, checked in order: an
+    /// `import` of it, a mention of it in the text, or a file path under it
+    /// (for pages documenting re-exported symbols). The package name only
+    /// matches whole, so `std.fs` matches `std.fs.*` but not `std.fsutil`.
+    pub(super) fn package_match(result: &SearchResult, package: &str) -> Option<PackageMatch> {
+        let package = package.trim();
+        if package.is_empty() {
+            return None;
+        }
+        let mut mentioned = false;
+        for start in package_occurrences(&result.text, package) {
+            if result.text[..start]
+                .trim_end_matches([' ', '\t'])
+                .strip_suffix("import")
+                .is_some_and(|before| !before.ends_with(is_ident_char))
+            {
+                return Some(PackageMatch::Import);
+            }
+            mentioned = true;
+        }
+        if mentioned {
+            return Some(PackageMatch::Mention);
+        }
+        let path_form = package.replace('.', "/");
+        let file_path = result.metadata.file_path.replace('\\', "/");
+        package_occurrences(&file_path, &path_form)
+            .next()
+            .map(|_| PackageMatch::Path)
+    }
 
-        for token in lower
+    /// Category a query clearly points at: a word naming an indexed category
+    /// or a topic found in only one category, or a tool name documented
+    /// under one. `None` unless exactly one category is implied, so queries
+    /// mixing several are left alone.
+    pub(super) fn infer_category(query: &str, topics: &TopicCategoryMap) -> Option<String> {
+        /// Tool names and the categories that document them, by preference.
+        const TOOL_CATEGORIES: &[(&str, &[&str])] = &[
+            ("cjpm", &["cjpm", "tools"]),
+            ("cjc", &["compile", "tools"]),
+            ("cjfmt", &["tools"]),
+            ("cjlint", &["tools"]),
+            ("cjdb", &["tools"]),
+            ("cjcov", &["tools"]),
+            ("cjprof", &["tools"]),
+        ];
+
+        let categories: BTreeSet<&str> = topics.values().flatten().map(String::as_str).collect();
+        let mut inferred = BTreeSet::new();
+        let lower = query.to_lowercase();
+        for word in lower
             .split(|c: char| !c.is_alphanumeric() && c != '_')
-            .filter(|t| !t.is_empty())
+            .filter(|w| w.len() >= 3)
         {
-            if !terms.iter().any(|t| t == token) {
-                terms.push(token.to_string());
+            if let Some(category) = categories.get(word) {
+                inferred.insert(*category);
+            } else if let Some((_, preferred)) = TOOL_CATEGORIES.iter().find(|(t, _)| *t == word) {
+                if let Some(category) = preferred.iter().find(|c| categories.contains(**c)) {
+                    inferred.insert(*category);
+                }
+            } else if let Some(owners) = topics.get(word).filter(|c| c.len() == 1) {
+                inferred.extend(owners.iter().map(String::as_str));
             }
         }
+        match inferred.len() {
+            1 => inferred.pop_first().map(str::to_string),
+            _ => None,
+        }
+    }
 
-        terms
+    /// Raise the scores of results in `category` by [`INFERRED_CATEGORY_BOOST`]
+    /// and reorder; results elsewhere are kept.
+    pub(super) fn boost_category(results: &mut [SearchResult], category: &str) {
+        for r in results.iter_mut() {
+            if r.metadata.category == category {
+                r.score *= INFERRED_CATEGORY_BOOST;
+            }
+        }
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    }
+
+    /// Scale each result's score by its category's `--category-weight` and
+    /// re-sort, so preferred categories rank up without others being dropped.
+    pub(super) fn weight_categories(results: &mut [SearchResult], settings: &Settings) {
+        if settings.category_weights.is_empty() {
+            return;
+        }
+        for r in results.iter_mut() {
+            r.score *= settings.category_weight(&r.metadata.category);
+        }
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    }
+
+    /// Distinct query tokens without stop words, segmented exactly as BM25
+    /// indexes documents.
+    fn query_terms(query: &str) -> Vec<String> {
+        let mut terms: Vec<String> = Vec::new();
+        for token in cangjie_indexer::search::tokenize(query) {
+            if !terms.contains(&token) {
+                terms.push(token);
+            }
+        }
+        cangjie_indexer::search::stopwords::stopwords().filter(terms)
     }
 
     fn lexical_boost(query_terms: &[String], query_lc: &str, item: &SearchResult) -> f64 {
@@ -75,11 +223,23 @@ impl CangjieServer {
         boost
     }
 
+    /// Score multiplier for a result of `len` characters: `1` up to the
+    /// median candidate length, then shrinking with the log of how many times
+    /// longer than the median it is.
+    fn length_factor(len: usize, median: usize, penalty: f64) -> f64 {
+        if penalty <= 0.0 || median == 0 || len <= median {
+            return 1.0;
+        }
+        let ratio = len as f64 / median as f64;
+        1.0 / (1.0 + penalty * ratio.ln())
+    }
+
     pub(super) fn rerank_and_dedup_results(
         results: Vec<SearchResult>,
         query: &str,
         top_k: usize,
         offset: usize,
+        length_penalty: f64,
     ) -> Vec<SearchResult> {
         /// Maximum possible boost per query term (topic exact 8 + title exact 6 + text 1.5)
         const MAX_BOOST_PER_TERM: f64 = 15.5;
@@ -91,6 +251,11 @@ impl CangjieServer {
         let query_terms = Self::query_terms(query);
         let query_lc = query.to_lowercase();
         let max_possible = query_terms.len() as f64 * MAX_BOOST_PER_TERM + MAX_WHOLE_QUERY_BOOST;
+        let median_len = {
+            let mut lens: Vec<usize> = results.iter().map(|r| r.text.chars().count()).collect();
+            lens.sort_unstable();
+            lens.get(lens.len() / 2).copied().unwrap_or(0)
+        };
         let mut scored: Vec<(SearchResult, f64)> = results
             .into_iter()
             .map(|r| {
@@ -100,7 +265,8 @@ impl CangjieServer {
                 } else {
                     0.0
                 };
-                let adjusted = r.score + BOOST_WEIGHT * normalized_boost;
+                let adjusted = (r.score + BOOST_WEIGHT * normalized_boost)
+                    * Self::length_factor(r.text.chars().count(), median_len, length_penalty);
                 (r, adjusted)
             })
             .collect();
@@ -114,13 +280,7 @@ impl CangjieServer {
         let mut seen_text_keys: HashSet<String> = HashSet::new();
         let mut candidates: Vec<(SearchResult, f64)> = Vec::new();
         for (result, adjusted) in scored {
-            let text_key = result
-                .text
-                .split_whitespace()
-                .collect::<Vec<_>>()
-                .join(" ")
-                .to_lowercase();
-            if !seen_text_keys.insert(text_key) {
+            if !seen_text_keys.insert(Self::snippet_key(&result.text)) {
                 continue;
             }
             candidates.push((result, adjusted));
@@ -159,6 +319,82 @@ impl CangjieServer {
 
         selected.into_iter().map(|(result, _)| result).collect()
     }
+
+    /// Whitespace- and case-insensitive key under which near-identical
+    /// snippets collapse.
+    fn snippet_key(text: &str) -> String {
+        text.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    }
+
+    /// Drop near-identical snippets, keeping the first of each. Count-only
+    /// searches use this so they count what a normal search could return.
+    pub(super) fn dedup_snippets(results: Vec<SearchResult>) -> Vec<SearchResult> {
+        let mut seen: HashSet<String> = HashSet::new();
+        results
+            .into_iter()
+            .filter(|r| seen.insert(Self::snippet_key(&r.text)))
+            .collect()
+    }
+
+    /// Keep the best-ranked result of each document. Input must already be in
+    /// rank order, so a document is scored by its best chunk.
+    pub(super) fn group_by_document(results: Vec<SearchResult>) -> Vec<SearchResult> {
+        let mut seen: HashSet<String> = HashSet::new();
+        results
+            .into_iter()
+            .filter(|r| seen.insert(r.metadata.file_path.clone()))
+            .collect()
+    }
+
+    /// Sort results by file path, then by position within the file.
+    pub(super) fn sort_by_path(results: &mut [SearchResult]) {
+        results.sort_by(|a, b| {
+            a.metadata
+                .file_path
+                .cmp(&b.metadata.file_path)
+                .then_with(|| {
+                    chunk_index(&a.metadata.chunk_id).cmp(&chunk_index(&b.metadata.chunk_id))
+                })
+        });
+    }
+}
+
+/// Heuristic that matched a result to the `package` filter of `search_docs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum PackageMatch {
+    Import,
+    Mention,
+    Path,
+}
+
+impl std::fmt::Display for PackageMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Import => "import",
+            Self::Mention => "mention",
+            Self::Path => "path",
+        })
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Byte offsets where `package` occurs in `text` as a whole name: not
+/// preceded by an identifier character or `.`, and not followed by an
+/// identifier character.
+fn package_occurrences<'a>(text: &'a str, package: &'a str) -> impl Iterator<Item = usize> + 'a {
+    text.match_indices(package).filter_map(move |(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + package.len()..].chars().next();
+        let bounded = !before.is_some_and(|c| is_ident_char(c) || c == '.')
+            && !after.is_some_and(is_ident_char);
+        bounded.then_some(start)
+    })
 }
 
 #[cfg(test)]
@@ -166,6 +402,50 @@ mod tests {
     use super::*;
     use cangjie_indexer::{SearchResult, SearchResultMetadata};
 
+    #[test]
+    fn test_candidate_fetch_count_caps_large_offsets() {
+        assert_eq!(
+            CangjieServer::candidate_fetch_count(0, 5, false, true, 500),
+            (24, false)
+        );
+        let (count, capped) = CangjieServer::candidate_fetch_count(1000, 20, true, true, 500);
+        assert_eq!(count, 500);
+        assert!(capped);
+    }
+
+    #[test]
+    fn test_candidate_fetch_count_is_smaller_without_reranker() {
+        let (reranked, _) = CangjieServer::candidate_fetch_count(0, 5, false, true, 500);
+        let (plain, _) = CangjieServer::candidate_fetch_count(0, 5, false, false, 500);
+        assert_eq!(plain, 12);
+        assert!(plain < reranked);
+        // Still enough to fill the page after dedup.
+        assert!(plain > 5);
+    }
+
+    #[test]
+    fn test_filter_note_names_active_filters() {
+        let none = HashMap::new();
+        let note = CangjieServer::filter_note(Some("cjpm"), Some("std.fs"), &none);
+        assert!(
+            note.contains("category 'cjpm' and package 'std.fs'"),
+            "{note}"
+        );
+        assert!(note.contains("dropping the package filter"), "{note}");
+
+        let note = CangjieServer::filter_note(None, Some("std.fs"), &none);
+        assert!(note.contains("package 'std.fs' filter"), "{note}");
+        assert!(!note.contains("category"), "{note}");
+
+        let audience = HashMap::from([("audience".to_string(), "internal".to_string())]);
+        let note = CangjieServer::filter_note(Some("cjpm"), None, &audience);
+        assert!(
+            note.contains("category 'cjpm' and front matter 'audience=internal' filter"),
+            "{note}"
+        );
+        assert!(note.contains("without the metadata filter"), "{note}");
+    }
+
     #[test]
     fn test_has_package_direct_match() {
         let result = SearchResult {
@@ -186,6 +466,226 @@ mod tests {
         assert!(CangjieServer::has_package(&result, "std.fs"));
     }
 
+    #[test]
+    fn test_category_weight_ranks_preferred_category_first() {
+        let make = |category: &str| SearchResult {
+            text: "HashMap stores key value pairs.".to_string(),
+            score: 0.8,
+            metadata: SearchResultMetadata {
+                file_path: format!("{category}/hashmap.md"),
+                category: category.to_string(),
+                ..Default::default()
+            },
+        };
+        let mut results = vec![make("release-notes"), make("stdlib")];
+        let settings = Settings {
+            category_weights: vec!["stdlib=1.5".parse().unwrap()],
+            ..Settings::default()
+        };
+
+        CangjieServer::weight_categories(&mut results, &settings);
+        assert_eq!(results[0].metadata.category, "stdlib");
+        assert!((results[0].score - 1.2).abs() < 1e-9);
+        assert!((results[1].score - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_query_terms_match_index_tokens() {
+        assert_eq!(
+            CangjieServer::query_terms("HashMap std.collection hashmap"),
+            ["hashmap", "std", "collection"]
+        );
+    }
+
+    #[test]
+    fn test_dedup_snippets_collapses_whitespace_and_case() {
+        let make = |text: &str, file_path: &str| SearchResult {
+            text: text.to_string(),
+            score: 1.0,
+            metadata: SearchResultMetadata {
+                file_path: file_path.to_string(),
+                ..Default::default()
+            },
+        };
+        let results = vec![
+            make("Use  HashMap\nfor lookups", "a.md"),
+            make("use hashmap for lookups", "b.md"),
+            make("Use ArrayList for lists", "a.md"),
+        ];
+        let deduped = CangjieServer::dedup_snippets(results);
+        let paths: Vec<_> = deduped
+            .iter()
+            .map(|r| r.metadata.file_path.as_str())
+            .collect();
+        assert_eq!(paths, ["a.md", "a.md"]);
+    }
+
+    #[test]
+    fn test_length_penalty_favors_focused_chunk() {
+        let make = |file: &str, text: String, score: f64| SearchResult {
+            text,
+            score,
+            metadata: SearchResultMetadata {
+                file_path: file.to_string(),
+                ..Default::default()
+            },
+        };
+        let results = vec![
+            make(
+                "long.md",
+                "HashMap stores key value pairs. ".repeat(20),
+                1.0,
+            ),
+            make(
+                "short.md",
+                "HashMap stores key value pairs.".to_string(),
+                0.9,
+            ),
+            make(
+                "other.md",
+                "A HashMap example follows here.".to_string(),
+                0.5,
+            ),
+        ];
+        let files = |length_penalty: f64| -> Vec<String> {
+            CangjieServer::rerank_and_dedup_results(
+                results.clone(),
+                "HashMap",
+                5,
+                0,
+                length_penalty,
+            )
+            .into_iter()
+            .map(|r| r.metadata.file_path)
+            .collect()
+        };
+
+        assert_eq!(files(0.0)[0], "long.md");
+        assert_eq!(files(0.5)[0], "short.md");
+    }
+
+    #[test]
+    fn test_group_by_document_keeps_first_per_file() {
+        let make = |file: &str, score: f64| SearchResult {
+            text: format!("{file} {score}"),
+            score,
+            metadata: SearchResultMetadata {
+                file_path: file.to_string(),
+                ..Default::default()
+            },
+        };
+        let grouped = CangjieServer::group_by_document(vec![
+            make("a.md", 0.9),
+            make("b.md", 0.8),
+            make("a.md", 0.7),
+        ]);
+        let files: Vec<_> = grouped
+            .iter()
+            .map(|r| r.metadata.file_path.as_str())
+            .collect();
+        assert_eq!(files, vec!["a.md", "b.md"]);
+        assert_eq!(grouped[0].score, 0.9);
+    }
+
+    #[test]
+    fn test_sort_by_path_orders_by_file_then_position() {
+        let make = |chunk_id: &str, score: f64| SearchResult {
+            text: chunk_id.to_string(),
+            score,
+            metadata: SearchResultMetadata {
+                file_path: chunk_id.split('#').next().unwrap().to_string(),
+                chunk_id: chunk_id.to_string(),
+                ..Default::default()
+            },
+        };
+        let mut results = vec![
+            make("b.md#0", 0.9),
+            make("a.md#10", 0.8),
+            make("a.md#2", 0.7),
+        ];
+        CangjieServer::sort_by_path(&mut results);
+        let ids: Vec<_> = results
+            .iter()
+            .map(|r| r.metadata.chunk_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["a.md#2", "a.md#10", "b.md#0"]);
+    }
+
+    #[test]
+    fn test_package_match_respects_name_boundaries() {
+        let text = |text: &str| SearchResult {
+            text: text.to_string(),
+            score: 1.0,
+            metadata: SearchResultMetadata::default(),
+        };
+        let matched = |t: &str| CangjieServer::package_match(&text(t), "std.fs");
+
+        assert_eq!(matched("import std.fs.*"), Some(PackageMatch::Import));
+        assert_eq!(matched("import std.fs.File"), Some(PackageMatch::Import));
+        assert_eq!(
+            matched("Functions in std.fs, such as..."),
+            Some(PackageMatch::Mention)
+        );
+        assert_eq!(matched("import std.fsutil.*"), None);
+        assert_eq!(matched("see mystd.fs and std.fs_ext"), None);
+        assert_eq!(matched("reimport std.fs"), Some(PackageMatch::Mention));
+
+        let reexported = SearchResult {
+            metadata: SearchResultMetadata {
+                file_path: "libs/std/fs/fs_package_api/fs_package_classes.md".to_string(),
+                ..Default::default()
+            },
+            ..text("class File re-exported here")
+        };
+        assert_eq!(
+            CangjieServer::package_match(&reexported, "std.fs"),
+            Some(PackageMatch::Path)
+        );
+        assert_eq!(CangjieServer::package_match(&reexported, "std.f"), None);
+    }
+
+    #[test]
+    fn test_cjpm_query_boosts_cjpm_results_without_excluding_others() {
+        let topics: TopicCategoryMap = [
+            ("build", &["cjpm"][..]),
+            ("functions", &["syntax"]),
+            ("overview", &["cjpm", "syntax"]),
+        ]
+        .into_iter()
+        .map(|(t, cs)| (t.to_string(), cs.iter().map(|c| c.to_string()).collect()))
+        .collect();
+
+        let category = CangjieServer::infer_category("cjpm build 失败怎么办", &topics);
+        assert_eq!(category.as_deref(), Some("cjpm"));
+        assert_eq!(
+            CangjieServer::infer_category("cjpm functions", &topics),
+            None
+        );
+        assert_eq!(CangjieServer::infer_category("overview", &topics), None);
+        assert_eq!(CangjieServer::infer_category("如何定义函数", &topics), None);
+
+        let make = |file: &str, category: &str, score: f64| SearchResult {
+            text: String::new(),
+            score,
+            metadata: SearchResultMetadata {
+                file_path: file.to_string(),
+                category: category.to_string(),
+                ..Default::default()
+            },
+        };
+        let mut results = vec![
+            make("syntax/a.md", "syntax", 1.0),
+            make("cjpm/build.md", "cjpm", 0.9),
+            make("syntax/b.md", "syntax", 0.5),
+        ];
+        CangjieServer::boost_category(&mut results, "cjpm");
+        let files: Vec<_> = results
+            .iter()
+            .map(|r| r.metadata.file_path.as_str())
+            .collect();
+        assert_eq!(files, vec!["cjpm/build.md", "syntax/a.md", "syntax/b.md"]);
+    }
+
     #[test]
     fn test_has_package_no_match() {
         let result = SearchResult {
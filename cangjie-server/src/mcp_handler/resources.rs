@@ -0,0 +1,90 @@
+use anyhow::Result;
+use rmcp::model::{AnnotateAble, RawResource, Resource, ResourceContents};
+
+use super::CangjieServer;
+
+const TOPIC_URI_SCHEME: &str = "cangjie://";
+
+/// Resource URI of a documentation topic: `cangjie://<category>/<topic>`.
+pub fn topic_resource_uri(category: &str, topic: &str) -> String {
+    format!("{TOPIC_URI_SCHEME}{category}/{topic}")
+}
+
+/// `(category, topic)` named by a topic resource URI. The topic is the part
+/// after the last `/`, so categories may contain `/` themselves.
+pub fn parse_topic_resource_uri(uri: &str) -> Option<(&str, &str)> {
+    uri.strip_prefix(TOPIC_URI_SCHEME)?
+        .trim_matches('/')
+        .rsplit_once('/')
+        .filter(|(category, topic)| !category.is_empty() && !topic.is_empty())
+}
+
+impl CangjieServer {
+    /// Every indexed topic as an MCP resource: `--pinned-topics` first, in
+    /// configured order, then the rest by category and topic. Described by
+    /// its document title when the backend knows it.
+    pub async fn topic_resources(&self) -> Result<Vec<Resource>> {
+        let catalog = self.search_backend().await?.topic_catalog().await?;
+        let pinned = &self.settings.pinned_topics;
+        let pin_rank = |category: &str, topic: &str| {
+            pinned
+                .iter()
+                .position(|p| p.trim_matches('/').rsplit_once('/') == Some((category, topic)))
+                .unwrap_or(usize::MAX)
+        };
+        let mut entries: Vec<_> = catalog
+            .iter()
+            .filter(|(category, _)| self.category_allowed(category))
+            .flat_map(|(category, topics)| topics.iter().map(move |t| (category, t)))
+            .collect();
+        entries.sort_by_key(|(category, entry)| pin_rank(category, &entry.topic));
+        Ok(entries
+            .into_iter()
+            .map(|(category, entry)| {
+                let topic = &entry.topic;
+                let mut raw = RawResource::new(topic_resource_uri(category, topic), topic.clone());
+                raw.description = Some(if entry.title.is_empty() {
+                    format!("Documentation topic '{topic}' in '{category}'")
+                } else {
+                    entry.title.clone()
+                });
+                raw.mime_type = Some("text/markdown".to_string());
+                raw.no_annotation()
+            })
+            .collect())
+    }
+
+    /// Full content of the topic resource at `uri`; `None` when the URI names
+    /// no indexed topic.
+    pub async fn read_topic_resource(&self, uri: &str) -> Result<Option<ResourceContents>> {
+        let Some((category, topic)) = parse_topic_resource_uri(uri) else {
+            return Ok(None);
+        };
+        if !self.category_allowed(category) {
+            return Ok(None);
+        }
+        let found = self
+            .search_backend()
+            .await?
+            .topic(topic, Some(category))
+            .await?;
+        Ok(found.map(|result| ResourceContents::text(result.content, uri)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_resource_uri_round_trips_nested_categories() {
+        let uri = topic_resource_uri("stdlib/collection", "hashmap");
+        assert_eq!(uri, "cangjie://stdlib/collection/hashmap");
+        assert_eq!(
+            parse_topic_resource_uri(&uri),
+            Some(("stdlib/collection", "hashmap"))
+        );
+        assert_eq!(parse_topic_resource_uri("cangjie://functions"), None);
+        assert_eq!(parse_topic_resource_uri("file:///syntax/functions"), None);
+    }
+}
@@ -1,16 +1,40 @@
+use std::collections::{BTreeMap, HashMap};
+
 use serde::{Deserialize, Serialize};
 
-use cangjie_core::config::DEFAULT_TOP_K;
+use cangjie_core::config::{OutputFormat, DEFAULT_TOP_K, MAX_SUGGESTIONS};
+use cangjie_indexer::document::{
+    extract_code_blocks, filter_code_blocks, suggest_topics, CodeBlock, TopicCategoryMap,
+};
+use cangjie_indexer::search::QueryTerms;
 use rmcp::schemars;
 
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 pub struct SearchResultItem {
     pub content: String,
     pub score: f64,
+    /// The retriever's score, set when `score` was normalized with `normalize_scores`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_score: Option<f64>,
     pub file_path: String,
     pub category: String,
     pub topic: String,
     pub title: String,
+    /// Zero-based position of the chunk within its document
+    #[serde(default)]
+    pub chunk_index: usize,
+    /// Character offset of the chunk within its document
+    #[serde(default)]
+    pub char_offset: usize,
+    /// Ancestor headings of the chunk, e.g. `标准库 > 集合类型 > HashMap`
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub breadcrumb: String,
+    /// How the result matched the `package` filter, set with `explain_package`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_match: Option<String>,
+    /// Link to the page in the upstream docs repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
@@ -21,6 +45,109 @@ pub struct DocsSearchResult {
     pub offset: usize,
     pub has_more: bool,
     pub next_offset: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_terms: Option<QueryTermsDebug>,
+    /// Items nested by document, set instead of `items` when `group_by_topic` is requested
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<TopicGroup>,
+    /// Set when the candidate fetch hit `max_fetch_candidates`, so `total`
+    /// and `has_more` only cover the capped pool
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub candidates_capped: bool,
+    /// Set when the query matched documents but the `category` and
+    /// `package` filters removed every one of them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter_note: Option<String>,
+    /// Matches per category, set for count-only searches (`top_k: 0`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub category_counts: BTreeMap<String, usize>,
+}
+
+impl DocsSearchResult {
+    /// Min-max scale item scores to 0..1 within this result set, keeping each
+    /// original in `raw_score`. Equal scores all become 1.0.
+    pub(crate) fn normalize_scores(&mut self) {
+        let (min, max) = self
+            .items
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), item| {
+                (lo.min(item.score), hi.max(item.score))
+            });
+        for item in &mut self.items {
+            item.raw_score = Some(item.score);
+            item.score = if max > min {
+                (item.score - min) / (max - min)
+            } else {
+                1.0
+            };
+        }
+    }
+
+    /// Move `items` into one [`TopicGroup`] per document, in order of each
+    /// document's first item.
+    pub(crate) fn group_by_topic(&mut self) {
+        let mut groups: Vec<TopicGroup> = Vec::new();
+        for item in std::mem::take(&mut self.items) {
+            let snippet = TopicSnippet {
+                content: item.content,
+                score: item.score,
+            };
+            match groups.iter_mut().find(|g| g.file_path == item.file_path) {
+                Some(group) => {
+                    group.score = group.score.max(snippet.score);
+                    group.snippets.push(snippet);
+                }
+                None => groups.push(TopicGroup {
+                    topic: item.topic,
+                    title: item.title,
+                    file_path: item.file_path,
+                    category: item.category,
+                    score: snippet.score,
+                    source_url: item.source_url,
+                    snippets: vec![snippet],
+                }),
+            }
+        }
+        self.groups = groups;
+    }
+}
+
+/// Matching chunks of one document.
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TopicGroup {
+    pub topic: String,
+    pub title: String,
+    pub file_path: String,
+    pub category: String,
+    /// Best score among the snippets
+    pub score: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    pub snippets: Vec<TopicSnippet>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TopicSnippet {
+    pub content: String,
+    pub score: f64,
+}
+
+/// Terms that drove BM25 retrieval, returned when `debug_terms` is set.
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct QueryTermsDebug {
+    pub terms: Vec<String>,
+    pub expanded: String,
+    pub variants: Vec<String>,
+}
+
+impl From<QueryTerms> for QueryTermsDebug {
+    fn from(q: QueryTerms) -> Self {
+        Self {
+            terms: q.terms,
+            expanded: q.expanded,
+            variants: q.variants,
+        }
+    }
 }
 
 /// Format search results as compact Markdown for LLM consumption.
@@ -28,27 +155,97 @@ pub(crate) fn format_results_markdown(result: &DocsSearchResult) -> String {
     use std::fmt::Write;
 
     let mut out = String::new();
-    let start = result.offset + 1;
-    let end = result.offset + result.count;
-    writeln!(
-        out,
-        "Found {} results (showing {start}-{end}):\n",
-        result.total
-    )
-    .unwrap();
+    if !result.category_counts.is_empty() {
+        writeln!(out, "Found {} results.\n", result.total).unwrap();
+        for (category, count) in &result.category_counts {
+            writeln!(out, "- {category}: {count}").unwrap();
+        }
+    } else if result.count == 0 {
+        writeln!(out, "Found {} results.\n", result.total).unwrap();
+    } else {
+        let start = result.offset + 1;
+        let end = result.offset + result.count;
+        writeln!(
+            out,
+            "Found {} results (showing {start}-{end}):\n",
+            result.total
+        )
+        .unwrap();
+    }
+
+    if let Some(ref q) = result.query_terms {
+        writeln!(out, "Query terms: {}", q.terms.join(" | ")).unwrap();
+        writeln!(out, "Expanded query: {}", q.expanded).unwrap();
+        writeln!(out, "Variants: {}\n", q.variants.join(" | ")).unwrap();
+    }
 
     for (i, item) in result.items.iter().enumerate() {
         let rank = result.offset + i + 1;
         writeln!(out, "---").unwrap();
+        let raw = item
+            .raw_score
+            .map(|raw| format!(", raw: {raw:.2}"))
+            .unwrap_or_default();
         writeln!(
             out,
-            "### [{rank}] {} ({}/{}) [score: {:.2}]\n",
+            "### [{rank}] {} ({}/{}) [score: {:.2}{raw}]\n",
             item.title, item.category, item.topic, item.score
         )
         .unwrap();
+        writeln!(
+            out,
+            "Path: {} (chunk {}, char {})\n",
+            item.file_path, item.chunk_index, item.char_offset
+        )
+        .unwrap();
+        if !item.breadcrumb.is_empty() {
+            writeln!(out, "Section: {}\n", item.breadcrumb).unwrap();
+        }
+        if let Some(ref url) = item.source_url {
+            writeln!(out, "Source: {url}\n").unwrap();
+        }
+        if let Some(ref how) = item.package_match {
+            writeln!(out, "Package match: {how}\n").unwrap();
+        }
         writeln!(out, "{}\n", item.content).unwrap();
     }
 
+    for (i, group) in result.groups.iter().enumerate() {
+        writeln!(out, "---").unwrap();
+        writeln!(
+            out,
+            "### [{}] {} ({}/{}) [score: {:.2}, {} snippets]\n",
+            i + 1,
+            group.title,
+            group.category,
+            group.topic,
+            group.score,
+            group.snippets.len()
+        )
+        .unwrap();
+        writeln!(out, "Path: {}\n", group.file_path).unwrap();
+        if let Some(ref url) = group.source_url {
+            writeln!(out, "Source: {url}\n").unwrap();
+        }
+        for snippet in &group.snippets {
+            writeln!(out, "#### [score: {:.2}]\n", snippet.score).unwrap();
+            writeln!(out, "{}\n", snippet.content).unwrap();
+        }
+    }
+
+    if let Some(ref note) = result.filter_note {
+        writeln!(out, "_{note}_\n").unwrap();
+    }
+
+    if result.candidates_capped {
+        writeln!(out, "---").unwrap();
+        writeln!(
+            out,
+            "_Candidate pool was capped; results past this point may be missing. Narrow the query or filters instead of paging further._"
+        )
+        .unwrap();
+    }
+
     if result.has_more {
         if let Some(next) = result.next_offset {
             writeln!(out, "---").unwrap();
@@ -63,6 +260,65 @@ pub(crate) fn format_results_markdown(result: &DocsSearchResult) -> String {
     out
 }
 
+/// Format `cangjie_get_topics` entries as Markdown, one section per topic in
+/// request order.
+pub(crate) fn format_topics_markdown(entries: &[TopicLookup]) -> String {
+    use std::fmt::Write;
+
+    let mut out = String::new();
+    for entry in entries {
+        writeln!(out, "---").unwrap();
+        match entry {
+            TopicLookup::Found(topic) => {
+                writeln!(
+                    out,
+                    "## {} ({}/{})\n",
+                    topic.title, topic.category, topic.topic
+                )
+                .unwrap();
+                writeln!(out, "Path: {}\n", topic.file_path).unwrap();
+                if let Some(ref url) = topic.source_url {
+                    writeln!(out, "Source: {url}\n").unwrap();
+                }
+                if !topic.other_categories.is_empty() {
+                    writeln!(out, "Also in: {}\n", topic.other_categories.join(", ")).unwrap();
+                }
+                writeln!(out, "{}\n", topic.content).unwrap();
+            }
+            TopicLookup::NotFound {
+                topic,
+                category,
+                suggestions,
+                ..
+            } => {
+                match category.as_deref().filter(|c| !c.is_empty()) {
+                    Some(category) => writeln!(out, "## {topic} ({category}) [not found]\n"),
+                    None => writeln!(out, "## {topic} [not found]\n"),
+                }
+                .unwrap();
+                if !suggestions.is_empty() {
+                    writeln!(out, "Did you mean: {}\n", suggestions.join(", ")).unwrap();
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Render a tool result as pretty JSON, or with `markdown` when that format
+/// is requested.
+pub(crate) fn render_response<T: Serialize + ?Sized>(
+    value: &T,
+    format: OutputFormat,
+    markdown: impl FnOnce(&T) -> String,
+) -> String {
+    match format {
+        OutputFormat::Markdown => markdown(value),
+        OutputFormat::Json => serde_json::to_string_pretty(value)
+            .unwrap_or_else(|e| format!("Failed to serialize result: {e}")),
+    }
+}
+
 // ── Tool parameter types ────────────────────────────────────────────────────
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -72,7 +328,11 @@ pub struct SearchDocsParams {
     /// Optional category to filter results (e.g., 'cjpm', 'syntax', 'stdlib')
     #[serde(default)]
     pub category: Option<String>,
-    /// Number of results to return (default: 5, max: 20)
+    /// Without category, rank results higher when the query clearly names one category (e.g. 'cjpm build'); results from other categories are kept
+    #[serde(default)]
+    pub auto_category_boost: bool,
+    /// Number of results to return (default: 5, max: 20). 0 returns only the
+    /// match count (`total`) and per-category counts, without items
     #[serde(default = "default_top_k")]
     pub top_k: usize,
     /// Number of results to skip for pagination
@@ -81,8 +341,401 @@ pub struct SearchDocsParams {
     /// Filter by stdlib package name (e.g., 'std.collection', 'std.fs')
     #[serde(default)]
     pub package: Option<String>,
+    /// With package, report on each result which heuristic matched it: 'import', 'mention', or 'path'
+    #[serde(default)]
+    pub explain_package: bool,
+    /// Rerank candidates with the configured reranker (default: true). Set to false for lower latency.
+    #[serde(default = "default_rerank")]
+    pub rerank: bool,
+    /// 'chunk' (default) returns matching sections; 'document' returns one result per document with its full content
+    #[serde(default)]
+    pub granularity: SearchGranularity,
+    /// Return only the fenced code blocks of each result instead of its full text
+    #[serde(default)]
+    pub extract_code: bool,
+    /// With extract_code, keep only code blocks in this language (case-insensitive; 'cangjie' and 'cj' are equivalent)
+    #[serde(default)]
+    pub code_language: Option<String>,
+    /// With code_language, also keep code blocks that have no language tag
+    #[serde(default)]
+    pub include_untagged_code: bool,
+    /// 'relevance' (default) orders results by score; 'path' orders them by file path and position within the file
+    #[serde(default)]
+    pub order: SearchOrder,
+    /// Search individual code blocks (with their introducing prose) instead of prose chunks; requires an index built with code indexing enabled
+    #[serde(default)]
+    pub search_code: bool,
+    /// Include the tokenized query terms, synonym expansion, and query variants used for retrieval
+    #[serde(default)]
+    pub debug_terms: bool,
+    /// Search another indexed documentation version instead of the one the server started with
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Nest matching chunks under their document (topic, title, file_path) with the best chunk score per group
+    #[serde(default)]
+    pub group_by_topic: bool,
+    /// Documentation language to search ("zh" or "en"); forwarded to a remote server, defaults to the server's language
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// Keep only documents whose front matter has all of these key/values (e.g. {"audience": "internal"}); a list field matches when any item equals the value
+    #[serde(default)]
+    pub metadata_filter: HashMap<String, String>,
+    /// Drop results from these topics (e.g. the one being read), to find something other than them
+    #[serde(default)]
+    pub exclude_topics: Vec<String>,
+    /// Scale the returned scores to 0..1 (min-max within this page of results, not globally comparable) and keep the original in raw_score
+    #[serde(default)]
+    pub normalize_scores: bool,
+    /// 'json' for structured output or 'markdown' for readable sections; defaults to the server's --output-format, else json
+    #[serde(default)]
+    pub response_format: Option<OutputFormat>,
+}
+
+/// The values an empty request deserializes to.
+impl Default for SearchDocsParams {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            category: None,
+            auto_category_boost: false,
+            top_k: default_top_k(),
+            offset: 0,
+            package: None,
+            explain_package: false,
+            rerank: default_rerank(),
+            granularity: SearchGranularity::default(),
+            extract_code: false,
+            code_language: None,
+            include_untagged_code: false,
+            order: SearchOrder::default(),
+            search_code: false,
+            debug_terms: false,
+            version: None,
+            group_by_topic: false,
+            lang: None,
+            metadata_filter: HashMap::new(),
+            exclude_topics: Vec::new(),
+            normalize_scores: false,
+            response_format: None,
+        }
+    }
+}
+
+impl SearchDocsParams {
+    /// Code blocks in `text` selected by `code_language` / `include_untagged_code`.
+    pub(crate) fn code_blocks(&self, text: &str) -> Vec<CodeBlock> {
+        let blocks = extract_code_blocks(text);
+        match self.code_language.as_deref().filter(|s| !s.is_empty()) {
+            Some(language) => filter_code_blocks(blocks, language, self.include_untagged_code),
+            None => blocks,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchGranularity {
+    #[default]
+    Chunk,
+    Document,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchOrder {
+    #[default]
+    Relevance,
+    Path,
+}
+
+/// One topic requested from `cangjie_get_topics`.
+#[derive(Debug, Clone, Deserialize, schemars::JsonSchema)]
+pub struct TopicRequest {
+    /// Topic name: the document file name without extension (e.g. 'functions')
+    pub topic: String,
+    /// Optional category to pick between topics with the same name
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct GetTopicsParams {
+    /// Topics to fetch (at most 10)
+    pub topics: Vec<TopicRequest>,
+    /// Read topics from another indexed documentation version instead of the one the server started with
+    #[serde(default)]
+    pub version: Option<String>,
+    /// 'json' for structured output or 'markdown' for readable sections; defaults to the server's --output-format, else json
+    #[serde(default)]
+    pub response_format: Option<OutputFormat>,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct RelatedTopicsParams {
+    /// Topic name: the document file name without extension (e.g. 'functions')
+    pub topic: String,
+    /// Optional category to pick between topics with the same name
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Number of related topics to return (default: 5)
+    #[serde(default = "default_top_k")]
+    pub k: usize,
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct DiffVersionsParams {
+    /// Version to compare from (tag, branch, commit, or 'latest')
+    pub from: String,
+    /// Version to compare to (tag, branch, commit, or 'latest')
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TopicResult {
+    pub topic: String,
+    pub category: String,
+    pub title: String,
+    pub file_path: String,
+    pub content: String,
+    /// Other categories that also have a topic with this name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub other_categories: Vec<String>,
+    /// Link to the page in the upstream docs repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+}
+
+/// Outcome of one topic lookup in `cangjie_get_topics`.
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum TopicLookup {
+    Found(TopicResult),
+    NotFound {
+        topic: String,
+        category: Option<String>,
+        not_found: bool,
+        /// Similarly named topics that do exist
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        suggestions: Vec<String>,
+    },
+}
+
+/// Result of `cangjie_topic_exists`.
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct TopicExistence {
+    pub exists: bool,
+    /// Category the topic resolves to when it exists
+    pub matched_category: Option<String>,
+    /// Other categories that have a topic with this name (including all of
+    /// them when the requested category does not)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub other_categories: Vec<String>,
+    /// Similarly named topics when no topic with this name exists
+    pub suggestions: Vec<String>,
+}
+
+impl TopicExistence {
+    /// Resolve `topic` against the index's topic map without fetching content.
+    ///
+    /// Without a category the alphabetically first category wins, matching
+    /// `cangjie_get_topics`. Suggestions prefer topics in the requested category.
+    pub(crate) fn resolve(
+        map: &TopicCategoryMap,
+        topic: &str,
+        category: Option<&str>,
+        threshold: f64,
+    ) -> Self {
+        let categories = map.get(topic);
+        let matched_category = match (categories, category) {
+            (Some(cats), Some(wanted)) => cats.contains(wanted).then(|| wanted.to_string()),
+            (Some(cats), None) => cats.iter().next().cloned(),
+            (None, _) => None,
+        };
+        let other_categories: Vec<String> = categories
+            .into_iter()
+            .flatten()
+            .filter(|c| Some(c.as_str()) != matched_category.as_deref())
+            .cloned()
+            .collect();
+
+        let suggestions = if categories.is_some() {
+            Vec::new()
+        } else {
+            let in_category = category.map(|wanted| {
+                map.iter()
+                    .filter(|(_, cats)| cats.contains(wanted))
+                    .map(|(name, _)| name.as_str())
+            });
+            let mut suggestions = in_category
+                .map(|names| suggest_topics(names, topic, threshold, MAX_SUGGESTIONS))
+                .unwrap_or_default();
+            if suggestions.is_empty() {
+                suggestions = suggest_topics(
+                    map.keys().map(String::as_str),
+                    topic,
+                    threshold,
+                    MAX_SUGGESTIONS,
+                );
+            }
+            suggestions
+        };
+
+        Self {
+            exists: matched_category.is_some(),
+            matched_category,
+            other_categories,
+            suggestions,
+        }
+    }
 }
 
 fn default_top_k() -> usize {
     DEFAULT_TOP_K
 }
+
+fn default_rerank() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(file_path: &str, score: f64) -> SearchResultItem {
+        let topic = file_path.trim_end_matches(".md").to_string();
+        SearchResultItem {
+            content: format!("{topic} {score}"),
+            score,
+            file_path: format!("syntax/{file_path}"),
+            category: "syntax".to_string(),
+            topic: topic.clone(),
+            title: topic,
+            chunk_index: 0,
+            char_offset: 0,
+            package_match: None,
+            source_url: None,
+            breadcrumb: String::new(),
+            raw_score: None,
+        }
+    }
+
+    #[test]
+    fn test_markdown_header_without_results_has_no_range() {
+        let result = DocsSearchResult {
+            items: Vec::new(),
+            total: 0,
+            count: 0,
+            offset: 0,
+            has_more: false,
+            next_offset: None,
+            query_terms: None,
+            groups: Vec::new(),
+            candidates_capped: false,
+            filter_note: None,
+            category_counts: BTreeMap::new(),
+        };
+        let markdown = format_results_markdown(&result);
+        assert!(markdown.starts_with("Found 0 results."), "{markdown}");
+        assert!(!markdown.contains("showing"), "{markdown}");
+    }
+
+    #[test]
+    fn test_normalize_scores_spans_zero_to_one() {
+        let mut result = DocsSearchResult {
+            items: vec![item("a.md", 12.5), item("b.md", 7.0), item("c.md", 2.5)],
+            total: 3,
+            count: 3,
+            offset: 0,
+            has_more: false,
+            next_offset: None,
+            query_terms: None,
+            groups: Vec::new(),
+            candidates_capped: false,
+            filter_note: None,
+            category_counts: BTreeMap::new(),
+        };
+        result.normalize_scores();
+
+        let scores: Vec<f64> = result.items.iter().map(|i| i.score).collect();
+        assert!((scores[0] - 1.0).abs() < 1e-9);
+        assert!((scores[1] - 0.45).abs() < 1e-9);
+        assert!(scores[2].abs() < 1e-9);
+        assert_eq!(result.items[0].raw_score, Some(12.5));
+        assert_eq!(result.items[2].raw_score, Some(2.5));
+
+        let markdown = format_results_markdown(&result);
+        assert!(
+            markdown.contains("### [1] a (syntax/a) [score: 1.00, raw: 12.50]"),
+            "{markdown}"
+        );
+    }
+
+    #[test]
+    fn test_group_by_topic_collapses_chunks_of_one_file() {
+        let mut result = DocsSearchResult {
+            items: vec![
+                item("functions.md", 0.7),
+                item("lambda.md", 0.8),
+                item("functions.md", 0.9),
+            ],
+            total: 3,
+            count: 3,
+            offset: 0,
+            has_more: false,
+            next_offset: None,
+            query_terms: None,
+            groups: Vec::new(),
+            candidates_capped: false,
+            filter_note: None,
+            category_counts: BTreeMap::new(),
+        };
+        result.group_by_topic();
+
+        assert!(result.items.is_empty());
+        assert_eq!(result.groups.len(), 2);
+        let functions = &result.groups[0];
+        assert_eq!(functions.file_path, "syntax/functions.md");
+        assert_eq!(functions.snippets.len(), 2);
+        assert_eq!(functions.score, 0.9);
+        assert_eq!(result.groups[1].snippets.len(), 1);
+
+        let markdown = format_results_markdown(&result);
+        assert!(markdown.contains("### [1] functions (syntax/functions) [score: 0.90, 2 snippets]"));
+    }
+
+    #[test]
+    fn test_format_topics_markdown_sections_in_request_order() {
+        let entries = vec![
+            TopicLookup::Found(TopicResult {
+                topic: "functions".to_string(),
+                category: "syntax".to_string(),
+                title: "函数".to_string(),
+                file_path: "syntax/functions.md".to_string(),
+                content: "func add()".to_string(),
+                other_categories: Vec::new(),
+                source_url: None,
+            }),
+            TopicLookup::NotFound {
+                topic: "fuctions".to_string(),
+                category: None,
+                not_found: true,
+                suggestions: vec!["functions".to_string()],
+            },
+        ];
+
+        let markdown = format_topics_markdown(&entries);
+        let found = markdown.find("## 函数 (syntax/functions)").unwrap();
+        let missing = markdown.find("## fuctions [not found]").unwrap();
+        assert!(found < missing);
+        assert!(markdown.contains("Path: syntax/functions.md"));
+        assert!(markdown.contains("Did you mean: functions"));
+
+        let json = render_response(
+            entries.as_slice(),
+            OutputFormat::Json,
+            format_topics_markdown,
+        );
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed[1]["not_found"], true);
+    }
+}